@@ -2,8 +2,9 @@
 
 extern crate alloc;
 
+use crate::symbol::MAX_SMALL_CHARS;
 use crate::xdr::ScError;
-use crate::Error;
+use crate::{Error, I128Small, I64Small, SymbolSmall, TimepointSmall, U128Small, U64Small, Val};
 use arbitrary::{Arbitrary, Unstructured};
 
 impl<'a> Arbitrary<'a> for Error {
@@ -13,3 +14,48 @@ impl<'a> Arbitrary<'a> for Error {
         Ok(error)
     }
 }
+
+impl<'a> Arbitrary<'a> for SymbolSmall {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        const CHARS: &[u8] = b"_0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let len = u.int_in_range(0..=MAX_SMALL_CHARS)?;
+        let mut buf = [0u8; MAX_SMALL_CHARS];
+        for b in buf[..len].iter_mut() {
+            *b = *u.choose(CHARS)?;
+        }
+        SymbolSmall::try_from_bytes(&buf[..len]).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+/// Only generates values that are fully contained in the [`Val`]. Object
+/// handles are only meaningful relative to the host that issued them, so an
+/// arbitrary one would just be invalid.
+impl<'a> Arbitrary<'a> for Val {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Small-value bodies are 56 bits wide, so the shifted values always
+        // fit.
+        let val = match u.int_in_range(0u8..=9)? {
+            0 => Val::VOID.to_val(),
+            1 => Val::from_bool(bool::arbitrary(u)?).to_val(),
+            2 => Error::arbitrary(u)?.to_val(),
+            3 => Val::from_u32(u32::arbitrary(u)?).to_val(),
+            4 => Val::from_i32(i32::arbitrary(u)?).to_val(),
+            5 => U64Small::try_from(u64::arbitrary(u)? >> 8)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_val(),
+            6 => I64Small::try_from(i64::arbitrary(u)? >> 8)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_val(),
+            7 => TimepointSmall::try_from(u64::arbitrary(u)? >> 8)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_val(),
+            8 => U128Small::try_from((u64::arbitrary(u)? >> 8) as u128)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_val(),
+            _ => I128Small::try_from((i64::arbitrary(u)? >> 8) as i128)
+                .map_err(|_| arbitrary::Error::IncorrectFormat)?
+                .to_val(),
+        };
+        Ok(val)
+    }
+}
@@ -287,6 +287,19 @@ impl Error {
         self.as_val().get_major()
     }
 
+    /// Returns the code of a contract-defined error, i.e. one of type
+    /// [ScErrorType::Contract], or `None` for errors raised by the host.
+    /// Contract errors pass through `try_call` unchanged, so this is how a
+    /// caller tells them apart from (narrowed) host errors.
+    #[inline(always)]
+    pub const fn get_contract_code(&self) -> Option<u32> {
+        if self.is_type(ScErrorType::Contract) {
+            Some(self.get_code())
+        } else {
+            None
+        }
+    }
+
     #[inline(always)]
     pub const fn from_contract_error(code: u32) -> Error {
         unsafe { Self::from_major_minor(code, ScErrorType::Contract as u32) }
@@ -406,4 +419,18 @@ mod tests {
 
         assert_eq!(pairs_xdr_sorted, pairs_host_sorted);
     }
+
+    #[test]
+    fn contract_error_roundtrips_through_scerror() {
+        for code in [0, 1, 12345, u32::MAX] {
+            let err = Error::from_contract_error(code);
+            assert_eq!(err.get_contract_code(), Some(code));
+            let sc = ScError::try_from(err).unwrap();
+            assert_eq!(sc, ScError::Contract(code));
+            assert_eq!(Error::from(sc), err);
+        }
+        // Host errors never look like contract errors, whatever their code.
+        let err = Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction);
+        assert_eq!(err.get_contract_code(), None);
+    }
 }
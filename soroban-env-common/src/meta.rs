@@ -70,3 +70,197 @@ pub const fn get_pre_release_version(interface_version: u64) -> u32 {
     // The pre-release version is the low 32 bits of INTERFACE_VERSION
     interface_version as u32
 }
+
+/// Combines a ledger protocol version and a pre-release version into an
+/// interface version, the inverse of [get_ledger_protocol_version] and
+/// [get_pre_release_version].
+pub const fn make_interface_version(protocol_version: u32, pre_release_version: u32) -> u64 {
+    ((protocol_version as u64) << 32) | (pre_release_version as u64)
+}
+
+// A JSON description of the host function interface defined in env.json, for
+// SDKs in other languages to generate their bindings from. It is generated from
+// the same file as the `Env` trait, so it cannot drift out of sync with it.
+soroban_env_macros::generate_interface_json!("env.json");
+
+/// A description of a single host function, as declared in env.json. See
+/// [host_functions].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HostFunctionInfo {
+    /// The name of the module the function belongs to, e.g. `"int"`.
+    pub mod_name: &'static str,
+    /// The Wasm import module name, e.g. `"i"`.
+    pub mod_export: &'static str,
+    /// The Wasm import field name, e.g. `"_"`.
+    pub fn_export: &'static str,
+    /// The name of the function, e.g. `"obj_from_u64"`.
+    pub name: &'static str,
+    /// The name and type of each argument, in order.
+    pub args: &'static [(&'static str, &'static str)],
+    /// The return type.
+    pub ret: &'static str,
+    pub min_supported_protocol: Option<u32>,
+    pub max_supported_protocol: Option<u32>,
+}
+
+impl HostFunctionInfo {
+    /// The number of arguments the function takes. Every argument and the
+    /// return value are passed as a single `i64` across the Wasm boundary.
+    pub const fn arity(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns true if a contract importing this function is compatible with
+    /// a host running at `protocol`.
+    pub fn is_supported_at_protocol(&self, protocol: u32) -> bool {
+        is_protocol_in_range(
+            protocol,
+            self.min_supported_protocol,
+            self.max_supported_protocol,
+        )
+    }
+}
+
+/// Returns true if `protocol` is within the inclusive range of a host
+/// function's `min_supported_protocol` and `max_supported_protocol`, either
+/// of which may be unbounded.
+pub fn is_protocol_in_range(protocol: u32, min: Option<u32>, max: Option<u32>) -> bool {
+    min.map_or(true, |min| protocol >= min) && max.map_or(true, |max| protocol <= max)
+}
+
+macro_rules! generate_host_function_infos {
+    {
+        $(
+            $(#[$mod_attr:meta])*
+            mod $mod_id:ident $mod_str:literal
+            {
+                $(
+                    $(#[$fn_attr:meta])*
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident ($($arg:ident:$type:ty),*) -> $ret:ty }
+                )*
+            }
+        )*
+    }
+    =>
+    {
+        // One constant per host function, named after it, so that both the
+        // list of all functions and the lookup by import name below can refer
+        // to it.
+        #[allow(non_upper_case_globals)]
+        mod infos {
+            use super::HostFunctionInfo;
+            $(
+                $(
+                    pub(super) const $fn_id: HostFunctionInfo = HostFunctionInfo {
+                        mod_name: stringify!($mod_id),
+                        mod_export: $mod_str,
+                        fn_export: $fn_str,
+                        name: stringify!($fn_id),
+                        args: &[$((stringify!($arg), stringify!($type))),*],
+                        ret: stringify!($ret),
+                        min_supported_protocol: $min_proto,
+                        max_supported_protocol: $max_proto,
+                    };
+                )*
+            )*
+        }
+
+        static HOST_FUNCTIONS: &[HostFunctionInfo] = &[
+            $(
+                $(
+                    infos::$fn_id,
+                )*
+            )*
+        ];
+
+        /// Looks up the host function imported as `mod_export`.`fn_export`.
+        /// The lookup dispatches on the module and then on the function
+        /// within it, rather than scanning every function in the interface.
+        pub fn find_host_function(
+            mod_export: &str,
+            fn_export: &str,
+        ) -> Option<&'static HostFunctionInfo> {
+            match mod_export {
+                $(
+                    $mod_str => match fn_export {
+                        $(
+                            $fn_str => Some(&infos::$fn_id),
+                        )*
+                        _ => None,
+                    },
+                )*
+                _ => None,
+            }
+        }
+    };
+}
+
+crate::call_macro_with_all_host_functions! { generate_host_function_infos }
+
+/// Returns a description of every host function in the env interface, in the
+/// order they are declared in env.json. This lets tools such as debuggers and
+/// linkers check a Wasm module's imports against the interface without
+/// parsing env.json themselves.
+pub fn host_functions() -> &'static [HostFunctionInfo] {
+    HOST_FUNCTIONS
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        find_host_function, get_ledger_protocol_version, get_pre_release_version, host_functions,
+        is_protocol_in_range, make_interface_version, ENV_INTERFACE_JSON, INTERFACE_VERSION,
+    };
+
+    #[test]
+    fn protocol_ranges_are_inclusive() {
+        assert!(is_protocol_in_range(0, None, None));
+        assert!(!is_protocol_in_range(20, Some(21), None));
+        assert!(is_protocol_in_range(21, Some(21), None));
+        assert!(is_protocol_in_range(21, None, Some(21)));
+        assert!(!is_protocol_in_range(22, None, Some(21)));
+        assert!(is_protocol_in_range(20, Some(20), Some(21)));
+    }
+
+    #[test]
+    fn interface_version_roundtrips_through_its_parts() {
+        let v = make_interface_version(
+            get_ledger_protocol_version(INTERFACE_VERSION),
+            get_pre_release_version(INTERFACE_VERSION),
+        );
+        assert_eq!(v, INTERFACE_VERSION);
+        assert_eq!(
+            get_ledger_protocol_version(make_interface_version(21, 3)),
+            21
+        );
+        assert_eq!(get_pre_release_version(make_interface_version(21, 3)), 3);
+    }
+
+    #[test]
+    fn interface_json_describes_host_functions() {
+        assert!(ENV_INTERFACE_JSON.starts_with("{\n  \"modules\": ["));
+        assert!(ENV_INTERFACE_JSON.contains("\"name\": \"obj_from_u64\""));
+        assert!(ENV_INTERFACE_JSON.contains("\"import\": \"i._\""));
+        assert!(ENV_INTERFACE_JSON.contains("\"return\": \"U64Object\""));
+    }
+
+    #[test]
+    fn host_functions_describe_env_interface() {
+        let f = find_host_function("i", "_").unwrap();
+        assert_eq!(f.mod_name, "int");
+        assert_eq!(f.name, "obj_from_u64");
+        assert_eq!(f.arity(), 1);
+        assert_eq!(f.args, &[("v", "u64")]);
+        assert_eq!(f.ret, "U64Object");
+        assert!(find_host_function("i", "zz").is_none());
+
+        let fns = host_functions();
+        for (i, a) in fns.iter().enumerate() {
+            for b in &fns[i + 1..] {
+                assert!((a.mod_export, a.fn_export) != (b.mod_export, b.fn_export));
+            }
+            assert_eq!(find_host_function(a.mod_export, a.fn_export), Some(a));
+        }
+        assert!(find_host_function("zz", "_").is_none());
+    }
+}
@@ -10,8 +10,12 @@ use num_derive::FromPrimitive;
 #[repr(u64)]
 #[derive(Debug, FromPrimitive, PartialEq, Eq, Clone)]
 pub enum StorageType {
+    /// Data stored in its own ledger entry, which is deleted once it expires.
     Temporary = 0,
+    /// Data stored in its own ledger entry, which is archived once it expires.
     Persistent = 1,
+    /// Data stored inside the contract's instance ledger entry, which shares
+    /// the instance's lifetime and rent.
     Instance = 2,
 }
 
@@ -141,7 +141,9 @@ impl<E: Env> Compare<Symbol> for E {
                     self.compare(&soa, &sob)
                 }
             }
-            other => Ok(other),
+            // A small symbol and a symbol object order by their contents,
+            // same as the corresponding `ScVal`s, not by their tags.
+            _ => self.compare(&a.0, &b.0),
         }
     }
 }
@@ -43,6 +43,9 @@ macro_rules! impl_for_tuple {
             type Error = crate::Error;
 
             fn try_from_val(env: &E, val: &[Val; N]) -> Result<Self, Self::Error> {
+                if N != $count_usize {
+                    return Err(crate::ConversionError.into());
+                }
                 Ok((
                     $({
                         $typ::try_from_val(&env, &val[$idx]).map_err(Into::into)?
@@ -58,6 +61,9 @@ macro_rules! impl_for_tuple {
             type Error = crate::Error;
 
             fn try_from_val(env: &E, val: &($($typ,)*)) -> Result<Self, Self::Error> {
+                if N != $count_usize {
+                    return Err(crate::ConversionError.into());
+                }
                 let mut arr: [Val; N] = [Val::VOID.into(); N];
                 $(arr[$idx] = val.$idx.try_into_val(env).map_err(Into::into)?;)*
                 Ok(arr)
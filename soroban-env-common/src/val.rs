@@ -410,6 +410,7 @@ pub trait WasmiMarshal: Sized {
 
 #[cfg(feature = "wasmi")]
 impl WasmiMarshal for Val {
+    #[inline(always)]
     fn try_marshal_from_value(v: wasmi::Value) -> Option<Self> {
         if let wasmi::Value::I64(i) = v {
             Some(Val::from_payload(i as u64))
@@ -418,6 +419,7 @@ impl WasmiMarshal for Val {
         }
     }
 
+    #[inline(always)]
     fn marshal_from_self(self) -> wasmi::Value {
         wasmi::Value::I64(self.get_payload() as i64)
     }
@@ -425,6 +427,7 @@ impl WasmiMarshal for Val {
 
 #[cfg(feature = "wasmi")]
 impl WasmiMarshal for u64 {
+    #[inline(always)]
     fn try_marshal_from_value(v: wasmi::Value) -> Option<Self> {
         if let wasmi::Value::I64(i) = v {
             Some(i as u64)
@@ -433,6 +436,7 @@ impl WasmiMarshal for u64 {
         }
     }
 
+    #[inline(always)]
     fn marshal_from_self(self) -> wasmi::Value {
         wasmi::Value::I64(self as i64)
     }
@@ -440,6 +444,7 @@ impl WasmiMarshal for u64 {
 
 #[cfg(feature = "wasmi")]
 impl WasmiMarshal for i64 {
+    #[inline(always)]
     fn try_marshal_from_value(v: wasmi::Value) -> Option<Self> {
         if let wasmi::Value::I64(i) = v {
             Some(i)
@@ -448,6 +453,7 @@ impl WasmiMarshal for i64 {
         }
     }
 
+    #[inline(always)]
     fn marshal_from_self(self) -> wasmi::Value {
         wasmi::Value::I64(self)
     }
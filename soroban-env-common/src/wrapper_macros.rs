@@ -84,6 +84,10 @@ macro_rules! impl_wrapper_wasmi_conversions {
         // wasmi / VM argument support
         #[cfg(feature = "wasmi")]
         impl $crate::WasmiMarshal for $wrapper {
+            // These are called for every argument and return value of every
+            // host function call from a VM, so must inline down to the tag
+            // check.
+            #[inline(always)]
             fn try_marshal_from_value(v: wasmi::Value) -> Option<Self> {
                 if let wasmi::Value::I64(i) = v {
                     let val = $crate::Val::from_payload(i as u64);
@@ -96,6 +100,7 @@ macro_rules! impl_wrapper_wasmi_conversions {
                 None
             }
 
+            #[inline(always)]
             fn marshal_from_self(self) -> wasmi::Value {
                 wasmi::Value::I64(self.as_val().get_payload() as i64)
             }
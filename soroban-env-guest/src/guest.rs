@@ -3,9 +3,9 @@ use core::convert::Infallible;
 use soroban_env_common::call_macro_with_all_host_functions;
 
 use super::{
-    AddressObject, Bool, BytesObject, DurationObject, Error, I128Object, I256Object, I256Val,
-    I64Object, MapObject, StorageType, StringObject, SymbolObject, TimepointObject, U128Object,
-    U256Object, U256Val, U32Val, U64Object, U64Val, Val, VecObject, Void,
+    AddressObject, Bool, BytesObject, DurationObject, Error, I128Object, I128Val, I256Object,
+    I256Val, I64Object, MapObject, StorageType, StringObject, SymbolObject, TimepointObject,
+    U128Object, U128Val, U256Object, U256Val, U32Val, U64Object, U64Val, Val, VecObject, Void,
 };
 use super::{Env, EnvBase, Symbol};
 use static_assertions as sa;
@@ -190,15 +190,19 @@ impl EnvBase for Guest {
 // This is a helper macro used only by impl_env_for_guest below. It consumes a
 // token-tree of the form:
 //
-//  {fn $fn_id:ident $args:tt -> $ret:ty}
+//  {$mod_id:ident, $(#[$attr:meta])* fn $fn_id:ident $args:tt -> $ret:ty}
 //
 // and produces the the corresponding method definition to be used in the
 // Guest implementation of the Env trait (calling through to the corresponding
-// unsafe extern function).
+// unsafe extern function). The attributes carry the function's docs from
+// env.json. The method is only a thin wrapper around the extern call, so it is
+// always inlined, leaving contracts with a direct call to the host import.
 macro_rules! guest_function_helper {
-    {$mod_id:ident, fn $fn_id:ident($($arg:ident:$type:ty),*) -> $ret:ty}
+    {$mod_id:ident, $(#[$attr:meta])* fn $fn_id:ident($($arg:ident:$type:ty),*) -> $ret:ty}
     =>
     {
+        $(#[$attr])*
+        #[inline(always)]
         fn $fn_id(&self, $($arg:$type),*) -> Result<$ret, Self::Error>{
             unsafe {
                 Ok($mod_id::$fn_id($($arg),*))
@@ -228,7 +232,7 @@ macro_rules! impl_env_for_guest {
                     // pattern-repetition matcher so that it will match all such
                     // descriptions.
                     $(#[$fn_attr:meta])*
-                    { $fn_str:literal, fn $fn_id:ident $args:tt -> $ret:ty }
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident $args:tt -> $ret:ty }
                 )*
             }
         )*
@@ -252,7 +256,7 @@ macro_rules! impl_env_for_guest {
                     // block repetition-level from the outer pattern in the
                     // expansion, flattening all functions from all 'mod' blocks
                     // into the implementation of Env for Guest.
-                    guest_function_helper!{$mod_id, fn $fn_id $args -> $ret}
+                    guest_function_helper!{$mod_id, $(#[$fn_attr])* fn $fn_id $args -> $ret}
                 )*
             )*
         }
@@ -307,7 +311,7 @@ macro_rules! generate_extern_modules {
                     // pattern-repetition matcher so that it will match all such
                     // descriptions.
                     $(#[$fn_attr:meta])*
-                    { $fn_str:literal, fn $fn_id:ident $args:tt -> $ret:ty }
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident $args:tt -> $ret:ty }
                 )*
             }
         )*
@@ -329,7 +333,7 @@ macro_rules! generate_extern_modules {
                 #[allow(unused_imports)]
                 use crate::{Val,Object,Symbol,Error,MapObject,VecObject,BytesObject};
                 #[allow(unused_imports)]
-                use crate::{I128Object, I256Object, I256Val, I64Object, I64Val, U128Object, U256Object, U256Val, U32Val, U64Object, U64Val, StorageType, TimepointObject, DurationObject};
+                use crate::{I128Object, I128Val, I256Object, I256Val, I64Object, I64Val, U128Object, U128Val, U256Object, U256Val, U32Val, U64Object, U64Val, StorageType, TimepointObject, DurationObject};
                 #[allow(unused_imports)]
                 use crate::{Void,AddressObject,SymbolObject,StringObject,Bool};
                 #[link(wasm_import_module = $mod_str)]
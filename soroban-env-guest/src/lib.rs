@@ -7,10 +7,43 @@
 //! It also re-exports all of the content of the [soroban_env_common] crate for
 //! use by guest code. Most of the type and module definitions visible here are
 //! actually defined in the common crate.
+//!
+//! On Wasm it also provides [report_panic], which passes the message of a guest
+//! panic on to the host before trapping. The `panic-handler` feature installs
+//! a `#[panic_handler]` calling it, for contracts that don't define their own.
+//!
+//! On Wasm it also provides [with_linear_memory_buffer], which lends out
+//! scratch buffers of linear memory for staging data passed to or from the
+//! host's linear-memory functions, for contracts that have no allocator.
+//!
+//! With the `mock` feature enabled it also provides the [MockEnv] type, an
+//! in-memory implementation of the [Env] interface for unit testing contract
+//! logic natively, without the full host.
 
 #[cfg(target_family = "wasm")]
 mod guest;
 
 #[cfg(target_family = "wasm")]
 pub use guest::Guest;
+
+#[cfg(target_family = "wasm")]
+mod panic;
+
+#[cfg(target_family = "wasm")]
+mod linear_memory;
+
+#[cfg(target_family = "wasm")]
+pub use linear_memory::{bytes_new_with, with_linear_memory_buffer};
+
+#[cfg(target_family = "wasm")]
+pub use panic::report_panic;
+
+#[cfg(feature = "mock")]
+extern crate alloc;
+
+#[cfg(feature = "mock")]
+mod mock;
+
+#[cfg(feature = "mock")]
+pub use mock::MockEnv;
 pub use soroban_env_common::*;
@@ -0,0 +1,108 @@
+use core::arch::wasm32;
+
+use super::{BytesObject, EnvBase, Guest};
+
+const PAGE_SIZE: usize = 0x10000;
+
+// A bump allocator over pages of linear memory it grows itself, so that it
+// doesn't need (or interfere with) a global allocator. Buffers are released
+// in the reverse order they're allocated in, by resetting the arena to the
+// state it had before, see `with_linear_memory_buffer`.
+#[derive(Clone, Copy)]
+struct ScratchArena {
+    // The start of the arena, which moves whenever the arena can't grow in
+    // place.
+    start: usize,
+    // The start of the free part of the arena.
+    next: usize,
+    // The end of the arena, which is always the end of linear memory as it was
+    // when the arena last grew it.
+    end: usize,
+}
+
+// Guests are single-threaded, and the arena is only ever borrowed for the
+// duration of an allocation, so a plain `static mut` is enough here.
+static mut ARENA: ScratchArena = ScratchArena {
+    start: 0,
+    next: 0,
+    end: 0,
+};
+
+impl ScratchArena {
+    fn alloc(&mut self, len: usize) -> *mut u8 {
+        if self.end - self.next < len {
+            let memory_end = wasm32::memory_size(0) * PAGE_SIZE;
+            if memory_end != self.end {
+                // Linear memory has been grown by someone else since (or this
+                // is the first allocation): start over at its current end, as
+                // what follows the arena isn't ours.
+                self.start = memory_end;
+                self.next = memory_end;
+                self.end = memory_end;
+            }
+            let missing = len - (self.end - self.next);
+            let pages = (missing + PAGE_SIZE - 1) / PAGE_SIZE;
+            if wasm32::memory_grow(0, pages) == usize::MAX {
+                wasm32::unreachable()
+            }
+            self.end += pages * PAGE_SIZE;
+        }
+        let ptr = self.next as *mut u8;
+        self.next += len;
+        ptr
+    }
+}
+
+/// Calls `f` with a buffer of `len` bytes of linear memory, for staging data
+/// passed to or from the host's linear-memory functions (such as
+/// `bytes_new_from_linear_memory`) in contracts that have no allocator. The
+/// buffer's initial contents are unspecified.
+///
+/// The buffer is taken from a region of linear memory that is grown for the
+/// purpose, and is reused once `f` returns, so it must not be retained.
+/// Calls may be nested, in which case the inner buffer follows the outer one.
+pub fn with_linear_memory_buffer<T>(len: usize, f: impl FnOnce(&mut [u8]) -> T) -> T {
+    if len == 0 {
+        return f(&mut []);
+    }
+    // Safety: see the comment on `ARENA`. The buffer handed to `f` is only
+    // released after `f` returns, and is never handed out twice meanwhile,
+    // since allocations made by `f` come after it.
+    let (released, buf) = unsafe {
+        let arena = &mut *core::ptr::addr_of_mut!(ARENA);
+        let ptr = arena.alloc(len);
+        // The arena as it is once the buffer is released again.
+        let released = ScratchArena {
+            next: ptr as usize,
+            ..*arena
+        };
+        (released, core::slice::from_raw_parts_mut(ptr, len))
+    };
+    let res = f(buf);
+    unsafe {
+        let arena = &mut *core::ptr::addr_of_mut!(ARENA);
+        if arena.start == released.start {
+            // The arena at most grew in place: keep the pages it grew by.
+            arena.next = released.next;
+        } else {
+            // The arena moved while `f` ran, so the memory between the old and
+            // the new one isn't ours. Going back to the old arena leaks the
+            // new one, which is rare enough not to matter.
+            *arena = released;
+        }
+    }
+    res
+}
+
+/// Creates a new bytes object of `len` bytes, filled in by `fill` from a
+/// buffer provided by [with_linear_memory_buffer].
+pub fn bytes_new_with(len: usize, fill: impl FnOnce(&mut [u8])) -> BytesObject {
+    with_linear_memory_buffer(len, |buf| {
+        fill(buf);
+        match Guest.bytes_new_from_slice(buf) {
+            Ok(b) => b,
+            // The host traps the guest rather than returning errors.
+            Err(e) => match e {},
+        }
+    })
+}
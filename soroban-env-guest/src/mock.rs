@@ -0,0 +1,983 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{cell::RefCell, cmp::Ordering};
+
+use soroban_env_common::call_macro_with_all_host_functions;
+
+use super::xdr::{ScErrorCode, ScErrorType, SCSYMBOL_LIMIT};
+use super::{
+    AddressObject, Bool, BytesObject, DurationObject, Error, I128Object, I128Small, I128Val,
+    I256Object, I256Val, I64Object, I64Small, MapObject, Object, StorageType, StringObject,
+    SymbolObject, SymbolSmall, SymbolStr, Tag, TimepointObject, U128Object, U128Small, U128Val,
+    U256Object, U256Val, U32Val, U64Object, U64Small, U64Val, Val, VecObject, Void,
+};
+use super::{Compare, DurationSmall, Env, EnvBase, Symbol, TimepointSmall, TryFromVal};
+
+/// The host objects a [MockEnv] knows how to store. Like host objects, these
+/// are immutable once created: every "mutating" operation allocates a new
+/// object.
+#[derive(Clone)]
+enum MockObject {
+    U64(u64),
+    I64(i64),
+    Timepoint(u64),
+    Duration(u64),
+    U128(u128),
+    I128(i128),
+    Bytes(Vec<u8>),
+    String(Vec<u8>),
+    Symbol(Vec<u8>),
+    Vec(Vec<Val>),
+    Map(Vec<(Val, Val)>),
+}
+
+impl MockObject {
+    fn tag(&self) -> Tag {
+        match self {
+            MockObject::U64(_) => Tag::U64Object,
+            MockObject::I64(_) => Tag::I64Object,
+            MockObject::Timepoint(_) => Tag::TimepointObject,
+            MockObject::Duration(_) => Tag::DurationObject,
+            MockObject::U128(_) => Tag::U128Object,
+            MockObject::I128(_) => Tag::I128Object,
+            MockObject::Bytes(_) => Tag::BytesObject,
+            MockObject::String(_) => Tag::StringObject,
+            MockObject::Symbol(_) => Tag::SymbolObject,
+            MockObject::Vec(_) => Tag::VecObject,
+            MockObject::Map(_) => Tag::MapObject,
+        }
+    }
+}
+
+// The comparable content of a value that is (or may be) an object, used to
+// order objects against one another and against small values of the same
+// type.
+enum Content {
+    Unsigned(u128),
+    Signed(i128),
+    Bytes(Vec<u8>),
+    Vec(Vec<Val>),
+    Map(Vec<(Val, Val)>),
+}
+
+/// The [MockEnv] is a lightweight, in-memory implementation of the [Env]
+/// interface for unit testing contract logic natively, without linking
+/// against `soroban-env-host`. It supports the integer, vector, map, bytes,
+/// string and symbol object operations, with the same semantics as the host
+/// but without any metering, authorization, storage or XDR. All other [Env]
+/// functions, as well as those that read or write guest linear memory, fail
+/// with an error of type [ScErrorType::Context] and code
+/// [ScErrorCode::InvalidAction].
+///
+/// Cloning a [MockEnv] is cheap and produces a handle to the same set of
+/// objects.
+#[derive(Clone, Default)]
+pub struct MockEnv {
+    objects: Rc<RefCell<Vec<MockObject>>>,
+}
+
+fn err(ty: ScErrorType, code: ScErrorCode) -> Error {
+    Error::from_type_and_code(ty, code)
+}
+
+fn index_bounds() -> Error {
+    err(ScErrorType::Object, ScErrorCode::IndexBounds)
+}
+
+fn unsupported() -> Error {
+    err(ScErrorType::Context, ScErrorCode::InvalidAction)
+}
+
+fn u32_len(len: usize) -> Result<U32Val, Error> {
+    u32::try_from(len)
+        .map(U32Val::from)
+        .map_err(|_| err(ScErrorType::Value, ScErrorCode::ArithDomain))
+}
+
+fn check_symbol_bytes(bytes: &[u8]) -> Result<(), Error> {
+    if bytes.len() > SCSYMBOL_LIMIT as usize {
+        return Err(err(ScErrorType::Value, ScErrorCode::InvalidInput));
+    }
+    for b in bytes {
+        SymbolSmall::validate_char(*b as char)?;
+    }
+    Ok(())
+}
+
+fn copy_from_slice(bytes: &mut Vec<u8>, pos: usize, slice: &[u8]) -> Result<(), Error> {
+    if pos > bytes.len() {
+        return Err(index_bounds());
+    }
+    let end = pos.checked_add(slice.len()).ok_or_else(index_bounds)?;
+    if end > bytes.len() {
+        bytes.resize(end, 0);
+    }
+    bytes[pos..end].copy_from_slice(slice);
+    Ok(())
+}
+
+fn copy_to_slice(bytes: &[u8], pos: U32Val, slice: &mut [u8]) -> Result<(), Error> {
+    let pos = u32::from(pos) as usize;
+    let end = pos.checked_add(slice.len()).ok_or_else(index_bounds)?;
+    let src = bytes.get(pos..end).ok_or_else(index_bounds)?;
+    slice.copy_from_slice(src);
+    Ok(())
+}
+
+impl MockEnv {
+    /// Returns the number of objects allocated in this environment so far.
+    pub fn object_count(&self) -> usize {
+        self.objects.borrow().len()
+    }
+
+    fn add(&self, obj: MockObject) -> Result<Object, Error> {
+        let tag = obj.tag();
+        let mut objects = self.objects.borrow_mut();
+        let handle = u32::try_from(objects.len())
+            .map_err(|_| err(ScErrorType::Object, ScErrorCode::ExceededLimit))?;
+        objects.push(obj);
+        Ok(Object::from_handle_and_tag(handle, tag))
+    }
+
+    fn visit<T>(
+        &self,
+        obj: impl Into<Object>,
+        f: impl FnOnce(&MockObject) -> Option<T>,
+    ) -> Result<T, Error> {
+        let obj: Object = obj.into();
+        let objects = self.objects.borrow();
+        let mobj = objects
+            .get(obj.get_handle() as usize)
+            .ok_or_else(|| err(ScErrorType::Object, ScErrorCode::MissingValue))?;
+        if mobj.tag() != obj.as_val().get_tag() {
+            return Err(err(ScErrorType::Object, ScErrorCode::UnexpectedType));
+        }
+        f(mobj).ok_or_else(|| err(ScErrorType::Object, ScErrorCode::UnexpectedType))
+    }
+
+    fn vec_of(&self, v: VecObject) -> Result<Vec<Val>, Error> {
+        self.visit(v, |o| match o {
+            MockObject::Vec(v) => Some(v.clone()),
+            _ => None,
+        })
+    }
+
+    fn map_of(&self, m: MapObject) -> Result<Vec<(Val, Val)>, Error> {
+        self.visit(m, |o| match o {
+            MockObject::Map(m) => Some(m.clone()),
+            _ => None,
+        })
+    }
+
+    fn bytes_of(&self, b: BytesObject) -> Result<Vec<u8>, Error> {
+        self.visit(b, |o| match o {
+            MockObject::Bytes(b) => Some(b.clone()),
+            _ => None,
+        })
+    }
+
+    fn new_vec(&self, v: Vec<Val>) -> Result<VecObject, Error> {
+        Ok(VecObject::try_from(self.add(MockObject::Vec(v))?)?)
+    }
+
+    fn new_map(&self, m: Vec<(Val, Val)>) -> Result<MapObject, Error> {
+        Ok(MapObject::try_from(self.add(MockObject::Map(m))?)?)
+    }
+
+    fn new_bytes(&self, b: Vec<u8>) -> Result<BytesObject, Error> {
+        Ok(BytesObject::try_from(self.add(MockObject::Bytes(b))?)?)
+    }
+
+    fn content(&self, v: Val) -> Result<Content, Error> {
+        if let Ok(obj) = Object::try_from(v) {
+            return self.visit(obj, |o| {
+                Some(match o {
+                    MockObject::U64(u) | MockObject::Timepoint(u) | MockObject::Duration(u) => {
+                        Content::Unsigned(*u as u128)
+                    }
+                    MockObject::U128(u) => Content::Unsigned(*u),
+                    MockObject::I64(i) => Content::Signed(*i as i128),
+                    MockObject::I128(i) => Content::Signed(*i),
+                    MockObject::Bytes(b) | MockObject::String(b) | MockObject::Symbol(b) => {
+                        Content::Bytes(b.clone())
+                    }
+                    MockObject::Vec(v) => Content::Vec(v.clone()),
+                    MockObject::Map(m) => Content::Map(m.clone()),
+                })
+            });
+        }
+        Ok(match v.get_tag() {
+            Tag::U64Small => Content::Unsigned(u64::from(U64Small::try_from(v)?) as u128),
+            Tag::TimepointSmall => {
+                Content::Unsigned(u64::from(TimepointSmall::try_from(v)?) as u128)
+            }
+            Tag::DurationSmall => Content::Unsigned(u64::from(DurationSmall::try_from(v)?) as u128),
+            Tag::U128Small => Content::Unsigned(u128::from(U128Small::try_from(v)?)),
+            Tag::I64Small => Content::Signed(i64::from(I64Small::try_from(v)?) as i128),
+            Tag::I128Small => Content::Signed(i128::from(I128Small::try_from(v)?)),
+            Tag::SymbolSmall => {
+                let s = SymbolStr::from(SymbolSmall::try_from(v)?);
+                Content::Bytes(AsRef::<[u8]>::as_ref(&s).to_vec())
+            }
+            _ => return Err(unsupported()),
+        })
+    }
+
+    fn compare_vals(&self, a: Val, b: Val) -> Result<Ordering, Error> {
+        if !a.is_object() && !b.is_object() {
+            return self.compare(&a, &b);
+        }
+        let atype = a.get_tag().get_scval_type();
+        let btype = b.get_tag().get_scval_type();
+        if atype != btype {
+            return Ok(atype.cmp(&btype));
+        }
+        match (self.content(a)?, self.content(b)?) {
+            (Content::Unsigned(a), Content::Unsigned(b)) => Ok(a.cmp(&b)),
+            (Content::Signed(a), Content::Signed(b)) => Ok(a.cmp(&b)),
+            (Content::Bytes(a), Content::Bytes(b)) => Ok(a.cmp(&b)),
+            (Content::Vec(a), Content::Vec(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match self.compare(a, b)? {
+                        Ordering::Equal => (),
+                        unequal => return Ok(unequal),
+                    }
+                }
+                Ok(a.len().cmp(&b.len()))
+            }
+            (Content::Map(a), Content::Map(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match self.compare(a, b)? {
+                        Ordering::Equal => (),
+                        unequal => return Ok(unequal),
+                    }
+                }
+                Ok(a.len().cmp(&b.len()))
+            }
+            _ => Err(err(ScErrorType::Value, ScErrorCode::InternalError)),
+        }
+    }
+
+    fn binary_search<T>(
+        &self,
+        items: &[T],
+        key: impl Fn(&T) -> Val,
+        x: Val,
+    ) -> Result<Result<usize, usize>, Error> {
+        let (mut lo, mut hi) = (0, items.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.compare(&key(&items[mid]), &x)? {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(lo))
+    }
+
+    fn map_insert(&self, map: &mut Vec<(Val, Val)>, k: Val, v: Val) -> Result<(), Error> {
+        match self.binary_search(map, |e| e.0, k)? {
+            Ok(i) => map[i].1 = v,
+            Err(i) => map.insert(i, (k, v)),
+        }
+        Ok(())
+    }
+
+    fn map_lookup(&self, m: MapObject, k: Val) -> Result<Option<Val>, Error> {
+        let map = self.map_of(m)?;
+        Ok(match self.binary_search(&map, |e| e.0, k)? {
+            Ok(i) => Some(map[i].1),
+            Err(_) => None,
+        })
+    }
+}
+
+impl EnvBase for MockEnv {
+    type Error = Error;
+
+    fn error_from_error_val(&self, e: Error) -> Self::Error {
+        e
+    }
+
+    #[cfg(feature = "testutils")]
+    fn escalate_error_to_panic(&self, e: Self::Error) -> ! {
+        panic!("{:?}", e)
+    }
+
+    fn check_same_env(&self, other: &Self) -> Result<(), Self::Error> {
+        if Rc::ptr_eq(&self.objects, &other.objects) {
+            Ok(())
+        } else {
+            Err(err(ScErrorType::Context, ScErrorCode::InternalError))
+        }
+    }
+
+    fn bytes_copy_from_slice(
+        &self,
+        b: BytesObject,
+        b_pos: U32Val,
+        slice: &[u8],
+    ) -> Result<BytesObject, Self::Error> {
+        let mut bytes = self.bytes_of(b)?;
+        copy_from_slice(&mut bytes, u32::from(b_pos) as usize, slice)?;
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_copy_to_slice(
+        &self,
+        b: BytesObject,
+        b_pos: U32Val,
+        slice: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        copy_to_slice(&self.bytes_of(b)?, b_pos, slice)
+    }
+
+    fn string_copy_to_slice(
+        &self,
+        b: StringObject,
+        b_pos: U32Val,
+        slice: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let bytes = self.visit(b, |o| match o {
+            MockObject::String(s) => Some(s.clone()),
+            _ => None,
+        })?;
+        copy_to_slice(&bytes, b_pos, slice)
+    }
+
+    fn symbol_copy_to_slice(
+        &self,
+        b: SymbolObject,
+        b_pos: U32Val,
+        slice: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let bytes = self.visit(b, |o| match o {
+            MockObject::Symbol(s) => Some(s.clone()),
+            _ => None,
+        })?;
+        copy_to_slice(&bytes, b_pos, slice)
+    }
+
+    fn bytes_new_from_slice(&self, slice: &[u8]) -> Result<BytesObject, Self::Error> {
+        self.new_bytes(slice.to_vec())
+    }
+
+    fn string_new_from_slice(&self, slice: &str) -> Result<StringObject, Self::Error> {
+        let obj = self.add(MockObject::String(slice.as_bytes().to_vec()))?;
+        Ok(StringObject::try_from(obj)?)
+    }
+
+    fn symbol_new_from_slice(&self, slice: &str) -> Result<SymbolObject, Self::Error> {
+        check_symbol_bytes(slice.as_bytes())?;
+        let obj = self.add(MockObject::Symbol(slice.as_bytes().to_vec()))?;
+        Ok(SymbolObject::try_from(obj)?)
+    }
+
+    fn map_new_from_slices(&self, keys: &[&str], vals: &[Val]) -> Result<MapObject, Self::Error> {
+        if keys.len() != vals.len() {
+            return Err(err(ScErrorType::Object, ScErrorCode::UnexpectedSize));
+        }
+        let mut map = Vec::with_capacity(keys.len());
+        for (k, v) in keys.iter().zip(vals.iter()) {
+            let k = Symbol::try_from_val(self, k)?;
+            self.map_insert(&mut map, k.to_val(), *v)?;
+        }
+        self.new_map(map)
+    }
+
+    fn map_unpack_to_slice(
+        &self,
+        map: MapObject,
+        keys: &[&str],
+        vals: &mut [Val],
+    ) -> Result<Void, Self::Error> {
+        if keys.len() != vals.len() || self.map_of(map)?.len() != keys.len() {
+            return Err(err(ScErrorType::Object, ScErrorCode::UnexpectedSize));
+        }
+        for (k, v) in keys.iter().zip(vals.iter_mut()) {
+            let k = Symbol::try_from_val(self, k)?;
+            *v = self
+                .map_lookup(map, k.to_val())?
+                .ok_or_else(|| err(ScErrorType::Object, ScErrorCode::MissingValue))?;
+        }
+        Ok(Val::VOID)
+    }
+
+    fn vec_new_from_slice(&self, vals: &[Val]) -> Result<VecObject, Self::Error> {
+        self.new_vec(vals.to_vec())
+    }
+
+    fn vec_unpack_to_slice(&self, vec: VecObject, vals: &mut [Val]) -> Result<Void, Self::Error> {
+        let v = self.vec_of(vec)?;
+        if v.len() != vals.len() {
+            return Err(err(ScErrorType::Object, ScErrorCode::UnexpectedSize));
+        }
+        vals.copy_from_slice(&v);
+        Ok(Val::VOID)
+    }
+
+    fn symbol_index_in_strs(&self, key: Symbol, strs: &[&str]) -> Result<U32Val, Self::Error> {
+        for (i, s) in strs.iter().enumerate() {
+            let sym = Symbol::try_from_val(self, s)?;
+            if self.compare(&sym.to_val(), &key.to_val())? == Ordering::Equal {
+                return u32_len(i);
+            }
+        }
+        Err(err(ScErrorType::Value, ScErrorCode::MissingValue))
+    }
+
+    fn log_from_slice(&self, _msg: &str, _vals: &[Val]) -> Result<Void, Self::Error> {
+        // Diagnostic logging is a no-op in the mock.
+        Ok(Val::VOID)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+/// X-macro use: impl Env for MockEnv
+///////////////////////////////////////////////////////////////////////////////
+
+// This is a callback macro that pattern-matches the token-tree passed by the
+// x-macro (call_macro_with_all_host_functions) and produces two items: the
+// `MockHostFunctions` trait, which has a default method for every host
+// function that fails as unsupported, and the implementation of Env for
+// MockEnv, which forwards every method to `MockHostFunctions`. The functions
+// the mock does support are then provided by overriding the defaults below.
+macro_rules! impl_env_for_mock {
+    {
+        $(
+            $(#[$mod_attr:meta])*
+            mod $mod_id:ident $mod_str:literal
+            {
+                $(
+                    $(#[$fn_attr:meta])*
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident($($arg:ident:$type:ty),*) -> $ret:ty }
+                )*
+            }
+        )*
+    }
+
+    =>
+
+    {
+        #[allow(unused_variables)]
+        trait MockHostFunctions {
+            $(
+                $(
+                    fn $fn_id(&self, $($arg:$type),*) -> Result<$ret, Error> {
+                        Err(unsupported())
+                    }
+                )*
+            )*
+        }
+
+        impl Env for MockEnv {
+            $(
+                $(
+                    fn $fn_id(&self, $($arg:$type),*) -> Result<$ret, Self::Error> {
+                        <Self as MockHostFunctions>::$fn_id(self, $($arg),*)
+                    }
+                )*
+            )*
+        }
+    };
+}
+
+call_macro_with_all_host_functions! { impl_env_for_mock }
+
+impl MockHostFunctions for MockEnv {
+    // Context functions
+
+    fn obj_cmp(&self, a: Val, b: Val) -> Result<i64, Error> {
+        Ok(match self.compare_vals(a, b)? {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        })
+    }
+
+    fn fail_with_error(&self, error: Error) -> Result<Void, Error> {
+        Err(error)
+    }
+
+    // Integer functions
+
+    fn obj_from_u64(&self, v: u64) -> Result<U64Object, Error> {
+        Ok(U64Object::try_from(self.add(MockObject::U64(v))?)?)
+    }
+
+    fn obj_to_u64(&self, obj: U64Object) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::U64(u) => Some(*u),
+            _ => None,
+        })
+    }
+
+    fn obj_from_i64(&self, v: i64) -> Result<I64Object, Error> {
+        Ok(I64Object::try_from(self.add(MockObject::I64(v))?)?)
+    }
+
+    fn obj_to_i64(&self, obj: I64Object) -> Result<i64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::I64(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    fn obj_from_u128_pieces(&self, hi: u64, lo: u64) -> Result<U128Object, Error> {
+        let u = (u128::from(hi) << 64) | u128::from(lo);
+        Ok(U128Object::try_from(self.add(MockObject::U128(u))?)?)
+    }
+
+    fn obj_to_u128_lo64(&self, obj: U128Object) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::U128(u) => Some(*u as u64),
+            _ => None,
+        })
+    }
+
+    fn obj_to_u128_hi64(&self, obj: U128Object) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::U128(u) => Some((*u >> 64) as u64),
+            _ => None,
+        })
+    }
+
+    fn obj_from_i128_pieces(&self, hi: i64, lo: u64) -> Result<I128Object, Error> {
+        let i = (i128::from(hi) << 64) | i128::from(lo);
+        Ok(I128Object::try_from(self.add(MockObject::I128(i))?)?)
+    }
+
+    fn obj_to_i128_lo64(&self, obj: I128Object) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::I128(i) => Some(*i as u64),
+            _ => None,
+        })
+    }
+
+    fn obj_to_i128_hi64(&self, obj: I128Object) -> Result<i64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::I128(i) => Some((*i >> 64) as i64),
+            _ => None,
+        })
+    }
+
+    fn timepoint_obj_from_u64(&self, v: u64) -> Result<TimepointObject, Error> {
+        Ok(TimepointObject::try_from(
+            self.add(MockObject::Timepoint(v))?,
+        )?)
+    }
+
+    fn timepoint_obj_to_u64(&self, obj: TimepointObject) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::Timepoint(u) => Some(*u),
+            _ => None,
+        })
+    }
+
+    fn duration_obj_from_u64(&self, v: u64) -> Result<DurationObject, Error> {
+        Ok(DurationObject::try_from(
+            self.add(MockObject::Duration(v))?,
+        )?)
+    }
+
+    fn duration_obj_to_u64(&self, obj: DurationObject) -> Result<u64, Error> {
+        self.visit(obj, |o| match o {
+            MockObject::Duration(u) => Some(*u),
+            _ => None,
+        })
+    }
+
+    // Map functions
+
+    fn map_new(&self) -> Result<MapObject, Error> {
+        self.new_map(Vec::new())
+    }
+
+    fn map_put(&self, m: MapObject, k: Val, v: Val) -> Result<MapObject, Error> {
+        let mut map = self.map_of(m)?;
+        self.map_insert(&mut map, k, v)?;
+        self.new_map(map)
+    }
+
+    fn map_get(&self, m: MapObject, k: Val) -> Result<Val, Error> {
+        self.map_lookup(m, k)?
+            .ok_or_else(|| err(ScErrorType::Object, ScErrorCode::MissingValue))
+    }
+
+    fn map_del(&self, m: MapObject, k: Val) -> Result<MapObject, Error> {
+        let mut map = self.map_of(m)?;
+        match self.binary_search(&map, |e| e.0, k)? {
+            Ok(i) => {
+                map.remove(i);
+                self.new_map(map)
+            }
+            Err(_) => Err(err(ScErrorType::Object, ScErrorCode::MissingValue)),
+        }
+    }
+
+    fn map_len(&self, m: MapObject) -> Result<U32Val, Error> {
+        u32_len(self.map_of(m)?.len())
+    }
+
+    fn map_has(&self, m: MapObject, k: Val) -> Result<Bool, Error> {
+        Ok(Val::from_bool(self.map_lookup(m, k)?.is_some()))
+    }
+
+    fn map_key_by_pos(&self, m: MapObject, i: U32Val) -> Result<Val, Error> {
+        let map = self.map_of(m)?;
+        map.get(u32::from(i) as usize)
+            .map(|e| e.0)
+            .ok_or_else(index_bounds)
+    }
+
+    fn map_val_by_pos(&self, m: MapObject, i: U32Val) -> Result<Val, Error> {
+        let map = self.map_of(m)?;
+        map.get(u32::from(i) as usize)
+            .map(|e| e.1)
+            .ok_or_else(index_bounds)
+    }
+
+    fn map_keys(&self, m: MapObject) -> Result<VecObject, Error> {
+        let keys = self.map_of(m)?.iter().map(|e| e.0).collect();
+        self.new_vec(keys)
+    }
+
+    fn map_values(&self, m: MapObject) -> Result<VecObject, Error> {
+        let vals = self.map_of(m)?.iter().map(|e| e.1).collect();
+        self.new_vec(vals)
+    }
+
+    fn map_entries(&self, m: MapObject) -> Result<VecObject, Error> {
+        let mut entries = Vec::new();
+        for (k, v) in self.map_of(m)? {
+            entries.push(self.new_vec(alloc::vec![k, v])?.to_val());
+        }
+        self.new_vec(entries)
+    }
+
+    // Vec functions
+
+    fn vec_new(&self) -> Result<VecObject, Error> {
+        self.new_vec(Vec::new())
+    }
+
+    fn vec_put(&self, v: VecObject, i: U32Val, x: Val) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        *vec.get_mut(u32::from(i) as usize)
+            .ok_or_else(index_bounds)? = x;
+        self.new_vec(vec)
+    }
+
+    fn vec_get(&self, v: VecObject, i: U32Val) -> Result<Val, Error> {
+        self.vec_of(v)?
+            .get(u32::from(i) as usize)
+            .copied()
+            .ok_or_else(index_bounds)
+    }
+
+    fn vec_del(&self, v: VecObject, i: U32Val) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        let i = u32::from(i) as usize;
+        if i >= vec.len() {
+            return Err(index_bounds());
+        }
+        vec.remove(i);
+        self.new_vec(vec)
+    }
+
+    fn vec_len(&self, v: VecObject) -> Result<U32Val, Error> {
+        u32_len(self.vec_of(v)?.len())
+    }
+
+    fn vec_push_front(&self, v: VecObject, x: Val) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        vec.insert(0, x);
+        self.new_vec(vec)
+    }
+
+    fn vec_pop_front(&self, v: VecObject) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        if vec.is_empty() {
+            return Err(index_bounds());
+        }
+        vec.remove(0);
+        self.new_vec(vec)
+    }
+
+    fn vec_push_back(&self, v: VecObject, x: Val) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        vec.push(x);
+        self.new_vec(vec)
+    }
+
+    fn vec_pop_back(&self, v: VecObject) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        vec.pop().ok_or_else(index_bounds)?;
+        self.new_vec(vec)
+    }
+
+    fn vec_front(&self, v: VecObject) -> Result<Val, Error> {
+        self.vec_of(v)?.first().copied().ok_or_else(index_bounds)
+    }
+
+    fn vec_back(&self, v: VecObject) -> Result<Val, Error> {
+        self.vec_of(v)?.last().copied().ok_or_else(index_bounds)
+    }
+
+    fn vec_insert(&self, v: VecObject, i: U32Val, x: Val) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v)?;
+        let i = u32::from(i) as usize;
+        if i > vec.len() {
+            return Err(index_bounds());
+        }
+        vec.insert(i, x);
+        self.new_vec(vec)
+    }
+
+    fn vec_append(&self, v1: VecObject, v2: VecObject) -> Result<VecObject, Error> {
+        let mut vec = self.vec_of(v1)?;
+        vec.extend(self.vec_of(v2)?);
+        self.new_vec(vec)
+    }
+
+    fn vec_slice(&self, v: VecObject, start: U32Val, end: U32Val) -> Result<VecObject, Error> {
+        let vec = self.vec_of(v)?;
+        let slice = vec
+            .get(u32::from(start) as usize..u32::from(end) as usize)
+            .ok_or_else(index_bounds)?;
+        self.new_vec(slice.to_vec())
+    }
+
+    fn vec_first_index_of(&self, v: VecObject, x: Val) -> Result<Val, Error> {
+        let vec = self.vec_of(v)?;
+        for (i, e) in vec.iter().enumerate() {
+            if self.compare(e, &x)? == Ordering::Equal {
+                return Ok(u32_len(i)?.to_val());
+            }
+        }
+        Ok(Val::VOID.to_val())
+    }
+
+    fn vec_last_index_of(&self, v: VecObject, x: Val) -> Result<Val, Error> {
+        let vec = self.vec_of(v)?;
+        for (i, e) in vec.iter().enumerate().rev() {
+            if self.compare(e, &x)? == Ordering::Equal {
+                return Ok(u32_len(i)?.to_val());
+            }
+        }
+        Ok(Val::VOID.to_val())
+    }
+
+    fn vec_binary_search(&self, v: VecObject, x: Val) -> Result<u64, Error> {
+        let vec = self.vec_of(v)?;
+        Ok(match self.binary_search(&vec, |e| *e, x)? {
+            Ok(i) => u64::from(u32::from(u32_len(i)?)) | (1 << u32::BITS),
+            Err(i) => u64::from(u32::from(u32_len(i)?)),
+        })
+    }
+
+    fn vec_sort(&self, v: VecObject) -> Result<VecObject, Error> {
+        // Insertion sort keeps this simple and stable; the mock isn't meant
+        // for large inputs.
+        let mut vec = self.vec_of(v)?;
+        for i in 1..vec.len() {
+            let mut j = i;
+            while j > 0 && self.compare(&vec[j], &vec[j - 1])? == Ordering::Less {
+                vec.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+        self.new_vec(vec)
+    }
+
+    // Buf functions
+
+    fn bytes_new(&self) -> Result<BytesObject, Error> {
+        self.new_bytes(Vec::new())
+    }
+
+    fn bytes_put(&self, b: BytesObject, i: U32Val, u: U32Val) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b)?;
+        let u = u8::try_from(u32::from(u))
+            .map_err(|_| err(ScErrorType::Value, ScErrorCode::InvalidInput))?;
+        *bytes
+            .get_mut(u32::from(i) as usize)
+            .ok_or_else(index_bounds)? = u;
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_get(&self, b: BytesObject, i: U32Val) -> Result<U32Val, Error> {
+        self.bytes_of(b)?
+            .get(u32::from(i) as usize)
+            .map(|u| U32Val::from(u32::from(*u)))
+            .ok_or_else(index_bounds)
+    }
+
+    fn bytes_del(&self, b: BytesObject, i: U32Val) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b)?;
+        let i = u32::from(i) as usize;
+        if i >= bytes.len() {
+            return Err(index_bounds());
+        }
+        bytes.remove(i);
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_len(&self, b: BytesObject) -> Result<U32Val, Error> {
+        u32_len(self.bytes_of(b)?.len())
+    }
+
+    fn bytes_push(&self, b: BytesObject, u: U32Val) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b)?;
+        let u = u8::try_from(u32::from(u))
+            .map_err(|_| err(ScErrorType::Value, ScErrorCode::InvalidInput))?;
+        bytes.push(u);
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_pop(&self, b: BytesObject) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b)?;
+        bytes.pop().ok_or_else(index_bounds)?;
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_front(&self, b: BytesObject) -> Result<U32Val, Error> {
+        self.bytes_of(b)?
+            .first()
+            .map(|u| U32Val::from(u32::from(*u)))
+            .ok_or_else(index_bounds)
+    }
+
+    fn bytes_back(&self, b: BytesObject) -> Result<U32Val, Error> {
+        self.bytes_of(b)?
+            .last()
+            .map(|u| U32Val::from(u32::from(*u)))
+            .ok_or_else(index_bounds)
+    }
+
+    fn bytes_insert(&self, b: BytesObject, i: U32Val, u: U32Val) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b)?;
+        let i = u32::from(i) as usize;
+        let u = u8::try_from(u32::from(u))
+            .map_err(|_| err(ScErrorType::Value, ScErrorCode::InvalidInput))?;
+        if i > bytes.len() {
+            return Err(index_bounds());
+        }
+        bytes.insert(i, u);
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_append(&self, b1: BytesObject, b2: BytesObject) -> Result<BytesObject, Error> {
+        let mut bytes = self.bytes_of(b1)?;
+        bytes.extend(self.bytes_of(b2)?);
+        self.new_bytes(bytes)
+    }
+
+    fn bytes_slice(
+        &self,
+        b: BytesObject,
+        start: U32Val,
+        end: U32Val,
+    ) -> Result<BytesObject, Error> {
+        let bytes = self.bytes_of(b)?;
+        let slice = bytes
+            .get(u32::from(start) as usize..u32::from(end) as usize)
+            .ok_or_else(index_bounds)?;
+        self.new_bytes(slice.to_vec())
+    }
+
+    fn string_len(&self, s: StringObject) -> Result<U32Val, Error> {
+        let len = self.visit(s, |o| match o {
+            MockObject::String(s) => Some(s.len()),
+            _ => None,
+        })?;
+        u32_len(len)
+    }
+
+    fn symbol_len(&self, s: SymbolObject) -> Result<U32Val, Error> {
+        let len = self.visit(s, |o| match o {
+            MockObject::Symbol(s) => Some(s.len()),
+            _ => None,
+        })?;
+        u32_len(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TryIntoVal;
+
+    #[test]
+    fn vec_ops() -> Result<(), Error> {
+        let env = MockEnv::default();
+        let v = env.vec_new()?;
+        let v = env.vec_push_back(v, U32Val::from(3).to_val())?;
+        let v = env.vec_push_front(v, U32Val::from(1).to_val())?;
+        let v = env.vec_insert(v, U32Val::from(1), U32Val::from(2).to_val())?;
+        assert_eq!(u32::from(env.vec_len(v)?), 3);
+        let mut vals = [Val::VOID.to_val(); 3];
+        env.vec_unpack_to_slice(v, &mut vals)?;
+        for (i, val) in vals.iter().enumerate() {
+            assert_eq!(u32::from(U32Val::try_from(*val)?), i as u32 + 1);
+        }
+        let found = env.vec_binary_search(v, U32Val::from(2).to_val())?;
+        assert_eq!(found, (1 << u32::BITS) | 1);
+        let missing = env.vec_binary_search(v, U32Val::from(4).to_val())?;
+        assert_eq!(missing, 3);
+        assert!(env.vec_get(v, U32Val::from(3)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn map_keeps_keys_ordered() -> Result<(), Error> {
+        let env = MockEnv::default();
+        let long_key = Symbol::try_from_val(&env, &"a_rather_long_symbol")?.to_val();
+        let small_key = Symbol::try_from_val(&env, &"zzz")?.to_val();
+        assert!(long_key.is_object() && !small_key.is_object());
+        let m = env.map_new()?;
+        let m = env.map_put(m, small_key, U32Val::from(1).to_val())?;
+        let m = env.map_put(m, long_key, U32Val::from(2).to_val())?;
+        let m = env.map_put(m, small_key, U32Val::from(3).to_val())?;
+        assert_eq!(u32::from(env.map_len(m)?), 2);
+        assert_eq!(
+            env.compare(&env.map_key_by_pos(m, U32Val::from(0))?, &long_key)?,
+            Ordering::Equal
+        );
+        assert_eq!(u32::from(U32Val::try_from(env.map_get(m, small_key)?)?), 3);
+        let m = env.map_del(m, long_key)?;
+        assert!(!env.map_has(m, long_key)?.to_val().is_true());
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_and_objects_compare_by_content() -> Result<(), Error> {
+        let env = MockEnv::default();
+        let a = env.bytes_new_from_slice(b"abc")?;
+        let b = env.bytes_push(env.bytes_new_from_slice(b"ab")?, U32Val::from(b'c' as u32))?;
+        assert_eq!(env.obj_cmp(a.to_val(), b.to_val())?, 0);
+        let mut buf = [0u8; 2];
+        env.bytes_copy_to_slice(b, U32Val::from(1), &mut buf)?;
+        assert_eq!(&buf, b"bc");
+
+        let big: Val = u64::MAX.try_into_val(&env)?;
+        let small: Val = 5u64.try_into_val(&env)?;
+        assert!(big.is_object() && !small.is_object());
+        assert_eq!(env.compare(&small, &big)?, Ordering::Less);
+        assert_eq!(u64::try_from_val(&env, &big)?, u64::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_functions_fail() {
+        let env = MockEnv::default();
+        let res = env.get_ledger_sequence();
+        assert_eq!(
+            res.err(),
+            Some(Error::from_type_and_code(
+                ScErrorType::Context,
+                ScErrorCode::InvalidAction
+            ))
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use core::{fmt::Write, panic::PanicInfo};
+
+use super::{EnvBase, Guest};
+
+// Panic messages longer than this are truncated. The buffer lives on the stack
+// since guests don't necessarily have an allocator.
+const PANIC_MESSAGE_BUF_LEN: usize = 256;
+
+struct PanicMessageBuf {
+    buf: [u8; PANIC_MESSAGE_BUF_LEN],
+    len: usize,
+}
+
+impl PanicMessageBuf {
+    fn as_str(&self) -> &str {
+        // Only whole chars are ever written to the buffer, see `write_str`.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for PanicMessageBuf {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            let end = self.len + c.len_utf8();
+            if end > PANIC_MESSAGE_BUF_LEN {
+                // Stop at the last char that fits rather than failing, so
+                // that at least the start of the message is reported.
+                break;
+            }
+            c.encode_utf8(&mut self.buf[self.len..end]);
+            self.len = end;
+        }
+        Ok(())
+    }
+}
+
+/// Reports a panic of the guest to the host, then traps.
+///
+/// In builds with debug assertions enabled, the panic message (along with its
+/// location) is first passed to the host's `log_from_linear_memory` function,
+/// so that it shows up as a diagnostic event next to the `HostError` the trap
+/// turns into, rather than the trap being the only trace of the panic. In
+/// other builds this just traps, keeping the formatting machinery out of the
+/// contract.
+///
+/// Contracts that define their own `#[panic_handler]` can call this from it;
+/// the `panic-handler` feature of this crate defines one that does so.
+pub fn report_panic(info: &PanicInfo) -> ! {
+    #[cfg(debug_assertions)]
+    {
+        let mut msg = PanicMessageBuf {
+            buf: [0; PANIC_MESSAGE_BUF_LEN],
+            len: 0,
+        };
+        let _ = write!(msg, "contract {}", info);
+        // The host fails the logging call rather than returning an error, so
+        // there's nothing to handle here.
+        let _ = Guest.log_from_slice(msg.as_str(), &[]);
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = info;
+    core::arch::wasm32::unreachable()
+}
+
+#[cfg(feature = "panic-handler")]
+#[panic_handler]
+fn handle_panic(info: &PanicInfo) -> ! {
+    report_panic(info)
+}
@@ -0,0 +1,150 @@
+// Run this with
+// $ cargo bench --features wasmi,testutils --bench calibrate -- --nocapture
+// This fits linear models to the worst-case measurements of every
+// `ContractCostType` and prints the resulting cost parameters both as a Rust
+// table (in the form used by `BudgetImpl::default`) and as hex-encoded
+// `ContractCostParams` XDR blobs, ready to be used in a network config upgrade.
+// Cost types that are not measured keep their currently compiled-in values.
+//
+// Pass `--compare` to instead print how the fitted parameters differ from the
+// compiled-in defaults:
+// $ cargo bench --features wasmi,testutils --bench calibrate -- --compare --nocapture
+// As with the other benches, you can optionally list the `ContractCostType`s to
+// calibrate, e.g.
+// $ cargo bench --features wasmi,testutils --bench calibrate -- VecNew --compare --nocapture
+mod common;
+use common::*;
+use soroban_env_host::{
+    budget::Budget,
+    xdr::{ContractCostParamEntry, ContractCostParams, ContractCostType, ExtensionPoint, WriteXdr},
+};
+use std::{collections::BTreeMap, io::Write};
+use tabwriter::{Alignment, TabWriter};
+
+struct Calibrate;
+impl Benchmark for Calibrate {
+    fn bench<HCM: HostCostMeasurement>() -> std::io::Result<(FPCostModel, FPCostModel)> {
+        let mut measurements = measure_worst_case_costs::<HCM>(1..20)?;
+        measurements.preprocess();
+        let cpu_model = measurements.fit_model_to_cpu();
+        let mem_model = measurements.fit_model_to_mem();
+        Ok((cpu_model, mem_model))
+    }
+}
+
+fn to_io_error<E: std::fmt::Debug>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e))
+}
+
+fn fitted_entry(model: &FPCostModel) -> ContractCostParamEntry {
+    let (const_term, linear_term) = model.params_as_u64();
+    ContractCostParamEntry {
+        ext: ExtensionPoint::V0,
+        const_term: const_term as i64,
+        linear_term: linear_term as i64,
+    }
+}
+
+// Overlays the fitted models on top of the default parameters, so that cost
+// types without a measurement keep their current values.
+fn apply_fitted_params(
+    defaults: &ContractCostParams,
+    params: &BTreeMap<ContractCostType, (FPCostModel, FPCostModel)>,
+    select: impl Fn(&(FPCostModel, FPCostModel)) -> &FPCostModel,
+) -> std::io::Result<ContractCostParams> {
+    let mut entries = defaults.0.to_vec();
+    for (ty, models) in params.iter() {
+        entries[*ty as usize] = fitted_entry(select(models));
+    }
+    Ok(ContractCostParams(entries.try_into().map_err(to_io_error)?))
+}
+
+fn write_rust_table(dim: &str, params: &ContractCostParams) {
+    println!();
+    for (ty, entry) in ContractCostType::variants().iter().zip(params.0.iter()) {
+        println!(
+            "ContractCostType::{:?} => {{ {}.const_term = {}; {}.lin_term = ScaledU64({}); }}",
+            ty, dim, entry.const_term, dim, entry.linear_term
+        );
+    }
+}
+
+fn write_xdr_blob(dim: &str, params: &ContractCostParams) -> std::io::Result<()> {
+    let xdr = params.to_xdr().map_err(to_io_error)?;
+    println!();
+    println!("{} cost params XDR: {}", dim, hex::encode(xdr));
+    Ok(())
+}
+
+fn relative_change(old: i64, new: i64) -> String {
+    if old == new {
+        "".to_string()
+    } else if old == 0 {
+        "new".to_string()
+    } else {
+        format!("{:+.1}%", (new - old) as f64 * 100.0 / old as f64)
+    }
+}
+
+fn write_comparison(
+    tw: &mut TabWriter<Vec<u8>>,
+    dim: &str,
+    defaults: &ContractCostParams,
+    fitted: &ContractCostParams,
+    params: &BTreeMap<ContractCostType, (FPCostModel, FPCostModel)>,
+) -> std::io::Result<()> {
+    writeln!(tw)?;
+    writeln!(
+        tw,
+        "cost_type\t{dim}_const_default\t{dim}_const_fitted\t{dim}_const_change\t{dim}_lin_default\t{dim}_lin_fitted\t{dim}_lin_change"
+    )?;
+    for ty in params.keys() {
+        let old = &defaults.0[*ty as usize];
+        let new = &fitted.0[*ty as usize];
+        writeln!(
+            tw,
+            "{:?}\t{}\t{}\t{}\t{}\t{}\t{}",
+            ty,
+            old.const_term,
+            new.const_term,
+            relative_change(old.const_term, new.const_term),
+            old.linear_term,
+            new.linear_term,
+            relative_change(old.linear_term, new.linear_term)
+        )?;
+    }
+    tw.flush()
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+fn main() -> std::io::Result<()> {
+    let compare = std::env::args().any(|arg| arg == "--compare");
+    let params = for_each_host_cost_measurement::<Calibrate>()?;
+
+    let (cpu_defaults, mem_defaults) = Budget::default().get_cost_params().map_err(to_io_error)?;
+    let cpu_fitted = apply_fitted_params(&cpu_defaults, &params, |(cpu, _)| cpu)?;
+    let mem_fitted = apply_fitted_params(&mem_defaults, &params, |(_, mem)| mem)?;
+
+    if compare {
+        for (dim, defaults, fitted) in [
+            ("cpu", &cpu_defaults, &cpu_fitted),
+            ("mem", &mem_defaults, &mem_fitted),
+        ] {
+            let mut tw = TabWriter::new(vec![])
+                .padding(5)
+                .alignment(Alignment::Right);
+            write_comparison(&mut tw, dim, defaults, fitted, &params)?;
+            println!(
+                "{}",
+                String::from_utf8(tw.into_inner().map_err(to_io_error)?).map_err(to_io_error)?
+            );
+        }
+        return Ok(());
+    }
+
+    write_rust_table("cpu", &cpu_fitted);
+    write_rust_table("mem", &mem_fitted);
+    write_xdr_blob("cpu", &cpu_fitted)?;
+    write_xdr_blob("mem", &mem_fitted)?;
+    Ok(())
+}
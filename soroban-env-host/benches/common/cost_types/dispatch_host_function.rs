@@ -0,0 +1,25 @@
+use std::marker::PhantomData;
+
+use crate::common::{HostCostMeasurement, InvokeVmFunctionMeasure};
+use rand::rngs::StdRng;
+use soroban_env_host::{
+    cost_runner::dispatch_host_function::{DispatchHostFunctionRun, DispatchSample},
+    Host,
+};
+
+// Measures the cost of dispatching a call from the VM to one particular host
+// function, given by `R`, one of the runners in
+// `cost_runner::dispatch_host_function`, with well-typed arguments for it.
+// Same as `InvokeHostFunctionMeasure`, both cpu and mem costs should be
+// constant.
+pub(crate) struct DispatchHostFunctionMeasure<R>(PhantomData<R>);
+
+impl<R: DispatchHostFunctionRun> HostCostMeasurement for DispatchHostFunctionMeasure<R> {
+    type Runner = R;
+
+    fn new_random_case(host: &Host, rng: &mut StdRng, input: u64) -> DispatchSample {
+        let vm = InvokeVmFunctionMeasure::new_random_case(host, rng, input);
+        let args = R::sample_args(host).unwrap();
+        DispatchSample { vm, args }
+    }
+}
@@ -20,4 +20,16 @@ impl HostCostMeasurement for MapEntryMeasure {
         keys.shuffle(rng);
         MapEntrySample { map, keys }
     }
+
+    // The worst case is keys that are as expensive as possible to compare:
+    // small symbols of the maximum length that all share a long prefix, so
+    // that every comparison made by the lookup walks most of their chars.
+    fn new_worst_case(host: &Host, rng: &mut StdRng, input: u64) -> MapEntrySample {
+        let input = 1 + input * Self::STEP_SIZE;
+        let mut keys: Vec<_> = util::long_prefix_symbols(input as u32).collect();
+        let om = keys.iter().cloned().zip(keys.iter().cloned()).collect();
+        let map: MeteredOrdMap<_, _, _> = MeteredOrdMap::from_map(om, host).unwrap();
+        keys.shuffle(rng);
+        MapEntrySample { map, keys }
+    }
 }
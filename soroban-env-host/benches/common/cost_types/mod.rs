@@ -3,6 +3,7 @@ mod compute_ecdsa_secp256k1_sig;
 mod compute_ed25519_pubkey;
 mod compute_keccak256_hash;
 mod compute_sha256_hash;
+mod dispatch_host_function;
 mod host_mem_alloc;
 mod host_mem_cmp;
 mod host_mem_cpy;
@@ -24,6 +25,7 @@ pub(crate) use compute_ecdsa_secp256k1_sig::*;
 pub(crate) use compute_ed25519_pubkey::*;
 pub(crate) use compute_keccak256_hash::*;
 pub(crate) use compute_sha256_hash::*;
+pub(crate) use dispatch_host_function::*;
 pub(crate) use host_mem_alloc::*;
 pub(crate) use host_mem_cmp::*;
 pub(crate) use host_mem_cpy::*;
@@ -16,4 +16,13 @@ impl HostCostMeasurement for ValDeserMeasure {
         let scval = ValSerMeasure::new_random_case(host, rng, input);
         scval.0.to_xdr().unwrap()
     }
+
+    fn new_worst_case(
+        host: &soroban_env_host::Host,
+        rng: &mut rand::prelude::StdRng,
+        input: u64,
+    ) -> Vec<u8> {
+        let scval = ValSerMeasure::new_worst_case(host, rng, input);
+        scval.0.to_xdr().unwrap()
+    }
 }
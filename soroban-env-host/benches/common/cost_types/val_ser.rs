@@ -1,6 +1,10 @@
 use crate::common::HostCostMeasurement;
 use rand::{rngs::StdRng, RngCore};
-use soroban_env_host::{cost_runner::ValSerRun, xdr::ScVal, Host};
+use soroban_env_host::{
+    cost_runner::ValSerRun,
+    xdr::{ScVal, ScVec, WriteXdr},
+    Host,
+};
 
 pub(crate) struct ValSerMeasure;
 
@@ -15,4 +19,24 @@ impl HostCostMeasurement for ValSerMeasure {
         let v = ScVal::Bytes(buf.try_into().unwrap());
         (v, Vec::default())
     }
+
+    // The worst case is the same bytes nested in as many vecs as the host's
+    // XDR depth limit allows, which costs more per byte than a flat value.
+    fn new_worst_case(host: &Host, rng: &mut StdRng, input: u64) -> (ScVal, Vec<u8>) {
+        let (v, buf) = Self::new_random_case(host, rng, input);
+        (nest_to_max_depth(host, v), buf)
+    }
+}
+
+// Wraps `v` in single-element vecs for as long as the result still reads back
+// within the host's XDR depth limit.
+fn nest_to_max_depth(host: &Host, mut v: ScVal) -> ScVal {
+    loop {
+        let wrapped = ScVal::Vec(Some(ScVec(vec![v.clone()].try_into().unwrap())));
+        let bytes = wrapped.to_xdr().unwrap();
+        if host.metered_from_xdr::<ScVal>(&bytes).is_err() {
+            return v;
+        }
+        v = wrapped;
+    }
 }
@@ -419,6 +419,34 @@ pub fn measure_worst_case_costs<HCM: HostCostMeasurement>(
     })
 }
 
+// Runs a single worst-case sample with the given input and returns what the
+// host charged the budget for `HCM`'s cost type, according to the current cost
+// models rather than to the hardware. This is only good for sanity-checking the
+// measurement code (see the `smoke_calibration` bench), not for calibrating.
+pub fn measure_charged_costs<HCM: HostCostMeasurement>(input: u64) -> Measurement {
+    let mut rng = StdRng::from_seed([0xff; 32]);
+    let host = crate::common::util::test_host();
+    let sample = HCM::new_worst_case(&host, &mut rng, input);
+    let mut recycled_samples = Vec::with_capacity(1);
+    host.as_budget().reset_unlimited().unwrap();
+    HCM::run(&host, vec![sample], &mut recycled_samples);
+    let ty = <HCM::Runner as CostRunner>::COST_TYPE;
+    let charged = host
+        .as_budget()
+        .cost_breakdown()
+        .unwrap()
+        .into_iter()
+        .find(|b| b.cost_type == ty)
+        .unwrap();
+    Measurement {
+        iterations: charged.iterations,
+        inputs: charged.input,
+        cpu_insns: charged.cpu_insns,
+        mem_bytes: charged.mem_bytes,
+        time_nsecs: 0,
+    }
+}
+
 pub fn measure_cost_variation<HCM: HostCostMeasurement>(
     large_input: u64,
 ) -> Result<Measurements, std::io::Error> {
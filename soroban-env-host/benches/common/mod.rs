@@ -10,7 +10,8 @@ pub use measure::*;
 pub use modelfit::*;
 
 use soroban_env_host::{
-    cost_runner::{CostRunner, WasmInsnType},
+    call_macro_with_all_host_functions,
+    cost_runner::{dispatch_host_function, CostRunner, WasmInsnType},
     xdr::ContractCostType,
 };
 use std::collections::BTreeMap;
@@ -49,6 +50,14 @@ fn should_run_wasm_insn(ty: WasmInsnType) -> bool {
     }
 }
 
+fn should_run_host_function_dispatch(fn_name: &str) -> bool {
+    if let Some(bench_names) = get_explicit_bench_names() {
+        bench_names.iter().any(|arg| *arg == fn_name)
+    } else {
+        true
+    }
+}
+
 fn call_bench<B: Benchmark, HCM: HostCostMeasurement>(
     params: &mut BTreeMap<ContractCostType, (FPCostModel, FPCostModel)>,
 ) -> std::io::Result<()> {
@@ -171,6 +180,43 @@ run_wasm_insn_measurement!(
     WasmI64RotrMeasure
 );
 
+// Generates `for_each_host_function_dispatch_measurement` from the host
+// function x-macro, so that every host function gets its dispatch measured.
+// The results are keyed by `module::function`, e.g. `vec::vec_contains`.
+macro_rules! run_host_function_dispatch_measurement {
+    {
+        $(
+            $(#[$mod_attr:meta])*
+            mod $mod_id:ident $mod_str:literal
+            {
+                $(
+                    $(#[$fn_attr:meta])*
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident $args:tt -> $ret:ty }
+                )*
+            }
+        )*
+    }
+    => {
+        pub(crate) fn for_each_host_function_dispatch_measurement<B: Benchmark>() -> std::io::Result<BTreeMap<String, (FPCostModel, FPCostModel)>> {
+            let mut params: BTreeMap<String, (FPCostModel, FPCostModel)> = BTreeMap::new();
+            $(
+                $(
+                    let name = concat!(stringify!($mod_id), "::", stringify!($fn_id));
+                    if should_run_host_function_dispatch(stringify!($fn_id)) {
+                        eprintln!("\nMeasuring dispatch costs for {}\n", name);
+                        params.insert(
+                            name.to_string(),
+                            B::bench::<DispatchHostFunctionMeasure<dispatch_host_function::$mod_id::$fn_id>>()?,
+                        );
+                    }
+                )*
+            )*
+            Ok(params)
+        }
+    };
+}
+call_macro_with_all_host_functions! { run_host_function_dispatch_measurement }
+
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub(crate) enum WasmInsnTier {
     BASE = 0,
@@ -1,4 +1,4 @@
-use soroban_env_host::{budget::AsBudget, meta, Host, LedgerInfo, Val, I256};
+use soroban_env_host::{budget::AsBudget, meta, Host, LedgerInfo, SymbolSmall, Val, I256};
 
 pub(crate) fn test_host() -> Host {
     let host = Host::default();
@@ -29,6 +29,24 @@ pub(crate) fn to_rawval_u32<I: Iterator<Item = u32>>(vals: I) -> impl Iterator<I
     vals.map(move |v| Val::from_u32(v).into())
 }
 
+// Symbol chars in ascending order, so that symbols of equal length order the
+// same as the numbers they're generated from.
+const SYMBOL_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz";
+
+/// Returns `n` distinct, ascending small symbols of the maximum length, that
+/// differ only in their last 3 chars.
+pub(crate) fn long_prefix_symbols(n: u32) -> impl Iterator<Item = Val> {
+    let base = SYMBOL_CHARS.len() as u32;
+    assert!(n <= base.pow(3));
+    (0..n).map(move |i| {
+        let mut s = *b"zzzzzz000";
+        s[6] = SYMBOL_CHARS[(i / base / base) as usize];
+        s[7] = SYMBOL_CHARS[(i / base % base) as usize];
+        s[8] = SYMBOL_CHARS[(i % base) as usize];
+        SymbolSmall::try_from_bytes(&s).unwrap().into()
+    })
+}
+
 pub(crate) fn repeating_byte_i256(byte: u8, input: u64) -> I256 {
     let buf: Vec<u8> = (0..input.min(32)).map(|_| byte).collect();
     let mut res = vec![0u8; 32 - input as usize];
@@ -0,0 +1,212 @@
+// Run this with
+// $ cargo bench --features wasmi,testutils --bench dispatch_marshalling -- --nocapture
+// This measures the cost of marshalling small values -- u32s, i32s, bools and
+// the like, which fit in a `Val` without a host object -- across the VM/host
+// boundary. Every argument and return value of a host function call from a
+// contract is marshalled, so this is a fixed overhead of every such call.
+//
+// The first table times the raw `WasmiMarshal` conversions of each type in
+// isolation. The second times contracts that do nothing but call a host
+// function on small values, so the time per call is dominated by dispatch and
+// marshalling. Compare the numbers before and after a change to the dispatch
+// path to see its effect.
+//
+// As with the other benches, you can optionally list the cases to run, e.g.
+// $ cargo bench --features wasmi,testutils --bench dispatch_marshalling -- obj_cmp_u32 --nocapture
+use soroban_env_host::{
+    budget::AsBudget,
+    storage::{SnapshotSource, Storage},
+    xdr::{
+        AccountId, LedgerEntry, LedgerKey, PublicKey, ScAddress, ScErrorCode, ScErrorType, ScVal,
+        Uint256,
+    },
+    AddressObject, Bool, Env, EnvBase, Error, Host, HostError, I32Val, LedgerInfo, Symbol,
+    TryFromVal, U32Val, Val, WasmiMarshal,
+};
+use soroban_synth_wasm::{Arity, ModEmitter, Operand};
+use std::{hint::black_box, io::Write, rc::Rc, time::Instant};
+use tabwriter::{Alignment, TabWriter};
+
+// Conversions per type in the first table.
+const MARSHALS: u64 = 10_000_000;
+// Host function calls per invocation, and invocations per case, in the
+// second table.
+const CALLS_PER_INVOCATION: usize = 1000;
+const INVOCATIONS: usize = 100;
+
+fn to_io_error<E: std::fmt::Debug>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e))
+}
+
+struct EmptySnapshotSource;
+
+impl SnapshotSource for EmptySnapshotSource {
+    fn get(&self, _key: &Rc<LedgerKey>) -> Result<(Rc<LedgerEntry>, Option<u32>), HostError> {
+        Err(Error::from_type_and_code(ScErrorType::Storage, ScErrorCode::MissingValue).into())
+    }
+
+    fn has(&self, _key: &Rc<LedgerKey>) -> Result<bool, HostError> {
+        Ok(false)
+    }
+}
+
+fn should_run(name: &str) -> bool {
+    let bare_args: Vec<_> = std::env::args().filter(|x| !x.starts_with('-')).collect();
+    bare_args.len() <= 1 || bare_args[1..].iter().any(|arg| arg == name)
+}
+
+// Returns the average time in nanoseconds of marshalling `v` out to a
+// `wasmi::Value` and back.
+fn time_marshal_roundtrip<T: WasmiMarshal + Copy>(v: T) -> f64 {
+    let start = Instant::now();
+    for _ in 0..MARSHALS {
+        let value = black_box(v).marshal_from_self();
+        black_box(T::try_marshal_from_value(black_box(value)));
+    }
+    start.elapsed().as_nanos() as f64 / MARSHALS as f64
+}
+
+fn marshal_cases() -> Vec<(&'static str, Box<dyn Fn() -> f64>)> {
+    vec![
+        (
+            "u32_val",
+            Box::new(|| time_marshal_roundtrip(U32Val::from(7))),
+        ),
+        (
+            "i32_val",
+            Box::new(|| time_marshal_roundtrip(I32Val::from(-7))),
+        ),
+        (
+            "bool",
+            Box::new(|| time_marshal_roundtrip(Bool::from(true))),
+        ),
+        ("void", Box::new(|| time_marshal_roundtrip(Val::VOID))),
+        (
+            "error",
+            Box::new(|| {
+                time_marshal_roundtrip(Error::from_type_and_code(
+                    ScErrorType::Context,
+                    ScErrorCode::InvalidInput,
+                ))
+            }),
+        ),
+        (
+            "val_u32",
+            Box::new(|| time_marshal_roundtrip(Val::from(7_u32))),
+        ),
+        (
+            "val_bool",
+            Box::new(|| time_marshal_roundtrip(Val::from(true))),
+        ),
+    ]
+}
+
+// A contract with a single function `test` that calls `obj_cmp` on `a` and
+// `b` `CALLS_PER_INVOCATION` times.
+fn obj_cmp_contract(a: Val, b: Val) -> Vec<u8> {
+    let mut fe = ModEmitter::new().func(Arity(0), 0);
+    for _ in 0..CALLS_PER_INVOCATION {
+        fe.obj_cmp(
+            Operand::Const64(a.get_payload() as i64),
+            Operand::Const64(b.get_payload() as i64),
+        );
+        fe.drop();
+    }
+    fe.push(Symbol::try_from_small_str("pass").unwrap());
+    fe.finish_and_export("test").finish()
+}
+
+fn new_host() -> Result<Host, HostError> {
+    let storage = Storage::with_recording_footprint(Rc::new(EmptySnapshotSource));
+    let host = Host::with_storage_and_budget(storage, Default::default());
+    host.set_ledger_info(LedgerInfo {
+        protocol_version: soroban_env_host::meta::get_ledger_protocol_version(
+            soroban_env_host::meta::INTERFACE_VERSION,
+        ),
+        sequence_number: 1234,
+        timestamp: 1234,
+        network_id: [7; 32],
+        base_reserve: 1,
+        min_persistent_entry_expiration: 4096,
+        min_temp_entry_expiration: 16,
+        max_entry_expiration: 6_312_000,
+    })?;
+    host.switch_to_recording_auth(true)?;
+    Ok(host)
+}
+
+fn address_obj(host: &Host, address: ScAddress) -> Result<AddressObject, HostError> {
+    let val = Val::try_from_val(host, &ScVal::Address(address))?;
+    Ok(AddressObject::try_from_val(host, &val)?)
+}
+
+fn register_wasm(host: &Host, wasm: &[u8]) -> Result<AddressObject, HostError> {
+    let wasm_hash = host.upload_wasm(host.bytes_new_from_slice(wasm)?)?;
+    let deployer = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([1; 32])));
+    host.create_contract(
+        address_obj(host, ScAddress::Account(deployer))?,
+        wasm_hash,
+        host.bytes_new_from_slice(&[0; 32])?,
+    )
+}
+
+// Returns the average time in nanoseconds of a call from the contract to the
+// host, including marshalling its arguments and result.
+fn time_host_calls(a: Val, b: Val) -> Result<f64, HostError> {
+    let host = new_host()?;
+    let contract = register_wasm(&host, &obj_cmp_contract(a, b))?;
+    let func = Symbol::try_from_val(&host, &"test")?;
+    let args = host.vec_new()?;
+    // Warm up, so that the first invocation's one-off costs don't count.
+    host.call(contract, func, args)?;
+    let mut total_nsecs = 0;
+    for _ in 0..INVOCATIONS {
+        host.as_budget().reset_unlimited()?;
+        let start = Instant::now();
+        host.call(contract, func, args)?;
+        total_nsecs += start.elapsed().as_nanos();
+    }
+    Ok(total_nsecs as f64 / (INVOCATIONS * CALLS_PER_INVOCATION) as f64)
+}
+
+fn host_call_cases() -> Vec<(&'static str, Val, Val)> {
+    vec![
+        ("obj_cmp_u32", Val::from(1_u32), Val::from(2_u32)),
+        ("obj_cmp_i32", Val::from(-1_i32), Val::from(2_i32)),
+        ("obj_cmp_bool", Val::from(true), Val::from(false)),
+        ("obj_cmp_void", Val::VOID.into(), Val::VOID.into()),
+    ]
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+fn main() -> std::io::Result<()> {
+    let mut tw = TabWriter::new(vec![])
+        .padding(5)
+        .alignment(Alignment::Right);
+    writeln!(tw, "marshal_roundtrip\tcount\tnsecs_per_roundtrip")?;
+    for (name, run) in marshal_cases() {
+        if should_run(name) {
+            writeln!(tw, "{}\t{}\t{:.3}", name, MARSHALS, run())?;
+        }
+    }
+    writeln!(tw)?;
+    writeln!(tw, "host_call\tcount\tnsecs_per_call")?;
+    for (name, a, b) in host_call_cases() {
+        if should_run(name) {
+            let nsecs = time_host_calls(a, b).map_err(to_io_error)?;
+            writeln!(
+                tw,
+                "{}\t{}\t{:.1}",
+                name,
+                INVOCATIONS * CALLS_PER_INVOCATION,
+                nsecs
+            )?;
+        }
+    }
+    tw.flush()?;
+    println!(
+        "{}",
+        String::from_utf8(tw.into_inner().map_err(to_io_error)?).map_err(to_io_error)?
+    );
+    Ok(())
+}
@@ -0,0 +1,297 @@
+// Run this with
+// $ cargo bench --features wasmi,testutils --bench invocation_throughput -- --nocapture
+// This measures end-to-end contract invocation throughput, as opposed to the
+// isolated cost types measured by the other benches. Every scenario sets up a
+// host with a representative contract and then repeatedly invokes it through
+// `Host::call`, so the numbers include frame setup, argument conversion,
+// dispatch, metering and the contract's own work.
+//
+// For each scenario it reports the achieved invocations per second, the
+// metered cost of an invocation, and how well the metered cpu instructions
+// track wall-clock time (their ratio, and their correlation across
+// invocations). A throughput regression with unchanged metered costs points at
+// the dispatch path; a drop in the correlation points at metering that no
+// longer reflects the actual work done.
+//
+// As with the other benches, you can optionally list the scenarios to run, e.g.
+// $ cargo bench --features wasmi,testutils --bench invocation_throughput -- token_transfer --nocapture
+use soroban_env_host::{
+    budget::AsBudget,
+    storage::{SnapshotSource, Storage},
+    xdr::{
+        AccountEntry, AccountEntryExt, AccountId, AlphaNum4, Asset, AssetCode4, Hash, LedgerEntry,
+        LedgerEntryData, LedgerEntryExt, LedgerKey, LedgerKeyAccount, PublicKey, ScAddress,
+        ScErrorCode, ScErrorType, ScVal, SequenceNumber, Thresholds, Uint256, WriteXdr,
+    },
+    AddressObject, Env, EnvBase, Error, Host, HostError, LedgerInfo, Symbol, TryFromVal, Val,
+    VecObject,
+};
+use std::{io::Write, rc::Rc, time::Instant};
+use tabwriter::{Alignment, TabWriter};
+
+const INVOCATIONS: usize = 1000;
+
+fn to_io_error<E: std::fmt::Debug>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e))
+}
+
+struct EmptySnapshotSource;
+
+impl SnapshotSource for EmptySnapshotSource {
+    fn get(&self, _key: &Rc<LedgerKey>) -> Result<(Rc<LedgerEntry>, Option<u32>), HostError> {
+        Err(Error::from_type_and_code(ScErrorType::Storage, ScErrorCode::MissingValue).into())
+    }
+
+    fn has(&self, _key: &Rc<LedgerKey>) -> Result<bool, HostError> {
+        Ok(false)
+    }
+}
+
+// A contract invocation to be repeated, along with the host it runs on.
+struct Scenario {
+    name: &'static str,
+    host: Host,
+    contract: AddressObject,
+    func: Symbol,
+    args: VecObject,
+}
+
+struct Measurement {
+    cpu_insns: u64,
+    mem_bytes: u64,
+    time_nsecs: u64,
+}
+
+fn new_host() -> Result<Host, HostError> {
+    let storage = Storage::with_recording_footprint(Rc::new(EmptySnapshotSource));
+    let host = Host::with_storage_and_budget(storage, Default::default());
+    host.set_ledger_info(LedgerInfo {
+        protocol_version: soroban_env_host::meta::get_ledger_protocol_version(
+            soroban_env_host::meta::INTERFACE_VERSION,
+        ),
+        sequence_number: 1234,
+        timestamp: 1234,
+        network_id: [7; 32],
+        base_reserve: 1,
+        min_persistent_entry_expiration: 4096,
+        min_temp_entry_expiration: 16,
+        max_entry_expiration: 6_312_000,
+    })?;
+    // Recording auth lets the scenarios skip building signed payloads, while
+    // still exercising the authorization bookkeeping.
+    host.switch_to_recording_auth(true)?;
+    Ok(host)
+}
+
+fn account_id(seed: u8) -> AccountId {
+    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([seed; 32])))
+}
+
+fn address_obj(host: &Host, address: ScAddress) -> Result<AddressObject, HostError> {
+    let val = Val::try_from_val(host, &ScVal::Address(address))?;
+    Ok(AddressObject::try_from_val(host, &val)?)
+}
+
+fn register_wasm(host: &Host, wasm: &[u8]) -> Result<AddressObject, HostError> {
+    let wasm_hash = host.upload_wasm(host.bytes_new_from_slice(wasm)?)?;
+    host.create_contract(
+        address_obj(host, ScAddress::Account(account_id(1)))?,
+        wasm_hash,
+        host.bytes_new_from_slice(&[0; 32])?,
+    )
+}
+
+fn wasm_scenario(
+    name: &'static str,
+    wasm: &[u8],
+    func: &str,
+    args: &[Val],
+) -> Result<Scenario, HostError> {
+    let host = new_host()?;
+    let contract = register_wasm(&host, wasm)?;
+    let func = Symbol::try_from_val(&host, &func)?;
+    let args = host.vec_new_from_slice(args)?;
+    Ok(Scenario {
+        name,
+        host,
+        contract,
+        func,
+        args,
+    })
+}
+
+// Transfers between two contract-owned balances of a Stellar asset contract,
+// which don't need any trustlines.
+fn token_transfer_scenario() -> Result<Scenario, HostError> {
+    let host = new_host()?;
+    let issuer = account_id(2);
+    host.add_ledger_entry(
+        &Rc::new(LedgerKey::Account(LedgerKeyAccount {
+            account_id: issuer.clone(),
+        })),
+        &Rc::new(LedgerEntry {
+            last_modified_ledger_seq: 0,
+            data: LedgerEntryData::Account(AccountEntry {
+                account_id: issuer.clone(),
+                balance: 0,
+                seq_num: SequenceNumber(0),
+                num_sub_entries: 0,
+                inflation_dest: None,
+                flags: 0,
+                home_domain: Default::default(),
+                thresholds: Thresholds([1, 0, 0, 0]),
+                signers: Default::default(),
+                ext: AccountEntryExt::V0,
+            }),
+            ext: LedgerEntryExt::V0,
+        }),
+        None,
+    )?;
+    let asset = Asset::CreditAlphanum4(AlphaNum4 {
+        asset_code: AssetCode4(*b"BNCH"),
+        issuer,
+    });
+    let asset_xdr = asset.to_xdr().map_err(|_| {
+        HostError::from(Error::from_type_and_code(
+            ScErrorType::Value,
+            ScErrorCode::InvalidInput,
+        ))
+    })?;
+    let token = host.create_asset_contract(host.bytes_new_from_slice(&asset_xdr)?)?;
+
+    let from = address_obj(&host, ScAddress::Contract(Hash([3; 32])))?;
+    let to = address_obj(&host, ScAddress::Contract(Hash([4; 32])))?;
+    let mint_args = host.vec_new_from_slice(&[
+        from.to_val(),
+        Val::try_from_val(&host, &(i64::MAX as i128))?,
+    ])?;
+    host.call(token, Symbol::try_from_val(&host, &"mint")?, mint_args)?;
+
+    let args = host.vec_new_from_slice(&[
+        from.to_val(),
+        to.to_val(),
+        Val::try_from_val(&host, &1_i128)?,
+    ])?;
+    let func = Symbol::try_from_val(&host, &"transfer")?;
+    Ok(Scenario {
+        name: "token_transfer",
+        host,
+        contract: token,
+        func,
+        args,
+    })
+}
+
+fn all_scenarios() -> Result<Vec<Scenario>, HostError> {
+    Ok(vec![
+        token_transfer_scenario()?,
+        // Builds a UDT (thus a map), vectors and bytes, hashes, logs, emits
+        // an event and writes contract data.
+        wasm_scenario("complex", soroban_test_wasms::COMPLEX, "go", &[])?,
+        // Does next to no work, so mostly measures the dispatch overhead.
+        wasm_scenario(
+            "add_i32",
+            soroban_test_wasms::ADD_I32,
+            "add",
+            &[Val::from(1_i32), Val::from(2_i32)],
+        )?,
+    ])
+}
+
+fn should_run(scenario: &Scenario) -> bool {
+    let bare_args: Vec<_> = std::env::args().filter(|x| !x.starts_with('-')).collect();
+    bare_args.len() <= 1 || bare_args[1..].iter().any(|arg| arg == scenario.name)
+}
+
+fn measure(scenario: &Scenario) -> Result<Vec<Measurement>, HostError> {
+    let budget = scenario.host.as_budget();
+    let mut measurements = Vec::with_capacity(INVOCATIONS);
+    for _ in 0..INVOCATIONS {
+        budget.reset_unlimited()?;
+        let start = Instant::now();
+        scenario
+            .host
+            .call(scenario.contract, scenario.func, scenario.args)?;
+        let time_nsecs = start.elapsed().as_nanos() as u64;
+        measurements.push(Measurement {
+            cpu_insns: budget.get_cpu_insns_consumed()?,
+            mem_bytes: budget.get_mem_bytes_consumed()?,
+            time_nsecs,
+        });
+    }
+    Ok(measurements)
+}
+
+fn mean(xs: impl Iterator<Item = f64> + Clone) -> f64 {
+    let n = xs.clone().count().max(1) as f64;
+    xs.sum::<f64>() / n
+}
+
+// Pearson correlation coefficient between the metered cpu instructions and
+// the wall-clock time of the individual invocations.
+fn cpu_time_correlation(measurements: &[Measurement]) -> f64 {
+    let cpu = measurements.iter().map(|m| m.cpu_insns as f64);
+    let time = measurements.iter().map(|m| m.time_nsecs as f64);
+    let (cpu_mean, time_mean) = (mean(cpu.clone()), mean(time.clone()));
+    let (mut cov, mut cpu_var, mut time_var) = (0.0, 0.0, 0.0);
+    for (c, t) in cpu.zip(time) {
+        cov += (c - cpu_mean) * (t - time_mean);
+        cpu_var += (c - cpu_mean).powi(2);
+        time_var += (t - time_mean).powi(2);
+    }
+    if cpu_var == 0.0 || time_var == 0.0 {
+        // Identical metered costs for every invocation (the common case for a
+        // deterministic contract) leave nothing to correlate.
+        f64::NAN
+    } else {
+        cov / (cpu_var * time_var).sqrt()
+    }
+}
+
+fn report(
+    tw: &mut TabWriter<Vec<u8>>,
+    name: &str,
+    measurements: &[Measurement],
+) -> std::io::Result<()> {
+    let total_nsecs: u64 = measurements.iter().map(|m| m.time_nsecs).sum();
+    let invocations_per_sec = measurements.len() as f64 * 1e9 / (total_nsecs.max(1) as f64);
+    let cpu_insns = mean(measurements.iter().map(|m| m.cpu_insns as f64));
+    let mem_bytes = mean(measurements.iter().map(|m| m.mem_bytes as f64));
+    let time_nsecs = mean(measurements.iter().map(|m| m.time_nsecs as f64));
+    writeln!(
+        tw,
+        "{}\t{}\t{:.0}\t{:.0}\t{:.0}\t{:.0}\t{:.3}\t{:.3}",
+        name,
+        measurements.len(),
+        invocations_per_sec,
+        cpu_insns,
+        mem_bytes,
+        time_nsecs,
+        cpu_insns / time_nsecs.max(1.0),
+        cpu_time_correlation(measurements)
+    )
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+fn main() -> std::io::Result<()> {
+    let mut tw = TabWriter::new(vec![])
+        .padding(5)
+        .alignment(Alignment::Right);
+    writeln!(
+        tw,
+        "scenario\tinvocations\tinvocations_per_sec\tcpu_insns\tmem_bytes\ttime_nsecs\tcpu_insns_per_nsec\tcpu_time_correlation"
+    )?;
+    for scenario in all_scenarios().map_err(to_io_error)? {
+        if !should_run(&scenario) {
+            continue;
+        }
+        let measurements = measure(&scenario).map_err(to_io_error)?;
+        report(&mut tw, scenario.name, &measurements)?;
+    }
+    tw.flush()?;
+    println!(
+        "{}",
+        String::from_utf8(tw.into_inner().map_err(to_io_error)?).map_err(to_io_error)?
+    );
+    Ok(())
+}
@@ -0,0 +1,56 @@
+// Run this with
+// $ cargo test --features testutils --bench smoke_calibration
+// It is also run by `cargo test --features testutils`.
+// This is a quick sanity check of the measurement code that the other benches
+// rely on, not a calibration: every `HostCostMeasurement` is run for a single
+// iteration at a small and a larger input, and the host is expected to have
+// charged its cost type a nonzero cost that doesn't decrease as the input
+// grows. The costs are the ones charged to the budget rather than measured on
+// the hardware, so this is deterministic and needs no perf counters.
+// As with the other benches, you can optionally list the measurements to run.
+mod common;
+use common::*;
+use soroban_env_host::cost_runner::CostRunner;
+
+// The larger of the two inputs each measurement is run at. Worst-case samples
+// grow with the input, so this is kept small for the run to stay quick.
+const LARGER_INPUT: u64 = 4;
+
+struct SmokeCalibration;
+impl Benchmark for SmokeCalibration {
+    fn bench<HCM: HostCostMeasurement>() -> std::io::Result<(FPCostModel, FPCostModel)> {
+        let ty = <HCM::Runner as CostRunner>::COST_TYPE;
+        let small = measure_charged_costs::<HCM>(1);
+        let large = measure_charged_costs::<HCM>(LARGER_INPUT);
+        assert!(small.iterations > 0, "{:?} was not charged", ty);
+        assert!(
+            small.cpu_insns > 0 || small.mem_bytes > 0,
+            "{:?} was charged nothing: {:?}",
+            ty,
+            small
+        );
+        assert!(
+            large.inputs >= small.inputs
+                && large.cpu_insns >= small.cpu_insns
+                && large.mem_bytes >= small.mem_bytes,
+            "{:?} got cheaper with a larger input: {:?} then {:?}",
+            ty,
+            small,
+            large
+        );
+        Ok(Default::default())
+    }
+}
+
+// This target has no test harness, so `cfg(test)` isn't set even when it is
+// run by `cargo test`, and `main` must not be gated on it.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn main() -> std::io::Result<()> {
+    for_each_host_cost_measurement::<SmokeCalibration>()?;
+    for_each_wasm_insn_measurement::<SmokeCalibration>()?;
+    for_each_host_function_dispatch_measurement::<SmokeCalibration>()?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn main() {}
@@ -2,6 +2,7 @@
 // $ cargo bench --features wasmi,testutils --bench worst_case_linear_models -- --nocapture
 // You can optionally pass in args listing the {`ContractCostType`, `WasmInsnType`} combination to run with, e.g.
 // $ cargo bench --features wasmi,testutils --bench worst_case_linear_models -- VecNew I64Rotr --nocapture
+// Host function names (e.g. `vec_contains`) select which host function dispatches to measure.
 mod common;
 use common::*;
 use soroban_env_host::{cost_runner::WasmInsnType, xdr::ContractCostType};
@@ -194,6 +195,7 @@ fn extract_wasmi_fuel_costs(
 fn main() -> std::io::Result<()> {
     let params = for_each_host_cost_measurement::<WorstCaseLinearModels>()?;
     let params_wasm = for_each_wasm_insn_measurement::<WorstCaseLinearModels>()?;
+    let params_dispatch = for_each_host_function_dispatch_measurement::<WorstCaseLinearModels>()?;
 
     let mut tw = TabWriter::new(vec![])
         .padding(5)
@@ -201,6 +203,12 @@ fn main() -> std::io::Result<()> {
     write_cost_params_table::<ContractCostType>(&mut tw, &params)?;
     eprintln!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
 
+    let mut tw = TabWriter::new(vec![])
+        .padding(5)
+        .alignment(Alignment::Right);
+    write_cost_params_table::<String>(&mut tw, &params_dispatch)?;
+    eprintln!("{}", String::from_utf8(tw.into_inner().unwrap()).unwrap());
+
     let wasm_tier_cost = extract_wasmi_fuel_costs(&params_wasm);
 
     if std::env::var("WRITE_PARAMS").is_ok() {
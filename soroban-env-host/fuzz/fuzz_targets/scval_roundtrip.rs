@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::{budget::AsBudget, xdr::ScVal, Compare, Host, TryFromVal, Val};
+
+// Converts pairs of arbitrary `ScVal`s to host values and back, checking that
+// the conversion is lossless and that comparing the host values agrees with
+// comparing the `ScVal`s.
+fuzz_target!(|input: (ScVal, ScVal)| {
+    let host = Host::default();
+    host.as_budget().reset_unlimited().unwrap();
+    let (a, b) = input;
+
+    // Not every `ScVal` has a host representation (e.g. ones nested too
+    // deeply or maps with unsorted keys): those must be rejected, but that is
+    // not a failure.
+    let (Ok(va), Ok(vb)) = (Val::try_from_val(&host, &a), Val::try_from_val(&host, &b)) else {
+        return;
+    };
+
+    assert_eq!(ScVal::try_from_val(&host, &va).unwrap(), a);
+    assert_eq!(ScVal::try_from_val(&host, &vb).unwrap(), b);
+
+    let host_ord = host.compare(&va, &vb).unwrap();
+    let xdr_ord = host.as_budget().compare(&a, &b).unwrap();
+    assert_eq!(host_ord, xdr_ord);
+});
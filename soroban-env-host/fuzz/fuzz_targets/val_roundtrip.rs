@@ -0,0 +1,17 @@
+#![no_main]
+
+use core::cmp::Ordering;
+
+use libfuzzer_sys::fuzz_target;
+use soroban_env_host::{budget::AsBudget, xdr::ScVal, Compare, Host, TryFromVal, Val};
+
+// Converts arbitrary object-free `Val`s to `ScVal`s and back, checking that
+// the result compares equal to the original.
+fuzz_target!(|val: Val| {
+    let host = Host::default();
+    host.as_budget().reset_unlimited().unwrap();
+
+    let scv = ScVal::try_from_val(&host, &val).unwrap();
+    let back = Val::try_from_val(&host, &scv).unwrap();
+    assert_eq!(host.compare(&val, &back).unwrap(), Ordering::Equal);
+});
@@ -113,6 +113,34 @@ pub struct RecordedAuthPayload {
     pub invocation: xdr::SorobanAuthorizedInvocation,
 }
 
+impl RecordedAuthPayload {
+    /// Converts the payload into the authorization entry that covers it in
+    /// the enforcing mode, with its nonce filled in but not yet signed: the
+    /// signature is `Void` for the client to replace with one valid until
+    /// `signature_expiration_ledger`. A payload without an address is
+    /// authorized by the transaction source account and needs no signature.
+    pub fn into_authorization_entry(
+        self,
+        signature_expiration_ledger: u32,
+    ) -> SorobanAuthorizationEntry {
+        let credentials = match (self.address, self.nonce) {
+            (Some(address), Some(nonce)) => {
+                SorobanCredentials::Address(xdr::SorobanAddressCredentials {
+                    address,
+                    nonce,
+                    signature_expiration_ledger,
+                    signature: ScVal::Void,
+                })
+            }
+            _ => SorobanCredentials::SourceAccount,
+        };
+        SorobanAuthorizationEntry {
+            credentials,
+            root_invocation: self.invocation,
+        }
+    }
+}
+
 // Snapshot of `AuthorizationManager` to use when performing the callstack
 // rollbacks.
 pub struct AuthorizationManagerSnapshot {
@@ -1853,6 +1881,22 @@ impl Host {
                 .get_recorded_auth_payloads(self)
         }
     }
+
+    /// Returns the authorization entries a client has to sign for the
+    /// recorded invocation to pass in the enforcing mode, see
+    /// [RecordedAuthPayload::into_authorization_entry]. Like
+    /// [Host::get_recorded_auth_payloads], this should only be called in the
+    /// recording authorization mode.
+    pub fn get_recorded_auth_entries(
+        &self,
+        signature_expiration_ledger: u32,
+    ) -> Result<Vec<SorobanAuthorizationEntry>, HostError> {
+        Ok(self
+            .get_recorded_auth_payloads()?
+            .into_iter()
+            .map(|p| p.into_authorization_entry(signature_expiration_ledger))
+            .collect())
+    }
 }
 
 #[cfg(any(test, feature = "testutils"))]
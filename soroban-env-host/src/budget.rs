@@ -7,8 +7,8 @@ use std::{
 use crate::{
     host::error::TryBorrowOrErr,
     xdr::{
-        ContractCostParamEntry, ContractCostParams, ContractCostType, DepthLimiter, ScErrorCode,
-        ScErrorType,
+        ConfigSettingEntry, ContractCostParamEntry, ContractCostParams, ContractCostType,
+        DepthLimiter, ExtensionPoint, ScErrorCode, ScErrorType, DEFAULT_XDR_RW_DEPTH_LIMIT,
     },
     Error, Host, HostError, DEFAULT_HOST_DEPTH_LIMIT,
 };
@@ -20,6 +20,16 @@ use wasmi::{errors, FuelCosts, ResourceLimiter};
 const DEFAULT_CPU_INSN_LIMIT: u64 = 100_000_000;
 const DEFAULT_MEM_BYTES_LIMIT: u64 = 40 * 1024 * 1024; // 40MB
 
+/// The default maximum number of host objects alive at once, see
+/// [Budget::set_max_live_objects].
+pub const DEFAULT_MAX_LIVE_OBJECTS: u32 = 1 << 20;
+
+/// The default maximum size of a single host object, see
+/// [Budget::set_max_object_bytes]. This is above what the default memory
+/// limit allows, so it only matters for budgets with a raised or disabled
+/// limit.
+pub const DEFAULT_MAX_OBJECT_BYTES: u32 = 64 * 1024 * 1024; // 64MB
+
 /// The number of bits to scale the linear term by. The linear coefficient has
 /// been scaled by this factor during parameter fitting to retain more significant
 /// digits. Thus to get the cost from the raw input, we need to scale the result
@@ -158,6 +168,10 @@ pub struct BudgetDimension {
     /// Tracks the sum of _output_ values from the cost model, for purposes
     /// of comparing to limit.
     total_count: u64,
+
+    /// The amount of the charge that last took `total_count` over `limit`,
+    /// which is by how much at most the limit can be overshot.
+    crossing_charge: u64,
 }
 
 impl Debug for BudgetDimension {
@@ -183,6 +197,7 @@ impl BudgetDimension {
             limit: Default::default(),
             counts: Default::default(),
             total_count: Default::default(),
+            crossing_charge: Default::default(),
         };
         for _ct in ContractCostType::variants() {
             bd.cost_models.push(MeteredCostComponent {
@@ -194,7 +209,12 @@ impl BudgetDimension {
         bd
     }
 
+    /// Builds a dimension from its cost model parameters, which must have an
+    /// entry for every [`ContractCostType`], indexed by the cost type.
     pub fn try_from_config(cost_params: ContractCostParams) -> Result<Self, HostError> {
+        if cost_params.0.len() < ContractCostType::variants().len() {
+            return Err((ScErrorType::Budget, ScErrorCode::InvalidInput).into());
+        }
         let cost_models = cost_params
             .0
             .iter()
@@ -206,9 +226,25 @@ impl BudgetDimension {
             limit: Default::default(),
             counts: vec![0; cost_params.0.len()],
             total_count: Default::default(),
+            crossing_charge: Default::default(),
         })
     }
 
+    /// Returns the cost model parameters of this dimension, in the form
+    /// accepted by [`BudgetDimension::try_from_config`].
+    pub fn to_config(&self) -> Result<ContractCostParams, HostError> {
+        let entries = self
+            .cost_models
+            .iter()
+            .map(|cm| ContractCostParamEntry {
+                ext: ExtensionPoint::V0,
+                const_term: cm.const_term as i64,
+                linear_term: cm.lin_term.0 as i64,
+            })
+            .collect::<Vec<_>>();
+        Ok(ContractCostParams(entries.try_into()?))
+    }
+
     pub(crate) fn get_cost_model(&self, ty: ContractCostType) -> &MeteredCostComponent {
         &self.cost_models[ty as usize]
     }
@@ -236,6 +272,7 @@ impl BudgetDimension {
     pub fn reset(&mut self, limit: u64) {
         self.limit = limit;
         self.total_count = 0;
+        self.crossing_charge = 0;
         for v in &mut self.counts {
             *v = 0;
         }
@@ -258,9 +295,13 @@ impl BudgetDimension {
     ) -> Result<(), HostError> {
         let cm = self.get_cost_model(ty);
         let amount = cm.evaluate(input)?.saturating_mul(iterations);
+        let was_over_budget = self.is_over_budget();
         self.counts[ty as usize] = self.counts[ty as usize].saturating_add(amount);
         self.total_count = self.total_count.saturating_add(amount);
         if self.is_over_budget() {
+            if !was_over_budget {
+                self.crossing_charge = amount;
+            }
             Err((ScErrorType::Budget, ScErrorCode::ExceededLimit).into())
         } else {
             Ok(())
@@ -280,8 +321,15 @@ impl BudgetDimension {
 /// doesn't derive all the traits we want. These fields (coarsely) define the
 /// relative costs of different wasm instruction types and are for wasmi internal
 /// fuel metering use only. Units are in "fuels".
-#[derive(Clone)]
-pub(crate) struct FuelConfig {
+///
+/// Every unit of fuel consumed by a VM is charged to the CPU budget as one
+/// iteration of [`ContractCostType::WasmInsnExec`], so scaling the fuel cost
+/// of a category scales the CPU charged for instructions in that category.
+/// The schedule can be customized with [`Budget::set_fuel_config`], and is
+/// picked up by every VM instantiated afterwards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FuelConfig {
     /// The base fuel costs for all instructions.
     pub base: u64,
     /// The fuel cost for instruction operating on Wasm entities.
@@ -357,6 +405,104 @@ impl MeterTracker {
     }
 }
 
+/// Cumulative costs charged while in shadow mode (see
+/// [`Budget::with_shadow_budget`]).
+#[derive(Clone, Default)]
+struct ShadowCounts {
+    cpu_insns: u64,
+    mem_bytes: u64,
+}
+
+/// Tracks the memory charged net of released VM instances, and the monotonic
+/// peak of that amount (see [`Budget::get_mem_bytes_peak`]).
+///
+/// This is not a measure of live memory: only the memory of VM instances is
+/// ever subtracted, when the VM scope it was charged in is popped. Nothing is
+/// subtracted when host objects are dropped or frames are popped, as there's
+/// no metered release for those to hook into, so everything else the budget
+/// charges for -- host objects, frames and their rollback points, storage and
+/// events, temporary buffers -- stays counted until the budget is reset.
+#[derive(Clone, Default)]
+struct MemPeak {
+    net: u64,
+    peak: u64,
+    /// Memory charged for the VM of each active VM scope, innermost last. It
+    /// is subtracted from `net` when the scope is popped.
+    vm_scopes: Vec<u64>,
+}
+
+impl MemPeak {
+    fn record(&mut self, ty: ContractCostType, bytes: u64) {
+        self.net = self.net.saturating_add(bytes);
+        self.peak = self.peak.max(self.net);
+        match ty {
+            ContractCostType::VmInstantiation
+            | ContractCostType::VmCachedInstantiation
+            | ContractCostType::WasmMemAlloc => {
+                if let Some(scope) = self.vm_scopes.last_mut() {
+                    *scope = scope.saturating_add(bytes);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.net = 0;
+        self.peak = 0;
+        for scope in &mut self.vm_scopes {
+            *scope = 0;
+        }
+    }
+}
+
+/// Amounts of the ledger resources the network charges fees for, other than
+/// cpu and memory: the ledger entries a transaction reads and writes, and
+/// their sizes in bytes. Used both for the limits set with
+/// [`Budget::set_ledger_resource_limits`] and for the usage reported by
+/// [`Budget::get_ledger_resource_usage`].
+///
+/// As in the network's fee model, written entries are counted as read
+/// entries too.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LedgerResources {
+    pub read_entries: u32,
+    pub write_entries: u32,
+    pub read_bytes: u32,
+    pub write_bytes: u32,
+}
+
+impl LedgerResources {
+    // Returns a description of the first of `limits` this exceeds, if any.
+    fn exceeded_limit(&self, limits: &LedgerResources) -> Option<&'static str> {
+        if self.read_entries > limits.read_entries {
+            Some("ledger read entries limit exceeded")
+        } else if self.write_entries > limits.write_entries {
+            Some("ledger write entries limit exceeded")
+        } else if self.read_bytes > limits.read_bytes {
+            Some("ledger read bytes limit exceeded")
+        } else if self.write_bytes > limits.write_bytes {
+            Some("ledger write bytes limit exceeded")
+        } else {
+            None
+        }
+    }
+}
+
+/// Per-[`ContractCostType`] summary of the charges made against a [`Budget`],
+/// as returned by [`Budget::cost_breakdown`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CostTypeBreakdown {
+    pub cost_type: ContractCostType,
+    /// Number of times this cost type was charged.
+    pub iterations: u64,
+    /// Sum of the inputs charged, or `None` for constant-cost types.
+    pub input: Option<u64>,
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+}
+
 #[derive(Clone)]
 pub(crate) struct BudgetImpl {
     pub cpu_insns: BudgetDimension,
@@ -364,8 +510,29 @@ pub(crate) struct BudgetImpl {
     /// For the purpose o calibration and reporting; not used for budget-limiting per se.
     tracker: MeterTracker,
     enabled: bool,
+    /// While set, charges are evaluated against the cost models but tallied
+    /// in `shadow` rather than counted against the limits.
+    shadow_mode: bool,
+    shadow: ShadowCounts,
+    mem_peak: MemPeak,
     fuel_config: FuelConfig,
     depth_limit: u32,
+    /// The maximum depth of the context stack, i.e. of nested contract
+    /// calls, see [Budget::set_call_depth_limit].
+    call_depth_limit: u32,
+    /// The maximum nesting depth of XDR values read or written by the metered
+    /// XDR functions, see [Budget::set_xdr_depth_limit].
+    xdr_depth_limit: u32,
+    /// The maximum number of host objects alive at once, see
+    /// [Budget::set_max_live_objects].
+    max_live_objects: u32,
+    /// The maximum size of a single host object, see
+    /// [Budget::set_max_object_bytes].
+    max_object_bytes: u32,
+    /// The ledger resource limits, if any, see
+    /// [Budget::set_ledger_resource_limits].
+    ledger_resource_limits: Option<LedgerResources>,
+    ledger_resource_usage: LedgerResources,
 }
 
 impl BudgetImpl {
@@ -381,8 +548,17 @@ impl BudgetImpl {
             mem_bytes: BudgetDimension::try_from_config(mem_cost_params)?,
             tracker: Default::default(),
             enabled: true,
+            shadow_mode: false,
+            shadow: Default::default(),
+            mem_peak: Default::default(),
             fuel_config: Default::default(),
             depth_limit: DEFAULT_HOST_DEPTH_LIMIT,
+            call_depth_limit: DEFAULT_HOST_DEPTH_LIMIT,
+            xdr_depth_limit: DEFAULT_XDR_RW_DEPTH_LIMIT,
+            max_live_objects: DEFAULT_MAX_LIVE_OBJECTS,
+            max_object_bytes: DEFAULT_MAX_OBJECT_BYTES,
+            ledger_resource_limits: None,
+            ledger_resource_usage: Default::default(),
         };
 
         b.init_tracker();
@@ -445,6 +621,20 @@ impl BudgetImpl {
             return Ok(());
         }
 
+        if self.shadow_mode {
+            let cpu = self.cpu_insns.get_cost_model(ty).evaluate(input)?;
+            let mem = self.mem_bytes.get_cost_model(ty).evaluate(input)?;
+            self.shadow.cpu_insns = self
+                .shadow
+                .cpu_insns
+                .saturating_add(cpu.saturating_mul(iterations));
+            self.shadow.mem_bytes = self
+                .shadow
+                .mem_bytes
+                .saturating_add(mem.saturating_mul(iterations));
+            return Ok(());
+        }
+
         // update tracker for reporting
         self.tracker.count = self.tracker.count.saturating_add(1);
         let (t_iters, t_inputs) = &mut self.tracker.cost_tracker[ty as usize];
@@ -458,7 +648,11 @@ impl BudgetImpl {
 
         // do the actual budget charging
         self.cpu_insns.charge(ty, iterations, input)?;
-        self.mem_bytes.charge(ty, iterations, input)
+        let mem_before = self.mem_bytes.get_total_count();
+        let res = self.mem_bytes.charge(ty, iterations, input);
+        let mem_charged = self.mem_bytes.get_total_count().saturating_sub(mem_before);
+        self.mem_peak.record(ty, mem_charged);
+        res
     }
 
     fn get_wasmi_fuel_remaining(&self) -> Result<u64, HostError> {
@@ -491,8 +685,17 @@ impl Default for BudgetImpl {
             mem_bytes: BudgetDimension::new(),
             tracker: Default::default(),
             enabled: true,
+            shadow_mode: false,
+            shadow: Default::default(),
+            mem_peak: Default::default(),
             fuel_config: Default::default(),
             depth_limit: DEFAULT_HOST_DEPTH_LIMIT,
+            call_depth_limit: DEFAULT_HOST_DEPTH_LIMIT,
+            xdr_depth_limit: DEFAULT_XDR_RW_DEPTH_LIMIT,
+            max_live_objects: DEFAULT_MAX_LIVE_OBJECTS,
+            max_object_bytes: DEFAULT_MAX_OBJECT_BYTES,
+            ledger_resource_limits: None,
+            ledger_resource_usage: Default::default(),
         };
 
         for ct in ContractCostType::variants() {
@@ -771,8 +974,8 @@ impl Debug for BudgetImpl {
         )?;
         writeln!(
             f,
-            "Mem limit: {}; used: {}",
-            self.mem_bytes.limit, self.mem_bytes.total_count
+            "Mem limit: {}; used: {}; peak: {}",
+            self.mem_bytes.limit, self.mem_bytes.total_count, self.mem_peak.peak
         )?;
         writeln!(f, "{:=<165}", "")?;
         writeln!(
@@ -820,8 +1023,8 @@ impl Display for BudgetImpl {
         )?;
         writeln!(
             f,
-            "Mem limit: {}; used: {}",
-            self.mem_bytes.limit, self.mem_bytes.total_count
+            "Mem limit: {}; used: {}; peak: {}",
+            self.mem_bytes.limit, self.mem_bytes.total_count, self.mem_peak.peak
         )?;
         writeln!(f, "{:=<55}", "")?;
         writeln!(
@@ -851,11 +1054,11 @@ impl DepthLimiter for BudgetImpl {
         if let Some(depth) = self.depth_limit.checked_sub(1) {
             self.depth_limit = depth;
         } else {
-            return Err(Error::from_type_and_code(
+            return Err(HostError::from(Error::from_type_and_code(
                 ScErrorType::Context,
                 ScErrorCode::ExceededLimit,
-            )
-            .into());
+            ))
+            .mark_depth_limit_exceeded());
         }
         Ok(())
     }
@@ -933,7 +1136,8 @@ impl DepthLimiter for Budget {
 }
 
 impl Budget {
-    /// Initializes the budget from network configuration settings.
+    /// Initializes the budget from network configuration settings. Each of
+    /// the cost params must have an entry for every [`ContractCostType`].
     pub fn try_from_configs(
         cpu_limit: u64,
         mem_limit: u64,
@@ -948,6 +1152,38 @@ impl Budget {
         )?))))
     }
 
+    /// Initializes the budget from the network's [`ConfigSettingEntry`]s, as
+    /// stored in the ledger. The limits are taken from the per-transaction
+    /// limits of the compute settings, and the cost params from the cpu and
+    /// memory cost param settings; other settings are ignored. It is an
+    /// error for any of those three settings to be missing.
+    pub fn try_from_config_settings(settings: &[ConfigSettingEntry]) -> Result<Self, HostError> {
+        let mut compute = None;
+        let mut cpu_cost_params = None;
+        let mut mem_cost_params = None;
+        for setting in settings {
+            match setting {
+                ConfigSettingEntry::ContractComputeV0(c) => compute = Some(c),
+                ConfigSettingEntry::ContractCostParamsCpuInstructions(p) => {
+                    cpu_cost_params = Some(p)
+                }
+                ConfigSettingEntry::ContractCostParamsMemoryBytes(p) => mem_cost_params = Some(p),
+                _ => (),
+            }
+        }
+        let (Some(compute), Some(cpu_cost_params), Some(mem_cost_params)) =
+            (compute, cpu_cost_params, mem_cost_params)
+        else {
+            return Err((ScErrorType::Budget, ScErrorCode::MissingValue).into());
+        };
+        Self::try_from_configs(
+            compute.tx_max_instructions.max(0) as u64,
+            compute.tx_memory_limit as u64,
+            cpu_cost_params.clone(),
+            mem_cost_params.clone(),
+        )
+    }
+
     // Helper function to avoid multiple borrow_mut
     fn mut_budget<T, F>(&self, f: F) -> Result<T, HostError>
     where
@@ -1002,6 +1238,130 @@ impl Budget {
         res
     }
 
+    /// Runs `f` in "shadow" mode: charges made by `f` are evaluated against
+    /// the cost models as usual, but are neither counted against the budget
+    /// limits nor recorded in the per-cost-type breakdown. Their totals are
+    /// instead accumulated separately and can be read with
+    /// [`Budget::get_shadow_cpu_insns_consumed`] and
+    /// [`Budget::get_shadow_mem_bytes_consumed`].
+    ///
+    /// This is intended for diagnostic work (eg. rendering debug output) that
+    /// should not affect the outcome of an invocation, but whose cost is still
+    /// worth knowing.
+    pub fn with_shadow_budget<F, T>(&self, f: F) -> Result<T, HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        let mut prev = false;
+        self.mut_budget(|mut b| {
+            prev = b.shadow_mode;
+            b.shadow_mode = true;
+            Ok(())
+        })?;
+
+        let res = f();
+
+        self.mut_budget(|mut b| {
+            b.shadow_mode = prev;
+            Ok(())
+        })?;
+        res
+    }
+
+    /// Runs `f` with the budget limits temporarily lowered, such that `f`
+    /// may consume at most `cpu_insns` and `mem_bytes` on top of what has
+    /// been consumed so far (or whatever remains of the current limits, if
+    /// that is less). The current limits are restored once `f` returns, and
+    /// whatever `f` consumed stays charged against them.
+    ///
+    /// A charge is only checked against the limits once it has been added, so
+    /// `f` can overshoot the lowered limits by the charge that crossed them,
+    /// but by no more than that: any further consumption is an internal error.
+    pub(crate) fn with_sub_limits<F, T>(
+        &self,
+        cpu_insns: u64,
+        mem_bytes: u64,
+        f: F,
+    ) -> Result<T, HostError>
+    where
+        F: FnOnce() -> Result<T, HostError>,
+    {
+        let mut prev = (0, 0);
+        self.mut_budget(|mut b| {
+            prev = (b.cpu_insns.limit, b.mem_bytes.limit);
+            b.cpu_insns.limit = b
+                .cpu_insns
+                .total_count
+                .saturating_add(cpu_insns)
+                .min(prev.0);
+            b.mem_bytes.limit = b
+                .mem_bytes
+                .total_count
+                .saturating_add(mem_bytes)
+                .min(prev.1);
+            b.cpu_insns.crossing_charge = 0;
+            b.mem_bytes.crossing_charge = 0;
+            Ok(())
+        })?;
+
+        let res = f();
+
+        let overshot = self.mut_budget(|mut b| {
+            let overshoots =
+                |d: &BudgetDimension| d.total_count > d.limit.saturating_add(d.crossing_charge);
+            let overshot = overshoots(&b.cpu_insns) || overshoots(&b.mem_bytes);
+            b.cpu_insns.limit = prev.0;
+            b.mem_bytes.limit = prev.1;
+            Ok(overshot)
+        })?;
+        if overshot {
+            return Err((ScErrorType::Budget, ScErrorCode::InternalError).into());
+        }
+        res
+    }
+
+    /// Returns `true` if the consumption of either dimension exceeds its
+    /// limit.
+    pub(crate) fn is_over_budget(&self) -> Result<bool, HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        Ok(b.cpu_insns.is_over_budget() || b.mem_bytes.is_over_budget())
+    }
+
+    pub fn get_shadow_cpu_insns_consumed(&self) -> Result<u64, HostError> {
+        Ok(self.0.try_borrow_or_err()?.shadow.cpu_insns)
+    }
+
+    pub fn get_shadow_mem_bytes_consumed(&self) -> Result<u64, HostError> {
+        Ok(self.0.try_borrow_or_err()?.shadow.mem_bytes)
+    }
+
+    /// Returns, for every [`ContractCostType`], the number of times it was
+    /// charged, the sum of its inputs and the cpu and memory it consumed.
+    pub fn cost_breakdown(&self) -> Result<Vec<CostTypeBreakdown>, HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        Ok(ContractCostType::variants()
+            .iter()
+            .map(|ct| {
+                let i = *ct as usize;
+                let (iterations, input) = b.tracker.cost_tracker[i];
+                CostTypeBreakdown {
+                    cost_type: *ct,
+                    iterations,
+                    input,
+                    cpu_insns: b.cpu_insns.get_count(*ct),
+                    mem_bytes: b.mem_bytes.get_count(*ct),
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the cpu and memory cost model parameters currently in use, in
+    /// the form accepted by [`Budget::try_from_configs`].
+    pub fn get_cost_params(&self) -> Result<(ContractCostParams, ContractCostParams), HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        Ok((b.cpu_insns.to_config()?, b.mem_bytes.to_config()?))
+    }
+
     pub fn get_tracker(&self, ty: ContractCostType) -> Result<(u64, Option<u64>), HostError> {
         Ok(self.0.try_borrow_or_err()?.tracker.cost_tracker[ty as usize])
     }
@@ -1014,6 +1374,48 @@ impl Budget {
         Ok(self.0.try_borrow_or_err()?.mem_bytes.get_total_count())
     }
 
+    /// Returns the memory charged so far, net of the memory of VM instances
+    /// that have since been released. This is not the memory currently live:
+    /// nothing else is subtracted, see [`Budget::get_mem_bytes_peak`].
+    pub fn get_mem_bytes_net(&self) -> Result<u64, HostError> {
+        Ok(self.0.try_borrow_or_err()?.mem_peak.net)
+    }
+
+    /// Returns the peak of [`Budget::get_mem_bytes_net`]. The peak is
+    /// monotonic: it never decreases until the budget is reset.
+    ///
+    /// Unlike [`Budget::get_mem_bytes_consumed`], which accumulates every
+    /// memory charge, this accounts for the memory of a contract's VM
+    /// instance -- its instantiation and linear memory -- being released when
+    /// the call into that contract returns, so it is lower for workloads that
+    /// call several contracts in turn. That is the only memory it accounts as
+    /// released: nothing is subtracted when host objects are dropped or frames
+    /// are popped. The peak is therefore an upper bound on the actual peak
+    /// memory usage, that is tighter than [`Budget::get_mem_bytes_consumed`]
+    /// only by the memory of VM instances that were released before it was
+    /// reached.
+    pub fn get_mem_bytes_peak(&self) -> Result<u64, HostError> {
+        Ok(self.0.try_borrow_or_err()?.mem_peak.peak)
+    }
+
+    /// Opens a scope in which memory charged for VM instantiation and linear
+    /// memory growth is attributed to a single VM instance, to be subtracted
+    /// from [`Budget::get_mem_bytes_net`] by the matching
+    /// [`Budget::pop_vm_mem_scope`].
+    pub(crate) fn push_vm_mem_scope(&self) -> Result<(), HostError> {
+        self.0.try_borrow_mut_or_err()?.mem_peak.vm_scopes.push(0);
+        Ok(())
+    }
+
+    pub(crate) fn pop_vm_mem_scope(&self) -> Result<(), HostError> {
+        let mut b = self.0.try_borrow_mut_or_err()?;
+        let Some(released) = b.mem_peak.vm_scopes.pop() else {
+            return Err((ScErrorType::Context, ScErrorCode::InternalError).into());
+        };
+        b.mem_peak.net = b.mem_peak.net.saturating_sub(released);
+        Ok(())
+    }
+
     pub fn get_cpu_insns_remaining(&self) -> Result<u64, HostError> {
         Ok(self.0.try_borrow_or_err()?.cpu_insns.get_remaining())
     }
@@ -1050,7 +1452,10 @@ impl Budget {
     }
 
     pub fn reset_tracker(&self) -> Result<(), HostError> {
-        self.0.try_borrow_mut_or_err()?.tracker.reset();
+        let mut b = self.0.try_borrow_mut_or_err()?;
+        b.tracker.reset();
+        b.shadow = Default::default();
+        b.mem_peak.reset();
         Ok(())
     }
 
@@ -1063,6 +1468,14 @@ impl Budget {
         self.reset_tracker()
     }
 
+    /// Returns the amount of the cpu charge that last took the consumption
+    /// over the limit in force at the time, including the lowered limits of
+    /// [`Budget::with_sub_limits`].
+    #[cfg(test)]
+    pub(crate) fn get_cpu_insns_crossing_charge(&self) -> Result<u64, HostError> {
+        Ok(self.0.try_borrow_or_err()?.cpu_insns.crossing_charge)
+    }
+
     #[cfg(test)]
     pub fn reset_models(&self) -> Result<(), HostError> {
         self.mut_budget(|mut b| {
@@ -1124,6 +1537,209 @@ impl Budget {
         Ok(())
     }
 
+    /// Returns the wasmi fuel schedule used by VMs instantiated under this
+    /// budget.
+    pub fn get_fuel_config(&self) -> Result<FuelConfig, HostError> {
+        Ok(self.0.try_borrow_or_err()?.fuel_config.clone())
+    }
+
+    /// Replaces the wasmi fuel schedule used by VMs instantiated under this
+    /// budget. VMs that already exist keep running with the schedule they were
+    /// instantiated with, as do modules held in a
+    /// [`ModuleCache`](crate::vm::ModuleCache).
+    ///
+    /// The base cost must be non-zero, as otherwise most instructions would
+    /// execute without being charged.
+    pub fn set_fuel_config(&self, config: FuelConfig) -> Result<(), HostError> {
+        if config.base == 0 {
+            return Err((ScErrorType::Budget, ScErrorCode::InvalidInput).into());
+        }
+        self.0.try_borrow_mut_or_err()?.fuel_config = config;
+        Ok(())
+    }
+
+    /// Returns the maximum nesting depth of XDR values that the host
+    /// serializes or deserializes under this budget.
+    pub fn get_xdr_depth_limit(&self) -> Result<u32, HostError> {
+        Ok(self.0.try_borrow_or_err()?.xdr_depth_limit)
+    }
+
+    /// Sets the maximum nesting depth of XDR values that the host serializes
+    /// or deserializes under this budget. Values nested deeper than this fail
+    /// to (de)serialize with a `(Context, ExceededLimit)` error before their
+    /// deeper levels are visited. Defaults to `DEFAULT_XDR_RW_DEPTH_LIMIT`.
+    ///
+    /// The limit must be non-zero, as no value could be (de)serialized
+    /// otherwise.
+    pub fn set_xdr_depth_limit(&self, limit: u32) -> Result<(), HostError> {
+        if limit == 0 {
+            return Err((ScErrorType::Budget, ScErrorCode::InvalidInput).into());
+        }
+        self.0.try_borrow_mut_or_err()?.xdr_depth_limit = limit;
+        Ok(())
+    }
+
+    /// Sets the maximum nesting depth of host values and containers that the
+    /// host converts or constructs under this budget, e.g. when converting
+    /// between `ScVal` and host objects. Exceeding it fails with a
+    /// `(Context, ExceededLimit)` error. Defaults to
+    /// `DEFAULT_HOST_DEPTH_LIMIT`.
+    ///
+    /// The limit is tracked as the depth remaining, so this must not be
+    /// called while a depth-limited operation is in progress. It must also be
+    /// non-zero, as no value could be converted otherwise.
+    pub fn set_depth_limit(&self, limit: u32) -> Result<(), HostError> {
+        if limit == 0 {
+            return Err((ScErrorType::Budget, ScErrorCode::InvalidInput).into());
+        }
+        self.0.try_borrow_mut_or_err()?.depth_limit = limit;
+        Ok(())
+    }
+
+    /// Returns the maximum nesting depth of host values, see
+    /// [Budget::set_depth_limit]. While a depth-limited operation is in
+    /// progress this is the depth remaining rather than the configured limit.
+    pub fn get_depth_limit(&self) -> Result<u32, HostError> {
+        Ok(self.0.try_borrow_or_err()?.depth_limit)
+    }
+
+    /// Returns the maximum depth of nested contract calls.
+    pub fn get_call_depth_limit(&self) -> Result<u32, HostError> {
+        Ok(self.0.try_borrow_or_err()?.call_depth_limit)
+    }
+
+    /// Sets the maximum depth of nested contract calls, counting the
+    /// top-level invocation. A call that would exceed it fails with a
+    /// `(Context, ExceededLimit)` error before its frame is pushed. Defaults
+    /// to `DEFAULT_HOST_DEPTH_LIMIT`.
+    ///
+    /// The limit must be non-zero, as no contract could be called otherwise.
+    pub fn set_call_depth_limit(&self, limit: u32) -> Result<(), HostError> {
+        if limit == 0 {
+            return Err((ScErrorType::Budget, ScErrorCode::InvalidInput).into());
+        }
+        self.0.try_borrow_mut_or_err()?.call_depth_limit = limit;
+        Ok(())
+    }
+
+    /// Returns the maximum number of host objects that may be alive at once.
+    pub fn get_max_live_objects(&self) -> Result<u32, HostError> {
+        Ok(self.0.try_borrow_or_err()?.max_live_objects)
+    }
+
+    /// Sets the maximum number of host objects that may be alive at once.
+    /// Host objects are only released along with the host, so this bounds
+    /// the total number of objects created; creating one more fails with an
+    /// `(Object, ExceededLimit)` error. Defaults to
+    /// `DEFAULT_MAX_LIVE_OBJECTS`.
+    pub fn set_max_live_objects(&self, limit: u32) -> Result<(), HostError> {
+        self.0.try_borrow_mut_or_err()?.max_live_objects = limit;
+        Ok(())
+    }
+
+    /// Returns the maximum size in bytes of a single host object.
+    pub fn get_max_object_bytes(&self) -> Result<u32, HostError> {
+        Ok(self.0.try_borrow_or_err()?.max_object_bytes)
+    }
+
+    /// Sets the maximum size in bytes of a single host object, where the
+    /// size of a bytes, string or symbol object is its length and the size of
+    /// a vector or map is the size of its elements (which are `Val`s, so 8
+    /// bytes each, or 16 per map entry). Creating a larger object fails with
+    /// an `(Object, ExceededLimit)` error. Defaults to
+    /// `DEFAULT_MAX_OBJECT_BYTES`.
+    pub fn set_max_object_bytes(&self, limit: u32) -> Result<(), HostError> {
+        self.0.try_borrow_mut_or_err()?.max_object_bytes = limit;
+        Ok(())
+    }
+
+    /// Limits the ledger entries read and written through the storage under
+    /// this budget, and their sizes, to `limits` for the rest of the
+    /// transaction, or with `None` stops limiting and tracking them. Going
+    /// over any of the limits fails with a `(Budget, ExceededLimit)` error,
+    /// which in debug mode records which of the limits was exceeded.
+    ///
+    /// Setting the limits resets the usage, so they should be set before the
+    /// storage is first accessed: entries accessed before that are not
+    /// counted. Entry sizes are those of their XDR encoding, as with the
+    /// network, and computing them is metered.
+    pub fn set_ledger_resource_limits(
+        &self,
+        limits: Option<LedgerResources>,
+    ) -> Result<(), HostError> {
+        let mut b = self.0.try_borrow_mut_or_err()?;
+        b.ledger_resource_limits = limits;
+        b.ledger_resource_usage = Default::default();
+        Ok(())
+    }
+
+    /// Returns the ledger resource limits, if any are set.
+    pub fn get_ledger_resource_limits(&self) -> Result<Option<LedgerResources>, HostError> {
+        Ok(self.0.try_borrow_or_err()?.ledger_resource_limits)
+    }
+
+    /// Returns the ledger resources used since the limits were set. This is
+    /// all zeros while no limits are set.
+    pub fn get_ledger_resource_usage(&self) -> Result<LedgerResources, HostError> {
+        Ok(self.0.try_borrow_or_err()?.ledger_resource_usage)
+    }
+
+    // Returns a description of the ledger resource limit the usage went
+    // over, if any, which is what a `(Budget, ExceededLimit)` error is about
+    // in that case. The budget has no host to record it with, so the host
+    // does that when the error reaches it.
+    pub(crate) fn get_exceeded_ledger_resource_limit(
+        &self,
+    ) -> Result<Option<&'static str>, HostError> {
+        let b = self.0.try_borrow_or_err()?;
+        Ok(b.ledger_resource_limits
+            .and_then(|limits| b.ledger_resource_usage.exceeded_limit(&limits)))
+    }
+
+    // Records the first access to a ledger entry of `bytes` bytes (zero if
+    // it doesn't exist).
+    pub(crate) fn charge_ledger_read(&self, bytes: u32) -> Result<(), HostError> {
+        self.charge_ledger_resources(|usage| {
+            usage.read_entries = usage.read_entries.saturating_add(1);
+            usage.read_bytes = usage.read_bytes.saturating_add(bytes);
+        })
+    }
+
+    // Records a write replacing `old_bytes` previously written to an entry
+    // with `new_bytes`; `first_write` is set for the first write to the
+    // entry, for which `old_bytes` is zero.
+    pub(crate) fn charge_ledger_write(
+        &self,
+        first_write: bool,
+        old_bytes: u32,
+        new_bytes: u32,
+    ) -> Result<(), HostError> {
+        self.charge_ledger_resources(|usage| {
+            if first_write {
+                usage.write_entries = usage.write_entries.saturating_add(1);
+            }
+            usage.write_bytes = usage
+                .write_bytes
+                .saturating_sub(old_bytes)
+                .saturating_add(new_bytes);
+        })
+    }
+
+    fn charge_ledger_resources<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce(&mut LedgerResources),
+    {
+        let mut b = self.0.try_borrow_mut_or_err()?;
+        let Some(limits) = b.ledger_resource_limits else {
+            return Ok(());
+        };
+        f(&mut b.ledger_resource_usage);
+        if b.ledger_resource_usage.exceeded_limit(&limits).is_some() {
+            return Err((ScErrorType::Budget, ScErrorCode::ExceededLimit).into());
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_wasmi_fuel_remaining(&self) -> Result<u64, HostError> {
         self.0.try_borrow_mut_or_err()?.get_wasmi_fuel_remaining()
     }
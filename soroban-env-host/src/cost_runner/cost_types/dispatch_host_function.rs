@@ -0,0 +1,258 @@
+// Dispatch cost runners for every host function.
+//
+// These are generated from the same x-macro (call_macro_with_all_host_functions)
+// as the dispatch functions themselves, so every host function declared in
+// env.json has one, and a newly added host function can't be left out of
+// calibration. They live in one module per env.json module, and are named
+// after the host function they call, e.g. `vec::vec_contains`.
+//
+// Each run calls the dispatch function from within a VM frame, with a
+// well-typed value for every argument: the smallest value of its type, e.g.
+// an empty vec or a zero integer, with objects passed as relative references
+// as a contract would pass them. So every run gets past argument conversion
+// and into the function; some functions still fail their own checks on those
+// values (e.g. `vec_get` on an empty vec), which is fine, as what's measured is
+// the cost of crossing from the VM into the host and back for that function's
+// signature. The baseline run sets up the same frame and arguments without
+// dispatching, so their cost is not attributed to the dispatch.
+
+use crate::{
+    cost_runner::CostRunner,
+    host::frame::Frame,
+    vm::dispatch,
+    xdr::{
+        ContractCostType, ContractExecutable, Hash, ScAddress, ScContractInstance, ScErrorCode,
+        ScErrorType,
+    },
+    AddressObject, BytesObject, DurationObject, Env, EnvBase, Error, Host, HostError, I128Object,
+    I128Val, I256Object, I256Val, I64Object, MapObject, StorageType, StringObject, Symbol,
+    SymbolObject, TimepointObject, U128Object, U128Val, U256Object, U256Val, U32Val, U64Object,
+    U64Val, Val, VecObject, Vm,
+};
+use soroban_env_common::call_macro_with_all_host_functions;
+use std::{hint::black_box, rc::Rc};
+
+/// A VM to dispatch host function calls from, and the arguments to pass to
+/// the host function, as absolute references. They are converted to relative
+/// references within the frame of each run.
+#[derive(Clone)]
+pub struct DispatchSample {
+    pub vm: Rc<Vm>,
+    pub args: Vec<Val>,
+}
+
+/// A dispatch cost runner, along with the arguments its host function takes.
+pub trait DispatchHostFunctionRun:
+    CostRunner<SampleType = DispatchSample, RecycledType = DispatchSample>
+{
+    /// Returns a well-typed value for every argument of the host function,
+    /// created on `host`.
+    fn sample_args(host: &Host) -> Result<Vec<Val>, HostError>;
+}
+
+// The value passed for an argument of a given type. Arguments that are not
+// `Val`s (plain integers and the storage type) are passed as the `Val` of the
+// same payload.
+trait DispatchArg {
+    fn dispatch_arg(host: &Host) -> Result<Val, HostError>;
+}
+
+macro_rules! impl_dispatch_arg {
+    ($T:ty, |$host:ident| $e:expr) => {
+        impl DispatchArg for $T {
+            fn dispatch_arg($host: &Host) -> Result<Val, HostError> {
+                Ok($e)
+            }
+        }
+    };
+}
+
+impl_dispatch_arg!(Val, |_host| Val::VOID.to_val());
+impl_dispatch_arg!(i64, |_host| Val::from_payload(0));
+impl_dispatch_arg!(u64, |_host| Val::from_payload(0));
+impl_dispatch_arg!(StorageType, |_host| Val::from_payload(
+    StorageType::Temporary as u64
+));
+impl_dispatch_arg!(U32Val, |_host| U32Val::from(0u32).to_val());
+impl_dispatch_arg!(Error, |_host| Error::from_type_and_code(
+    ScErrorType::Context,
+    ScErrorCode::InternalError
+)
+.to_val());
+impl_dispatch_arg!(Symbol, |_host| DISPATCH_SYM.to_val());
+impl_dispatch_arg!(SymbolObject, |host| host
+    .symbol_new_from_slice("dispatch_host_function")?
+    .to_val());
+impl_dispatch_arg!(AddressObject, |host| host
+    .add_host_object(ScAddress::Contract(Hash([0; 32])))?
+    .to_val());
+impl_dispatch_arg!(BytesObject, |host| host.bytes_new()?.to_val());
+impl_dispatch_arg!(StringObject, |host| host
+    .string_new_from_slice("")?
+    .to_val());
+impl_dispatch_arg!(VecObject, |host| host.vec_new()?.to_val());
+impl_dispatch_arg!(MapObject, |host| host.map_new()?.to_val());
+impl_dispatch_arg!(DurationObject, |host| host
+    .duration_obj_from_u64(0)?
+    .to_val());
+impl_dispatch_arg!(TimepointObject, |host| host
+    .timepoint_obj_from_u64(0)?
+    .to_val());
+impl_dispatch_arg!(U64Object, |host| host.obj_from_u64(0)?.to_val());
+impl_dispatch_arg!(I64Object, |host| host.obj_from_i64(0)?.to_val());
+impl_dispatch_arg!(U128Object, |host| host.obj_from_u128_pieces(0, 0)?.to_val());
+impl_dispatch_arg!(I128Object, |host| host.obj_from_i128_pieces(0, 0)?.to_val());
+impl_dispatch_arg!(U256Object, |host| host
+    .obj_from_u256_pieces(0, 0, 0, 0)?
+    .to_val());
+impl_dispatch_arg!(I256Object, |host| host
+    .obj_from_i256_pieces(0, 0, 0, 0)?
+    .to_val());
+// The `*Val` integer types take either a small value or an object; passing
+// the object form also exercises its relative reference conversion.
+impl_dispatch_arg!(U64Val, |host| U64Object::dispatch_arg(host)?);
+impl_dispatch_arg!(U128Val, |host| U128Object::dispatch_arg(host)?);
+impl_dispatch_arg!(I128Val, |host| I128Object::dispatch_arg(host)?);
+impl_dispatch_arg!(U256Val, |host| U256Object::dispatch_arg(host)?);
+impl_dispatch_arg!(I256Val, |host| I256Object::dispatch_arg(host)?);
+
+const DISPATCH_SYM: Symbol = match Symbol::try_from_small_str("dispatch") {
+    Ok(s) => s,
+    _ => panic!(),
+};
+
+// Runs `f` with the sample's arguments converted to the `i64`s a contract
+// running in the sample's VM would pass for them.
+fn with_dispatch_frame<F>(host: &Host, sample: &DispatchSample, f: F) -> Result<Val, HostError>
+where
+    F: FnOnce(Vec<i64>) -> Result<(), HostError>,
+{
+    let frame = Frame::ContractVM {
+        vm: Rc::clone(&sample.vm),
+        fn_name: DISPATCH_SYM,
+        args: vec![],
+        instance: ScContractInstance {
+            executable: ContractExecutable::Wasm(Hash([0; 32])),
+            storage: None,
+        },
+        relative_objects: vec![],
+    };
+    host.with_frame(frame, || {
+        let args = sample
+            .args
+            .iter()
+            .map(|arg| Ok(host.absolute_to_relative(*arg)?.get_payload() as i64))
+            .collect::<Result<Vec<i64>, HostError>>()?;
+        f(args)?;
+        Ok(Val::VOID.to_val())
+    })
+}
+
+macro_rules! next_dispatch_arg {
+    ($arg:ident, $args:ident) => {
+        $args.next().unwrap_or_default()
+    };
+}
+
+///////////////////////////////////////////////////////////////////////////////
+/// X-macro use: a CostRunner per host function
+///////////////////////////////////////////////////////////////////////////////
+
+// This is a callback macro that pattern-matches the token-tree passed by the
+// x-macro (call_macro_with_all_host_functions) and produces a module of
+// CostRunners for each 'mod' block, along with a list of all their names.
+macro_rules! generate_dispatch_cost_runners {
+    {
+        $(
+            $(#[$mod_attr:meta])*
+            mod $mod_id:ident $mod_str:literal
+            {
+                $(
+                    $(#[$fn_attr:meta])*
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident ($($arg:ident:$type:ty),*) -> $ret:ty }
+                )*
+            }
+        )*
+    }
+
+    =>  // The part of the macro above this line is a matcher; below is its expansion.
+
+    {
+        $(
+            $(#[$mod_attr])*
+            pub mod $mod_id {
+                use super::*;
+
+                $(
+                    $(#[$fn_attr])*
+                    #[allow(non_camel_case_types)]
+                    pub struct $fn_id;
+
+                    impl CostRunner for $fn_id {
+                        const COST_TYPE: ContractCostType = ContractCostType::DispatchHostFunction;
+
+                        const RUN_ITERATIONS: u64 = 1000;
+
+                        type SampleType = DispatchSample;
+
+                        type RecycledType = DispatchSample;
+
+                        fn run_iter(host: &Host, _iter: u64, sample: Self::SampleType) -> Self::RecycledType {
+                            // The host function itself may fail on the
+                            // sample arguments, see the comment at the top.
+                            black_box(
+                                with_dispatch_frame(host, &sample, |args| {
+                                    #[allow(unused_mut, unused_variables)]
+                                    let mut args = args.into_iter();
+                                    let res = sample.vm.with_caller(|caller| {
+                                        Ok(dispatch::$fn_id(caller, $(next_dispatch_arg!($arg, args)),*).is_ok())
+                                    })?;
+                                    black_box(res);
+                                    Ok(())
+                                })
+                                .unwrap(),
+                            );
+                            black_box(sample)
+                        }
+
+                        fn run_baseline_iter(
+                            host: &Host,
+                            _iter: u64,
+                            sample: Self::SampleType,
+                        ) -> Self::RecycledType {
+                            black_box(
+                                with_dispatch_frame(host, &sample, |args| {
+                                    black_box(args);
+                                    Ok(())
+                                })
+                                .unwrap(),
+                            );
+                            black_box(host.charge_budget(Self::COST_TYPE, None).unwrap());
+                            black_box(sample)
+                        }
+                    }
+
+                    $(#[$fn_attr])*
+                    impl DispatchHostFunctionRun for $fn_id {
+                        fn sample_args(host: &Host) -> Result<Vec<Val>, HostError> {
+                            Ok(vec![$(<$type as DispatchArg>::dispatch_arg(host)?),*])
+                        }
+                    }
+                )*
+            }
+        )*
+
+        /// The `(module, function)` import names of every host function that
+        /// has a dispatch cost runner, which is all of them.
+        pub const HOST_FUNCTION_DISPATCH_RUNS: &[(&str, &str)] = &[
+            $(
+                $(
+                    ($mod_str, $fn_str),
+                )*
+            )*
+        ];
+    };
+}
+
+// Here we invoke the x-macro passing generate_dispatch_cost_runners as its callback macro.
+call_macro_with_all_host_functions! { generate_dispatch_cost_runners }
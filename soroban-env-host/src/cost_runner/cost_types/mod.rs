@@ -3,6 +3,7 @@ mod compute_ecdsa_secp256k1_sig;
 mod compute_ed25519_pubkey;
 mod compute_keccak256_hash;
 mod compute_sha256_hash;
+pub mod dispatch_host_function;
 mod host_mem_alloc;
 mod host_mem_cmp;
 mod host_mem_cpy;
@@ -9,7 +9,7 @@ use soroban_env_common::{
         AccountId, ContractDataDurability, ContractEventType, DiagnosticEvent, ExpirationEntry,
         HostFunction, LedgerEntry, LedgerEntryData, LedgerFootprint, LedgerKey, LedgerKeyAccount,
         LedgerKeyContractCode, LedgerKeyContractData, LedgerKeyTrustLine, ScErrorCode, ScErrorType,
-        SorobanAuthorizationEntry, SorobanResources,
+        ScVal, SorobanAuthorizationEntry, SorobanResources,
     },
     Error,
 };
@@ -24,7 +24,9 @@ use crate::{
         metered_clone::{MeteredAlloc, MeteredClone, MeteredContainer, MeteredIterator},
         metered_xdr::{metered_from_xdr_with_budget, metered_write_xdr},
     },
-    storage::{AccessType, Footprint, FootprintMap, SnapshotSource, Storage, StorageMap},
+    storage::{
+        AccessType, Footprint, FootprintMap, FootprintMode, SnapshotSource, Storage, StorageMap,
+    },
     DiagnosticLevel, Host, HostError, LedgerInfo, MeteredOrdMap,
 };
 
@@ -84,10 +86,30 @@ pub struct LedgerEntryExpirationChange {
     pub new_expiration_ledger: u32,
 }
 
+/// Resources consumed by a host function invocation run through
+/// [Host::enact_recording_mode], for use in transaction simulation.
+pub struct ResourceEstimate {
+    /// CPU instructions consumed by the invocation.
+    pub cpu_insns: u64,
+    /// Memory bytes consumed by the invocation.
+    pub mem_bytes: u64,
+    /// Peak memory charged net of released VM instances during the
+    /// invocation, see
+    /// [`Budget::get_mem_bytes_peak`].
+    pub mem_bytes_peak: u64,
+    /// The footprint recorded while running the invocation.
+    pub footprint: Footprint,
+    /// Rent-related changes of the entries modified or bumped by the
+    /// invocation, suitable for `fees::compute_rent_fee`.
+    ///
+    /// Empty when invocation fails.
+    pub rent_bumps: Vec<LedgerEntryRentChange>,
+}
+
 /// Returns the difference between the `storage` and its initial snapshot as
 /// `LedgerEntryChanges`.
 /// Returns an entry for every item in `storage` footprint.
-pub fn get_ledger_changes<T: SnapshotSource>(
+pub fn get_ledger_changes<T: SnapshotSource + ?Sized>(
     budget: &Budget,
     storage: &Storage,
     init_storage_snapshot: &T,
@@ -278,11 +300,14 @@ pub fn invoke_host_function<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
     if enable_diagnostics {
         extract_diagnostic_events(&events, diagnostic_events);
     }
-    let encoded_invoke_result = result.map(|res| {
+    // Invocation failures are reported via `encoded_invoke_result` rather
+    // than propagated, so that the embedder can still consume the
+    // diagnostics and charge for the consumed budget.
+    let encoded_invoke_result = result.and_then(|res| {
         let mut encoded_result_sc_val = vec![];
         metered_write_xdr(&budget, &res, &mut encoded_result_sc_val)?;
         Ok(encoded_result_sc_val)
-    })?;
+    });
     if encoded_invoke_result.is_ok() {
         let init_storage_snapshot = StorageMapSnapshotSource {
             budget: &budget,
@@ -463,6 +488,63 @@ fn build_storage_map_from_xdr_ledger_entries<T: AsRef<[u8]>, I: ExactSizeIterato
 }
 
 impl Host {
+    /// Runs a host function in "preflight" fashion and estimates the resources
+    /// it needs: the budget is reset to an effectively unlimited one before
+    /// the invocation, and the storage footprint is recorded against the
+    /// host's [SnapshotSource].
+    ///
+    /// The host must have been constructed with
+    /// [Storage::with_recording_footprint]. The estimate covers all the
+    /// storage accesses made through the host so far, so a fresh host should
+    /// be used for every simulated invocation. Authorization mode is left
+    /// as-is; use [Host::switch_to_recording_auth] to also record the
+    /// required authorizations.
+    ///
+    /// The invocation result is returned alongside the estimate; only
+    /// internal errors (such as a host in the wrong storage mode) are
+    /// reported in the outer `Result`.
+    pub fn enact_recording_mode(
+        &self,
+        hf: HostFunction,
+    ) -> Result<(Result<ScVal, HostError>, ResourceEstimate), HostError> {
+        let FootprintMode::Recording(src) = self.try_borrow_storage()?.mode.clone() else {
+            return Err(self.err(
+                ScErrorType::Storage,
+                ScErrorCode::InternalError,
+                "resource estimation requires recording footprint mode",
+                &[],
+            ));
+        };
+        self.as_budget().reset_unlimited()?;
+        let result = self.invoke_function(hf);
+        let cpu_insns = self.as_budget().get_cpu_insns_consumed()?;
+        let mem_bytes = self.as_budget().get_mem_bytes_consumed()?;
+        let mem_bytes_peak = self.as_budget().get_mem_bytes_peak()?;
+
+        let storage = self.try_borrow_storage()?.clone();
+        let rent_bumps = if result.is_ok() {
+            let ledger_changes = get_ledger_changes(
+                self.as_budget(),
+                &storage,
+                src.as_ref(),
+                ExpirationEntryMap::new(),
+            )?;
+            extract_rent_changes(&ledger_changes)
+        } else {
+            vec![]
+        };
+        Ok((
+            result,
+            ResourceEstimate {
+                cpu_insns,
+                mem_bytes,
+                mem_bytes_peak,
+                footprint: storage.footprint,
+                rent_bumps,
+            },
+        ))
+    }
+
     fn build_auth_entries_from_xdr<T: AsRef<[u8]>, I: ExactSizeIterator<Item = T>>(
         &self,
         encoded_contract_auth_entries: I,
@@ -12,14 +12,31 @@ use super::{
     InternalEvent, InternalEventsBuffer,
 };
 
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub enum DiagnosticLevel {
     #[default]
     None,
     Debug,
 }
 
-/// None of these functions are metered, which is why they're behind the is_debug check
+/// The severity of a single diagnostic event. Severities are ordered from the
+/// least to the most severe, so that diagnostic events can be filtered by a
+/// minimum severity, see [Host::get_diagnostic_events].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiagnosticSeverity {
+    /// Contract calls and returns.
+    Trace,
+    /// Messages logged by contracts and the host.
+    Debug,
+    /// Suspicious conditions that do not fail the invocation.
+    Warn,
+    /// Host errors.
+    Error,
+}
+
+/// None of these functions are metered, which is why they're behind the
+/// `with_debug_mode` gate
 impl Host {
     pub fn set_diagnostic_level(&self, diagnostic_level: DiagnosticLevel) -> Result<(), HostError> {
         *self.try_borrow_diagnostic_level_mut()? = diagnostic_level;
@@ -38,14 +55,43 @@ impl Host {
         ))
     }
 
+    /// Runs `f` only if diagnostics are enabled, and without charging the
+    /// budget for it. All diagnostic recording goes through this gate, so that
+    /// with [DiagnosticLevel::None] none of the formatting, allocation or
+    /// metering it involves takes place.
+    pub(crate) fn with_debug_mode<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce() -> Result<(), HostError>,
+    {
+        if !self.is_debug()? {
+            return Ok(());
+        }
+        self.as_budget().with_free_budget(f)
+    }
+
+    /// As [Host::with_debug_mode], for the error paths that build a value
+    /// either way: returns the result of `f` if diagnostics are enabled, or
+    /// `None` without running `f` if they are not (or the level can't be
+    /// read). `f` must not charge the budget.
+    pub(crate) fn if_debug_mode<T>(&self, f: impl FnOnce() -> T) -> Option<T> {
+        match self.is_debug() {
+            Ok(true) => Some(f()),
+            _ => None,
+        }
+    }
+
     pub(crate) fn record_diagnostic_event(
         &self,
+        severity: DiagnosticSeverity,
         contract_id: Option<Hash>,
+        function: Option<Symbol>,
         topics: Vec<InternalDiagnosticArg>,
         args: Vec<InternalDiagnosticArg>,
     ) -> Result<(), HostError> {
         let de = Rc::new(InternalDiagnosticEvent {
+            severity,
             contract_id,
+            function,
             topics,
             args,
         });
@@ -66,19 +112,40 @@ impl Host {
         })
     }
 
+    // Will not return error if frame is missing
+    pub(crate) fn get_current_function_unmetered(&self) -> Result<Option<Symbol>, HostError> {
+        self.with_current_frame_opt(|frame| match frame {
+            Some(Frame::ContractVM { fn_name, .. }) => Ok(Some(*fn_name)),
+            Some(Frame::HostFunction(_)) => Ok(None),
+            Some(Frame::Token(_, func, ..)) => Ok(Some(*func)),
+            #[cfg(any(test, feature = "testutils"))]
+            Some(Frame::TestContract(tc)) => Ok(Some(tc.func)),
+            None => Ok(None),
+        })
+    }
+
     pub fn log_diagnostics(&self, msg: &str, args: &[Val]) -> Result<(), HostError> {
-        if !self.is_debug()? {
-            return Ok(());
-        }
-        let calling_contract = self.get_current_contract_id_unmetered()?;
-        self.as_budget().with_free_budget(|| {
+        self.log_diagnostics_with_severity(DiagnosticSeverity::Debug, msg, args)
+    }
+
+    // As above, but records the event with the given severity rather than
+    // `Debug`.
+    pub fn log_diagnostics_with_severity(
+        &self,
+        severity: DiagnosticSeverity,
+        msg: &str,
+        args: &[Val],
+    ) -> Result<(), HostError> {
+        self.with_debug_mode(|| {
+            let calling_contract = self.get_current_contract_id_unmetered()?;
+            let calling_function = self.get_current_function_unmetered()?;
             let log_sym = SymbolSmall::try_from_str("log")?;
             let topics = vec![InternalDiagnosticArg::HostVal(log_sym.to_val())];
             let msg = ScVal::String(ScString::from(StringM::try_from(msg.as_bytes().to_vec())?));
             let args: Vec<_> = std::iter::once(InternalDiagnosticArg::XdrVal(msg))
                 .chain(args.iter().map(|rv| InternalDiagnosticArg::HostVal(*rv)))
                 .collect();
-            self.record_diagnostic_event(calling_contract, topics, args)
+            self.record_diagnostic_event(severity, calling_contract, calling_function, topics, args)
         })
     }
 
@@ -87,22 +154,19 @@ impl Host {
         events: &mut InternalEventsBuffer,
         error: Error,
         msg: &str,
-        args: &[Val],
+        args: impl IntoIterator<Item = InternalDiagnosticArg>,
     ) -> Result<(), HostError> {
-        if !self.is_debug()? {
-            return Ok(());
-        }
-
-        self.as_budget().with_free_budget(|| {
+        self.with_debug_mode(|| {
             let error_sym = SymbolSmall::try_from_str("error")?;
             let contract_id = self.get_current_contract_id_unmetered()?;
+            let function = self.get_current_function_unmetered()?;
             let topics = vec![
                 InternalDiagnosticArg::HostVal(error_sym.to_val()),
                 InternalDiagnosticArg::HostVal(error.to_val()),
             ];
             let msg = ScVal::String(ScString::from(StringM::try_from(msg.as_bytes().to_vec())?));
             let args: Vec<_> = std::iter::once(InternalDiagnosticArg::XdrVal(msg))
-                .chain(args.iter().map(|rv| InternalDiagnosticArg::HostVal(*rv)))
+                .chain(args)
                 .collect();
 
             // We do the event-recording ourselves here rather than calling
@@ -110,7 +174,9 @@ impl Host {
             // only be called with an already-borrowed events buffer (to
             // insulate against double-faulting).
             let ce = Rc::new(InternalDiagnosticEvent {
+                severity: DiagnosticSeverity::Error,
                 contract_id,
+                function,
                 topics,
                 args,
             });
@@ -127,13 +193,9 @@ impl Host {
         func: &Symbol,
         args: &[Val],
     ) -> Result<(), HostError> {
-        if !self.is_debug()? {
-            return Ok(());
-        }
-
-        let calling_contract = self.get_current_contract_id_unmetered()?;
-
-        self.as_budget().with_free_budget(|| {
+        self.with_debug_mode(|| {
+            let calling_contract = self.get_current_contract_id_unmetered()?;
+            let calling_function = self.get_current_function_unmetered()?;
             let topics = vec![
                 InternalDiagnosticArg::HostVal(SymbolSmall::try_from_str("fn_call")?.into()),
                 InternalDiagnosticArg::XdrVal(ScVal::Bytes(ScBytes::try_from(
@@ -142,7 +204,9 @@ impl Host {
                 InternalDiagnosticArg::HostVal(func.into()),
             ];
             self.record_diagnostic_event(
+                DiagnosticSeverity::Trace,
                 calling_contract,
+                calling_function,
                 topics,
                 args.iter()
                     .map(|rv| InternalDiagnosticArg::HostVal(*rv))
@@ -159,18 +223,16 @@ impl Host {
         func: &Symbol,
         res: &Val,
     ) -> Result<(), HostError> {
-        if !self.is_debug()? {
-            return Ok(());
-        }
-
-        self.as_budget().with_free_budget(|| {
+        self.with_debug_mode(|| {
             let topics = vec![
                 InternalDiagnosticArg::HostVal(SymbolSmall::try_from_str("fn_return")?.into()),
                 InternalDiagnosticArg::HostVal(func.into()),
             ];
 
             self.record_diagnostic_event(
+                DiagnosticSeverity::Trace,
                 Some(contract_id.clone()),
+                Some(*func),
                 topics,
                 vec![InternalDiagnosticArg::HostVal(*res)],
             )
@@ -2,13 +2,13 @@ use std::rc::Rc;
 
 use soroban_env_common::{BytesObject, VecObject};
 
-use super::{Events, HostEvent};
+use super::{diagnostic::DiagnosticSeverity, Events, HostDiagnosticEvent, HostEvent};
 use crate::{
     budget::{AsBudget, Budget},
     host::metered_clone::MeteredContainer,
     xdr,
     xdr::ScVal,
-    Host, HostError, Val,
+    Host, HostError, Symbol, Val,
 };
 
 /// The internal representation of a `ContractEvent` that is stored in the events buffer
@@ -58,7 +58,10 @@ impl InternalContractEvent {
 
 #[derive(Clone, Debug)]
 pub struct InternalDiagnosticEvent {
+    pub severity: DiagnosticSeverity,
     pub contract_id: Option<crate::xdr::Hash>,
+    /// The contract function that was executing when the event was recorded.
+    pub function: Option<Symbol>,
     pub topics: Vec<InternalDiagnosticArg>,
     pub args: Vec<InternalDiagnosticArg>,
 }
@@ -182,4 +185,42 @@ impl InternalEventsBuffer {
             .collect();
         Ok(Events(vec?))
     }
+
+    /// Converts the diagnostic events of at least `min_severity` into their
+    /// structured external representation. Like diagnostics in general, this
+    /// is not metered.
+    pub fn externalize_diagnostics(
+        &self,
+        host: &Host,
+        min_severity: DiagnosticSeverity,
+    ) -> Result<Vec<HostDiagnosticEvent>, HostError> {
+        host.as_budget().with_free_budget(|| {
+            let mut res = vec![];
+            for (e, err) in self.vec.iter() {
+                let InternalEvent::Diagnostic(de) = e else {
+                    continue;
+                };
+                if de.severity < min_severity {
+                    continue;
+                }
+                let function = match de.function {
+                    Some(func) => match host.from_host_val(func.to_val())? {
+                        ScVal::Symbol(sym) => Some(sym),
+                        _ => None,
+                    },
+                    None => None,
+                };
+                res.push(HostDiagnosticEvent {
+                    severity: de.severity,
+                    contract_id: de.contract_id.clone(),
+                    function,
+                    event: xdr::DiagnosticEvent {
+                        in_successful_contract_call: *err == EventError::FromSuccessfulCall,
+                        event: de.to_xdr(host)?,
+                    },
+                });
+            }
+            Ok(res)
+        })
+    }
 }
@@ -2,107 +2,79 @@ pub(crate) mod diagnostic;
 mod internal;
 pub(crate) mod system_events;
 
-pub(crate) use internal::{EventError, InternalEventsBuffer};
+pub use diagnostic::DiagnosticSeverity;
+
+pub(crate) use internal::InternalDiagnosticArg;
 #[cfg(test)]
-pub(crate) use internal::{InternalDiagnosticArg, InternalDiagnosticEvent};
+pub(crate) use internal::InternalDiagnosticEvent;
+pub(crate) use internal::{EventError, InternalEventsBuffer};
 // expose them as pub use for benches
 pub use internal::{InternalContractEvent, InternalEvent};
 use soroban_env_common::{
-    num::{i256_from_pieces, u256_from_pieces},
-    xdr::{
-        ContractEventBody, ContractEventType, ContractExecutable, PublicKey::PublicKeyTypeEd25519,
-        ScAddress, ScContractInstance, ScVal,
-    },
-    Error, Val, VecObject,
+    xdr::{ContractEventBody, ContractEventType, Hash, ScErrorCode, ScErrorType, ScSymbol, ScVal},
+    U32Val, Val, VecObject,
 };
 
-use crate::{budget::AsBudget, Host, HostError};
+use std::rc::Rc;
+
+use crate::{
+    budget::AsBudget,
+    display::ScValDisplay,
+    host::{metered_clone::MeteredClone, metered_xdr::metered_write_xdr},
+    host_object::HostVec,
+    Host, HostError,
+};
+
+/// Limits on the contract events a contract may emit, so that the network can
+/// bound how much event data a transaction produces. Emitting an event that
+/// exceeds any of them fails with an `(Events, ExceededLimit)` error.
+///
+/// By default every limit is `u32::MAX`, i.e. events are unlimited.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EventLimits {
+    /// The maximum number of topics of an event.
+    pub max_topics: u32,
+    /// The maximum size of each topic, in bytes of its XDR encoding.
+    pub max_topic_bytes: u32,
+    /// The maximum nesting depth of an event's data, where a value that
+    /// isn't a vec or a map has depth 1.
+    pub max_data_depth: u32,
+}
+
+impl Default for EventLimits {
+    fn default() -> Self {
+        EventLimits {
+            max_topics: u32::MAX,
+            max_topic_bytes: u32::MAX,
+            max_data_depth: u32::MAX,
+        }
+    }
+}
+
+// The nesting depth of `v`, see `EventLimits::max_data_depth`. Converting a
+// value to XDR already bounds its depth, so this can't recurse too deeply.
+fn scval_depth(v: &ScVal) -> usize {
+    let inner = match v {
+        ScVal::Vec(Some(v)) => v.iter().map(scval_depth).max().unwrap_or(0),
+        ScVal::Map(Some(m)) => m
+            .iter()
+            .map(|e| scval_depth(&e.key).max(scval_depth(&e.val)))
+            .max()
+            .unwrap_or(0),
+        _ => 0,
+    };
+    inner.saturating_add(1)
+}
 
 /// The external representation of a host event.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostEvent {
     pub event: crate::xdr::ContractEvent,
     // failed_call keeps track of if the call this event was emitted in failed
     pub failed_call: bool,
 }
 
-fn display_address(addr: &ScAddress, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    match addr {
-        ScAddress::Account(acct) => match &acct.0 {
-            PublicKeyTypeEd25519(e) => write!(f, "Address(Account({}))", e),
-        },
-        ScAddress::Contract(hash) => write!(f, "Address(Contract({}))", hash),
-    }
-}
-
-fn display_scval(scv: &ScVal, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    match scv {
-        ScVal::Bool(v) => write!(f, "{}", v),
-        ScVal::Void => write!(f, "Void"),
-        ScVal::Error(e) => write!(f, "{:?}", Error::from_scerror(e.clone())),
-        ScVal::U32(v) => write!(f, "{}", v),
-        ScVal::I32(v) => write!(f, "{}", v),
-        ScVal::U64(v) => write!(f, "{}", v),
-        ScVal::I64(v) => write!(f, "{}", v),
-        ScVal::Timepoint(v) => write!(f, "TimePoint({})", v.0),
-        ScVal::Duration(v) => write!(f, "Duration({})", v.0),
-        ScVal::U128(v) => write!(f, "{}", u128::from(v)),
-        ScVal::I128(v) => write!(f, "{}", i128::from(v)),
-        ScVal::U256(v) => write!(
-            f,
-            "{}",
-            u256_from_pieces(v.hi_hi, v.hi_lo, v.lo_hi, v.lo_lo)
-        ),
-        ScVal::I256(v) => write!(
-            f,
-            "{}",
-            i256_from_pieces(v.hi_hi, v.hi_lo, v.lo_hi, v.lo_lo)
-        ),
-        ScVal::Bytes(v) => write!(f, "Bytes({})", v.0),
-        ScVal::String(v) => write!(f, "\"{}\"", v.0),
-        ScVal::Symbol(v) => write!(f, "{}", v.0),
-        ScVal::Vec(None) => write!(f, "[]"),
-        ScVal::Vec(Some(vec)) => {
-            write!(f, "[")?;
-            for (i, e) in vec.0.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                display_scval(e, f)?;
-            }
-            write!(f, "]")
-        }
-        ScVal::Map(None) => write!(f, "{{}}"),
-        ScVal::Map(Some(pairs)) => {
-            write!(f, "{{")?;
-            for (i, e) in pairs.0.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                display_scval(&e.key, f)?;
-                write!(f, ": ")?;
-                display_scval(&e.val, f)?;
-            }
-            write!(f, "}}")
-        }
-        ScVal::Address(addr) => display_address(addr, f),
-        ScVal::LedgerKeyContractInstance => write!(f, "LedgerKeyContractInstance"),
-        ScVal::LedgerKeyNonce(n) => {
-            write!(f, "LedgerKeyNonce({})", n.nonce)
-        }
-        ScVal::ContractInstance(ScContractInstance {
-            executable: ContractExecutable::Wasm(hash),
-            ..
-        }) => {
-            write!(f, "ContractInstance(Wasm({}))", hash)
-        }
-        ScVal::ContractInstance(ScContractInstance {
-            executable: ContractExecutable::Token,
-            ..
-        }) => write!(f, "ContractInstance(Token)"),
-    }
-}
-
 impl core::fmt::Display for HostEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.failed_call {
@@ -121,19 +93,61 @@ impl core::fmt::Display for HostEvent {
                     if i != 0 {
                         write!(f, ", ")?;
                     }
-                    display_scval(topic, f)?;
+                    write!(f, "{}", ScValDisplay(topic))?;
                 }
                 write!(f, "], data:")?;
-                display_scval(&ceb.data, f)
+                write!(f, "{}", ScValDisplay(&ceb.data))
             }
         }
     }
 }
 
+/// The structured external representation of a diagnostic event, see
+/// [Host::get_diagnostic_events].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostDiagnosticEvent {
+    pub severity: DiagnosticSeverity,
+    /// The contract that was executing when the event was recorded, if any.
+    pub contract_id: Option<Hash>,
+    /// The contract function that was executing when the event was
+    /// recorded, if any.
+    pub function: Option<ScSymbol>,
+    /// The event as it is externalized to XDR.
+    pub event: crate::xdr::DiagnosticEvent,
+}
+
 /// The external representation of events in the chronological order.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Events(pub Vec<HostEvent>);
 
+/// Selects which events are passed to the hook installed with
+/// [Host::set_event_hook]. An empty filter matches every event.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventFilter {
+    /// If set, only events emitted by this contract match.
+    pub contract_id: Option<Hash>,
+    /// Only events whose topics start with these values match.
+    pub topic_prefix: Vec<ScVal>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &crate::xdr::ContractEvent) -> bool {
+        if self.contract_id.is_some() && self.contract_id != event.contract_id {
+            return false;
+        }
+        match &event.body {
+            ContractEventBody::V0(ceb) => ceb.topics.starts_with(self.topic_prefix.as_slice()),
+        }
+    }
+}
+
+/// A callback invoked by the host as contract events are recorded. See
+/// [Host::set_event_hook].
+pub type EventHook = Rc<dyn Fn(&HostEvent) -> Result<(), HostError>>;
+
 impl Host {
     pub(crate) fn with_events_mut<F, U>(&self, f: F) -> Result<U, HostError>
     where
@@ -146,6 +160,133 @@ impl Host {
         self.try_borrow_events()?.externalize(self)
     }
 
+    /// Returns the diagnostic events of at least `min_severity` recorded so
+    /// far, along with the contract and function that emitted them. Nothing
+    /// is recorded unless diagnostics are enabled, see
+    /// [Host::set_diagnostic_level].
+    pub fn get_diagnostic_events(
+        &self,
+        min_severity: DiagnosticSeverity,
+    ) -> Result<Vec<HostDiagnosticEvent>, HostError> {
+        self.try_borrow_events()?
+            .externalize_diagnostics(self, min_severity)
+    }
+
+    /// Installs a hook that is called with every contract and system event
+    /// matching `filter` as soon as it is recorded, so that embedders can
+    /// stream events rather than waiting for the invocation to finish.
+    /// Replaces any previously installed hook.
+    ///
+    /// Events are delivered before the outcome of the call that emitted them
+    /// is known, so they always have `failed_call` set to `false`; an event
+    /// from a call that later fails is not emitted on-chain. Diagnostic
+    /// events are not delivered to the hook.
+    ///
+    /// Calling the hook is not metered. An error returned by the hook fails
+    /// the call that emitted the event.
+    pub fn set_event_hook<F>(&self, filter: EventFilter, hook: F) -> Result<(), HostError>
+    where
+        F: Fn(&HostEvent) -> Result<(), HostError> + 'static,
+    {
+        *self.try_borrow_event_hook_mut()? = Some((filter, Rc::new(hook)));
+        Ok(())
+    }
+
+    /// Removes the hook installed with [Host::set_event_hook], if any.
+    pub fn remove_event_hook(&self) -> Result<(), HostError> {
+        *self.try_borrow_event_hook_mut()? = None;
+        Ok(())
+    }
+
+    // Returns the installed hook along with `ce` as the event to pass it, if
+    // there is a hook and its filter matches the event.
+    fn event_for_hook(
+        &self,
+        ce: &InternalContractEvent,
+    ) -> Result<Option<(EventHook, HostEvent)>, HostError> {
+        let hook_ref = self.try_borrow_event_hook()?;
+        let Some((filter, hook)) = hook_ref.as_ref() else {
+            return Ok(None);
+        };
+        let event = self.as_budget().with_free_budget(|| ce.to_xdr(self))?;
+        if !filter.matches(&event) {
+            return Ok(None);
+        }
+        // The hook is cloned out of the cell so that it may itself call back
+        // into the host (including replacing the hook).
+        Ok(Some((
+            Rc::clone(hook),
+            HostEvent {
+                event,
+                failed_call: false,
+            },
+        )))
+    }
+
+    /// Sets the limits on the contract events that contracts may emit.
+    /// Events emitted by the host itself aren't subject to them.
+    pub fn set_event_limits(&self, limits: EventLimits) -> Result<(), HostError> {
+        *self.try_borrow_event_limits_mut()? = limits;
+        Ok(())
+    }
+
+    /// Returns the limits on the contract events that contracts may emit.
+    pub fn get_event_limits(&self) -> Result<EventLimits, HostError> {
+        Ok(*self.try_borrow_event_limits()?)
+    }
+
+    fn event_limit_exceeded(&self, msg: &'static str, actual: usize, limit: u32) -> HostError {
+        let actual = U32Val::from(u32::try_from(actual).unwrap_or(u32::MAX));
+        self.err(
+            ScErrorType::Events,
+            ScErrorCode::ExceededLimit,
+            msg,
+            &[actual.to_val(), U32Val::from(limit).to_val()],
+        )
+    }
+
+    // Checks a contract event against the event limits. Measuring topics and
+    // data means converting them to XDR, so that's only done for the limits
+    // that are set.
+    fn check_event_limits(&self, topics: VecObject, data: Val) -> Result<(), HostError> {
+        let limits = *self.try_borrow_event_limits()?;
+        if limits == EventLimits::default() {
+            return Ok(());
+        }
+        let topics = self.visit_obj(topics, |hv: &HostVec| hv.metered_clone(self))?;
+        if topics.len() > limits.max_topics as usize {
+            return Err(self.event_limit_exceeded(
+                "too many event topics",
+                topics.len(),
+                limits.max_topics,
+            ));
+        }
+        if limits.max_topic_bytes != u32::MAX {
+            for topic in topics.iter() {
+                let mut buf = vec![];
+                metered_write_xdr(self.budget_ref(), &self.from_host_val(*topic)?, &mut buf)?;
+                if buf.len() > limits.max_topic_bytes as usize {
+                    return Err(self.event_limit_exceeded(
+                        "event topic too large",
+                        buf.len(),
+                        limits.max_topic_bytes,
+                    ));
+                }
+            }
+        }
+        if limits.max_data_depth != u32::MAX {
+            let depth = scval_depth(&self.from_host_val(data)?);
+            if depth > limits.max_data_depth as usize {
+                return Err(self.event_limit_exceeded(
+                    "event data nested too deeply",
+                    depth,
+                    limits.max_data_depth,
+                ));
+            }
+        }
+        Ok(())
+    }
+
     // Records a contract event.
     pub(crate) fn record_contract_event(
         &self,
@@ -153,14 +294,22 @@ impl Host {
         topics: VecObject,
         data: Val,
     ) -> Result<(), HostError> {
+        if type_ == ContractEventType::Contract {
+            self.check_event_limits(topics, data)?;
+        }
         let ce = InternalContractEvent {
             type_,
             contract_id: self.bytesobj_from_internal_contract_id()?,
             topics,
             data,
         };
+        let hooked = self.event_for_hook(&ce)?;
         self.with_events_mut(|events| {
             Ok(events.record(InternalEvent::Contract(ce), self.as_budget()))
-        })?
+        })??;
+        match hooked {
+            Some((hook, event)) => hook(&event),
+            None => Ok(()),
+        }
     }
 }
@@ -1,7 +1,17 @@
-use crate::{native_contract::common_types::ContractExecutable, Host, HostError};
+use crate::{
+    host::metered_clone::MeteredClone, native_contract::common_types::ContractExecutable, Host,
+    HostError,
+};
 use soroban_env_common::{xdr, EnvBase, Symbol, TryFromVal, TryIntoVal, Val, VecObject};
 
 const CONTRACT_EXECUTABLE_UPDATE_TOPIC: &str = "executable_update";
+const CONTRACT_DEPLOY_TOPIC: &str = "deploy";
+// Events are part of the invocation result, so the deploy event only exists
+// from the protocol it was introduced in on.
+const CONTRACT_DEPLOY_EVENT_MIN_PROTOCOL: u32 = 21;
+
+// Contracts are never removed from the ledger by the host, only archived once
+// their instance expires, so there is no system event for removal.
 
 impl Host {
     pub fn system_event(&self, topics: VecObject, data: Val) -> Result<(), HostError> {
@@ -28,4 +38,37 @@ impl Host {
         )?;
         Ok(())
     }
+
+    // Returns whether creating a contract emits a deploy event: only from
+    // `CONTRACT_DEPLOY_EVENT_MIN_PROTOCOL` on, and only from within a frame,
+    // as events belong to the frame they're emitted from (so a contract
+    // created outside of any frame, which only test code does, emits none).
+    pub(crate) fn should_emit_deploy_contract_event(&self) -> Result<bool, HostError> {
+        Ok(
+            self.get_ledger_protocol_version()? >= CONTRACT_DEPLOY_EVENT_MIN_PROTOCOL
+                && !self.try_borrow_context()?.is_empty(),
+        )
+    }
+
+    // Emits a system event for deploying a new contract. The event topics are
+    // "deploy", the address of the new contract and its executable (encoded
+    // like those of the update event above), and the data is empty. Callers
+    // must check `should_emit_deploy_contract_event` first.
+    pub(crate) fn emit_deploy_contract_event(
+        &self,
+        contract_id: &xdr::Hash,
+        executable: &xdr::ContractExecutable,
+    ) -> Result<(), HostError> {
+        let address =
+            self.add_host_object(xdr::ScAddress::Contract(contract_id.metered_clone(self)?))?;
+        self.system_event(
+            self.vec_new_from_slice(&[
+                Symbol::try_from_val(self, &CONTRACT_DEPLOY_TOPIC)?.into(),
+                address.into(),
+                ContractExecutable::from_xdr(self, executable)?.try_into_val(self)?,
+            ])?,
+            self.vec_new_from_slice(&[])?.into(),
+        )?;
+        Ok(())
+    }
 }
@@ -14,6 +14,7 @@ const INSTRUCTIONS_INCREMENT: i64 = 10000;
 const DATA_SIZE_1KB_INCREMENT: i64 = 1024;
 
 /// These are the resource upper bounds specified by the Soroban transaction.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct TransactionResources {
     /// Number of CPU instructions.
     pub instructions: u32,
@@ -37,6 +38,7 @@ pub struct TransactionResources {
 /// This should be normally loaded from the ledger, with exception of the
 /// `fee_per_write_1kb`, that has to be computed via `compute_write_fee_per_1kb`
 /// function.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct FeeConfiguration {
     /// Fee per `INSTRUCTIONS_INCREMENT=10000` instructions.
     pub fee_per_instruction_increment: i64,
@@ -61,6 +63,7 @@ pub struct FeeConfiguration {
 /// Network configuration used to determine the ledger write fee.
 ///
 /// This should be normally loaded from the ledger.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct WriteFeeConfiguration {
     // Write fee grows linearly until bucket list reaches this size.
     pub bucket_list_target_size_bytes: i64,
@@ -79,6 +82,7 @@ pub struct WriteFeeConfiguration {
 ///
 /// This represents the entry state before and after transaction has been
 /// applied.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct LedgerEntryRentChange {
     /// Whether this is persistent or temporary entry.
     pub is_persistent: bool,
@@ -101,6 +105,7 @@ pub struct LedgerEntryRentChange {
 /// This should be normally loaded from the ledger, with exception of the
 /// `fee_per_write_1kb`, that has to be computed via `compute_write_fee_per_1kb`
 /// function.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct RentFeeConfiguration {
     /// Fee per 1KB written to ledger.
     /// This is the same field as in `FeeConfiguration` and it has to be
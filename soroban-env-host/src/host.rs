@@ -7,33 +7,46 @@ use std::rc::Rc;
 use crate::{
     auth::AuthorizationManager,
     budget::{AsBudget, Budget},
-    events::{diagnostic::DiagnosticLevel, Events, InternalEventsBuffer},
+    events::{
+        diagnostic::DiagnosticLevel, EventFilter, EventHook, EventLimits, Events,
+        InternalEventsBuffer,
+    },
     host_object::{HostMap, HostObject, HostObjectType, HostVec},
     impl_bignum_host_fns_rhs_u32, impl_wrapping_obj_from_num, impl_wrapping_obj_to_num,
     num::*,
-    storage::Storage,
+    storage::{Storage, StorageCommitHook},
     xdr::{
         int128_helpers, AccountId, Asset, ContractCostType, ContractEventType, ContractExecutable,
         CreateContractArgs, Duration, Hash, LedgerEntryData, PublicKey, ScAddress, ScBytes,
-        ScErrorType, ScString, ScSymbol, ScVal, TimePoint,
+        ScErrorType, ScString, ScSymbol, ScVal, TimePoint, Uint256,
     },
     AddressObject, Bool, BytesObject, ConversionError, Error, I128Object, I256Object, MapObject,
     StorageType, StringObject, SymbolObject, SymbolSmall, SymbolStr, TryFromVal, U128Object,
     U256Object, U32Val, U64Val, VecObject, VmCaller, VmCallerEnv, Void, I256, U256,
 };
 
-use crate::Vm;
+use crate::vm::{ModuleCache, Vm, VmConfig};
 use crate::{EnvBase, Object, Symbol, Val};
 
+mod builder;
+#[cfg(any(test, feature = "testutils"))]
+mod call_counts;
+#[cfg(any(test, feature = "testutils"))]
+pub(crate) mod call_trace;
 mod comparison;
 mod conversion;
 pub(crate) mod crypto;
 mod data_helper;
-mod declared_size;
+pub(crate) mod declared_size;
+pub mod display;
 pub(crate) mod error;
 pub(crate) mod frame;
+#[cfg(any(test, feature = "testutils"))]
+mod frame_costs;
 pub(crate) mod ledger_info_helper;
 mod lifecycle;
+mod limits;
+pub use limits::Limits;
 mod mem_helper;
 pub(crate) mod metered_clone;
 pub(crate) mod metered_map;
@@ -42,7 +55,22 @@ pub(crate) mod metered_xdr;
 mod num;
 mod prng;
 pub use prng::{Seed, SEED_BYTES};
+#[cfg(any(test, feature = "testutils"))]
+mod snapshot;
+#[cfg(any(test, feature = "testutils"))]
+pub use snapshot::HostSnapshot;
+#[cfg(any(test, feature = "testutils"))]
+mod test_ledger;
+#[cfg(any(test, feature = "testutils"))]
+pub use test_ledger::{LedgerAutoAdvance, LedgerInfoBuilder};
+#[cfg(any(test, feature = "testutils"))]
+mod typed_contract;
+#[cfg(any(test, feature = "testutils"))]
+mod unhandled_errors;
+#[cfg(any(test, feature = "testutils"))]
+pub use typed_contract::{TypedContractFn, TypedContractFunctionSet};
 mod validity;
+pub use builder::HostBuilder;
 pub use error::HostError;
 use soroban_env_common::xdr::{ContractIdPreimage, ContractIdPreimageFromAddress, ScErrorCode};
 
@@ -57,8 +85,12 @@ use self::{
 use crate::impl_bignum_host_fns;
 use crate::Compare;
 #[cfg(any(test, feature = "testutils"))]
+pub use call_trace::CallTraceNode;
+#[cfg(any(test, feature = "testutils"))]
 pub use frame::ContractFunctionSet;
 pub(crate) use frame::Frame;
+#[cfg(any(test, feature = "testutils"))]
+pub use frame_costs::{FrameCost, ObjectSummary};
 
 /// Defines the maximum depth for recursive calls in the host, i.e. `Val` conversion, comparison,
 /// and deep clone, to prevent stack overflow.
@@ -74,6 +106,29 @@ pub(crate) use frame::Frame;
 /// `DEFAULT_HOST_DEPTH_LIMIT` here is set to a smaller value.
 pub const DEFAULT_HOST_DEPTH_LIMIT: u32 = 100;
 
+/// Length of the strkey form of both account (`G...`) and contract (`C...`)
+/// addresses.
+const STRKEY_LEN: usize = 56;
+
+/// Protocol from which unpacking a vector or map to linear memory fails if the
+/// output slice isn't exactly as long as the object. Before that the lengths
+/// aren't compared.
+const UNPACK_LENGTH_CHECK_MIN_PROTOCOL: u32 = 21;
+
+// Returns the key of `entry`, for the functions navigating the keys of a map
+// (`map_min_key` and so on), or fails with a `MissingValue` error if there is
+// no entry.
+fn map_key_or_missing(
+    host: &Host,
+    entry: Option<&(Val, Val)>,
+    msg: &str,
+) -> Result<Val, HostError> {
+    match entry {
+        Some((k, _)) => Ok(*k),
+        None => Err(host.err(ScErrorType::Object, ScErrorCode::MissingValue, msg, &[])),
+    }
+}
+
 /// Temporary helper for denoting a slice of guest memory, as formed by
 /// various bytes operations.
 pub(crate) struct VmSlice {
@@ -101,6 +156,11 @@ struct HostImpl {
     objects: RefCell<Vec<HostObject>>,
     storage: RefCell<Storage>,
     context: RefCell<Vec<Context>>,
+    // Contracts that the contract running in a frame has explicitly permitted
+    // to re-enter it, via `allow_reentry`, along with the index of that frame
+    // on the context stack, in stack order. Kept apart from `context` so that
+    // frames without any don't pay for them.
+    reentry_allowances: RefCell<Vec<(usize, Hash)>>,
     // Note: budget is refcounted and is _not_ deep-cloned when you call HostImpl::deep_clone,
     // mainly because it's not really possible to achieve (the same budget is connected to many
     // metered sub-objects) but also because it's plausible that the person calling deep_clone
@@ -111,12 +171,60 @@ struct HostImpl {
     authorization_manager: RefCell<AuthorizationManager>,
     diagnostic_level: RefCell<DiagnosticLevel>,
     base_prng: RefCell<Option<Prng>>,
+    // Optional cache of parsed Wasm modules, keyed by contract code hash. This
+    // is a shared handle, so it may outlive the host and be reused by others.
+    module_cache: RefCell<Option<ModuleCache>>,
+    // Wasm proposals accepted by this host, if overridden via
+    // `Host::set_vm_config`.
+    vm_config: RefCell<Option<VmConfig>>,
+    // Optional callback for streaming events as they are recorded, installed
+    // via `Host::set_event_hook`.
+    event_hook: RefCell<Option<(EventFilter, EventHook)>>,
+    // Limits on the events contracts may emit, see `Host::set_event_limits`.
+    event_limits: RefCell<EventLimits>,
+    // Optional callback for the storage changes of every successful top-level
+    // invocation, installed via `Host::set_storage_commit_hook`.
+    storage_commit_hook: RefCell<Option<StorageCommitHook>>,
     // Note: we're not going to charge metering for testutils because it's out of the scope
     // of what users will be charged for in production -- it's scaffolding for testing a contract,
     // but shouldn't be charged to the contract itself (and will never be compiled-in to
     // production hosts)
     #[cfg(any(test, feature = "testutils"))]
     contracts: RefCell<std::collections::HashMap<Hash, Rc<dyn ContractFunctionSet>>>,
+    // Tree of contract invocations, recorded only when enabled via
+    // `Host::enable_call_trace`.
+    #[cfg(any(test, feature = "testutils"))]
+    call_trace: RefCell<Option<call_trace::CallTraceRecorder>>,
+    // Number of calls into each host function from Wasm, counted only when
+    // enabled via `Host::enable_function_call_counts`.
+    #[cfg(any(test, feature = "testutils"))]
+    function_call_counts: RefCell<Option<std::collections::BTreeMap<&'static str, u64>>>,
+    // Budget consumed by every contract frame popped so far, see
+    // `Host::get_per_frame_costs`.
+    #[cfg(any(test, feature = "testutils"))]
+    frame_costs: RefCell<Vec<frame_costs::FrameCost>>,
+    // The contract function that created each host object, by object index,
+    // see `Host::dump_objects`.
+    #[cfg(any(test, feature = "testutils"))]
+    object_origins: RefCell<Vec<Option<(Hash, Symbol)>>>,
+    // The contract function run by each frame on the context stack, kept
+    // apart from `context` so that new objects can be attributed to their
+    // creator while the context is borrowed.
+    #[cfg(any(test, feature = "testutils"))]
+    frame_origins: RefCell<Vec<Option<(Hash, Symbol)>>>,
+    // The budget (cpu, mem) consumed when each frame on the context stack was
+    // pushed, for recording per-frame costs. Kept apart from `context` so as
+    // not to change the size of its entries.
+    #[cfg(any(test, feature = "testutils"))]
+    frame_consumed_at_push: RefCell<Vec<(u64, u64)>>,
+    // How far to advance the ledger before every top-level invocation, see
+    // `Host::set_ledger_auto_advance`.
+    #[cfg(any(test, feature = "testutils"))]
+    ledger_auto_advance: RefCell<Option<LedgerAutoAdvance>>,
+    // Errors returned by `try_call` that the test hasn't handled yet, see
+    // `Host::enable_unhandled_error_check`.
+    #[cfg(any(test, feature = "testutils"))]
+    unhandled_errors: unhandled_errors::UnhandledErrors,
     // Store a copy of the `AuthorizationManager` for the last host function
     // invocation. In order to emulate the production behavior in tests, we reset
     // authorization manager after every invocation (as it's not meant to be
@@ -179,6 +287,12 @@ impl_checked_borrow_helpers!(
     try_borrow_objects_mut
 );
 impl_checked_borrow_helpers!(storage, Storage, try_borrow_storage, try_borrow_storage_mut);
+impl_checked_borrow_helpers!(
+    reentry_allowances,
+    Vec<(usize, Hash)>,
+    try_borrow_reentry_allowances,
+    try_borrow_reentry_allowances_mut
+);
 impl_checked_borrow_helpers!(
     context,
     Vec<Context>,
@@ -209,10 +323,96 @@ impl_checked_borrow_helpers!(
     try_borrow_base_prng,
     try_borrow_base_prng_mut
 );
+impl_checked_borrow_helpers!(
+    module_cache,
+    Option<ModuleCache>,
+    try_borrow_module_cache,
+    try_borrow_module_cache_mut
+);
+impl_checked_borrow_helpers!(
+    vm_config,
+    Option<VmConfig>,
+    try_borrow_vm_config,
+    try_borrow_vm_config_mut
+);
+impl_checked_borrow_helpers!(
+    event_hook,
+    Option<(EventFilter, EventHook)>,
+    try_borrow_event_hook,
+    try_borrow_event_hook_mut
+);
+impl_checked_borrow_helpers!(
+    event_limits,
+    EventLimits,
+    try_borrow_event_limits,
+    try_borrow_event_limits_mut
+);
+impl_checked_borrow_helpers!(
+    storage_commit_hook,
+    Option<StorageCommitHook>,
+    try_borrow_storage_commit_hook,
+    try_borrow_storage_commit_hook_mut
+);
 
 #[cfg(any(test, feature = "testutils"))]
 impl_checked_borrow_helpers!(contracts, std::collections::HashMap<Hash, Rc<dyn ContractFunctionSet>>, try_borrow_contracts, try_borrow_contracts_mut);
 
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    call_trace,
+    Option<call_trace::CallTraceRecorder>,
+    try_borrow_call_trace,
+    try_borrow_call_trace_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    function_call_counts,
+    Option<std::collections::BTreeMap<&'static str, u64>>,
+    try_borrow_function_call_counts,
+    try_borrow_function_call_counts_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    frame_costs,
+    Vec<frame_costs::FrameCost>,
+    try_borrow_frame_costs,
+    try_borrow_frame_costs_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    object_origins,
+    Vec<Option<(Hash, Symbol)>>,
+    try_borrow_object_origins,
+    try_borrow_object_origins_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    frame_origins,
+    Vec<Option<(Hash, Symbol)>>,
+    try_borrow_frame_origins,
+    try_borrow_frame_origins_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    frame_consumed_at_push,
+    Vec<(u64, u64)>,
+    try_borrow_frame_consumed_at_push,
+    try_borrow_frame_consumed_at_push_mut
+);
+
+#[cfg(any(test, feature = "testutils"))]
+impl_checked_borrow_helpers!(
+    ledger_auto_advance,
+    Option<LedgerAutoAdvance>,
+    try_borrow_ledger_auto_advance,
+    try_borrow_ledger_auto_advance_mut
+);
+
 #[cfg(any(test, feature = "testutils"))]
 impl_checked_borrow_helpers!(
     previous_authorization_manager,
@@ -246,6 +446,7 @@ impl Host {
             objects: Default::default(),
             storage: RefCell::new(storage),
             context: Default::default(),
+            reentry_allowances: Default::default(),
             budget,
             events: Default::default(),
             authorization_manager: RefCell::new(
@@ -253,9 +454,30 @@ impl Host {
             ),
             diagnostic_level: Default::default(),
             base_prng: RefCell::new(None),
+            module_cache: RefCell::new(None),
+            vm_config: RefCell::new(None),
+            event_hook: RefCell::new(None),
+            event_limits: Default::default(),
+            storage_commit_hook: RefCell::new(None),
             #[cfg(any(test, feature = "testutils"))]
             contracts: Default::default(),
             #[cfg(any(test, feature = "testutils"))]
+            call_trace: RefCell::new(None),
+            #[cfg(any(test, feature = "testutils"))]
+            function_call_counts: RefCell::new(None),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_costs: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            object_origins: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_origins: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_consumed_at_push: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            ledger_auto_advance: RefCell::new(None),
+            #[cfg(any(test, feature = "testutils"))]
+            unhandled_errors: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
             previous_authorization_manager: RefCell::new(None),
         }))
     }
@@ -385,6 +607,82 @@ impl Host {
             })
     }
 
+    /// Creates an independent copy of this host, so that an invocation can be
+    /// executed speculatively on the copy, its outcome inspected and the copy
+    /// discarded, all without affecting this host.
+    ///
+    /// The copy starts out with the same storage, objects, events, budget and
+    /// authorization state as this host, so any values obtained from this
+    /// host remain valid in it. From then on the two hosts only share the
+    /// storage snapshot source, which is read-only. The copy gets its own
+    /// module cache, starting out with the modules cached on this host, so
+    /// modules parsed by the copy are not added to the cache of this host.
+    /// The event and storage commit hooks, if any, are not carried over, so
+    /// the events and storage changes of the copy are not streamed to the
+    /// listeners of this host.
+    ///
+    /// Must be called between top-level invocations, i.e. not from within a
+    /// contract call. The copying itself is not metered.
+    pub fn fork(&self) -> Result<Host, HostError> {
+        use crate::host::error::TryBorrowOrErr;
+        if !self.try_borrow_context()?.is_empty() {
+            return Err(self.err(
+                ScErrorType::Context,
+                ScErrorCode::InternalError,
+                "cannot fork host during a contract call",
+                &[],
+            ));
+        }
+        let budget = Budget(Rc::new(RefCell::new(
+            self.budget_ref().0.try_borrow_or_err()?.clone(),
+        )));
+        Ok(Host(Rc::new(HostImpl {
+            source_account: RefCell::new(self.try_borrow_source_account()?.clone()),
+            ledger: RefCell::new(self.try_borrow_ledger()?.clone()),
+            objects: RefCell::new(self.try_borrow_objects()?.clone()),
+            storage: RefCell::new(self.try_borrow_storage()?.clone()),
+            context: Default::default(),
+            reentry_allowances: Default::default(),
+            budget,
+            events: RefCell::new(self.try_borrow_events()?.clone()),
+            authorization_manager: RefCell::new(self.try_borrow_authorization_manager()?.clone()),
+            diagnostic_level: RefCell::new(self.try_borrow_diagnostic_level()?.clone()),
+            base_prng: RefCell::new(self.try_borrow_base_prng()?.clone()),
+            module_cache: RefCell::new(
+                self.try_borrow_module_cache()?
+                    .as_ref()
+                    .map(|cache| cache.deep_clone())
+                    .transpose()?,
+            ),
+            vm_config: RefCell::new(*self.try_borrow_vm_config()?),
+            event_hook: RefCell::new(None),
+            event_limits: RefCell::new(*self.try_borrow_event_limits()?),
+            storage_commit_hook: RefCell::new(None),
+            #[cfg(any(test, feature = "testutils"))]
+            contracts: RefCell::new(self.try_borrow_contracts()?.clone()),
+            #[cfg(any(test, feature = "testutils"))]
+            call_trace: RefCell::new(self.try_borrow_call_trace()?.clone()),
+            #[cfg(any(test, feature = "testutils"))]
+            function_call_counts: RefCell::new(self.try_borrow_function_call_counts()?.clone()),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_costs: RefCell::new(self.try_borrow_frame_costs()?.clone()),
+            #[cfg(any(test, feature = "testutils"))]
+            object_origins: RefCell::new(self.try_borrow_object_origins()?.clone()),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_origins: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            frame_consumed_at_push: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            ledger_auto_advance: RefCell::new(*self.try_borrow_ledger_auto_advance()?),
+            #[cfg(any(test, feature = "testutils"))]
+            unhandled_errors: Default::default(),
+            #[cfg(any(test, feature = "testutils"))]
+            previous_authorization_manager: RefCell::new(
+                self.try_borrow_previous_authorization_manager()?.clone(),
+            ),
+        })))
+    }
+
     // Testing interface to create values directly for later use via Env functions.
     // It needs to be a `pub` method because benches are considered a separate crate.
     #[cfg(any(test, feature = "testutils"))]
@@ -488,7 +786,13 @@ impl EnvBase for Host {
     fn augment_err_result<T>(&self, mut x: Result<T, Self::Error>) -> Result<T, Self::Error> {
         if let Err(e) = &mut x {
             if e.info.is_none() {
-                e.info = self.maybe_get_debug_info()
+                // Errors for the ledger resource limits come from the budget
+                // without a message, as it has no host to record one with.
+                if let Some(msg) = self.ledger_resource_limit_err_msg(e) {
+                    *e = self.error(e.error, msg, &[]);
+                } else {
+                    e.info = self.maybe_get_debug_info()
+                }
             }
         }
         x
@@ -687,27 +991,25 @@ impl VmCallerEnv for Host {
         vals_pos: U32Val,
         vals_len: U32Val,
     ) -> Result<Void, HostError> {
-        if self.is_debug()? {
-            // FIXME: change to a "debug budget" https://github.com/stellar/rs-soroban-env/issues/1061
-            self.as_budget().with_free_budget(|| {
-                let VmSlice { vm, pos, len } = self.decode_vmslice(msg_pos, msg_len)?;
-                let mut msg: Vec<u8> = vec![0u8; len as usize];
-                self.metered_vm_read_bytes_from_linear_memory(vmcaller, &vm, pos, &mut msg)?;
-                let msg = String::from_utf8_lossy(&msg);
-
-                let VmSlice { vm, pos, len } = self.decode_vmslice(vals_pos, vals_len)?;
-                let mut vals: Vec<Val> = vec![Val::VOID.to_val(); len as usize];
-                self.metered_vm_read_vals_from_linear_memory::<8, Val>(
-                    vmcaller,
-                    &vm,
-                    pos,
-                    vals.as_mut_slice(),
-                    |buf| self.relative_to_absolute(Val::from_payload(u64::from_le_bytes(*buf))),
-                )?;
-
-                self.log_diagnostics(&msg, &vals)
-            })?;
-        }
+        // FIXME: change to a "debug budget" https://github.com/stellar/rs-soroban-env/issues/1061
+        self.with_debug_mode(|| {
+            let VmSlice { vm, pos, len } = self.decode_vmslice(msg_pos, msg_len)?;
+            let mut msg: Vec<u8> = vec![0u8; len as usize];
+            self.metered_vm_read_bytes_from_linear_memory(vmcaller, &vm, pos, &mut msg)?;
+            let msg = String::from_utf8_lossy(&msg);
+
+            let VmSlice { vm, pos, len } = self.decode_vmslice(vals_pos, vals_len)?;
+            let mut vals: Vec<Val> = vec![Val::VOID.to_val(); len as usize];
+            self.metered_vm_read_vals_from_linear_memory::<8, Val>(
+                vmcaller,
+                &vm,
+                pos,
+                vals.as_mut_slice(),
+                |buf| self.relative_to_absolute(Val::from_payload(u64::from_le_bytes(*buf))),
+            )?;
+
+            self.log_diagnostics(&msg, &vals)
+        })?;
         Ok(Val::VOID)
     }
 
@@ -839,13 +1141,27 @@ impl VmCallerEnv for Host {
         ))
     }
 
-    fn get_max_expiration_ledger(
+    fn get_max_live_until_ledger(
         &self,
         _vmcaller: &mut VmCaller<Host>,
     ) -> Result<U32Val, Self::Error> {
         Ok(self.max_expiration_ledger()?.into())
     }
 
+    // Notes on metering: covered by the components.
+    fn get_current_call_stack(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+    ) -> Result<VecObject, HostError> {
+        let ids = self.get_contract_call_stack_internal()?;
+        Vec::<Val>::charge_bulk_init_cpy(ids.len() as u64, self)?;
+        let mut addresses = Vec::with_capacity(ids.len());
+        for id in ids {
+            addresses.push(self.add_host_object(ScAddress::Contract(id))?.to_val());
+        }
+        self.add_host_object(HostVec::from_vec(addresses)?)
+    }
+
     // endregion "context" module functions
 
     // region: "int" module functions
@@ -1075,6 +1391,32 @@ impl VmCallerEnv for Host {
         })
     }
 
+    // There are no dedicated cost types for 128-bit arithmetic, so it's
+    // charged as its 256-bit counterpart, which is a safe upper bound.
+    impl_bignum_host_fns!(u128_add, checked_add, u128, U128Val, Int256AddSub);
+    impl_bignum_host_fns!(u128_sub, checked_sub, u128, U128Val, Int256AddSub);
+    impl_bignum_host_fns!(u128_mul, checked_mul, u128, U128Val, Int256Mul);
+    impl_bignum_host_fns!(u128_div, checked_div, u128, U128Val, Int256Div);
+    impl_bignum_host_fns!(
+        u128_rem_euclid,
+        checked_rem_euclid,
+        u128,
+        U128Val,
+        Int256Div
+    );
+
+    impl_bignum_host_fns!(i128_add, checked_add, i128, I128Val, Int256AddSub);
+    impl_bignum_host_fns!(i128_sub, checked_sub, i128, I128Val, Int256AddSub);
+    impl_bignum_host_fns!(i128_mul, checked_mul, i128, I128Val, Int256Mul);
+    impl_bignum_host_fns!(i128_div, checked_div, i128, I128Val, Int256Div);
+    impl_bignum_host_fns!(
+        i128_rem_euclid,
+        checked_rem_euclid,
+        i128,
+        I128Val,
+        Int256Div
+    );
+
     impl_bignum_host_fns!(u256_add, checked_add, U256, U256Val, Int256AddSub);
     impl_bignum_host_fns!(u256_sub, checked_sub, U256, U256Val, Int256AddSub);
     impl_bignum_host_fns!(u256_mul, checked_mul, U256, U256Val, Int256Mul);
@@ -1082,6 +1424,13 @@ impl VmCallerEnv for Host {
     impl_bignum_host_fns_rhs_u32!(u256_pow, checked_pow, U256, U256Val, Int256Pow);
     impl_bignum_host_fns_rhs_u32!(u256_shl, checked_shl, U256, U256Val, Int256Shift);
     impl_bignum_host_fns_rhs_u32!(u256_shr, checked_shr, U256, U256Val, Int256Shift);
+    impl_bignum_host_fns!(
+        u256_rem_euclid,
+        checked_rem_euclid,
+        U256,
+        U256Val,
+        Int256Div
+    );
 
     impl_bignum_host_fns!(i256_add, checked_add, I256, I256Val, Int256AddSub);
     impl_bignum_host_fns!(i256_sub, checked_sub, I256, I256Val, Int256AddSub);
@@ -1090,6 +1439,13 @@ impl VmCallerEnv for Host {
     impl_bignum_host_fns_rhs_u32!(i256_pow, checked_pow, I256, I256Val, Int256Pow);
     impl_bignum_host_fns_rhs_u32!(i256_shl, checked_shl, I256, I256Val, Int256Shift);
     impl_bignum_host_fns_rhs_u32!(i256_shr, checked_shr, I256, I256Val, Int256Shift);
+    impl_bignum_host_fns!(
+        i256_rem_euclid,
+        checked_rem_euclid,
+        I256,
+        I256Val,
+        Int256Div
+    );
 
     // endregion "int" module functions
     // region: "map" module functions
@@ -1187,6 +1543,50 @@ impl VmCallerEnv for Host {
         })
     }
 
+    fn map_min_key(&self, _vmcaller: &mut VmCaller<Host>, m: MapObject) -> Result<Val, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            map_key_or_missing(self, hm.get_min(self)?, "map is empty in map_min_key")
+        })
+    }
+
+    fn map_max_key(&self, _vmcaller: &mut VmCaller<Host>, m: MapObject) -> Result<Val, HostError> {
+        self.visit_obj(m, |hm: &HostMap| {
+            map_key_or_missing(self, hm.get_max(self)?, "map is empty in map_max_key")
+        })
+    }
+
+    fn map_prev_key(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        m: MapObject,
+        k: Val,
+    ) -> Result<Val, HostError> {
+        self.check_val_integrity(k)?;
+        self.visit_obj(m, |hm: &HostMap| {
+            map_key_or_missing(
+                self,
+                hm.get_prev(&k, self)?,
+                "no smaller key in map_prev_key",
+            )
+        })
+    }
+
+    fn map_next_key(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        m: MapObject,
+        k: Val,
+    ) -> Result<Val, HostError> {
+        self.check_val_integrity(k)?;
+        self.visit_obj(m, |hm: &HostMap| {
+            map_key_or_missing(
+                self,
+                hm.get_next(&k, self)?,
+                "no greater key in map_next_key",
+            )
+        })
+    }
+
     fn map_keys(
         &self,
         _vmcaller: &mut VmCaller<Host>,
@@ -1209,6 +1609,26 @@ impl VmCallerEnv for Host {
         self.add_host_object(vec)
     }
 
+    // Notes on metering: the map is scanned once, then each entry's pair
+    // vector is charged as it is created.
+    fn map_entries(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        m: MapObject,
+    ) -> Result<VecObject, HostError> {
+        let pairs = self.visit_obj(m, |hm: &HostMap| {
+            Vec::<(Val, Val)>::charge_bulk_init_cpy(hm.len() as u64, self)?;
+            Ok(hm.iter(self)?.cloned().collect::<Vec<(Val, Val)>>())
+        })?;
+        Vec::<Val>::charge_bulk_init_cpy(pairs.len() as u64, self)?;
+        let mut entries = Vec::with_capacity(pairs.len());
+        for (k, v) in pairs {
+            let pair = HostVec::from_array(&[k, v], self.budget_ref())?;
+            entries.push(self.add_host_object(pair)?.to_val());
+        }
+        self.add_host_object(HostVec::from_vec(entries)?)
+    }
+
     fn map_new_from_linear_memory(
         &self,
         vmcaller: &mut VmCaller<Host>,
@@ -1276,6 +1696,16 @@ impl VmCallerEnv for Host {
             len,
         } = self.decode_vmslice(keys_pos, len)?;
         self.visit_obj(map, |mapobj: &HostMap| {
+            if mapobj.len() != len as usize
+                && self.get_ledger_protocol_version()? >= UNPACK_LENGTH_CHECK_MIN_PROTOCOL
+            {
+                return Err(self.err(
+                    ScErrorType::Object,
+                    ScErrorCode::UnexpectedSize,
+                    "differing host map and output slice lengths when unpacking map to linear memory",
+                    &[],
+                ));
+            }
             // Step 1: check all key symbols.
             self.metered_vm_scan_slices_in_linear_memory(
                 vmcaller,
@@ -1524,6 +1954,30 @@ impl VmCallerEnv for Host {
         })
     }
 
+    fn vec_sort(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        v: VecObject,
+    ) -> Result<VecObject, HostError> {
+        let vnew = self.visit_obj(v, |hv: &HostVec| {
+            hv.sort_by(|a, b| self.compare(a, b), self.as_budget())
+        })?;
+        self.add_host_object(vnew)
+    }
+
+    fn vec_contains(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        v: VecObject,
+        x: Val,
+    ) -> Result<Bool, HostError> {
+        self.check_val_integrity(x)?;
+        self.visit_obj(v, |hv: &HostVec| {
+            let found = hv.first_index_of(|other| self.compare(&x, other), self.as_budget())?;
+            Ok(found.is_some().into())
+        })
+    }
+
     fn vec_new_from_linear_memory(
         &self,
         vmcaller: &mut VmCaller<Host>,
@@ -1555,6 +2009,16 @@ impl VmCallerEnv for Host {
     ) -> Result<Void, HostError> {
         let VmSlice { vm, pos, len } = self.decode_vmslice(vals_pos, len)?;
         self.visit_obj(vec, |vecobj: &HostVec| {
+            if vecobj.len() != len as usize
+                && self.get_ledger_protocol_version()? >= UNPACK_LENGTH_CHECK_MIN_PROTOCOL
+            {
+                return Err(self.err(
+                    ScErrorType::Object,
+                    ScErrorCode::UnexpectedSize,
+                    "differing host vector and output slice lengths when unpacking vec to linear memory",
+                    &[],
+                ));
+            }
             self.metered_vm_write_vals_to_linear_memory(
                 vmcaller,
                 &vm,
@@ -1895,44 +2359,83 @@ impl VmCallerEnv for Host {
             ContractReentryMode::Prohibited,
             false,
         );
+        self.try_call_result_to_val(res, func, args)
+    }
+
+    // Notes on metering: covered by the components. Whatever the callee
+    // consumes stays charged, whether or not it exceeds its sub-limits.
+    fn try_call_with_budget(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        contract_address: AddressObject,
+        func: Symbol,
+        args: VecObject,
+        cpu_limit: U64Val,
+        mem_limit: U64Val,
+    ) -> Result<Val, HostError> {
+        let cpu_limit = u64::try_from_val(self, &cpu_limit.to_val())?;
+        let mem_limit = u64::try_from_val(self, &mem_limit.to_val())?;
+        let argvec = self.call_args_from_obj(args)?;
+        let contract_id = self.contract_id_from_address(contract_address)?;
+        let budget = self.as_budget();
+        let sub_limits_apply = cpu_limit < budget.get_cpu_insns_remaining()?
+            || mem_limit < budget.get_mem_bytes_remaining()?;
+        let res = budget.with_sub_limits(cpu_limit, mem_limit, || {
+            self.call_n_internal(
+                &contract_id,
+                func,
+                argvec.as_slice(),
+                ContractReentryMode::Prohibited,
+                false,
+            )
+        });
         match res {
-            Ok(rv) => Ok(rv),
-            Err(e) => {
+            // Running out of budget is normally non-recoverable, but if only
+            // the sub-limits were exceeded the caller still has budget left to
+            // handle the failure.
+            Err(e)
+                if sub_limits_apply
+                    && e.error.is_type(ScErrorType::Budget)
+                    && e.error.is_code(ScErrorCode::ExceededLimit)
+                    && !budget.is_over_budget()? =>
+            {
                 self.error(
                     e.error,
-                    "contract try_call failed",
+                    "contract try_call_with_budget exceeded its budget",
                     &[func.to_val(), args.to_val()],
                 );
-                // Only allow to gracefully handle the recoverable errors.
-                // Non-recoverable errors should still cause guest to panic and
-                // abort execution.
-                if e.is_recoverable() {
-                    // Pass contract errors through.
-                    if e.error.is_type(ScErrorType::Contract) {
-                        Ok(e.error.to_val())
-                    } else {
-                        // Narrow all the remaining host errors down to a single
-                        // error type. We don't want to expose the granular host
-                        // errors to the guest, consistently with how every
-                        // other host function works. This reduces the risk of
-                        // implementation being 'locked' into specific error
-                        // codes due to them being exposed to the guest and
-                        // hashed into blockchain.
-                        // The granular error codes are still observable with
-                        // diagnostic events.
-                        Ok(Error::from_type_and_code(
-                            ScErrorType::Context,
-                            ScErrorCode::InvalidAction,
-                        )
-                        .to_val())
-                    }
-                } else {
-                    Err(e)
-                }
+                Ok(e.error.to_val())
             }
+            res => self.try_call_result_to_val(res, func, args),
         }
     }
 
+    // Notes on metering: growing the permission list is charged, checking it
+    // on re-entry is covered by the call itself.
+    fn allow_reentry(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        callee: AddressObject,
+    ) -> Result<Void, HostError> {
+        let callee_id = self.contract_id_from_address(callee)?;
+        self.with_current_frame(|frame| match frame {
+            Frame::HostFunction(_) => Err(self.err(
+                ScErrorType::Context,
+                ScErrorCode::InvalidAction,
+                "can't allow re-entry from a host function",
+                &[callee.to_val()],
+            )),
+            _ => Ok(()),
+        })?;
+        let depth = self.try_borrow_context()?.len().saturating_sub(1);
+        if !self.is_reentry_allowed(depth, &callee_id)? {
+            Vec::<(usize, Hash)>::charge_bulk_init_cpy(1, self.as_budget())?;
+            self.try_borrow_reentry_allowances_mut()?
+                .push((depth, callee_id));
+        }
+        Ok(Val::VOID)
+    }
+
     // endregion "call" module functions
     // region: "buf" module functions
 
@@ -2323,6 +2826,55 @@ impl VmCallerEnv for Host {
         self.add_host_object(self.scbytes_from_vec(vnew)?)
     }
 
+    fn bytes_xor(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        b1: BytesObject,
+        b2: BytesObject,
+    ) -> Result<BytesObject, HostError> {
+        let vnew = self.visit_obj(b1, |sb1: &ScBytes| {
+            self.visit_obj(b2, |sb2: &ScBytes| {
+                if sb1.len() != sb2.len() {
+                    return Err(self.err(
+                        ScErrorType::Object,
+                        ScErrorCode::UnexpectedSize,
+                        "bytes_xor operands have different lengths",
+                        &[b1.to_val(), b2.to_val()],
+                    ));
+                }
+                Vec::<u8>::charge_bulk_init_cpy(sb1.len() as u64, self)?;
+                Ok(sb1
+                    .iter()
+                    .zip(sb2.iter())
+                    .map(|(x, y)| x ^ y)
+                    .collect::<Vec<u8>>())
+            })
+        })?;
+        self.add_host_object(self.scbytes_from_vec(vnew)?)
+    }
+
+    fn bytes_fill(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        b: BytesObject,
+        start: U32Val,
+        end: U32Val,
+        u: U32Val,
+    ) -> Result<BytesObject, HostError> {
+        let start: u32 = start.into();
+        let end: u32 = end.into();
+        let u = self.u8_from_u32val_input("u", u)?;
+        let vnew = self.visit_obj(b, |hv: &ScBytes| {
+            let range = self.valid_range_from_start_end_bound(start, end, hv.len())?;
+            let mut vnew = self.metered_slice_to_vec(hv.as_slice())?;
+            vnew.get_mut(range)
+                .ok_or_else(|| self.err_oob_object_index(None))?
+                .fill(u);
+            Ok(vnew)
+        })?;
+        self.add_host_object(self.scbytes_from_vec(vnew)?)
+    }
+
     // endregion "buf" module functions
     // region: "crypto" module functions
 
@@ -2484,6 +3036,58 @@ impl VmCallerEnv for Host {
         }
     }
 
+    // Notes on metering: encoding is linear in the length of the strkey,
+    // which is fixed for both account and contract addresses.
+    fn address_to_strkey(
+        &self,
+        _vmcaller: &mut VmCaller<Self::VmUserState>,
+        address: AddressObject,
+    ) -> Result<StringObject, Self::Error> {
+        let addr = self.visit_obj(address, |addr: &ScAddress| addr.metered_clone(self))?;
+        Vec::<u8>::charge_bulk_init_cpy(STRKEY_LEN as u64, self)?;
+        let strkey = match addr {
+            ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(pk))) => {
+                stellar_strkey::ed25519::PublicKey(pk.0).to_string()
+            }
+            ScAddress::Contract(Hash(h)) => stellar_strkey::Contract(h).to_string(),
+        };
+        self.add_host_object(ScString(strkey.into_bytes().try_into()?))
+    }
+
+    // Notes on metering: decoding is linear in the length of the input, which
+    // is checked up front so that oversized inputs are rejected cheaply.
+    fn strkey_to_address(
+        &self,
+        _vmcaller: &mut VmCaller<Self::VmUserState>,
+        strkey: StringObject,
+    ) -> Result<AddressObject, Self::Error> {
+        let addr = self.visit_obj(strkey, |s: &ScString| {
+            let invalid = || {
+                self.err(
+                    ScErrorType::Value,
+                    ScErrorCode::InvalidInput,
+                    "invalid address strkey",
+                    &[strkey.to_val()],
+                )
+            };
+            if s.len() != STRKEY_LEN {
+                return Err(invalid());
+            }
+            Vec::<u8>::charge_bulk_init_cpy(STRKEY_LEN as u64, self)?;
+            let s = std::str::from_utf8(s.as_slice()).map_err(|_| invalid())?;
+            match stellar_strkey::Strkey::from_string(s) {
+                Ok(stellar_strkey::Strkey::PublicKeyEd25519(pk)) => Ok(ScAddress::Account(
+                    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(pk.0))),
+                )),
+                Ok(stellar_strkey::Strkey::Contract(stellar_strkey::Contract(h))) => {
+                    Ok(ScAddress::Contract(Hash(h)))
+                }
+                _ => Err(invalid()),
+            }
+        })?;
+        self.add_host_object(addr)
+    }
+
     // endregion "address" module functions
     // region: "prng" module functions
 
@@ -2549,6 +3153,82 @@ impl VmCallerEnv for Host {
         self.add_host_object(vnew)
     }
     // endregion "prng" module functions
+    // region: "str" module functions
+
+    fn string_concat(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        s1: StringObject,
+        s2: StringObject,
+    ) -> Result<StringObject, HostError> {
+        let vnew = self.visit_obj(s1, |ss1: &ScString| {
+            self.visit_obj(s2, |ss2: &ScString| {
+                // we allocate large enough memory to hold the new combined string, so that
+                // allocation only happens once, and charge for it upfront.
+                let len = self.validate_usize_sum_fits_in_u32(ss1.len(), ss2.len())?;
+                Vec::<u8>::charge_bulk_init_cpy(len as u64, self)?;
+                let mut vnew: Vec<u8> = Vec::with_capacity(len);
+                vnew.extend_from_slice(ss1.as_slice());
+                vnew.extend_from_slice(ss2.as_slice());
+                Ok(vnew)
+            })
+        })?;
+        self.add_host_object(ScString(vnew.try_into()?))
+    }
+
+    fn string_substr(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        s: StringObject,
+        start: U32Val,
+        end: U32Val,
+    ) -> Result<StringObject, HostError> {
+        let start: u32 = start.into();
+        let end: u32 = end.into();
+        let vnew = self.visit_obj(s, |ss: &ScString| {
+            let range = self.valid_range_from_start_end_bound(start, end, ss.len())?;
+            self.metered_slice_to_vec(
+                &ss.as_slice()
+                    .get(range)
+                    .ok_or_else(|| self.err_oob_object_index(None))?,
+            )
+        })?;
+        self.add_host_object(ScString(vnew.try_into()?))
+    }
+
+    // Notes on metering: covered by `Compare<&[u8]>` for `Budget`.
+    fn string_compare(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        s1: StringObject,
+        s2: StringObject,
+    ) -> Result<i64, HostError> {
+        let ord = self.visit_obj(s1, |ss1: &ScString| {
+            self.visit_obj(s2, |ss2: &ScString| {
+                self.as_budget().compare(&ss1.as_slice(), &ss2.as_slice())
+            })
+        })?;
+        Ok(match ord {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        })
+    }
+
+    fn symbol_to_string(
+        &self,
+        _vmcaller: &mut VmCaller<Host>,
+        sym: Symbol,
+    ) -> Result<StringObject, HostError> {
+        let vnew = if let Ok(so) = SymbolObject::try_from(sym.to_val()) {
+            self.visit_obj(so, |ss: &ScSymbol| self.metered_slice_to_vec(ss.as_slice()))?
+        } else {
+            let ss = SymbolStr::try_from_val(self, &sym)?;
+            self.metered_slice_to_vec(ss.as_ref())?
+        };
+        self.add_host_object(ScString(vnew.try_into()?))
+    }
+    // endregion "str" module functions
 }
 
 #[cfg(any(test, feature = "testutils"))]
@@ -0,0 +1,143 @@
+use soroban_env_common::meta::{get_ledger_protocol_version, INTERFACE_VERSION};
+
+use crate::{
+    budget::Budget,
+    storage::Storage,
+    xdr::{AccountId, ScErrorCode, ScErrorType, SorobanAuthorizationEntry},
+    DiagnosticLevel, Host, HostError, LedgerInfo, Seed,
+};
+
+/// Builds a fully-configured [Host], as an alternative to constructing one
+/// with [Host::with_storage_and_budget] and then calling the individual
+/// setters, which are easy to forget.
+///
+/// Ledger info is required; everything else defaults to what
+/// [Host::default] would use. [HostBuilder::build] checks the configuration
+/// for consistency, so that a misconfigured host is rejected up front rather
+/// than failing in the middle of an invocation.
+#[derive(Clone, Default)]
+pub struct HostBuilder {
+    storage: Storage,
+    budget: Budget,
+    ledger_info: Option<LedgerInfo>,
+    source_account: Option<AccountId>,
+    authorization_entries: Option<Vec<SorobanAuthorizationEntry>>,
+    base_prng_seed: Option<Seed>,
+    diagnostic_level: DiagnosticLevel,
+}
+
+impl HostBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn storage(mut self, storage: Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn ledger_info(mut self, ledger_info: LedgerInfo) -> Self {
+        self.ledger_info = Some(ledger_info);
+        self
+    }
+
+    pub fn source_account(mut self, source_account: AccountId) -> Self {
+        self.source_account = Some(source_account);
+        self
+    }
+
+    /// Sets the authorization entries to enforce, see
+    /// [Host::set_authorization_entries].
+    pub fn authorization_entries(mut self, entries: Vec<SorobanAuthorizationEntry>) -> Self {
+        self.authorization_entries = Some(entries);
+        self
+    }
+
+    pub fn base_prng_seed(mut self, seed: Seed) -> Self {
+        self.base_prng_seed = Some(seed);
+        self
+    }
+
+    pub fn diagnostic_level(mut self, diagnostic_level: DiagnosticLevel) -> Self {
+        self.diagnostic_level = diagnostic_level;
+        self
+    }
+
+    /// Validates the configuration and constructs the [Host].
+    pub fn build(self) -> Result<Host, HostError> {
+        let host = Host::with_storage_and_budget(self.storage, self.budget);
+        let Some(ledger_info) = self.ledger_info else {
+            return Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::MissingValue,
+                "host requires ledger info",
+                &[],
+            ));
+        };
+        Self::check_ledger_info(&host, &ledger_info)?;
+        if host.budget_ref().get_cpu_insns_remaining()? == 0
+            || host.budget_ref().get_mem_bytes_remaining()? == 0
+        {
+            return Err(host.err(
+                ScErrorType::Budget,
+                ScErrorCode::InvalidInput,
+                "budget has no cpu or memory left",
+                &[],
+            ));
+        }
+
+        host.set_ledger_info(ledger_info)?;
+        host.set_diagnostic_level(self.diagnostic_level)?;
+        if let Some(source_account) = self.source_account {
+            host.set_source_account(source_account)?;
+        }
+        if let Some(entries) = self.authorization_entries {
+            host.set_authorization_entries(entries)?;
+        }
+        if let Some(seed) = self.base_prng_seed {
+            host.set_base_prng_seed(seed)?;
+        }
+        Ok(host)
+    }
+
+    fn check_ledger_info(host: &Host, li: &LedgerInfo) -> Result<(), HostError> {
+        let env_proto = get_ledger_protocol_version(INTERFACE_VERSION);
+        if li.protocol_version > env_proto {
+            return Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::InvalidInput,
+                "ledger protocol number is ahead of supported env protocol number",
+                &[li.protocol_version.into(), env_proto.into()],
+            ));
+        }
+        // An all-zero network id is never valid and almost certainly means it
+        // was left unset.
+        if li.network_id == [0; 32] {
+            return Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::InvalidInput,
+                "network id is not set",
+                &[],
+            ));
+        }
+        for min_expiration in [
+            li.min_temp_entry_expiration,
+            li.min_persistent_entry_expiration,
+        ] {
+            if min_expiration == 0 || min_expiration > li.max_entry_expiration {
+                return Err(host.err(
+                    ScErrorType::Context,
+                    ScErrorCode::InvalidInput,
+                    "minimum entry expiration must be between 1 and the maximum entry expiration",
+                    &[min_expiration.into(), li.max_entry_expiration.into()],
+                ));
+            }
+        }
+        Ok(())
+    }
+}
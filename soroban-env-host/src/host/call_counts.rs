@@ -0,0 +1,42 @@
+// "testutils" is not covered by budget metering.
+use std::collections::BTreeMap;
+
+use crate::{Host, HostError};
+
+impl Host {
+    /// Starts counting the calls a contract's Wasm makes into each host
+    /// function, discarding any previous counts. Only calls dispatched from
+    /// the VM are counted: host functions called by native contracts or by
+    /// the host itself aren't. See [Host::get_function_call_counts].
+    pub fn enable_function_call_counts(&self) -> Result<(), HostError> {
+        *self.try_borrow_function_call_counts_mut()? = Some(BTreeMap::new());
+        Ok(())
+    }
+
+    /// Stops counting host function calls and discards the counts.
+    pub fn disable_function_call_counts(&self) -> Result<(), HostError> {
+        *self.try_borrow_function_call_counts_mut()? = None;
+        Ok(())
+    }
+
+    /// Returns the number of times each host function has been called since
+    /// [Host::enable_function_call_counts], keyed by the function's name in
+    /// the environment interface (e.g. `vec_push_back`), or `None` if call
+    /// counting is not enabled. Functions that were never called are left
+    /// out.
+    pub fn get_function_call_counts(
+        &self,
+    ) -> Result<Option<BTreeMap<&'static str, u64>>, HostError> {
+        Ok(self.try_borrow_function_call_counts()?.clone())
+    }
+
+    // Called by the dispatch function of every host function, before the
+    // call is charged for.
+    pub(crate) fn record_function_call(&self, name: &'static str) -> Result<(), HostError> {
+        if let Some(counts) = self.try_borrow_function_call_counts_mut()?.as_mut() {
+            let count = counts.entry(name).or_default();
+            *count = count.saturating_add(1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+// "testutils" is not covered by budget metering.
+use crate::{
+    budget::AsBudget,
+    xdr::{Hash, ScAddress, ScError, ScErrorCode, ScMap, ScMapEntry, ScSymbol, ScVal, ScVec},
+    Error, Host, HostError, Symbol, Val,
+};
+
+/// A single contract invocation recorded by the call-trace recorder, along
+/// with all the invocations it made in turn.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallTraceNode {
+    pub contract_id: Hash,
+    pub function: ScSymbol,
+    pub args: Vec<ScVal>,
+    /// The value returned by the invocation, or the error it failed with.
+    /// Invocations that have not yet returned (i.e. when the trace is read
+    /// from within a contract call) report `Ok(ScVal::Void)`.
+    pub result: Result<ScVal, Error>,
+    pub sub_calls: Vec<CallTraceNode>,
+}
+
+impl CallTraceNode {
+    /// Converts the node and all of its sub-calls to an [ScVal::Map], which
+    /// can be XDR-serialized and handed to off-chain tooling.
+    pub fn to_scval(&self) -> Result<ScVal, crate::xdr::Error> {
+        fn entry(key: &str, val: ScVal) -> Result<ScMapEntry, crate::xdr::Error> {
+            Ok(ScMapEntry {
+                key: ScVal::Symbol(ScSymbol(key.try_into()?)),
+                val,
+            })
+        }
+        let args = ScVal::Vec(Some(ScVec(self.args.clone().try_into()?)));
+        let result = match &self.result {
+            Ok(val) => val.clone(),
+            Err(err) => ScVal::Error(
+                ScError::try_from(*err).unwrap_or(ScError::Context(ScErrorCode::InternalError)),
+            ),
+        };
+        let sub_calls = self
+            .sub_calls
+            .iter()
+            .map(|c| c.to_scval())
+            .collect::<Result<Vec<ScVal>, _>>()?;
+        // Entries must be sorted by key.
+        let entries = vec![
+            entry("args", args)?,
+            entry(
+                "contract",
+                ScVal::Address(ScAddress::Contract(self.contract_id.clone())),
+            )?,
+            entry("function", ScVal::Symbol(self.function.clone()))?,
+            entry("result", result)?,
+            entry("sub_calls", ScVal::Vec(Some(ScVec(sub_calls.try_into()?))))?,
+        ];
+        Ok(ScVal::Map(Some(ScMap(entries.try_into()?))))
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct CallTraceRecorder {
+    // Invocations that have been entered but not yet exited, outermost first.
+    open: Vec<CallTraceNode>,
+    // Completed top-level invocations.
+    roots: Vec<CallTraceNode>,
+}
+
+impl CallTraceRecorder {
+    fn snapshot(&self) -> Vec<CallTraceNode> {
+        // Fold any still-open invocations into the returned tree so the trace
+        // is meaningful when observed mid-call.
+        let mut roots = self.roots.clone();
+        let mut pending: Option<CallTraceNode> = None;
+        for node in self.open.iter().rev() {
+            let mut node = node.clone();
+            if let Some(child) = pending.take() {
+                node.sub_calls.push(child);
+            }
+            pending = Some(node);
+        }
+        roots.extend(pending);
+        roots
+    }
+}
+
+impl Host {
+    /// Starts recording a tree of all contract invocations made on this host,
+    /// discarding any previously recorded trace. See [Host::get_call_trace].
+    pub fn enable_call_trace(&self) -> Result<(), HostError> {
+        *self.try_borrow_call_trace_mut()? = Some(CallTraceRecorder::default());
+        Ok(())
+    }
+
+    /// Stops recording contract invocations and discards the recorded trace.
+    pub fn disable_call_trace(&self) -> Result<(), HostError> {
+        *self.try_borrow_call_trace_mut()? = None;
+        Ok(())
+    }
+
+    /// Returns the trees of contract invocations recorded since
+    /// [Host::enable_call_trace] was called, one per top-level invocation, or
+    /// `None` if call tracing is not enabled.
+    pub fn get_call_trace(&self) -> Result<Option<Vec<CallTraceNode>>, HostError> {
+        Ok(self
+            .try_borrow_call_trace()?
+            .as_ref()
+            .map(|recorder| recorder.snapshot()))
+    }
+
+    fn is_call_trace_enabled(&self) -> bool {
+        matches!(self.try_borrow_call_trace().as_deref(), Ok(Some(_)))
+    }
+
+    // Converts a value for the call trace. Tracing must never change the
+    // outcome of a call, so a value that can't be converted is recorded as
+    // an internal error instead.
+    fn call_trace_val(&self, val: Val) -> ScVal {
+        self.as_budget()
+            .with_free_budget(|| self.from_host_val(val))
+            .unwrap_or(ScVal::Error(ScError::Context(ScErrorCode::InternalError)))
+    }
+
+    // Called once the frame of an invocation has been set up, and always
+    // followed by exactly one `call_trace_exit` for it.
+    pub(crate) fn call_trace_enter(&self, id: &Hash, func: &Symbol, args: &[Val]) {
+        if !self.is_call_trace_enabled() {
+            return;
+        }
+        let function = match self.call_trace_val(func.to_val()) {
+            ScVal::Symbol(function) => function,
+            _ => ScSymbol::default(),
+        };
+        let args = args.iter().map(|v| self.call_trace_val(*v)).collect();
+        let Ok(mut call_trace) = self.try_borrow_call_trace_mut() else {
+            return;
+        };
+        let Some(recorder) = call_trace.as_mut() else {
+            return;
+        };
+        recorder.open.push(CallTraceNode {
+            contract_id: id.clone(),
+            function,
+            args,
+            result: Ok(ScVal::Void),
+            sub_calls: vec![],
+        });
+    }
+
+    pub(crate) fn call_trace_exit(&self, res: &Result<Val, HostError>) {
+        if !self.is_call_trace_enabled() {
+            return;
+        }
+        let result = match res {
+            Ok(val) => Ok(self.call_trace_val(*val)),
+            Err(err) => Err(err.error),
+        };
+        let Ok(mut call_trace) = self.try_borrow_call_trace_mut() else {
+            return;
+        };
+        let Some(recorder) = call_trace.as_mut() else {
+            return;
+        };
+        if let Some(mut node) = recorder.open.pop() {
+            node.result = result;
+            match recorder.open.last_mut() {
+                Some(parent) => parent.sub_calls.push(node),
+                None => recorder.roots.push(node),
+            }
+        }
+    }
+}
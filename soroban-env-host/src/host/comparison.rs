@@ -632,6 +632,79 @@ mod tests {
         }
     }
 
+    /// Test that values of the same `ScVal` type compare by their contents,
+    /// regardless of whether each side is represented as a small value or as
+    /// an object, and that the resulting order is antisymmetric.
+    #[test]
+    fn compare_small_and_object_of_same_type() {
+        use crate::{xdr, Error, Symbol};
+
+        let host = Host::default();
+        let sym = |s: &str| ScVal::Symbol(xdr::ScSymbol::try_from(s).unwrap());
+        let u128 = |u: u128| {
+            ScVal::U128(xdr::UInt128Parts {
+                hi: (u >> 64) as u64,
+                lo: u as u64,
+            })
+        };
+        let i128 = |i: i128| {
+            ScVal::I128(xdr::Int128Parts {
+                hi: (i >> 64) as i64,
+                lo: i as u64,
+            })
+        };
+        let scvals: Vec<ScVal> = vec![
+            ScVal::Error(xdr::ScError::Context(ScErrorCode::InternalError)),
+            ScVal::Error(xdr::ScError::Budget(ScErrorCode::ArithDomain)),
+            ScVal::Error(xdr::ScError::Budget(ScErrorCode::ExceededLimit)),
+            ScVal::U64(0),
+            ScVal::U64(1 << 55),
+            ScVal::U64(u64::MAX),
+            ScVal::I64(i64::MIN),
+            ScVal::I64(-1),
+            ScVal::I64(1 << 54),
+            ScVal::I64(i64::MAX),
+            ScVal::Timepoint(xdr::TimePoint(1)),
+            ScVal::Timepoint(xdr::TimePoint(u64::MAX)),
+            ScVal::Duration(xdr::Duration(1)),
+            ScVal::Duration(xdr::Duration(u64::MAX)),
+            u128(0),
+            u128(u128::MAX),
+            i128(i128::MIN),
+            i128(-1),
+            i128(i128::MAX),
+            sym(""),
+            sym("a"),
+            sym("abcdefghi"),
+            sym("abcdefghij"),
+            sym("abcdefghi_long_symbol"),
+            sym("zzz"),
+        ];
+        let vals: Vec<Val> = scvals
+            .iter()
+            .map(|v| Val::try_from_val(&host, v).expect("val"))
+            .collect();
+
+        for ((val1, val2), (scval1, scval2)) in vals
+            .iter()
+            .cartesian_product(&vals)
+            .zip(scvals.iter().cartesian_product(&scvals))
+        {
+            let val_cmp = host.compare(val1, val2).expect("compare");
+            assert_eq!(val_cmp, scval1.cmp(scval2), "{:?} vs {:?}", scval1, scval2);
+            assert_eq!(
+                val_cmp.reverse(),
+                host.compare(val2, val1).expect("compare")
+            );
+            if let (Ok(e1), Ok(e2)) = (Error::try_from(*val1), Error::try_from(*val2)) {
+                assert_eq!(val_cmp, host.compare(&e1, &e2).expect("compare"));
+            }
+            if let (Ok(s1), Ok(s2)) = (Symbol::try_from(*val1), Symbol::try_from(*val2)) {
+                assert_eq!(val_cmp, host.compare(&s1, &s2).expect("compare"));
+            }
+        }
+    }
+
     fn all_tags() -> Vec<Tag> {
         (0_u8..=255)
             .map(Tag::from_u8)
@@ -8,8 +8,9 @@ use soroban_env_common::xdr::{
 };
 use soroban_env_common::{AddressObject, Env, StorageType, U32Val, Val};
 
-use crate::budget::AsBudget;
-use crate::storage::{InstanceStorageMap, Storage};
+use crate::budget::{AsBudget, LedgerResources};
+use crate::events::DiagnosticSeverity;
+use crate::storage::{Footprint, InstanceStorageMap, LedgerEntryDiff, Storage, StorageMap};
 use crate::xdr::{
     AccountEntry, AccountId, ContractDataEntry, Hash, HashIdPreimage, LedgerEntry, LedgerEntryData,
     LedgerEntryExt, LedgerKey, LedgerKeyAccount, LedgerKeyContractCode, LedgerKeyContractData,
@@ -20,6 +21,10 @@ use crate::{err, Host, HostError};
 
 use super::metered_clone::{MeteredAlloc, MeteredClone};
 
+// Protocol from which writing to the key of an expired temporary entry creates
+// a new entry, rather than overwriting the value of the expired one.
+const RECREATE_EXPIRED_TEMP_ENTRY_MIN_PROTOCOL: u32 = 21;
+
 impl Host {
     pub fn with_mut_storage<F, U>(&self, f: F) -> Result<U, HostError>
     where
@@ -28,6 +33,103 @@ impl Host {
         f(&mut *self.try_borrow_storage_mut()?)
     }
 
+    /// Returns the footprint of the storage: every ledger key accessed so far
+    /// in recording mode, or declared up front in enforcing mode, along with
+    /// its access type.
+    pub fn get_footprint(&self) -> Result<Footprint, HostError> {
+        Ok(self.try_borrow_storage()?.footprint.clone())
+    }
+
+    /// Returns the changes made to the ledger entries accessed so far, with
+    /// their old and new values and live-until ledgers, so that the outcome
+    /// of the invocations can be applied to the ledger. See
+    /// [Storage::get_ledger_changes], and [Storage::record_initial_entries],
+    /// which an enforcing-mode storage needs for this.
+    pub fn get_ledger_changes(&self) -> Result<Vec<LedgerEntryDiff>, HostError> {
+        self.try_borrow_storage()?
+            .get_ledger_changes(self.budget_ref())
+    }
+
+    /// Limits the ledger entries the host's invocations read and write, and
+    /// their sizes, for the rest of the transaction, as the network does with
+    /// the transaction's declared resources. See
+    /// [Budget::set_ledger_resource_limits](crate::budget::Budget::set_ledger_resource_limits).
+    pub fn set_ledger_resource_limits(
+        &self,
+        limits: Option<LedgerResources>,
+    ) -> Result<(), HostError> {
+        self.budget_ref().set_ledger_resource_limits(limits)
+    }
+
+    /// Returns the ledger resources used since the limits were set with
+    /// [Host::set_ledger_resource_limits].
+    pub fn get_ledger_resource_usage(&self) -> Result<LedgerResources, HostError> {
+        self.budget_ref().get_ledger_resource_usage()
+    }
+
+    /// Installs a hook that is called whenever a top-level invocation
+    /// succeeds, with the footprint of the storage and the entries whose
+    /// value or live-until ledger that invocation changed, so that embedders
+    /// can pipeline ledger writes without iterating the storage themselves.
+    /// Replaces any previously installed hook.
+    ///
+    /// The `old_entry` of each change is the state of the entry right before
+    /// the invocation, not its initial state. Calling the hook is not
+    /// metered. The invocation has already been committed when the hook
+    /// runs, so an error returned by the hook does not fail it: the error is
+    /// only reported as a diagnostic event.
+    pub fn set_storage_commit_hook<F>(&self, hook: F) -> Result<(), HostError>
+    where
+        F: Fn(&Footprint, &[LedgerEntryDiff]) -> Result<(), HostError> + 'static,
+    {
+        *self.try_borrow_storage_commit_hook_mut()? = Some(Rc::new(hook));
+        Ok(())
+    }
+
+    /// Removes the hook installed with [Host::set_storage_commit_hook], if
+    /// any.
+    pub fn remove_storage_commit_hook(&self) -> Result<(), HostError> {
+        *self.try_borrow_storage_commit_hook_mut()? = None;
+        Ok(())
+    }
+
+    // Returns a copy of the storage map to diff a top-level invocation
+    // against, if a storage commit hook is installed.
+    pub(crate) fn storage_map_for_commit_hook(&self) -> Result<Option<StorageMap>, HostError> {
+        if self.try_borrow_storage_commit_hook()?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.try_borrow_storage()?.map.clone()))
+    }
+
+    // Called when a top-level invocation has succeeded, with the storage map
+    // as it was before the invocation.
+    pub(crate) fn call_storage_commit_hook(&self, pre: StorageMap) -> Result<(), HostError> {
+        // Clone the hook out of the cell so that it may itself call back into
+        // the host (including replacing the hook).
+        let Some(hook) = self.try_borrow_storage_commit_hook()?.clone() else {
+            return Ok(());
+        };
+        let (footprint, changes) = self.as_budget().with_free_budget(|| {
+            // Entries that were already in the storage map before the
+            // invocation are diffed against their state at that point; the
+            // others were first loaded by this invocation.
+            let mut changes = self
+                .try_borrow_storage()?
+                .get_ledger_changes_since(&pre, self.budget_ref())?;
+            changes.retain(|c| c.old_entry != c.new_entry);
+            Ok((self.get_footprint()?, changes))
+        })?;
+        if let Err(e) = hook(&footprint, &changes) {
+            self.log_diagnostics_with_severity(
+                DiagnosticSeverity::Warn,
+                "storage commit hook failed",
+                &[e.error.to_val()],
+            )?;
+        }
+        Ok(())
+    }
+
     /// Immutable accessor to the instance storage of the currently running
     /// contract.
     /// Performs lazy initialization of instance storage on access.
@@ -439,7 +541,7 @@ impl Host {
         // operation might only modify only the internal `ScVal` value. Thus we
         // need to only overwrite the value in case if there is already an
         // existing ledger entry value for the key in the storage.
-        if self
+        let existing = if self
             .try_borrow_storage_mut()?
             .has(&key, self.as_budget())
             .map_err(|e| self.decorate_contract_data_storage_error(e, k))?
@@ -448,6 +550,22 @@ impl Host {
                 .try_borrow_storage_mut()?
                 .get_with_expiration(&key, self.as_budget())
                 .map_err(|e| self.decorate_contract_data_storage_error(e, k))?;
+            // An expired temporary entry is gone for good, so writing to its
+            // key creates a brand new entry rather than reviving the old one.
+            let ledger_seq = self.with_ledger_info(|li| Ok(li.sequence_number))?;
+            let expired = matches!(expiration_ledger, Some(e) if e < ledger_seq);
+            if durability == ContractDataDurability::Temporary
+                && expired
+                && self.get_ledger_protocol_version()? >= RECREATE_EXPIRED_TEMP_ENTRY_MIN_PROTOCOL
+            {
+                None
+            } else {
+                Some((current, expiration_ledger))
+            }
+        } else {
+            None
+        };
+        if let Some((current, expiration_ledger)) = existing {
             let mut current = (*current).metered_clone(self)?;
             match current.data {
                 LedgerEntryData::ContractData(ref mut entry) => {
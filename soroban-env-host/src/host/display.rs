@@ -0,0 +1,125 @@
+// Human-readable rendering of values, for diagnostics.
+//
+// This is what events and errors are printed with, and is exposed publicly so
+// that tools built on the host (CLIs, test harnesses) print values the same
+// way. The format is meant for people, not for parsing: it is not guaranteed
+// to be stable or to round-trip.
+
+use soroban_env_common::{
+    num::{i256_from_pieces, u256_from_pieces},
+    xdr::{
+        ContractExecutable, Hash, PublicKey::PublicKeyTypeEd25519, ScAddress, ScContractInstance,
+        ScVal,
+    },
+    Error, Val,
+};
+
+use crate::{budget::AsBudget, Host, HostError};
+
+/// Values nested deeper than this are elided when displayed, so that a
+/// pathologically deep value can't blow up the output (or the stack).
+pub const MAX_DISPLAY_DEPTH: usize = 32;
+
+/// Displays an [ScVal] in a compact, human-readable form, e.g.
+/// `{amount: 100_i128, to: GABC...}`.
+pub struct ScValDisplay<'a>(pub &'a ScVal);
+
+impl core::fmt::Display for ScValDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        display_scval(self.0, 0, f)
+    }
+}
+
+fn display_address(addr: &ScAddress, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match addr {
+        ScAddress::Account(acct) => match &acct.0 {
+            PublicKeyTypeEd25519(e) => {
+                write!(f, "{}", stellar_strkey::ed25519::PublicKey(e.0))
+            }
+        },
+        ScAddress::Contract(Hash(h)) => write!(f, "{}", stellar_strkey::Contract(*h)),
+    }
+}
+
+fn display_scval(scv: &ScVal, depth: usize, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if depth > MAX_DISPLAY_DEPTH {
+        return write!(f, "...");
+    }
+    match scv {
+        ScVal::Bool(v) => write!(f, "{}", v),
+        ScVal::Void => write!(f, "Void"),
+        ScVal::Error(e) => write!(f, "{:?}", Error::from_scerror(e.clone())),
+        ScVal::U32(v) => write!(f, "{}", v),
+        ScVal::I32(v) => write!(f, "{}", v),
+        ScVal::U64(v) => write!(f, "{}_u64", v),
+        ScVal::I64(v) => write!(f, "{}_i64", v),
+        ScVal::Timepoint(v) => write!(f, "TimePoint({})", v.0),
+        ScVal::Duration(v) => write!(f, "Duration({})", v.0),
+        ScVal::U128(v) => write!(f, "{}_u128", u128::from(v)),
+        ScVal::I128(v) => write!(f, "{}_i128", i128::from(v)),
+        ScVal::U256(v) => write!(
+            f,
+            "{}_u256",
+            u256_from_pieces(v.hi_hi, v.hi_lo, v.lo_hi, v.lo_lo)
+        ),
+        ScVal::I256(v) => write!(
+            f,
+            "{}_i256",
+            i256_from_pieces(v.hi_hi, v.hi_lo, v.lo_hi, v.lo_lo)
+        ),
+        ScVal::Bytes(v) => write!(f, "Bytes({})", v.0),
+        ScVal::String(v) => write!(f, "\"{}\"", v.0),
+        ScVal::Symbol(v) => write!(f, "{}", v.0),
+        ScVal::Vec(None) => write!(f, "[]"),
+        ScVal::Vec(Some(vec)) => {
+            write!(f, "[")?;
+            for (i, e) in vec.0.iter().enumerate() {
+                if i != 0 {
+                    write!(f, ", ")?;
+                }
+                display_scval(e, depth + 1, f)?;
+            }
+            write!(f, "]")
+        }
+        ScVal::Map(None) => write!(f, "{{}}"),
+        ScVal::Map(Some(pairs)) => {
+            write!(f, "{{")?;
+            for (i, e) in pairs.0.iter().enumerate() {
+                if i != 0 {
+                    write!(f, ", ")?;
+                }
+                display_scval(&e.key, depth + 1, f)?;
+                write!(f, ": ")?;
+                display_scval(&e.val, depth + 1, f)?;
+            }
+            write!(f, "}}")
+        }
+        ScVal::Address(addr) => display_address(addr, f),
+        ScVal::LedgerKeyContractInstance => write!(f, "LedgerKeyContractInstance"),
+        ScVal::LedgerKeyNonce(n) => {
+            write!(f, "LedgerKeyNonce({})", n.nonce)
+        }
+        ScVal::ContractInstance(ScContractInstance {
+            executable: ContractExecutable::Wasm(hash),
+            ..
+        }) => {
+            write!(f, "ContractInstance(Wasm({}))", hash)
+        }
+        ScVal::ContractInstance(ScContractInstance {
+            executable: ContractExecutable::Token,
+            ..
+        }) => write!(f, "ContractInstance(Token)"),
+    }
+}
+
+impl Host {
+    /// Renders `val`, along with any host objects it refers to, in the same
+    /// form as [ScValDisplay]. This is meant for diagnostics and is not
+    /// metered.
+    pub fn display_val(&self, val: Val) -> Result<String, HostError> {
+        let scv = self
+            .as_budget()
+            .with_free_budget(|| self.from_host_val(val))?;
+        Ok(ScValDisplay(&scv).to_string())
+    }
+}
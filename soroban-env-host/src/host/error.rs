@@ -1,6 +1,6 @@
 use crate::{
     budget::AsBudget,
-    events::Events,
+    events::{Events, InternalDiagnosticArg},
     xdr::{self, Hash, LedgerKey, ScAddress, ScError, ScErrorCode, ScErrorType},
     ConversionError, EnvBase, Error, Host, TryFromVal, U32Val, Val,
 };
@@ -18,6 +18,10 @@ use super::metered_clone::MeteredClone;
 pub(crate) struct DebugInfo {
     pub(crate) events: Events,
     pub(crate) backtrace: Backtrace,
+    // Set only where the host raises the error for one of its own depth
+    // limits, as a contract can produce the same `Error` code by other means.
+    // It's kept here rather than in `HostError` to keep that small.
+    pub(crate) depth_limit_exceeded: bool,
 }
 
 #[derive(Clone)]
@@ -115,6 +119,38 @@ impl HostError {
         }
     }
 
+    /// Identifies whether the error is the host refusing to go deeper than
+    /// one of its [Limits](crate::Limits), i.e. too many nested contract calls
+    /// or too deeply nested values. These errors leave the budget intact.
+    ///
+    /// This is not implied by the error being `(Context, ExceededLimit)`: a
+    /// contract returning or failing with that error code is not a depth
+    /// limit being exceeded.
+    pub fn is_depth_limit_exceeded(&self) -> bool {
+        self.info
+            .as_ref()
+            .is_some_and(|info| info.depth_limit_exceeded)
+    }
+
+    /// Marks the error as raised for one of the host's depth limits, see
+    /// [HostError::is_depth_limit_exceeded].
+    ///
+    /// Outside of debug mode the error carries no [DebugInfo], so an empty
+    /// one is attached to hold the marker.
+    pub(crate) fn mark_depth_limit_exceeded(mut self) -> Self {
+        match &mut self.info {
+            Some(info) => info.depth_limit_exceeded = true,
+            None => {
+                self.info = Some(Box::new(DebugInfo {
+                    events: Events(vec![]),
+                    backtrace: Vec::<BacktraceFrame>::new().into(),
+                    depth_limit_exceeded: true,
+                }))
+            }
+        }
+        self
+    }
+
     /// Identifies whether the error can be meaningfully recovered from.
     ///
     /// We consider errors that occur due to broken execution preconditions (
@@ -210,11 +246,8 @@ impl Host {
         msg: &str,
         debug_args: impl FnOnce() -> &'a [Val] + 'a,
     ) -> HostError {
-        if let Ok(true) = self.is_debug() {
-            self.error(error, msg, debug_args())
-        } else {
-            self.error(error, msg, &[])
-        }
+        let debug_args = self.if_debug_mode(debug_args).unwrap_or(&[]);
+        self.error(error, msg, debug_args)
     }
 
     /// Convenience function to construct an [Error] and pass to [Host::error].
@@ -229,7 +262,23 @@ impl Host {
     /// enriches the returned [Error] with [DebugInfo] in the form of a
     /// [Backtrace] and snapshot of the [Events] buffer.
     pub fn error(&self, error: Error, msg: &str, args: &[Val]) -> HostError {
-        if let Ok(true) = self.is_debug() {
+        self.error_with_diagnostic_args(
+            error,
+            msg,
+            args.iter().map(|v| InternalDiagnosticArg::HostVal(*v)),
+        )
+    }
+
+    /// As [Host::error], but takes the arguments to record in the diagnostic
+    /// event as [InternalDiagnosticArg]s, so that they can also be plain XDR
+    /// values that are not (and should not become) host objects.
+    pub(crate) fn error_with_diagnostic_args(
+        &self,
+        error: Error,
+        msg: &str,
+        args: impl IntoIterator<Item = InternalDiagnosticArg>,
+    ) -> HostError {
+        self.if_debug_mode(|| {
             // We _try_ to take a mutable borrow of the events buffer refcell
             // while building up the event we're going to emit into the events
             // log, failing gracefully (just emitting a no-debug-info
@@ -243,26 +292,26 @@ impl Host {
                 }
             }
             let info = self.maybe_get_debug_info();
-            return HostError { error, info };
-        }
-        error.into()
+            HostError { error, info }
+        })
+        .unwrap_or_else(|| error.into())
     }
 
     pub(crate) fn maybe_get_debug_info(&self) -> Option<Box<DebugInfo>> {
-        if let Ok(true) = self.is_debug() {
-            if let Ok(events_ref) = self.0.events.try_borrow() {
-                let events = match self
-                    .as_budget()
-                    .with_free_budget(|| events_ref.externalize(self))
-                {
-                    Ok(events) => events,
-                    Err(e) => return None,
-                };
-                let backtrace = Backtrace::new_unresolved();
-                return Some(Box::new(DebugInfo { backtrace, events }));
-            }
-        }
-        None
+        self.if_debug_mode(|| {
+            let events_ref = self.0.events.try_borrow().ok()?;
+            let events = self
+                .as_budget()
+                .with_free_budget(|| events_ref.externalize(self))
+                .ok()?;
+            let backtrace = Backtrace::new_unresolved();
+            Some(Box::new(DebugInfo {
+                backtrace,
+                events,
+                depth_limit_exceeded: false,
+            }))
+        })
+        .flatten()
     }
 
     // Some common error patterns here.
@@ -295,6 +344,29 @@ impl Host {
         }
     }
 
+    pub(crate) fn err_call_depth_limit_exceeded(&self, depth: u32) -> HostError {
+        self.err(
+            ScErrorType::Context,
+            ScErrorCode::ExceededLimit,
+            "contract call depth limit exceeded",
+            &[U32Val::from(depth).to_val()],
+        )
+        .mark_depth_limit_exceeded()
+    }
+
+    // Returns the message for `err` if it is the budget failing a charge for
+    // one of its ledger resource limits, naming the limit that was exceeded.
+    pub(crate) fn ledger_resource_limit_err_msg(&self, err: &HostError) -> Option<&'static str> {
+        if !err.error.is_type(ScErrorType::Budget) || !err.error.is_code(ScErrorCode::ExceededLimit)
+        {
+            return None;
+        }
+        self.as_budget()
+            .get_exceeded_ledger_resource_limit()
+            .ok()
+            .flatten()
+    }
+
     pub(crate) fn err_wasmi_fuel_metering_disabled(&self) -> HostError {
         self.err(
             ScErrorType::WasmVm,
@@ -327,12 +399,8 @@ impl Host {
         E: Debug,
     {
         res.map_err(|e| {
-            if let Ok(true) = self.is_debug() {
-                let msg = format!("{:?}", e);
-                self.error(e.into(), &msg, &[])
-            } else {
-                self.error(e.into(), "", &[])
-            }
+            let msg = self.if_debug_mode(|| format!("{:?}", e));
+            self.error(e.into(), msg.as_deref().unwrap_or(""), &[])
         })
     }
 
@@ -512,11 +580,10 @@ impl DebugArg for usize {
 macro_rules! err {
     ($host:expr, $error:expr, $msg:literal, $($args:expr),*) => {
         {
-            if let Ok(true) = $host.is_debug() {
-                $host.error($error.into(), $msg, &[$(<_ as $crate::host::error::DebugArg>::debug_arg($host, &$args)),*])
-            } else {
-                $host.error($error.into(), $msg, &[])
-            }
+            let args = $host.if_debug_mode(|| {
+                vec![$(<_ as $crate::host::error::DebugArg>::debug_arg($host, &$args)),*]
+            });
+            $host.error($error.into(), $msg, args.as_deref().unwrap_or(&[]))
         }
     };
 }
@@ -1,6 +1,6 @@
 use soroban_env_common::{
     xdr::{ContractIdPreimage, ScAddress, ScContractInstance, ScErrorCode, ScErrorType},
-    AddressObject,
+    AddressObject, VecObject,
 };
 
 use crate::{
@@ -9,7 +9,6 @@ use crate::{
     storage::{InstanceStorageMap, StorageMap},
     xdr::{ContractExecutable, Hash, HostFunction, HostFunctionType, ScVal},
     Error, Host, HostError, Object, Symbol, SymbolStr, TryFromVal, TryIntoVal, Val,
-    DEFAULT_HOST_DEPTH_LIMIT,
 };
 
 #[cfg(any(test, feature = "testutils"))]
@@ -121,6 +120,10 @@ impl Host {
     /// it had before its associated [`Frame`] was pushed.
     pub(super) fn push_frame(&self, frame: Frame) -> Result<RollbackPoint, HostError> {
         let _span = tracy_span!("push frame");
+        #[cfg(any(test, feature = "testutils"))]
+        let consumed_at_push = self.budget_consumed_for_frame_costs()?;
+        #[cfg(any(test, feature = "testutils"))]
+        let origin = super::frame_costs::frame_contract_fn(&frame);
         let auth_manager = self.try_borrow_authorization_manager()?;
         let auth_snapshot = auth_manager.snapshot(self)?;
         auth_manager.push_frame(self, &frame)?;
@@ -132,6 +135,11 @@ impl Host {
         };
         Vec::<Context>::charge_bulk_init_cpy(1, self.as_budget())?;
         self.try_borrow_context_mut()?.push(ctx);
+        #[cfg(any(test, feature = "testutils"))]
+        self.try_borrow_frame_origins_mut()?.push(origin);
+        #[cfg(any(test, feature = "testutils"))]
+        self.try_borrow_frame_consumed_at_push_mut()?
+            .push(consumed_at_push);
         Ok(RollbackPoint {
             storage: self.try_borrow_storage()?.map.metered_clone(self)?,
             events: self.try_borrow_events()?.vec.len(),
@@ -139,6 +147,29 @@ impl Host {
         })
     }
 
+    // Whether the contract running in the frame at `depth` on the context
+    // stack has permitted `caller_id` to re-enter it.
+    pub(crate) fn is_reentry_allowed(
+        &self,
+        depth: usize,
+        caller_id: &Hash,
+    ) -> Result<bool, HostError> {
+        Ok(self
+            .try_borrow_reentry_allowances()?
+            .iter()
+            .any(|(d, id)| *d == depth && id == caller_id))
+    }
+
+    // Drops the re-entry permissions of the frames at `depth` and above,
+    // which are the last ones as the permissions are kept in stack order.
+    fn drop_reentry_allowances(&self, depth: usize) -> Result<(), HostError> {
+        let mut allowances = self.try_borrow_reentry_allowances_mut()?;
+        while allowances.last().is_some_and(|(d, _)| *d >= depth) {
+            allowances.pop();
+        }
+        Ok(())
+    }
+
     /// Helper function for [`Host::with_frame`] below. Pops a [`Frame`] off
     /// the current context and optionally rolls back the [`Host`]'s objects
     /// and storage map to the state in the provided [`RollbackPoint`].
@@ -150,9 +181,18 @@ impl Host {
         if orp.is_none() {
             self.persist_instance_storage()?;
         }
-        self.try_borrow_context_mut()?
-            .pop()
-            .expect("unmatched host frame push/pop");
+        #[cfg(any(test, feature = "testutils"))]
+        self.record_frame_cost()?;
+        let depth = {
+            let mut context = self.try_borrow_context_mut()?;
+            context.pop().expect("unmatched host frame push/pop");
+            context.len()
+        };
+        self.drop_reentry_allowances(depth)?;
+        #[cfg(any(test, feature = "testutils"))]
+        self.try_borrow_frame_origins_mut()?.pop();
+        #[cfg(any(test, feature = "testutils"))]
+        self.try_borrow_frame_consumed_at_push_mut()?.pop();
         self.try_borrow_authorization_manager()?.pop_frame(self)?;
 
         if self.try_borrow_context()?.is_empty() {
@@ -164,6 +204,10 @@ impl Host {
 
         if let Some(rp) = orp {
             self.try_borrow_storage_mut()?.map = rp.storage;
+            #[cfg(any(test, feature = "testutils"))]
+            self.try_borrow_storage()?
+                .map
+                .check_invariants(self.budget_ref())?;
             self.try_borrow_events_mut()?.rollback(rp.events)?;
             self.try_borrow_authorization_manager()?
                 .rollback(self, rp.auth)?;
@@ -339,14 +383,24 @@ impl Host {
         F: FnOnce() -> Result<Val, HostError>,
     {
         let start_depth = self.try_borrow_context()?.len();
-        if start_depth as u32 == DEFAULT_HOST_DEPTH_LIMIT {
-            return Err(Error::from_type_and_code(
-                ScErrorType::Context,
-                ScErrorCode::ExceededLimit,
-            )
-            .into());
+        if start_depth as u32 >= self.budget_ref().get_call_depth_limit()? {
+            return Err(self.err_call_depth_limit_exceeded(start_depth as u32));
         }
+        let pre_storage = if start_depth == 0 {
+            self.storage_map_for_commit_hook()?
+        } else {
+            None
+        };
         let rp = self.push_frame(frame)?;
+        // The ledger only advances once the frame is pushed, so that a call
+        // that fails to start observes no change.
+        #[cfg(any(test, feature = "testutils"))]
+        if start_depth == 0 {
+            if let Err(e) = self.auto_advance_ledger() {
+                self.pop_frame(Some(rp))?;
+                return Err(e);
+            }
+        }
         let res = f();
         let res = if let Ok(v) = res {
             if let Ok(err) = Error::try_from(v) {
@@ -367,6 +421,9 @@ impl Host {
         // Every push and pop should be matched; if not there is a bug.
         let end_depth = self.try_borrow_context()?.len();
         assert_eq!(start_depth, end_depth);
+        if let (Some(pre), Ok(_)) = (pre_storage, &res) {
+            self.call_storage_commit_hook(pre)?;
+        }
         res
     }
 
@@ -399,6 +456,27 @@ impl Host {
         }
     }
 
+    /// Returns the [`Hash`] contract IDs of all the contract frames on the
+    /// context stack, from the outermost one to the one at the top of the
+    /// stack. Host function frames don't belong to any contract and are
+    /// skipped.
+    pub(crate) fn get_contract_call_stack_internal(&self) -> Result<Vec<Hash>, HostError> {
+        let context = self.try_borrow_context()?;
+        Vec::<Hash>::charge_bulk_init_cpy(context.len() as u64, self)?;
+        let mut ids = Vec::with_capacity(context.len());
+        for ctx in context.iter() {
+            let id = match &ctx.frame {
+                Frame::ContractVM { vm, .. } => &vm.contract_id,
+                Frame::Token(id, ..) => id,
+                #[cfg(any(test, feature = "testutils"))]
+                Frame::TestContract(tc) => &tc.id,
+                Frame::HostFunction(_) => continue,
+            };
+            ids.push(id.metered_clone(self)?);
+        }
+        Ok(ids)
+    }
+
     /// Pushes a test contract [`Frame`], runs a closure, and then pops the
     /// frame, rolling back if the closure returned an error. Returns the result
     /// that the closure returned (or any error caused during the frame
@@ -448,18 +526,39 @@ impl Host {
         match &instance.executable {
             ContractExecutable::Wasm(wasm_hash) => {
                 let code_entry = self.retrieve_wasm_from_storage(&wasm_hash)?;
-                let vm = Vm::new(self, id.metered_clone(self)?, code_entry.as_slice())?;
-                let relative_objects = Vec::new();
-                self.with_frame(
-                    Frame::ContractVM {
-                        vm: Rc::clone(&vm),
-                        fn_name: *func,
-                        args: args_vec,
-                        instance,
-                        relative_objects,
-                    },
-                    || vm.invoke_function_raw(self, func, args),
-                )
+                // Clone the cache handle out so no borrow is held while the
+                // contract runs (it may re-enter the host and call other
+                // contracts).
+                let module_cache = self.try_borrow_module_cache()?.clone();
+                // The VM instance is dropped when this call returns, so its
+                // memory only counts towards the budget's net memory (and so
+                // its memory peak) for the duration of the call.
+                self.as_budget().push_vm_mem_scope()?;
+                let res = (|| {
+                    let vm = match module_cache {
+                        Some(cache) => Vm::new_with_cache(
+                            self,
+                            id.metered_clone(self)?,
+                            wasm_hash,
+                            code_entry.as_slice(),
+                            &cache,
+                        )?,
+                        None => Vm::new(self, id.metered_clone(self)?, code_entry.as_slice())?,
+                    };
+                    let relative_objects = Vec::new();
+                    self.with_frame(
+                        Frame::ContractVM {
+                            vm: Rc::clone(&vm),
+                            fn_name: *func,
+                            args: args_vec,
+                            instance,
+                            relative_objects,
+                        },
+                        || vm.invoke_function_raw(self, func, args),
+                    )
+                })();
+                self.as_budget().pop_vm_mem_scope()?;
+                res
             }
             ContractExecutable::Token => self.with_frame(
                 Frame::Token(id.metered_clone(self)?, *func, args_vec, instance),
@@ -471,6 +570,55 @@ impl Host {
         }
     }
 
+    // Converts the outcome of a `try_call` into the value returned to the
+    // guest: the result of the call on success, or an error value on a
+    // recoverable failure.
+    pub(crate) fn try_call_result_to_val(
+        &self,
+        res: Result<Val, HostError>,
+        func: Symbol,
+        args: VecObject,
+    ) -> Result<Val, HostError> {
+        match res {
+            Ok(rv) => Ok(rv),
+            Err(e) => {
+                self.error(
+                    e.error,
+                    "contract try_call failed",
+                    &[func.to_val(), args.to_val()],
+                );
+                // Only allow to gracefully handle the recoverable errors.
+                // Non-recoverable errors should still cause guest to panic and
+                // abort execution.
+                if e.is_recoverable() {
+                    #[cfg(any(test, feature = "testutils"))]
+                    self.record_unhandled_error(e.error)?;
+                    // Pass contract errors through.
+                    if e.error.is_type(ScErrorType::Contract) {
+                        Ok(e.error.to_val())
+                    } else {
+                        // Narrow all the remaining host errors down to a single
+                        // error type. We don't want to expose the granular host
+                        // errors to the guest, consistently with how every
+                        // other host function works. This reduces the risk of
+                        // implementation being 'locked' into specific error
+                        // codes due to them being exposed to the guest and
+                        // hashed into blockchain.
+                        // The granular error codes are still observable with
+                        // diagnostic events.
+                        Ok(Error::from_type_and_code(
+                            ScErrorType::Context,
+                            ScErrorCode::InvalidAction,
+                        )
+                        .to_val())
+                    }
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
     // Notes on metering: this is covered by the called components.
     pub(crate) fn call_n_internal(
         &self,
@@ -496,8 +644,9 @@ impl Host {
             ));
         }
         if !matches!(reentry_mode, ContractReentryMode::Allowed) {
+            let caller_id = self.get_current_contract_id_unmetered()?;
             let mut is_last_non_host_frame = true;
-            for ctx in self.try_borrow_context()?.iter().rev() {
+            for (depth, ctx) in self.try_borrow_context()?.iter().enumerate().rev() {
                 let exist_id = match &ctx.frame {
                     Frame::ContractVM { vm, .. } => &vm.contract_id,
                     Frame::Token(id, ..) => id,
@@ -512,6 +661,14 @@ impl Host {
                         is_last_non_host_frame = false;
                         continue;
                     }
+                    // The re-entered contract may have explicitly permitted
+                    // the calling contract to re-enter it.
+                    if let Some(caller_id) = &caller_id {
+                        if self.is_reentry_allowed(depth, caller_id)? {
+                            is_last_non_host_frame = false;
+                            continue;
+                        }
+                    }
                     return Err(self.err(
                         ScErrorType::Context,
                         ScErrorCode::InvalidAction,
@@ -538,7 +695,8 @@ impl Host {
             if let Some(cfs) = cfs_option {
                 let frame = self.create_test_contract_frame(id.clone(), func, args.to_vec())?;
                 let panic = frame.panic.clone();
-                return self.with_frame(Frame::TestContract(frame), || {
+                self.call_trace_enter(id, &func, args);
+                let res = self.with_frame(Frame::TestContract(frame), || {
                     use std::any::Any;
                     use std::panic::AssertUnwindSafe;
                     type PanicVal = Box<dyn Any + Send>;
@@ -613,29 +771,40 @@ impl Host {
                             // payload into the diagnostic event buffer. This
                             // code path will get hit when contracts do
                             // `panic!("some string")` in native testing mode.
-                            if !recovered_error_from_panic_refcell && self.is_debug()? {
-                                if let Some(str) = panic_payload.downcast_ref::<&str>() {
-                                    let msg: String = format!(
-                                        "caught panic '{}' from contract function '{:?}'",
-                                        str, func
-                                    );
-                                    let _ = self.log_diagnostics(&msg, args);
-                                } else if let Some(str) = panic_payload.downcast_ref::<String>() {
-                                    let msg: String = format!(
-                                        "caught panic '{}' from contract function '{:?}'",
-                                        str, func
-                                    );
-                                    let _ = self.log_diagnostics(&msg, args);
-                                }
+                            if !recovered_error_from_panic_refcell {
+                                self.with_debug_mode(|| {
+                                    let payload = panic_payload
+                                        .downcast_ref::<&str>()
+                                        .copied()
+                                        .or_else(|| {
+                                            panic_payload
+                                                .downcast_ref::<String>()
+                                                .map(|s| s.as_str())
+                                        });
+                                    if let Some(str) = payload {
+                                        let msg: String = format!(
+                                            "caught panic '{}' from contract function '{:?}'",
+                                            str, func
+                                        );
+                                        let _ = self.log_diagnostics(&msg, args);
+                                    }
+                                    Ok(())
+                                })?;
                             }
                             Err(self.error(error, "caught error from function", &[]))
                         }
                     }
                 });
+                self.call_trace_exit(&res);
+                return res;
             }
         }
 
+        #[cfg(any(test, feature = "testutils"))]
+        self.call_trace_enter(id, &func, args);
         let res = self.call_contract_fn(id, &func, args);
+        #[cfg(any(test, feature = "testutils"))]
+        self.call_trace_exit(&res);
 
         match &res {
             Ok(res) => self.fn_return_diagnostics(id, &func, res)?,
@@ -0,0 +1,187 @@
+// "testutils" is not covered by budget metering.
+use std::collections::BTreeMap;
+
+use crate::{
+    budget::AsBudget,
+    host_object::HostObject,
+    xdr::{Hash, ScErrorCode, ScErrorType, ScSymbol, ScVal},
+    Host, HostError, Symbol,
+};
+
+use super::Frame;
+
+/// The budget consumed by a single contract invocation, as recorded when its
+/// frame is popped. See [Host::get_per_frame_costs].
+///
+/// The costs are inclusive: they cover the invocation itself along with any
+/// sub-invocations it made, as well as the host's own bookkeeping for pushing
+/// and popping the frame.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameCost {
+    pub contract_id: Hash,
+    pub function: ScSymbol,
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+}
+
+/// The live host objects of one type that were created by the same contract
+/// function, as returned by [Host::dump_objects].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ObjectSummary {
+    /// The type of the objects, e.g. `"Vec"` or `"Bytes"`.
+    pub object_type: &'static str,
+    /// The contract and function that created the objects, or `None` for
+    /// objects created outside of any contract, e.g. by the test itself or
+    /// while setting up an invocation.
+    pub created_in: Option<(Hash, ScSymbol)>,
+    pub count: usize,
+    /// The approximate memory held by the objects: the object itself along
+    /// with its variable-length contents, if any.
+    pub bytes: usize,
+}
+
+fn object_type_name(obj: &HostObject) -> &'static str {
+    match obj {
+        HostObject::Vec(_) => "Vec",
+        HostObject::Map(_) => "Map",
+        HostObject::U64(_) => "U64",
+        HostObject::I64(_) => "I64",
+        HostObject::TimePoint(_) => "TimePoint",
+        HostObject::Duration(_) => "Duration",
+        HostObject::U128(_) => "U128",
+        HostObject::I128(_) => "I128",
+        HostObject::U256(_) => "U256",
+        HostObject::I256(_) => "I256",
+        HostObject::Bytes(_) => "Bytes",
+        HostObject::String(_) => "String",
+        HostObject::Symbol(_) => "Symbol",
+        HostObject::Address(_) => "Address",
+    }
+}
+
+// The contract and function a frame runs, if it runs one.
+pub(super) fn frame_contract_fn(frame: &Frame) -> Option<(Hash, Symbol)> {
+    match frame {
+        Frame::ContractVM { vm, fn_name, .. } => Some((vm.contract_id.clone(), *fn_name)),
+        Frame::Token(id, func, ..) => Some((id.clone(), *func)),
+        Frame::TestContract(tc) => Some((tc.id.clone(), tc.func)),
+        // Host function frames don't belong to any contract.
+        Frame::HostFunction(_) => None,
+    }
+}
+
+impl Host {
+    /// Returns a summary of every live host object, grouped by type and by
+    /// the contract function that created it, largest groups first. As host
+    /// objects are never released before the host is dropped, this shows
+    /// which invocations are responsible for the host's memory use.
+    pub fn dump_objects(&self) -> Result<Vec<ObjectSummary>, HostError> {
+        let mut groups: BTreeMap<(&'static str, Option<(Hash, ScSymbol)>), (usize, usize)> =
+            BTreeMap::new();
+        let objects = self.try_borrow_objects()?;
+        let origins = self.try_borrow_object_origins()?;
+        for (obj, origin) in objects.iter().zip(origins.iter()) {
+            let created_in = match origin {
+                Some((id, func)) => Some((
+                    id.clone(),
+                    self.as_budget()
+                        .with_free_budget(|| self.frame_cost_fn_name(*func))?,
+                )),
+                None => None,
+            };
+            let group = groups
+                .entry((object_type_name(obj), created_in))
+                .or_default();
+            group.0 += 1;
+            group.1 += std::mem::size_of::<HostObject>() + obj.contents_byte_size();
+        }
+        let mut summaries: Vec<ObjectSummary> = groups
+            .into_iter()
+            .map(
+                |((object_type, created_in), (count, bytes))| ObjectSummary {
+                    object_type,
+                    created_in,
+                    count,
+                    bytes,
+                },
+            )
+            .collect();
+        summaries.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        Ok(summaries)
+    }
+
+    // Records the contract function running in the current frame as the
+    // creator of the host object that was just added. Objects are created
+    // while the context is mutably borrowed (e.g. when loading instance
+    // storage), so this reads the frame origins rather than the context.
+    pub(crate) fn record_object_origin(&self) -> Result<(), HostError> {
+        let origin = self.try_borrow_frame_origins()?.last().cloned().flatten();
+        self.try_borrow_object_origins_mut()?.push(origin);
+        Ok(())
+    }
+
+    /// Returns the budget consumed by every contract frame that has been
+    /// popped since the host was created (or since the last call to
+    /// [Host::reset_per_frame_costs]), in the order the frames were popped.
+    /// Nested invocations hence appear before the invocations that made them.
+    pub fn get_per_frame_costs(&self) -> Result<Vec<FrameCost>, HostError> {
+        Ok(self.try_borrow_frame_costs()?.clone())
+    }
+
+    /// Discards all the frame costs recorded so far.
+    pub fn reset_per_frame_costs(&self) -> Result<(), HostError> {
+        self.try_borrow_frame_costs_mut()?.clear();
+        Ok(())
+    }
+
+    pub(crate) fn budget_consumed_for_frame_costs(&self) -> Result<(u64, u64), HostError> {
+        let budget = self.as_budget();
+        Ok((
+            budget.get_cpu_insns_consumed()?,
+            budget.get_mem_bytes_consumed()?,
+        ))
+    }
+
+    // Records the cost of the frame at the top of the context stack. Called
+    // right before that frame is popped.
+    pub(crate) fn record_frame_cost(&self) -> Result<(), HostError> {
+        let (contract_id, func) = {
+            let context = self.try_borrow_context()?;
+            let Some(ctx) = context.last() else {
+                return Ok(());
+            };
+            let Some((contract_id, func)) = frame_contract_fn(&ctx.frame) else {
+                return Ok(());
+            };
+            (contract_id, func)
+        };
+        let Some(consumed_at_push) = self.try_borrow_frame_consumed_at_push()?.last().copied()
+        else {
+            return Ok(());
+        };
+        let (cpu_at_pop, mem_at_pop) = self.budget_consumed_for_frame_costs()?;
+        let function = self
+            .as_budget()
+            .with_free_budget(|| self.frame_cost_fn_name(func))?;
+        self.try_borrow_frame_costs_mut()?.push(FrameCost {
+            contract_id,
+            function,
+            cpu_insns: cpu_at_pop.saturating_sub(consumed_at_push.0),
+            mem_bytes: mem_at_pop.saturating_sub(consumed_at_push.1),
+        });
+        Ok(())
+    }
+
+    fn frame_cost_fn_name(&self, func: Symbol) -> Result<ScSymbol, HostError> {
+        match self.from_host_val(func.to_val())? {
+            ScVal::Symbol(function) => Ok(function),
+            _ => Err(self.err(
+                ScErrorType::Value,
+                ScErrorCode::UnexpectedType,
+                "function name is not a symbol",
+                &[func.to_val()],
+            )),
+        }
+    }
+}
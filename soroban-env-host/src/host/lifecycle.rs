@@ -119,8 +119,18 @@ impl Host {
         let id_preimage =
             self.get_full_contract_id_preimage(args.contract_id_preimage.metered_clone(self)?)?;
         let hash_id = Hash(self.metered_hash_xdr(&id_preimage)?);
+        // Only pay for keeping a copy of the executable if it goes into an
+        // event.
+        let deployed_executable = if self.should_emit_deploy_contract_event()? {
+            Some(args.executable.metered_clone(self)?)
+        } else {
+            None
+        };
         self.create_contract_with_id(hash_id.metered_clone(self)?, args.executable)?;
         self.maybe_initialize_asset_token(&hash_id, &args.contract_id_preimage)?;
+        if let Some(executable) = deployed_executable {
+            self.emit_deploy_contract_event(&hash_id, &executable)?;
+        }
         self.add_host_object(ScAddress::Contract(hash_id))
     }
 
@@ -0,0 +1,67 @@
+use soroban_env_common::xdr::DEFAULT_XDR_RW_DEPTH_LIMIT;
+
+use crate::{
+    xdr::{ScErrorCode, ScErrorType},
+    Host, HostError, DEFAULT_HOST_DEPTH_LIMIT,
+};
+
+/// The depth limits the host enforces to keep recursion, whether through
+/// nested contract calls or nested values, from overflowing the native stack.
+///
+/// Exceeding any of them fails with a `(Context, ExceededLimit)` error for
+/// which [HostError::is_depth_limit_exceeded] holds, raised before the deeper
+/// level is visited and so leaving the budget and the host state intact.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    /// The maximum depth of nested contract calls, counting the top-level
+    /// invocation.
+    pub max_call_depth: u32,
+    /// The maximum nesting depth of host values and containers that the host
+    /// converts, compares or clones.
+    pub max_value_nesting: u32,
+    /// The maximum nesting depth of XDR values the host reads or writes.
+    pub max_xdr_depth: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_call_depth: DEFAULT_HOST_DEPTH_LIMIT,
+            max_value_nesting: DEFAULT_HOST_DEPTH_LIMIT,
+            max_xdr_depth: DEFAULT_XDR_RW_DEPTH_LIMIT,
+        }
+    }
+}
+
+impl Host {
+    /// Sets the depth limits of this host. The limits are kept in the host's
+    /// budget, so they are shared with any host using the same budget.
+    ///
+    /// Every limit must be non-zero, otherwise none of them is changed. This
+    /// must not be called during an invocation.
+    pub fn set_limits(&self, limits: Limits) -> Result<(), HostError> {
+        if limits.max_call_depth == 0 || limits.max_value_nesting == 0 || limits.max_xdr_depth == 0
+        {
+            return Err(self.err(
+                ScErrorType::Budget,
+                ScErrorCode::InvalidInput,
+                "depth limits must be non-zero",
+                &[],
+            ));
+        }
+        let budget = self.budget_ref();
+        budget.set_call_depth_limit(limits.max_call_depth)?;
+        budget.set_depth_limit(limits.max_value_nesting)?;
+        budget.set_xdr_depth_limit(limits.max_xdr_depth)
+    }
+
+    /// Returns the depth limits of this host.
+    pub fn get_limits(&self) -> Result<Limits, HostError> {
+        let budget = self.budget_ref();
+        Ok(Limits {
+            max_call_depth: budget.get_call_depth_limit()?,
+            max_value_nesting: budget.get_depth_limit()?,
+            max_xdr_depth: budget.get_xdr_depth_limit()?,
+        })
+    }
+}
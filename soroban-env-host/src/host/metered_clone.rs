@@ -30,11 +30,11 @@ use crate::{
 
 use super::declared_size::DeclaredSizeForMetering;
 
-// Charge for an N-element "shallow copy" of some type, not cloning any substructure. The charge
-// unit is number of elements `n_elts` multiplied by a declared size of each element. In a better
-// world we would multiply by `size_of<Self>` instead but that's not guaranteed to be stable, which
-// might cause metering to differ across compilations, causing problems in concensus and replay.
-pub(crate) fn charge_shallow_copy<T: DeclaredSizeForMetering>(
+/// Charge for an N-element "shallow copy" of some type, not cloning any substructure. The charge
+/// unit is number of elements `n_elts` multiplied by a declared size of each element. In a better
+/// world we would multiply by `size_of<Self>` instead but that's not guaranteed to be stable, which
+/// might cause metering to differ across compilations, causing problems in concensus and replay.
+pub fn charge_shallow_copy<T: DeclaredSizeForMetering>(
     n_elts: u64,
     budget: impl AsBudget,
 ) -> Result<(), HostError> {
@@ -55,9 +55,10 @@ pub(crate) fn charge_shallow_copy<T: DeclaredSizeForMetering>(
     )
 }
 
+/// Charge for a heap allocation of `n_elts` elements of `T`, by their declared size.
 // Let it be a free function instead of a trait because charge_heap_alloc maybe called elsewhere,
 // not just metered clone, e.g. Box::<T>::new().
-pub(crate) fn charge_heap_alloc<T: DeclaredSizeForMetering>(
+pub fn charge_heap_alloc<T: DeclaredSizeForMetering>(
     n_elts: u64,
     budget: impl AsBudget,
 ) -> Result<(), HostError> {
@@ -174,7 +174,10 @@ where
                 let init = self.map.iter().take(replace_pos).cloned();
                 let fini = self.map.iter().skip(replace_pos + 1).cloned();
                 let iter = init.chain([(key, value)]).chain(fini);
-                Self::from_exact_iter(iter, ctx)
+                let new = Self::from_exact_iter(iter, ctx)?;
+                #[cfg(any(test, feature = "testutils"))]
+                new.check_mutation(self.len(), ctx)?;
+                Ok(new)
             }
             Err(insert_pos) => {
                 // [0,1,2] insert_pos == 1
@@ -186,7 +189,10 @@ where
                     let init = self.map.iter().take(insert_pos).cloned();
                     let fini = self.map.iter().skip(insert_pos).cloned();
                     let iter = init.chain([(key, value)]).chain(fini);
-                    Self::from_exact_iter(iter, ctx)
+                    let new = Self::from_exact_iter(iter, ctx)?;
+                    #[cfg(any(test, feature = "testutils"))]
+                    new.check_mutation(self.len() + 1, ctx)?;
+                    Ok(new)
                 }
             }
         }
@@ -213,6 +219,53 @@ where
         })
     }
 
+    /// Returns the entry with the smallest key, or `None` if the map is empty.
+    pub fn get_min(&self, ctx: &Ctx) -> Result<Option<&(K, V)>, HostError> {
+        self.charge_access(1, ctx)?;
+        Ok(self.map.first())
+    }
+
+    /// Returns the entry with the largest key, or `None` if the map is empty.
+    pub fn get_max(&self, ctx: &Ctx) -> Result<Option<&(K, V)>, HostError> {
+        self.charge_access(1, ctx)?;
+        Ok(self.map.last())
+    }
+
+    /// Returns the entry with the largest key less than `key`, or `None` if
+    /// there is no such entry. `key` does not need to be in the map.
+    pub fn get_prev<Q>(&self, key: &Q, ctx: &Ctx) -> Result<Option<&(K, V)>, HostError>
+    where
+        K: Borrow<Q>,
+        Ctx: Compare<Q, Error = HostError>,
+    {
+        let (Ok(pos) | Err(pos)) = self.find(key, ctx)?;
+        match pos.checked_sub(1) {
+            Some(prev) => {
+                self.charge_access(1, ctx)?;
+                Ok(self.map.get(prev))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the entry with the smallest key greater than `key`, or `None`
+    /// if there is no such entry. `key` does not need to be in the map.
+    pub fn get_next<Q>(&self, key: &Q, ctx: &Ctx) -> Result<Option<&(K, V)>, HostError>
+    where
+        K: Borrow<Q>,
+        Ctx: Compare<Q, Error = HostError>,
+    {
+        // `found` cannot be `usize::MAX`, see `remove`.
+        let next = match self.find(key, ctx)? {
+            Ok(found) => found + 1,
+            Err(insert_pos) => insert_pos,
+        };
+        if next < self.map.len() {
+            self.charge_access(1, ctx)?;
+        }
+        Ok(self.map.get(next))
+    }
+
     /// Returns a `Some((new_self, val))` pair where `new_self` no longer
     /// contains an entry for `key`, if the key existed, otherwise `None` if
     /// `key` didn't exist (in which case there's no need to clone).
@@ -233,6 +286,8 @@ where
                 let fini = self.map.iter().skip(found + 1).cloned();
                 let iter = init.chain(fini);
                 let new = Self::from_exact_iter(iter, ctx)?;
+                #[cfg(any(test, feature = "testutils"))]
+                new.check_mutation(self.len() - 1, ctx)?;
                 let res = self.map[found].1.metered_clone(ctx.as_budget())?;
                 Ok(Some((new, res)))
             }
@@ -241,6 +296,8 @@ where
                 // If the suffix is empty it's harmless.
                 let iter = self.map.iter().skip(1).cloned();
                 let new = Self::from_exact_iter(iter, ctx)?;
+                #[cfg(any(test, feature = "testutils"))]
+                new.check_mutation(self.len() - 1, ctx)?;
                 let res = self.map[found].1.metered_clone(ctx.as_budget())?;
                 Ok(Some((new, res)))
             }
@@ -276,6 +333,47 @@ where
     }
 }
 
+// Invariant checks, run after every mutation in test builds. Maps are
+// persistent: a mutation returns a new map and leaves the original intact, so
+// that rolling back is a matter of reinstating the original. A map with
+// unsorted or duplicate keys would iterate, and so hash and serialize,
+// differently depending on how it was built, which is a source of consensus
+// splitting nondeterminism. None of these checks are charged to the budget.
+#[cfg(any(test, feature = "testutils"))]
+impl<K, V, Ctx> MeteredOrdMap<K, V, Ctx>
+where
+    K: MeteredClone,
+    V: MeteredClone,
+    Ctx: AsBudget + Compare<K, Error = HostError>,
+{
+    /// Checks that the keys of the map are strictly increasing, i.e. sorted
+    /// and unique, and that its length fits a `u32`.
+    pub(crate) fn check_invariants(&self, ctx: &Ctx) -> Result<(), HostError> {
+        let violation =
+            || -> HostError { (ScErrorType::Object, ScErrorCode::InternalError).into() };
+        if u32::try_from(self.map.len()).is_err() {
+            return Err(violation());
+        }
+        ctx.as_budget().with_free_budget(|| {
+            for w in self.map.as_slice().windows(2) {
+                if <Ctx as Compare<K>>::compare(ctx, &w[0].0, &w[1].0)? != Ordering::Less {
+                    return Err(violation());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Checks that `self`, the result of a mutation, is valid and has the
+    // length the mutation should have left it with.
+    fn check_mutation(&self, expected_len: usize, ctx: &Ctx) -> Result<(), HostError> {
+        if self.map.len() != expected_len {
+            return Err((ScErrorType::Object, ScErrorCode::InternalError).into());
+        }
+        self.check_invariants(ctx)
+    }
+}
+
 impl<K, V, Ctx> DeclaredSizeForMetering for MeteredOrdMap<K, V, Ctx>
 where
     K: DeclaredSizeForMetering,
@@ -3,7 +3,9 @@ use soroban_env_common::{
     Compare, Error,
 };
 
-use super::{declared_size::DeclaredSizeForMetering, MeteredClone};
+use super::{
+    declared_size::DeclaredSizeForMetering, metered_clone::MeteredContainer, MeteredClone,
+};
 use crate::{
     budget::{AsBudget, Budget},
     xdr::ContractCostType,
@@ -42,6 +44,16 @@ where
         let mag = 64 - (self.vec.len() as u64).leading_zeros();
         budget.bulk_charge(ContractCostType::VecEntry, 1 + mag as u64, None)
     }
+
+    fn charge_sort(&self, budget: &Budget) -> Result<(), HostError> {
+        let len = self.vec.len() as u64;
+        let mag = 64 - len.leading_zeros();
+        budget.bulk_charge(
+            ContractCostType::VecEntry,
+            len.saturating_mul(mag as u64),
+            None,
+        )
+    }
 }
 
 impl<A> MeteredVector<A>
@@ -302,6 +314,47 @@ where
         }
     }
 
+    /// Returns a new vector with the elements of this one in the order given
+    /// by `cmp`. The sort is stable. Unlike [`slice::sort_by`], any error
+    /// returned by `cmp` aborts the sort and is returned to the caller.
+    pub fn sort_by<F>(&self, mut cmp: F, budget: &Budget) -> Result<Self, HostError>
+    where
+        F: FnMut(&A, &A) -> Result<Ordering, HostError>,
+    {
+        self.charge_sort(budget)?;
+        let len = self.vec.len();
+        let mut src = self.vec.metered_clone(budget)?;
+        // A bottom-up merge sort, ping-ponging between two buffers.
+        Vec::<A>::charge_bulk_init_cpy(len as u64, budget)?;
+        let mut dst = src.clone();
+        let mut width = 1;
+        while width < len {
+            let mut start = 0;
+            while start < len {
+                let mid = len.min(start + width);
+                let end = len.min(start + 2 * width);
+                let (mut i, mut j, mut k) = (start, mid, start);
+                while i < mid && j < end {
+                    if cmp(&src[j], &src[i])? == Ordering::Less {
+                        dst[k] = src[j].clone();
+                        j += 1;
+                    } else {
+                        dst[k] = src[i].clone();
+                        i += 1;
+                    }
+                    k += 1;
+                }
+                dst[k..k + (mid - i)].clone_from_slice(&src[i..mid]);
+                k += mid - i;
+                dst[k..end].clone_from_slice(&src[j..end]);
+                start = end;
+            }
+            std::mem::swap(&mut src, &mut dst);
+            width *= 2;
+        }
+        Self::from_vec(src)
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, A> {
         self.vec.iter()
     }
@@ -7,9 +7,7 @@ use crate::{
 use std::io::Write;
 
 use sha2::{Digest, Sha256};
-use soroban_env_common::xdr::{
-    DepthLimitedWrite, ScErrorCode, ScErrorType, DEFAULT_XDR_RW_DEPTH_LIMIT,
-};
+use soroban_env_common::xdr::{DepthLimitedWrite, Error as XdrError, ScErrorCode, ScErrorType};
 
 struct MeteredWrite<'a, W: Write> {
     budget: &'a Budget,
@@ -44,7 +42,16 @@ impl Host {
     pub fn metered_from_xdr<T: ReadXdr>(&self, bytes: &[u8]) -> Result<T, HostError> {
         let _span = tracy_span!("read xdr");
         self.charge_budget(ContractCostType::ValDeser, Some(bytes.len() as u64))?;
-        self.map_err(T::from_xdr(bytes))
+        let depth_limit = self.budget_ref().get_xdr_depth_limit()?;
+        let res = T::from_xdr_with_depth_limit(bytes, depth_limit);
+        let depth_limit_exceeded = matches!(res, Err(XdrError::DepthLimitExceeded));
+        self.map_err(res).map_err(|e| {
+            if depth_limit_exceeded {
+                e.mark_depth_limit_exceeded()
+            } else {
+                e
+            }
+        })
     }
 
     pub(crate) fn metered_from_xdr_obj<T: ReadXdr>(
@@ -61,13 +68,20 @@ pub fn metered_write_xdr(
     w: &mut Vec<u8>,
 ) -> Result<(), HostError> {
     let _span = tracy_span!("write xdr");
+    let depth_limit = budget.get_xdr_depth_limit()?;
     let mw = MeteredWrite { budget, w };
-    let mut w = DepthLimitedWrite::new(mw, DEFAULT_XDR_RW_DEPTH_LIMIT);
+    let mut w = DepthLimitedWrite::new(mw, depth_limit);
     // MeteredWrite above turned any budget failure into an IO error; we turn it
     // back to a budget failure here, since there's really no "IO error" that can
-    // occur when writing to a Vec<u8>.
-    obj.write_xdr(&mut w)
-        .map_err(|_| (ScErrorType::Budget, ScErrorCode::ExceededLimit).into())
+    // occur when writing to a Vec<u8>. The only other failure is hitting the
+    // depth limit.
+    obj.write_xdr(&mut w).map_err(|e| match e {
+        XdrError::DepthLimitExceeded => {
+            HostError::from((ScErrorType::Context, ScErrorCode::ExceededLimit))
+                .mark_depth_limit_exceeded()
+        }
+        _ => (ScErrorType::Budget, ScErrorCode::ExceededLimit).into(),
+    })
 }
 
 // Host-less metered XDR decoding.
@@ -79,5 +93,5 @@ pub fn metered_from_xdr_with_budget<T: ReadXdr>(
 ) -> Result<T, HostError> {
     let _span = tracy_span!("read xdr with budget");
     budget.charge(ContractCostType::ValDeser, Some(bytes.len() as u64))?;
-    T::from_xdr(bytes).map_err(|e| e.into())
+    T::from_xdr_with_depth_limit(bytes, budget.get_xdr_depth_limit()?).map_err(|e| e.into())
 }
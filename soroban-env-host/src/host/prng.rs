@@ -6,8 +6,8 @@ use crate::{
     budget::Budget,
     host::metered_clone::MeteredClone,
     host_object::HostVec,
-    xdr::{ContractCostType, ScBytes},
-    HostError,
+    xdr::{ContractCostType, ScBytes, ScErrorCode, ScErrorType},
+    Error, HostError,
 };
 use rand::{distributions::Uniform, prelude::Distribution, seq::SliceRandom};
 use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
@@ -98,6 +98,12 @@ impl Prng {
         range: RangeInclusive<u64>,
         budget: &Budget,
     ) -> Result<u64, HostError> {
+        // `Uniform::from` panics on an empty range, so reject it up front.
+        if range.is_empty() {
+            return Err(
+                Error::from_type_and_code(ScErrorType::Value, ScErrorCode::InvalidInput).into(),
+            );
+        }
         // We over-estimate the number of bytes drawn by a factor of 2, to
         // account for the fact that a range sample is rejection-sampling which
         // is expected to only do one draw but might do more than one.
@@ -0,0 +1,132 @@
+// "testutils" is not covered by budget metering.
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use crate::{
+    auth::AuthorizationManager,
+    budget::BudgetImpl,
+    events::{
+        diagnostic::DiagnosticLevel, EventFilter, EventHook, EventLimits, InternalEventsBuffer,
+    },
+    host_object::HostObject,
+    storage::{Storage, StorageCommitHook},
+    vm::VmConfig,
+    xdr::{AccountId, Hash, ScErrorCode, ScErrorType},
+    ContractFunctionSet, Error, Host, HostError, LedgerAutoAdvance, LedgerInfo, Symbol,
+};
+
+use super::{
+    call_trace::CallTraceRecorder, error::TryBorrowOrErr, frame_costs::FrameCost, prng::Prng,
+};
+
+/// A copy of the entire mutable state of a [Host], taken by
+/// [Host::snapshot_state] and reinstated by [Host::restore_state].
+///
+/// This includes the host's configuration, such as its event limits and hooks,
+/// but not its module cache, which only ever caches immutable data, so there
+/// is nothing in it to roll back.
+#[derive(Clone)]
+pub struct HostSnapshot {
+    source_account: Option<AccountId>,
+    ledger: Option<LedgerInfo>,
+    objects: Vec<HostObject>,
+    object_origins: Vec<Option<(Hash, Symbol)>>,
+    storage: Storage,
+    budget: BudgetImpl,
+    events: InternalEventsBuffer,
+    authorization_manager: AuthorizationManager,
+    diagnostic_level: DiagnosticLevel,
+    base_prng: Option<Prng>,
+    contracts: HashMap<Hash, Rc<dyn ContractFunctionSet>>,
+    call_trace: Option<CallTraceRecorder>,
+    function_call_counts: Option<BTreeMap<&'static str, u64>>,
+    frame_costs: Vec<FrameCost>,
+    previous_authorization_manager: Option<AuthorizationManager>,
+    unhandled_errors: Option<Vec<Error>>,
+    ledger_auto_advance: Option<LedgerAutoAdvance>,
+    event_limits: EventLimits,
+    vm_config: Option<VmConfig>,
+    event_hook: Option<(EventFilter, EventHook)>,
+    storage_commit_hook: Option<StorageCommitHook>,
+}
+
+impl Host {
+    fn check_no_active_frames(&self, msg: &'static str) -> Result<(), HostError> {
+        if self.try_borrow_context()?.is_empty() {
+            Ok(())
+        } else {
+            Err(self.err(ScErrorType::Context, ScErrorCode::InternalError, msg, &[]))
+        }
+    }
+
+    /// Captures the entire mutable state of the host -- storage, events,
+    /// objects, budget, authorization state and so on -- so that it can later
+    /// be reinstated with [Host::restore_state]. This lets tests fork
+    /// execution: run a call, inspect its results, then roll the host back.
+    ///
+    /// Must be called between top-level invocations, i.e. not from within a
+    /// contract call.
+    pub fn snapshot_state(&self) -> Result<HostSnapshot, HostError> {
+        self.check_no_active_frames("cannot snapshot host state during a contract call")?;
+        Ok(HostSnapshot {
+            source_account: self.try_borrow_source_account()?.clone(),
+            ledger: self.try_borrow_ledger()?.clone(),
+            objects: self.try_borrow_objects()?.clone(),
+            object_origins: self.try_borrow_object_origins()?.clone(),
+            storage: self.try_borrow_storage()?.clone(),
+            budget: self.budget_ref().0.try_borrow_or_err()?.clone(),
+            events: self.try_borrow_events()?.clone(),
+            authorization_manager: self.try_borrow_authorization_manager()?.clone(),
+            diagnostic_level: self.try_borrow_diagnostic_level()?.clone(),
+            base_prng: self.try_borrow_base_prng()?.clone(),
+            contracts: self.try_borrow_contracts()?.clone(),
+            call_trace: self.try_borrow_call_trace()?.clone(),
+            function_call_counts: self.try_borrow_function_call_counts()?.clone(),
+            frame_costs: self.try_borrow_frame_costs()?.clone(),
+            previous_authorization_manager: self
+                .try_borrow_previous_authorization_manager()?
+                .clone(),
+            unhandled_errors: self.0.unhandled_errors.0.try_borrow_or_err()?.clone(),
+            ledger_auto_advance: *self.try_borrow_ledger_auto_advance()?,
+            event_limits: *self.try_borrow_event_limits()?,
+            vm_config: *self.try_borrow_vm_config()?,
+            event_hook: self.try_borrow_event_hook()?.clone(),
+            storage_commit_hook: self.try_borrow_storage_commit_hook()?.clone(),
+        })
+    }
+
+    /// Reinstates the host state captured by [Host::snapshot_state]. Any
+    /// objects, events, storage changes and budget consumption that happened
+    /// since the snapshot was taken are discarded.
+    ///
+    /// Must be called between top-level invocations, i.e. not from within a
+    /// contract call.
+    pub fn restore_state(&self, snapshot: HostSnapshot) -> Result<(), HostError> {
+        self.check_no_active_frames("cannot restore host state during a contract call")?;
+        *self.try_borrow_source_account_mut()? = snapshot.source_account;
+        *self.try_borrow_ledger_mut()? = snapshot.ledger;
+        *self.try_borrow_objects_mut()? = snapshot.objects;
+        *self.try_borrow_object_origins_mut()? = snapshot.object_origins;
+        *self.try_borrow_storage_mut()? = snapshot.storage;
+        *self.budget_ref().0.try_borrow_mut_or_err()? = snapshot.budget;
+        *self.try_borrow_events_mut()? = snapshot.events;
+        *self.try_borrow_authorization_manager_mut()? = snapshot.authorization_manager;
+        *self.try_borrow_diagnostic_level_mut()? = snapshot.diagnostic_level;
+        *self.try_borrow_base_prng_mut()? = snapshot.base_prng;
+        *self.try_borrow_contracts_mut()? = snapshot.contracts;
+        *self.try_borrow_call_trace_mut()? = snapshot.call_trace;
+        *self.try_borrow_function_call_counts_mut()? = snapshot.function_call_counts;
+        *self.try_borrow_frame_costs_mut()? = snapshot.frame_costs;
+        *self.try_borrow_previous_authorization_manager_mut()? =
+            snapshot.previous_authorization_manager;
+        *self.0.unhandled_errors.0.try_borrow_mut_or_err()? = snapshot.unhandled_errors;
+        *self.try_borrow_ledger_auto_advance_mut()? = snapshot.ledger_auto_advance;
+        *self.try_borrow_event_limits_mut()? = snapshot.event_limits;
+        *self.try_borrow_vm_config_mut()? = snapshot.vm_config;
+        *self.try_borrow_event_hook_mut()? = snapshot.event_hook;
+        *self.try_borrow_storage_commit_hook_mut()? = snapshot.storage_commit_hook;
+        Ok(())
+    }
+}
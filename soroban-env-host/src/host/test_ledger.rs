@@ -0,0 +1,124 @@
+// "testutils" is not covered by budget metering.
+use crate::{
+    meta::{get_ledger_protocol_version, INTERFACE_VERSION},
+    Host, HostError, LedgerInfo,
+};
+
+/// Builds a [LedgerInfo] for tests, starting from defaults that are valid
+/// for the current protocol so that only the fields a test cares about need
+/// to be set.
+#[derive(Clone, Debug)]
+pub struct LedgerInfoBuilder(LedgerInfo);
+
+impl Default for LedgerInfoBuilder {
+    fn default() -> Self {
+        Self(LedgerInfo {
+            protocol_version: get_ledger_protocol_version(INTERFACE_VERSION),
+            sequence_number: 0,
+            timestamp: 0,
+            network_id: [0; 32],
+            base_reserve: 0,
+            min_persistent_entry_expiration: 4096,
+            min_temp_entry_expiration: 16,
+            max_entry_expiration: 6_312_000,
+        })
+    }
+}
+
+impl LedgerInfoBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn protocol_version(mut self, protocol_version: u32) -> Self {
+        self.0.protocol_version = protocol_version;
+        self
+    }
+
+    pub fn sequence_number(mut self, sequence_number: u32) -> Self {
+        self.0.sequence_number = sequence_number;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.0.timestamp = timestamp;
+        self
+    }
+
+    pub fn network_id(mut self, network_id: [u8; 32]) -> Self {
+        self.0.network_id = network_id;
+        self
+    }
+
+    pub fn base_reserve(mut self, base_reserve: u32) -> Self {
+        self.0.base_reserve = base_reserve;
+        self
+    }
+
+    pub fn min_persistent_entry_expiration(mut self, expiration: u32) -> Self {
+        self.0.min_persistent_entry_expiration = expiration;
+        self
+    }
+
+    pub fn min_temp_entry_expiration(mut self, expiration: u32) -> Self {
+        self.0.min_temp_entry_expiration = expiration;
+        self
+    }
+
+    pub fn max_entry_expiration(mut self, expiration: u32) -> Self {
+        self.0.max_entry_expiration = expiration;
+        self
+    }
+
+    pub fn build(self) -> LedgerInfo {
+        self.0
+    }
+}
+
+/// How far the ledger moves forward before every top-level invocation, see
+/// [Host::set_ledger_auto_advance].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LedgerAutoAdvance {
+    pub sequence_number: u32,
+    pub timestamp: u64,
+}
+
+impl Host {
+    /// Calls `f` with the host's ledger info for it to modify, e.g. to move
+    /// time forward between calls. A host without ledger info starts from
+    /// [LedgerInfoBuilder::default].
+    pub fn with_test_ledger<F>(&self, f: F) -> Result<(), HostError>
+    where
+        F: FnOnce(&mut LedgerInfo),
+    {
+        let mut ledger = self.try_borrow_ledger_mut()?;
+        f(ledger.get_or_insert_with(|| LedgerInfoBuilder::default().build()));
+        Ok(())
+    }
+
+    /// Makes the ledger sequence number and timestamp advance by the given
+    /// amounts before every top-level invocation (but not before the
+    /// sub-invocations it makes), so that each call observes a later ledger
+    /// than the previous one. Pass `None` to stop advancing.
+    ///
+    /// The ledger info must be set for this to take effect.
+    pub fn set_ledger_auto_advance(
+        &self,
+        advance: Option<LedgerAutoAdvance>,
+    ) -> Result<(), HostError> {
+        *self.try_borrow_ledger_auto_advance_mut()? = advance;
+        Ok(())
+    }
+
+    // Called right after a frame is pushed onto an empty context stack.
+    pub(crate) fn auto_advance_ledger(&self) -> Result<(), HostError> {
+        let Some(advance) = *self.try_borrow_ledger_auto_advance()? else {
+            return Ok(());
+        };
+        if let Some(li) = self.try_borrow_ledger_mut()?.as_mut() {
+            li.sequence_number = li.sequence_number.saturating_add(advance.sequence_number);
+            li.timestamp = li.timestamp.saturating_add(advance.timestamp);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+// "testutils" is not covered by budget metering.
+use std::collections::HashMap;
+
+use crate::{
+    xdr::{ScErrorCode, ScErrorType},
+    ContractFunctionSet, Host, HostError, Symbol, SymbolStr, TryFromVal, TryIntoVal, Val,
+};
+
+type DynContractFn = Box<dyn Fn(&Host, &[Val]) -> Result<Val, HostError>>;
+
+/// A Rust function that can be registered in a [TypedContractFunctionSet].
+///
+/// This is implemented for functions and closures taking a `&Host` followed
+/// by up to 8 arguments convertible from [Val], and returning a
+/// `Result<R, HostError>` where `R` is convertible to [Val]. The `Args` type
+/// parameter only serves to tell apart the implementations for different
+/// arities.
+pub trait TypedContractFn<Args>: 'static {
+    fn arity(&self) -> usize;
+    fn call_with_vals(&self, host: &Host, args: &[Val]) -> Result<Val, HostError>;
+}
+
+macro_rules! impl_typed_contract_fn {
+    ($count:literal $($arg:ident $idx:tt)*) => {
+        impl<F, R, $($arg),*> TypedContractFn<($($arg,)*)> for F
+        where
+            F: Fn(&Host $(, $arg)*) -> Result<R, HostError> + 'static,
+            R: TryIntoVal<Host, Val>,
+            HostError: From<<R as TryIntoVal<Host, Val>>::Error>,
+            $(
+                $arg: TryFromVal<Host, Val>,
+                HostError: From<<$arg as TryFromVal<Host, Val>>::Error>,
+            )*
+        {
+            fn arity(&self) -> usize {
+                $count
+            }
+
+            #[allow(unused_variables)]
+            fn call_with_vals(&self, host: &Host, args: &[Val]) -> Result<Val, HostError> {
+                let res = self(host $(, <$arg as TryFromVal<Host, Val>>::try_from_val(host, &args[$idx])?)*)?;
+                Ok(res.try_into_val(host)?)
+            }
+        }
+    };
+}
+
+impl_typed_contract_fn!(0);
+impl_typed_contract_fn!(1 A0 0);
+impl_typed_contract_fn!(2 A0 0 A1 1);
+impl_typed_contract_fn!(3 A0 0 A1 1 A2 2);
+impl_typed_contract_fn!(4 A0 0 A1 1 A2 2 A3 3);
+impl_typed_contract_fn!(5 A0 0 A1 1 A2 2 A3 3 A4 4);
+impl_typed_contract_fn!(6 A0 0 A1 1 A2 2 A3 3 A4 4 A5 5);
+impl_typed_contract_fn!(7 A0 0 A1 1 A2 2 A3 3 A4 4 A5 5 A6 6);
+impl_typed_contract_fn!(8 A0 0 A1 1 A2 2 A3 3 A4 4 A5 5 A6 6 A7 7);
+
+/// A [ContractFunctionSet] built from named Rust functions with typed
+/// arguments and return values, so that native test contracts don't need to
+/// unpack [Val]s by hand:
+///
+/// ```ignore
+/// let contract = TypedContractFunctionSet::new()
+///     .with_fn("add", |_host: &Host, a: u32, b: u32| -> Result<u32, HostError> {
+///         Ok(a + b)
+///     });
+/// host.register_test_contract(address, Rc::new(contract))?;
+/// ```
+///
+/// Calling a function with the wrong number of arguments, or with arguments
+/// that can't be converted to the declared types, fails the call with an
+/// error, as does an `Err` returned by the function itself.
+#[derive(Default)]
+pub struct TypedContractFunctionSet {
+    fns: HashMap<String, (usize, DynContractFn)>,
+}
+
+impl TypedContractFunctionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a function named `name` to the set, replacing any function that
+    /// was previously added under the same name.
+    pub fn with_fn<Args, F: TypedContractFn<Args>>(mut self, name: &str, f: F) -> Self {
+        let arity = f.arity();
+        self.fns.insert(
+            name.to_string(),
+            (
+                arity,
+                Box::new(move |host: &Host, args: &[Val]| f.call_with_vals(host, args)),
+            ),
+        );
+        self
+    }
+
+    fn dispatch(&self, func: &Symbol, host: &Host, args: &[Val]) -> Result<Option<Val>, HostError> {
+        let name = SymbolStr::try_from_val(host, func)?;
+        let Some((arity, f)) = self.fns.get(name.to_string().as_str()) else {
+            return Ok(None);
+        };
+        if args.len() != *arity {
+            return Err(host.err(
+                ScErrorType::Context,
+                ScErrorCode::UnexpectedSize,
+                "wrong number of arguments for test contract function",
+                &[func.to_val(), (args.len() as u32).into()],
+            ));
+        }
+        f(host, args).map(Some)
+    }
+}
+
+impl ContractFunctionSet for TypedContractFunctionSet {
+    fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Option<Val> {
+        // Errors are returned as `Error` values, which the host escalates to
+        // a failure of the call, same as for Wasm contracts returning errors.
+        match self.dispatch(func, host, args) {
+            Ok(res) => res,
+            Err(err) => Some(err.error.to_val()),
+        }
+    }
+}
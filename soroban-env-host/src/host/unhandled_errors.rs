@@ -0,0 +1,75 @@
+// "testutils" is not covered by budget metering.
+use std::cell::RefCell;
+
+use crate::{host::error::TryBorrowOrErr, Error, Host, HostError};
+
+// The errors that `try_call` has returned to test code as `Ok(Error)` values
+// and that the test hasn't acknowledged yet, or `None` while the check is
+// disabled. See `Host::enable_unhandled_error_check`.
+//
+// This is kept apart from the other host fields so that it can check for
+// leftover errors when dropped, without `HostImpl` itself implementing `Drop`.
+#[derive(Default)]
+pub(crate) struct UnhandledErrors(pub(crate) RefCell<Option<Vec<Error>>>);
+
+impl Drop for UnhandledErrors {
+    fn drop(&mut self) {
+        // Don't turn a failing test into a double panic.
+        if std::thread::panicking() {
+            return;
+        }
+        if let Some(errors) = self.0.get_mut() {
+            if !errors.is_empty() {
+                panic!(
+                    "host dropped with {} unhandled error(s) returned by try_call: {:?}; \
+                     take them with Host::take_unhandled_errors to mark them as handled",
+                    errors.len(),
+                    errors
+                );
+            }
+        }
+    }
+}
+
+impl Host {
+    /// Makes the host track the errors that `try_call` (and
+    /// `try_call_with_budget`) return to test code as `Ok(Error)` values,
+    /// which are easy to discard by accident since they don't fail the call.
+    /// Unless they are taken with [Host::take_unhandled_errors], the host
+    /// panics when dropped, so a test that ignores a failed call fails too.
+    ///
+    /// Only calls made directly by the test are tracked: errors that
+    /// `try_call` returns to a contract are the contract's to handle.
+    pub fn enable_unhandled_error_check(&self) -> Result<(), HostError> {
+        self.0
+            .unhandled_errors
+            .0
+            .try_borrow_mut_or_err()?
+            .get_or_insert_with(Vec::new);
+        Ok(())
+    }
+
+    /// Returns the errors tracked since the check was enabled (or since the
+    /// last call to this function) and marks them as handled.
+    pub fn take_unhandled_errors(&self) -> Result<Vec<Error>, HostError> {
+        Ok(self
+            .0
+            .unhandled_errors
+            .0
+            .try_borrow_mut_or_err()?
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default())
+    }
+
+    // Called with every error `try_call` is about to return as a value.
+    pub(crate) fn record_unhandled_error(&self, error: Error) -> Result<(), HostError> {
+        if !self.try_borrow_context()?.is_empty() {
+            return Ok(());
+        }
+        if let Some(errors) = self.0.unhandled_errors.0.try_borrow_mut_or_err()?.as_mut() {
+            errors.push(error);
+        }
+        Ok(())
+    }
+}
@@ -125,6 +125,27 @@ impl HostObject {
         };
         Ok(res)
     }
+
+    // The size of the object's variable-length contents, checked against
+    // `Budget::get_max_object_bytes`. Fixed-size objects count as empty.
+    pub(crate) fn contents_byte_size(&self) -> usize {
+        match self {
+            HostObject::Vec(v) => v.len().saturating_mul(std::mem::size_of::<Val>()),
+            HostObject::Map(m) => m.len().saturating_mul(2 * std::mem::size_of::<Val>()),
+            HostObject::Bytes(b) => b.len(),
+            HostObject::String(s) => s.len(),
+            HostObject::Symbol(s) => s.len(),
+            HostObject::U64(_)
+            | HostObject::I64(_)
+            | HostObject::TimePoint(_)
+            | HostObject::Duration(_)
+            | HostObject::U128(_)
+            | HostObject::I128(_)
+            | HostObject::U256(_)
+            | HostObject::I256(_)
+            | HostObject::Address(_) => 0,
+        }
+    }
 }
 
 pub trait HostObjectType: MeteredClone {
@@ -333,11 +354,32 @@ impl Host {
     ) -> Result<HOT::Wrapper, HostError> {
         let _span = tracy_span!("add host object");
         let index = self.try_borrow_objects()?.len();
+        let max_live_objects = self.budget_ref().get_max_live_objects()?;
+        if index >= max_live_objects as usize {
+            return Err(self.err(
+                ScErrorType::Object,
+                ScErrorCode::ExceededLimit,
+                "too many live host objects",
+                &[max_live_objects.into()],
+            ));
+        }
+        let obj = HOT::inject(hot);
+        let max_object_bytes = self.budget_ref().get_max_object_bytes()?;
+        if obj.contents_byte_size() > max_object_bytes as usize {
+            return Err(self.err(
+                ScErrorType::Object,
+                ScErrorCode::ExceededLimit,
+                "host object is too large",
+                &[max_object_bytes.into()],
+            ));
+        }
         let handle = index_to_handle(self, index, false)?;
         // charge for the new host object, which is just the amortized cost of a single
         // `HostObject` allocation
         metered_clone::charge_heap_alloc::<HostObject>(1, self)?;
-        self.try_borrow_objects_mut()?.push(HOT::inject(hot));
+        self.try_borrow_objects_mut()?.push(obj);
+        #[cfg(any(test, feature = "testutils"))]
+        self.record_object_origin()?;
         Ok(HOT::new_from_handle(handle))
     }
 
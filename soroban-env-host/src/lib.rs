@@ -45,7 +45,7 @@ macro_rules! tracy_span {
 
 pub mod budget;
 pub mod events;
-pub use events::diagnostic::DiagnosticLevel;
+pub use events::diagnostic::{DiagnosticLevel, DiagnosticSeverity};
 mod host;
 pub(crate) mod host_object;
 
@@ -53,23 +53,45 @@ mod native_contract;
 
 pub mod auth;
 pub mod vm;
-pub use vm::Vm;
+pub use vm::{Vm, VmConfig};
 #[cfg(any(test, feature = "testutils"))]
 pub mod cost_runner;
 pub mod storage;
 #[cfg(test)]
 mod test;
 
+pub use host::display;
 #[cfg(any(test, feature = "testutils"))]
 #[doc(hidden)]
 pub use host::testutils::call_with_suppressed_panic_hook;
+pub use host::{
+    metered_map::MeteredOrdMap, metered_vector::MeteredVector, Host, HostBuilder, HostError,
+    LedgerInfo, Limits, Seed, DEFAULT_HOST_DEPTH_LIMIT, SEED_BYTES,
+};
 #[cfg(any(test, feature = "testutils"))]
-pub use host::ContractFunctionSet;
 pub use host::{
-    metered_map::MeteredOrdMap, metered_vector::MeteredVector, Host, HostError, LedgerInfo, Seed,
-    DEFAULT_HOST_DEPTH_LIMIT, SEED_BYTES,
+    CallTraceNode, ContractFunctionSet, FrameCost, HostSnapshot, LedgerAutoAdvance,
+    LedgerInfoBuilder, ObjectSummary, TypedContractFn, TypedContractFunctionSet,
 };
 pub use soroban_env_common::*;
 
 pub mod e2e_invoke;
 pub mod fees;
+
+/// Budget-metered collections, cloning and allocation helpers, for native
+/// contracts and embedder extensions that need to allocate under the host's
+/// budget rather than with raw `Vec`s and maps.
+///
+/// This API is not stable: it follows the host's internal metering and may
+/// change in any release.
+pub mod metered {
+    pub use crate::host::{
+        declared_size::DeclaredSizeForMetering,
+        metered_clone::{
+            charge_heap_alloc, charge_shallow_copy, MeteredAlloc, MeteredClone, MeteredContainer,
+            MeteredIterator,
+        },
+        metered_map::MeteredOrdMap,
+        metered_vector::MeteredVector,
+    };
+}
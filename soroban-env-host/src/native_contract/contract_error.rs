@@ -21,6 +21,7 @@ pub enum ContractError {
     BalanceDeauthorizedError = 11,
     OverflowError = 12,
     TrustlineMissingError = 13,
+    MaxSupplyExceededError = 14,
 }
 
 impl From<ContractError> for Error {
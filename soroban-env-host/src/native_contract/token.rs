@@ -7,6 +7,7 @@ mod event;
 mod metadata;
 pub(crate) mod public_types;
 mod storage_types;
+mod supply;
 
 #[cfg(test)]
 pub(crate) mod test_token;
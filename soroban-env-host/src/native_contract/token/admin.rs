@@ -22,3 +22,40 @@ pub fn write_administrator(e: &Host, id: Address) -> Result<(), HostError> {
     )?;
     Ok(())
 }
+
+// Metering: covered by components
+fn has_pending_administrator(e: &Host) -> Result<bool, HostError> {
+    let key = InstanceDataKey::PendingAdmin;
+    let rv = e.has_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+    Ok(rv.try_into()?)
+}
+
+// Metering: covered by components
+pub fn read_pending_administrator(e: &Host) -> Result<Option<Address>, HostError> {
+    if !has_pending_administrator(e)? {
+        return Ok(None);
+    }
+    let key = InstanceDataKey::PendingAdmin;
+    let rv = e.get_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+    Ok(Some(rv.try_into_val(e)?))
+}
+
+// Metering: covered by components
+pub fn write_pending_administrator(e: &Host, id: Address) -> Result<(), HostError> {
+    let key = InstanceDataKey::PendingAdmin;
+    e.put_contract_data(
+        key.try_into_val(e)?,
+        id.try_into_val(e)?,
+        StorageType::Instance,
+    )?;
+    Ok(())
+}
+
+// Metering: covered by components
+pub fn remove_pending_administrator(e: &Host) -> Result<(), HostError> {
+    if has_pending_administrator(e)? {
+        let key = InstanceDataKey::PendingAdmin;
+        e.del_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+    }
+    Ok(())
+}
@@ -10,17 +10,29 @@ use super::storage_types::AllowanceValue;
 
 // Metering: covered by components
 pub fn read_allowance(e: &Host, from: Address, spender: Address) -> Result<i128, HostError> {
+    Ok(read_allowance_data(e, from, spender)?.0)
+}
+
+// Returns the usable allowance amount along with the ledger at which it
+// expires. Expired allowances have zero amount, but still report their
+// expiration ledger; missing allowances report `(0, 0)`.
+// Metering: covered by components
+pub fn read_allowance_data(
+    e: &Host,
+    from: Address,
+    spender: Address,
+) -> Result<(i128, u32), HostError> {
     let key = DataKey::Allowance(AllowanceDataKey { from, spender });
     if let Some(allowance) = StorageUtils::try_get(e, key.try_into_val(e)?, StorageType::Temporary)?
     {
         let val: AllowanceValue = allowance.try_into_val(e)?;
         if val.expiration_ledger < e.get_ledger_sequence()?.into() {
-            Ok(0)
+            Ok((0, val.expiration_ledger))
         } else {
-            Ok(val.amount)
+            Ok((val.amount, val.expiration_ledger))
         }
     } else {
-        Ok(0)
+        Ok((0, 0))
     }
 }
 
@@ -20,6 +20,15 @@ use soroban_env_common::{Env, StorageType, TryIntoVal};
 
 use super::storage_types::{BalanceValue, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
 
+// Protocol from which contract balances, and the amounts moved between
+// balances, are held to the `i64` range of classic balances.
+pub(crate) const CLASSIC_BALANCE_RANGE_MIN_PROTOCOL: u32 = 21;
+
+// Protocol from which clawing back the native asset from a contract balance
+// fails the same way as from an account balance. Before that it fails on the
+// balance not being clawbackable instead.
+const NATIVE_CONTRACT_CLAWBACK_ERR_MIN_PROTOCOL: u32 = 21;
+
 /// This module handles all balance and authorization related logic for both
 /// Accounts and non-Accounts. For Accounts, a trustline is expected (unless this
 /// contract is for the native asset) and trustline semantics will be followed,
@@ -115,13 +124,26 @@ pub fn receive_balance(e: &Host, addr: Address, amount: i128) -> Result<(), Host
                 }
             };
 
-            let new_balance = balance.amount.checked_add(amount).ok_or_else(|| {
-                e.error(
-                    ContractError::OverflowError.into(),
-                    "balance overflow in receive_balance",
-                    &[],
-                )
-            })?;
+            // Contract balances are stored as `i128`, but are held to the
+            // same `i64` range as classic balances, whatever the number of
+            // operations that added up to them.
+            let max_balance =
+                if e.get_ledger_protocol_version()? >= CLASSIC_BALANCE_RANGE_MIN_PROTOCOL {
+                    i64::MAX as i128
+                } else {
+                    i128::MAX
+                };
+            let new_balance = balance
+                .amount
+                .checked_add(amount)
+                .filter(|b| *b <= max_balance)
+                .ok_or_else(|| {
+                    e.error(
+                        ContractError::OverflowError.into(),
+                        "balance exceeds the maximum classic balance in receive_balance",
+                        &[],
+                    )
+                })?;
 
             balance.amount = new_balance;
             write_balance(e, addr, balance)
@@ -274,13 +296,18 @@ pub fn check_clawbackable(e: &Host, addr: Address) -> Result<(), HostError> {
             Ok(())
         };
 
+    // The native asset can't be clawed back from either kind of balance.
+    let native_err = || {
+        e.error(
+            ContractError::OperationNotSupportedError.into(),
+            "cannot clawback native asset",
+            &[],
+        )
+    };
+
     match addr.to_sc_address()? {
         ScAddress::Account(acc_id) => match read_asset_info(e)? {
-            AssetInfo::Native => Err(e.error(
-                ContractError::OperationNotSupportedError.into(),
-                "cannot clawback native asset",
-                &[],
-            )),
+            AssetInfo::Native => Err(native_err()),
             AssetInfo::AlphaNum4(asset) => {
                 let issuer_account_id = e.account_id_from_bytesobj(asset.issuer.into())?;
                 validate_trustline(
@@ -305,6 +332,11 @@ pub fn check_clawbackable(e: &Host, addr: Address) -> Result<(), HostError> {
             }
         },
         ScAddress::Contract(_) => {
+            if e.get_ledger_protocol_version()? >= NATIVE_CONTRACT_CLAWBACK_ERR_MIN_PROTOCOL {
+                if let AssetInfo::Native = read_asset_info(e)? {
+                    return Err(native_err());
+                }
+            }
             let key = DataKey::Balance(addr);
             if let Some(raw_balance) =
                 StorageUtils::try_get(e, key.try_into_val(e)?, StorageType::Persistent)?
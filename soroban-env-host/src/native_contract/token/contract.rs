@@ -1,7 +1,9 @@
 use crate::host::{metered_clone::MeteredClone, Host};
-use crate::native_contract::base_types::{Address, Bytes, BytesN, String};
+use crate::native_contract::base_types::{Address, Bytes, BytesN, String, Vec};
 use crate::native_contract::contract_error::ContractError;
-use crate::native_contract::token::allowance::{read_allowance, spend_allowance, write_allowance};
+use crate::native_contract::token::allowance::{
+    read_allowance, read_allowance_data, spend_allowance, write_allowance,
+};
 use crate::native_contract::token::asset_info::{has_asset_info, write_asset_info};
 use crate::native_contract::token::balance::{
     is_authorized, read_balance, receive_balance, spend_balance, write_authorization,
@@ -10,18 +12,25 @@ use crate::native_contract::token::event;
 use crate::native_contract::token::public_types::AssetInfo;
 use crate::{err, HostError};
 
-use soroban_env_common::xdr::Asset;
+use soroban_env_common::xdr::{Asset, ScErrorCode, ScErrorType};
 use soroban_env_common::{ConversionError, Env, EnvBase, TryFromVal, TryIntoVal};
 use soroban_native_sdk_macros::contractimpl;
 
-use super::admin::{read_administrator, write_administrator};
+use super::admin::{
+    read_administrator, read_pending_administrator, remove_pending_administrator,
+    write_administrator, write_pending_administrator,
+};
 use super::asset_info::read_asset_info;
 use super::balance::{
     check_clawbackable, get_spendable_balance, spend_balance_no_authorization_check,
+    CLASSIC_BALANCE_RANGE_MIN_PROTOCOL,
 };
-use super::metadata::{read_name, read_symbol, set_metadata, DECIMAL};
+use super::metadata::{read_name, read_symbol, set_metadata, write_name_and_symbol, DECIMAL};
 use super::public_types::{AlphaNum12AssetInfo, AlphaNum4AssetInfo};
 use super::storage_types::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
+use super::supply::{
+    decrease_supply, increase_supply, read_max_supply, read_total_supply, write_max_supply,
+};
 
 pub trait TokenTrait {
     /// init_asset can create a contract for a wrapped classic asset
@@ -36,6 +45,12 @@ pub trait TokenTrait {
 
     fn allowance(e: &Host, from: Address, spender: Address) -> Result<i128, HostError>;
 
+    /// allowance_data returns the amount `spender` is currently allowed to
+    /// spend on behalf of `from`, along with the ledger at which the
+    /// allowance expires. The amount is zero if the allowance has expired,
+    /// and both values are zero if no allowance was ever approved.
+    fn allowance_data(e: &Host, from: Address, spender: Address) -> Result<(i128, u32), HostError>;
+
     fn approve(
         e: &Host,
         from: Address,
@@ -52,6 +67,12 @@ pub trait TokenTrait {
 
     fn transfer(e: &Host, from: Address, to: Address, amount: i128) -> Result<(), HostError>;
 
+    /// transfer_many transfers tokens from `from` to several recipients at
+    /// once. `transfers` is a vector of `(to, amount)` pairs, each encoded as
+    /// a two-element vector. `from` only needs to authorize the call once,
+    /// and a separate transfer event is emitted for every recipient.
+    fn transfer_many(e: &Host, from: Address, transfers: Vec) -> Result<(), HostError>;
+
     fn transfer_from(
         e: &Host,
         spender: Address,
@@ -64,16 +85,68 @@ pub trait TokenTrait {
 
     fn burn_from(e: &Host, spender: Address, from: Address, amount: i128) -> Result<(), HostError>;
 
+    /// burn_from_many burns from several balances at once, spending the
+    /// allowance `spender` has for each of them. `burns` is a vector of
+    /// `(from, amount)` pairs, each encoded as a two-element vector. The
+    /// spender only needs to authorize the call once, every entry is checked
+    /// against the remaining allowance for its `from`, and a separate burn
+    /// event is emitted for every entry.
+    fn burn_from_many(e: &Host, spender: Address, burns: Vec) -> Result<(), HostError>;
+
     fn set_authorized(e: &Host, addr: Address, authorize: bool) -> Result<(), HostError>;
 
     fn mint(e: &Host, to: Address, amount: i128) -> Result<(), HostError>;
 
+    /// mint_many mints to several recipients at once. `mints` is a vector of
+    /// `(to, amount)` pairs, each encoded as a two-element vector. The admin
+    /// only needs to authorize the call once, and a separate mint event is
+    /// emitted for every recipient.
+    fn mint_many(e: &Host, mints: Vec) -> Result<(), HostError>;
+
+    /// total_supply returns the amount minted through this contract, less
+    /// what was burned or clawed back through it, since a max supply was
+    /// first set with `set_max_supply`; without a max supply it is not
+    /// tracked and is always 0. Balances of the underlying classic asset
+    /// issued outside of the contract are not included.
+    fn total_supply(e: &Host) -> Result<i128, HostError>;
+
+    /// set_max_supply caps the total supply at `max_supply`, so that mints
+    /// that would take the total supply above it fail. It requires
+    /// authorization from the admin, and can't be set below the current
+    /// total supply. From then on every mint, burn and clawback updates the
+    /// total supply in the contract instance, so writes it.
+    fn set_max_supply(e: &Host, max_supply: i128) -> Result<(), HostError>;
+
+    /// max_supply returns the cap set with `set_max_supply`, if any.
+    fn max_supply(e: &Host) -> Result<Option<i128>, HostError>;
+
     fn clawback(e: &Host, from: Address, amount: i128) -> Result<(), HostError>;
 
+    /// set_admin hands the admin rights over to `new_admin` immediately,
+    /// discarding any pending proposal made with `propose_admin`.
     fn set_admin(e: &Host, new_admin: Address) -> Result<(), HostError>;
 
+    /// propose_admin nominates `new_admin` to take over the admin rights,
+    /// which only happens once `new_admin` calls `accept_admin`. Until then
+    /// the current admin keeps its rights, and may propose a different admin
+    /// to replace the pending one. This avoids handing the token over to an
+    /// address nobody controls.
+    fn propose_admin(e: &Host, new_admin: Address) -> Result<(), HostError>;
+
+    /// accept_admin completes the transfer of the admin rights to the admin
+    /// proposed with `propose_admin`, and requires its authorization.
+    fn accept_admin(e: &Host) -> Result<(), HostError>;
+
     fn admin(e: &Host) -> Result<Address, HostError>;
 
+    /// set_metadata replaces the display name and symbol of the token. It
+    /// requires authorization from the admin, and so is not available for
+    /// the Native token. Decimals are fixed at 7 and can't be changed.
+    ///
+    /// Token events carry the token name as a topic, so events published
+    /// after this call will carry the new name.
+    fn set_metadata(e: &Host, name: String, symbol: String) -> Result<(), HostError>;
+
     fn decimals(e: &Host) -> Result<u32, HostError>;
 
     fn name(e: &Host) -> Result<String, HostError>;
@@ -96,6 +169,43 @@ fn check_nonnegative_amount(e: &Host, amount: i128) -> Result<(), HostError> {
     }
 }
 
+// Balances of classic assets are stored as `i64` (both in trustlines and
+// accounts), so amounts moved between balances can't exceed that, even for
+// contract balances which are stored as `i128`. Allowances are not limited, so
+// that an effectively unlimited amount can be approved. Only checked from
+// `CLASSIC_BALANCE_RANGE_MIN_PROTOCOL` on.
+fn check_balance_amount(e: &Host, amount: i128) -> Result<(), HostError> {
+    check_nonnegative_amount(e, amount)?;
+    if amount > i64::MAX as i128
+        && e.get_ledger_protocol_version()? >= CLASSIC_BALANCE_RANGE_MIN_PROTOCOL
+    {
+        Err(err!(
+            e,
+            ContractError::OverflowError,
+            "amount exceeds the maximum classic balance",
+            amount
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn read_transfer_entry(e: &Host, transfers: &Vec, i: u32) -> Result<(Address, i128), HostError> {
+    let entry: Vec = transfers.get(i)?;
+    if entry.len()? != 2 {
+        return Err(err!(
+            e,
+            (ScErrorType::Value, ScErrorCode::UnexpectedSize),
+            "transfer entry must be a (to, amount) pair",
+            i
+        ));
+    }
+    let to: Address = entry.get(0)?;
+    let amount: i128 = entry.get(1)?;
+    check_balance_amount(e, amount)?;
+    Ok((to, amount))
+}
+
 fn check_non_native(e: &Host) -> Result<(), HostError> {
     match read_asset_info(e)? {
         AssetInfo::Native => Err(e.error(
@@ -190,6 +300,15 @@ impl TokenTrait for Token {
         read_allowance(e, from, spender)
     }
 
+    fn allowance_data(e: &Host, from: Address, spender: Address) -> Result<(i128, u32), HostError> {
+        let _span = tracy_span!("native token allowance_data");
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+        read_allowance_data(e, from, spender)
+    }
+
     // Metering: covered by components
     fn approve(
         e: &Host,
@@ -250,7 +369,7 @@ impl TokenTrait for Token {
     // Metering: covered by components
     fn transfer(e: &Host, from: Address, to: Address, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("native token transfer");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         from.require_auth()?;
 
         e.bump_current_contract_instance_and_code(
@@ -264,6 +383,41 @@ impl TokenTrait for Token {
         Ok(())
     }
 
+    // Metering: covered by components
+    fn transfer_many(e: &Host, from: Address, transfers: Vec) -> Result<(), HostError> {
+        let _span = tracy_span!("native token transfer_many");
+        from.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        // Validate every entry and debit the sender once for the total, so
+        // that a batch either fully succeeds or fails before any recipient is
+        // credited.
+        let len = transfers.len()?;
+        let mut total: i128 = 0;
+        for i in 0..len {
+            let (_, amount) = read_transfer_entry(e, &transfers, i)?;
+            total = total.checked_add(amount).ok_or_else(|| {
+                e.error(
+                    ContractError::OverflowError.into(),
+                    "total transfer amount overflows",
+                    &[],
+                )
+            })?;
+        }
+        spend_balance(e, from.metered_clone(e)?, total)?;
+
+        for i in 0..len {
+            let (to, amount) = read_transfer_entry(e, &transfers, i)?;
+            receive_balance(e, to.metered_clone(e)?, amount)?;
+            event::transfer(e, from.metered_clone(e)?, to, amount)?;
+        }
+        Ok(())
+    }
+
     // Metering: covered by components
     fn transfer_from(
         e: &Host,
@@ -273,7 +427,7 @@ impl TokenTrait for Token {
         amount: i128,
     ) -> Result<(), HostError> {
         let _span = tracy_span!("native token transfer_from");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         spender.require_auth()?;
 
         e.bump_current_contract_instance_and_code(
@@ -291,7 +445,7 @@ impl TokenTrait for Token {
     // Metering: covered by components
     fn burn(e: &Host, from: Address, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("native token burn");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         check_non_native(e)?;
         from.require_auth()?;
 
@@ -301,6 +455,7 @@ impl TokenTrait for Token {
         )?;
 
         spend_balance(e, from.metered_clone(e)?, amount)?;
+        decrease_supply(e, amount)?;
         event::burn(e, from, amount)?;
         Ok(())
     }
@@ -308,7 +463,7 @@ impl TokenTrait for Token {
     // Metering: covered by components
     fn burn_from(e: &Host, spender: Address, from: Address, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("native token burn_from");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         check_non_native(e)?;
         spender.require_auth()?;
 
@@ -319,14 +474,53 @@ impl TokenTrait for Token {
 
         spend_allowance(e, from.metered_clone(e)?, spender, amount)?;
         spend_balance(e, from.metered_clone(e)?, amount)?;
+        decrease_supply(e, amount)?;
         event::burn(e, from, amount)?;
         Ok(())
     }
 
+    // Metering: covered by components
+    fn burn_from_many(e: &Host, spender: Address, burns: Vec) -> Result<(), HostError> {
+        let _span = tracy_span!("native token burn_from_many");
+        check_non_native(e)?;
+        spender.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        // As with transfer_many, validate every entry first. The allowances
+        // and balances are then spent entry by entry, so that several entries
+        // for the same `from` add up against its allowance; if any of them
+        // fails, the whole call is rolled back.
+        let len = burns.len()?;
+        let mut total: i128 = 0;
+        for i in 0..len {
+            let (_, amount) = read_transfer_entry(e, &burns, i)?;
+            total = total.checked_add(amount).ok_or_else(|| {
+                e.error(
+                    ContractError::OverflowError.into(),
+                    "total burn amount overflows",
+                    &[],
+                )
+            })?;
+        }
+
+        for i in 0..len {
+            let (from, amount) = read_transfer_entry(e, &burns, i)?;
+            spend_allowance(e, from.metered_clone(e)?, spender.metered_clone(e)?, amount)?;
+            spend_balance(e, from.metered_clone(e)?, amount)?;
+            event::burn(e, from, amount)?;
+        }
+        decrease_supply(e, total)?;
+        Ok(())
+    }
+
     // Metering: covered by components
     fn clawback(e: &Host, from: Address, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("native token clawback");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         check_clawbackable(e, from.metered_clone(e)?)?;
         let admin = read_administrator(e)?;
         admin.require_auth()?;
@@ -337,6 +531,7 @@ impl TokenTrait for Token {
         )?;
 
         spend_balance_no_authorization_check(e, from.metered_clone(e)?, amount)?;
+        decrease_supply(e, amount)?;
         event::clawback(e, admin, from, amount)?;
         Ok(())
     }
@@ -360,7 +555,7 @@ impl TokenTrait for Token {
     // Metering: covered by components
     fn mint(e: &Host, to: Address, amount: i128) -> Result<(), HostError> {
         let _span = tracy_span!("native token mint");
-        check_nonnegative_amount(e, amount)?;
+        check_balance_amount(e, amount)?;
         let admin = read_administrator(e)?;
         admin.require_auth()?;
 
@@ -369,11 +564,75 @@ impl TokenTrait for Token {
             INSTANCE_BUMP_AMOUNT.into(),
         )?;
 
+        increase_supply(e, amount)?;
         receive_balance(e, to.metered_clone(e)?, amount)?;
         event::mint(e, admin, to, amount)?;
         Ok(())
     }
 
+    // Metering: covered by components
+    fn mint_many(e: &Host, mints: Vec) -> Result<(), HostError> {
+        let _span = tracy_span!("native token mint_many");
+        let admin = read_administrator(e)?;
+        admin.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        // As with transfer_many, validate every entry and check the supply
+        // for the total first, so that a batch either fully succeeds or fails
+        // before any recipient is credited.
+        let len = mints.len()?;
+        let mut total: i128 = 0;
+        for i in 0..len {
+            let (_, amount) = read_transfer_entry(e, &mints, i)?;
+            total = total.checked_add(amount).ok_or_else(|| {
+                e.error(
+                    ContractError::OverflowError.into(),
+                    "total mint amount overflows",
+                    &[],
+                )
+            })?;
+        }
+        increase_supply(e, total)?;
+
+        for i in 0..len {
+            let (to, amount) = read_transfer_entry(e, &mints, i)?;
+            receive_balance(e, to.metered_clone(e)?, amount)?;
+            event::mint(e, admin.metered_clone(e)?, to, amount)?;
+        }
+        Ok(())
+    }
+
+    fn total_supply(e: &Host) -> Result<i128, HostError> {
+        let _span = tracy_span!("native token total_supply");
+        read_total_supply(e)
+    }
+
+    // Metering: covered by components
+    fn set_max_supply(e: &Host, max_supply: i128) -> Result<(), HostError> {
+        let _span = tracy_span!("native token set_max_supply");
+        check_nonnegative_amount(e, max_supply)?;
+        let admin = read_administrator(e)?;
+        admin.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        write_max_supply(e, max_supply)?;
+        event::set_max_supply(e, admin, max_supply)?;
+        Ok(())
+    }
+
+    fn max_supply(e: &Host) -> Result<Option<i128>, HostError> {
+        let _span = tracy_span!("native token max_supply");
+        read_max_supply(e)
+    }
+
     // Metering: covered by components
     fn set_admin(e: &Host, new_admin: Address) -> Result<(), HostError> {
         let _span = tracy_span!("native token set_admin");
@@ -386,15 +645,74 @@ impl TokenTrait for Token {
         )?;
 
         write_administrator(e, new_admin.metered_clone(e)?)?;
+        remove_pending_administrator(e)?;
         event::set_admin(e, admin, new_admin)?;
         Ok(())
     }
 
+    // Metering: covered by components
+    fn propose_admin(e: &Host, new_admin: Address) -> Result<(), HostError> {
+        let _span = tracy_span!("native token propose_admin");
+        let admin = read_administrator(e)?;
+        admin.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        write_pending_administrator(e, new_admin.metered_clone(e)?)?;
+        event::propose_admin(e, admin, new_admin)?;
+        Ok(())
+    }
+
+    // Metering: covered by components
+    fn accept_admin(e: &Host) -> Result<(), HostError> {
+        let _span = tracy_span!("native token accept_admin");
+        let Some(new_admin) = read_pending_administrator(e)? else {
+            return Err(e.error(
+                ContractError::OperationNotSupportedError.into(),
+                "no admin has been proposed",
+                &[],
+            ));
+        };
+        new_admin.require_auth()?;
+        let admin = read_administrator(e)?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        write_administrator(e, new_admin.metered_clone(e)?)?;
+        remove_pending_administrator(e)?;
+        event::accept_admin(e, admin, new_admin)?;
+        Ok(())
+    }
+
     fn admin(e: &Host) -> Result<Address, HostError> {
         let _span = tracy_span!("native token admin");
         read_administrator(e)
     }
 
+    // Metering: covered by components
+    fn set_metadata(e: &Host, name: String, symbol: String) -> Result<(), HostError> {
+        let _span = tracy_span!("native token set_metadata");
+        let admin = read_administrator(e)?;
+        admin.require_auth()?;
+
+        e.bump_current_contract_instance_and_code(
+            INSTANCE_LIFETIME_THRESHOLD.into(),
+            INSTANCE_BUMP_AMOUNT.into(),
+        )?;
+
+        // Publish the event before the update, so that it is tagged with the
+        // name the token was known by until now.
+        event::set_metadata(e, admin, name.clone(), symbol.clone())?;
+        write_name_and_symbol(e, name, symbol)?;
+        Ok(())
+    }
+
     fn decimals(_e: &Host) -> Result<u32, HostError> {
         let _span = tracy_span!("native token decimals");
         // no need to load metadata since this is fixed for all SAC tokens
@@ -1,6 +1,9 @@
 use crate::native_contract::base_types::Vec;
 use crate::HostError;
-use crate::{host::Host, native_contract::base_types::Address};
+use crate::{
+    host::Host,
+    native_contract::base_types::{Address, String},
+};
 use soroban_env_common::{Env, Symbol, TryFromVal, TryIntoVal};
 
 use super::metadata::read_name;
@@ -89,6 +92,51 @@ pub(crate) fn set_admin(e: &Host, admin: Address, new_admin: Address) -> Result<
     Ok(())
 }
 
+pub(crate) fn propose_admin(e: &Host, admin: Address, new_admin: Address) -> Result<(), HostError> {
+    let mut topics = Vec::new(e)?;
+    topics.push(&Symbol::try_from_val(e, &"propose_admin")?)?;
+    topics.push(&admin)?;
+    topics.push(&read_name(e)?)?;
+    e.contract_event(topics.into(), new_admin.try_into_val(e)?)?;
+    Ok(())
+}
+
+pub(crate) fn accept_admin(e: &Host, admin: Address, new_admin: Address) -> Result<(), HostError> {
+    let mut topics = Vec::new(e)?;
+    topics.push(&Symbol::try_from_val(e, &"accept_admin")?)?;
+    topics.push(&admin)?;
+    topics.push(&read_name(e)?)?;
+    e.contract_event(topics.into(), new_admin.try_into_val(e)?)?;
+    Ok(())
+}
+
+pub(crate) fn set_max_supply(e: &Host, admin: Address, max_supply: i128) -> Result<(), HostError> {
+    let mut topics = Vec::new(e)?;
+    topics.push(&Symbol::try_from_val(e, &"set_max_supply")?)?;
+    topics.push(&admin)?;
+    topics.push(&read_name(e)?)?;
+    e.contract_event(topics.into(), max_supply.try_into_val(e)?)?;
+    Ok(())
+}
+
+pub(crate) fn set_metadata(
+    e: &Host,
+    admin: Address,
+    name: String,
+    symbol: String,
+) -> Result<(), HostError> {
+    let mut topics = Vec::new(e)?;
+    topics.push(&Symbol::try_from_val(e, &"set_metadata")?)?;
+    topics.push(&admin)?;
+    topics.push(&read_name(e)?)?;
+
+    let mut data = Vec::new(e)?;
+    data.push(&name)?;
+    data.push(&symbol)?;
+    e.contract_event(topics.into(), data.into())?;
+    Ok(())
+}
+
 pub(crate) fn burn(e: &Host, from: Address, amount: i128) -> Result<(), HostError> {
     let mut topics = Vec::new(e)?;
     topics.push(&Symbol::try_from_val(e, &"burn")?)?;
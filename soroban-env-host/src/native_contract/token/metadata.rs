@@ -170,10 +170,16 @@ pub fn set_metadata(e: &Host) -> Result<(), HostError> {
         }
     };
 
+    write_name_and_symbol(e, name_and_symbol.0, name_and_symbol.1)
+}
+
+/// Overwrites the token's name and symbol. Decimals are fixed for all SAC
+/// tokens and can't be changed.
+pub fn write_name_and_symbol(e: &Host, name: String, symbol: String) -> Result<(), HostError> {
     let metadata = TokenMetadata {
         decimal: DECIMAL,
-        name: name_and_symbol.0,
-        symbol: name_and_symbol.1,
+        name,
+        symbol,
     };
 
     let key = SymbolSmall::try_from_str(METADATA_KEY)?;
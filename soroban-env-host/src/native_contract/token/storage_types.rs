@@ -40,4 +40,7 @@ pub enum DataKey {
 pub enum InstanceDataKey {
     Admin,
     AssetInfo,
+    PendingAdmin,
+    MaxSupply,
+    TotalSupply,
 }
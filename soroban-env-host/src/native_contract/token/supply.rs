@@ -0,0 +1,112 @@
+use crate::host::Host;
+use crate::native_contract::contract_error::ContractError;
+use crate::{err, HostError};
+use soroban_env_common::{Env, StorageType, TryIntoVal};
+
+use super::storage_types::InstanceDataKey;
+
+// The total supply only accounts for what was minted, burned or clawed back
+// through this contract: balances of the underlying classic asset that were
+// issued on the classic side are not part of it.
+//
+// It is also only tracked while a max supply is set. Tracking it means
+// writing the contract instance on every mint and burn, which would make the
+// instance read-write in the footprint of every such transaction and have
+// them all contend on it; contracts that never set a max supply are left
+// as they were.
+
+// Metering: covered by components
+fn has_instance_data(e: &Host, key: &InstanceDataKey) -> Result<bool, HostError> {
+    let rv = e.has_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+    Ok(rv.try_into()?)
+}
+
+// Metering: covered by components
+pub fn read_total_supply(e: &Host) -> Result<i128, HostError> {
+    let key = InstanceDataKey::TotalSupply;
+    if has_instance_data(e, &key)? {
+        let rv = e.get_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+        rv.try_into_val(e)
+    } else {
+        Ok(0)
+    }
+}
+
+// Metering: covered by components
+fn write_total_supply(e: &Host, amount: i128) -> Result<(), HostError> {
+    let key = InstanceDataKey::TotalSupply;
+    e.put_contract_data(
+        key.try_into_val(e)?,
+        amount.try_into_val(e)?,
+        StorageType::Instance,
+    )?;
+    Ok(())
+}
+
+// Metering: covered by components
+pub fn read_max_supply(e: &Host) -> Result<Option<i128>, HostError> {
+    let key = InstanceDataKey::MaxSupply;
+    if has_instance_data(e, &key)? {
+        let rv = e.get_contract_data(key.try_into_val(e)?, StorageType::Instance)?;
+        Ok(Some(rv.try_into_val(e)?))
+    } else {
+        Ok(None)
+    }
+}
+
+// Metering: covered by components
+pub fn write_max_supply(e: &Host, max_supply: i128) -> Result<(), HostError> {
+    let total_supply = read_total_supply(e)?;
+    if max_supply < total_supply {
+        return Err(err!(
+            e,
+            ContractError::MaxSupplyExceededError,
+            "max supply is below the total supply",
+            max_supply,
+            total_supply
+        ));
+    }
+    let key = InstanceDataKey::MaxSupply;
+    e.put_contract_data(
+        key.try_into_val(e)?,
+        max_supply.try_into_val(e)?,
+        StorageType::Instance,
+    )?;
+    Ok(())
+}
+
+// Metering: covered by components
+pub fn increase_supply(e: &Host, amount: i128) -> Result<(), HostError> {
+    let Some(max_supply) = read_max_supply(e)? else {
+        return Ok(());
+    };
+    let total_supply = read_total_supply(e)?.checked_add(amount).ok_or_else(|| {
+        err!(
+            e,
+            ContractError::OverflowError,
+            "total supply overflows",
+            amount
+        )
+    })?;
+    if total_supply > max_supply {
+        return Err(err!(
+            e,
+            ContractError::MaxSupplyExceededError,
+            "minting would exceed the max supply",
+            amount,
+            max_supply
+        ));
+    }
+    write_total_supply(e, total_supply)
+}
+
+// Metering: covered by components
+pub fn decrease_supply(e: &Host, amount: i128) -> Result<(), HostError> {
+    if read_max_supply(e)?.is_none() {
+        return Ok(());
+    }
+    // Burning classic-issued balances can take the supply tracked here below
+    // zero, which is clamped instead.
+    let total_supply = read_total_supply(e)?.saturating_sub(amount).max(0);
+    write_total_supply(e, total_supply)
+}
@@ -47,6 +47,21 @@ impl<'a> TestToken<'a> {
             .try_into_val(self.host)?)
     }
 
+    pub(crate) fn allowance_data(
+        &self,
+        from: Address,
+        spender: Address,
+    ) -> Result<(i128, u32), HostError> {
+        Ok(self
+            .host
+            .call(
+                self.address.clone().into(),
+                Symbol::try_from_val(self.host, &"allowance_data")?,
+                host_vec![self.host, from, spender].into(),
+            )?
+            .try_into_val(self.host)?)
+    }
+
     fn call_with_single_signer(
         &self,
         signer: &TestSigner,
@@ -136,6 +151,22 @@ impl<'a> TestToken<'a> {
         )
     }
 
+    pub(crate) fn transfer_many(
+        &self,
+        from: &TestSigner,
+        transfers: &[(Address, i128)],
+    ) -> Result<(), HostError> {
+        let mut entries = HostVec::new(self.host)?;
+        for (to, amount) in transfers {
+            entries.push(&host_vec![self.host, to.clone(), *amount])?;
+        }
+        self.call_with_single_signer(
+            from,
+            "transfer_many",
+            host_vec![self.host, from.address(self.host), entries],
+        )
+    }
+
     pub(crate) fn transfer_from(
         &self,
         spender: &TestSigner,
@@ -171,6 +202,22 @@ impl<'a> TestToken<'a> {
         )
     }
 
+    pub(crate) fn burn_from_many(
+        &self,
+        spender: &TestSigner,
+        burns: &[(Address, i128)],
+    ) -> Result<(), HostError> {
+        let mut entries = HostVec::new(self.host)?;
+        for (from, amount) in burns {
+            entries.push(&host_vec![self.host, from.clone(), *amount])?;
+        }
+        self.call_with_single_signer(
+            spender,
+            "burn_from_many",
+            host_vec![self.host, spender.address(self.host), entries],
+        )
+    }
+
     pub(crate) fn set_authorized(
         &self,
         admin: &TestSigner,
@@ -193,6 +240,48 @@ impl<'a> TestToken<'a> {
         self.call_with_single_signer(admin, "mint", host_vec![self.host, to, amount])
     }
 
+    pub(crate) fn mint_many(
+        &self,
+        admin: &TestSigner,
+        mints: &[(Address, i128)],
+    ) -> Result<(), HostError> {
+        let mut entries = HostVec::new(self.host)?;
+        for (to, amount) in mints {
+            entries.push(&host_vec![self.host, to.clone(), *amount])?;
+        }
+        self.call_with_single_signer(admin, "mint_many", host_vec![self.host, entries])
+    }
+
+    pub(crate) fn total_supply(&self) -> Result<i128, HostError> {
+        Ok(self
+            .host
+            .call(
+                self.address.clone().into(),
+                Symbol::try_from_val(self.host, &"total_supply")?,
+                host_vec![self.host].into(),
+            )?
+            .try_into_val(self.host)?)
+    }
+
+    pub(crate) fn set_max_supply(
+        &self,
+        admin: &TestSigner,
+        max_supply: i128,
+    ) -> Result<(), HostError> {
+        self.call_with_single_signer(admin, "set_max_supply", host_vec![self.host, max_supply])
+    }
+
+    pub(crate) fn max_supply(&self) -> Result<Option<i128>, HostError> {
+        Ok(self
+            .host
+            .call(
+                self.address.clone().into(),
+                Symbol::try_from_val(self.host, &"max_supply")?,
+                host_vec![self.host].into(),
+            )?
+            .try_into_val(self.host)?)
+    }
+
     pub(crate) fn clawback(
         &self,
         admin: &TestSigner,
@@ -210,6 +299,18 @@ impl<'a> TestToken<'a> {
         self.call_with_single_signer(admin, "set_admin", host_vec![self.host, new_admin])
     }
 
+    pub(crate) fn propose_admin(
+        &self,
+        admin: &TestSigner,
+        new_admin: Address,
+    ) -> Result<(), HostError> {
+        self.call_with_single_signer(admin, "propose_admin", host_vec![self.host, new_admin])
+    }
+
+    pub(crate) fn accept_admin(&self, new_admin: &TestSigner) -> Result<(), HostError> {
+        self.call_with_single_signer(new_admin, "accept_admin", host_vec![self.host])
+    }
+
     pub(crate) fn admin(&self) -> Result<Address, HostError> {
         self.host
             .call(
@@ -220,6 +321,15 @@ impl<'a> TestToken<'a> {
             .try_into_val(self.host)
     }
 
+    pub(crate) fn set_metadata(
+        &self,
+        admin: &TestSigner,
+        name: String,
+        symbol: String,
+    ) -> Result<(), HostError> {
+        self.call_with_single_signer(admin, "set_metadata", host_vec![self.host, name, symbol])
+    }
+
     pub(crate) fn decimals(&self) -> Result<u32, HostError> {
         Ok(self
             .host
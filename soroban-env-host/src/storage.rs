@@ -15,10 +15,16 @@ use soroban_env_common::{Env, Val};
 use crate::budget::Budget;
 use crate::xdr::{LedgerEntry, LedgerKey};
 use crate::Host;
-use crate::{host::metered_map::MeteredOrdMap, HostError};
+use crate::{
+    host::{metered_map::MeteredOrdMap, metered_xdr::metered_write_xdr},
+    HostError,
+};
 
 pub type FootprintMap = MeteredOrdMap<Rc<LedgerKey>, AccessType, Budget>;
 pub type StorageMap = MeteredOrdMap<Rc<LedgerKey>, Option<(Rc<LedgerEntry>, Option<u32>)>, Budget>;
+// The keys accessed while the budget has ledger resource limits, mapped to the
+// size of what was last written to them, or `None` if they've only been read.
+type LedgerAccessMap = MeteredOrdMap<Rc<LedgerKey>, Option<u32>, Budget>;
 
 /// The in-memory instance storage of the current running contract. Initially
 /// contains entries from the `ScMap` of the corresponding `ScContractInstance`
@@ -51,8 +57,13 @@ pub enum AccessType {
     ReadWrite,
 }
 
-/// A helper type used by [FootprintMode::Recording] to provide access
-/// to a stable read-snapshot of a ledger.
+/// A helper type used by [FootprintMode::Recording] and
+/// [FootprintMode::EnforcingLazy] to provide access to a stable read-snapshot
+/// of a ledger.
+///
+/// Implementations may be backed by anything that can answer point lookups,
+/// such as a database, in which case entries are only loaded when the host
+/// first accesses them.
 pub trait SnapshotSource {
     // Returns the ledger entry for the key and its expiration.
     fn get(&self, key: &Rc<LedgerKey>) -> Result<(Rc<LedgerEntry>, Option<u32>), HostError>;
@@ -128,6 +139,11 @@ pub enum FootprintMode {
     Recording(Rc<dyn SnapshotSource>),
     #[default]
     Enforcing,
+    /// Like [FootprintMode::Enforcing], but entries are not required to be
+    /// present in the storage map up front: the first in-footprint read of a
+    /// [LedgerKey] that is not yet in the map reads through to the
+    /// [SnapshotSource].
+    EnforcingLazy(Rc<dyn SnapshotSource>),
 }
 
 /// A special-purpose map from [LedgerKey]s to [LedgerEntry]s. Represents a
@@ -136,20 +152,53 @@ pub enum FootprintMode {
 /// through the use of IO [Footprint]s.
 ///
 /// Specifically: access to each [LedgerKey] is mediated by the [Footprint],
-/// which may be in either [FootprintMode::Recording] or
-/// [FootprintMode::Enforcing] mode.
+/// which may be in either [FootprintMode::Recording],
+/// [FootprintMode::Enforcing] or [FootprintMode::EnforcingLazy] mode.
 ///
 /// [FootprintMode::Recording] mode is used to calculate [Footprint]s during
 /// "preflight" execution of a contract. Once calculated, a recorded [Footprint]
 /// can be provided to "real" execution, which always runs in
-/// [FootprintMode::Enforcing] mode and enforces partitioned access.
+/// [FootprintMode::Enforcing] (or [FootprintMode::EnforcingLazy]) mode and
+/// enforces partitioned access.
 #[derive(Clone, Default)]
 pub struct Storage {
     pub footprint: Footprint,
     pub mode: FootprintMode,
     pub map: StorageMap,
+    // In [FootprintMode::Enforcing], the entry each written key had when the
+    // storage was constructed, recorded as the key is first written, to tell
+    // the ledger changes apart from the initial state, see
+    // [Host::get_ledger_changes](crate::Host::get_ledger_changes). `None`
+    // unless enabled with [Storage::record_initial_entries]. The other modes
+    // get the initial state from their [SnapshotSource].
+    initial_entries: Option<StorageMap>,
+    // For charging the budget's ledger resource limits, see
+    // [Budget::set_ledger_resource_limits].
+    ledger_access: LedgerAccessMap,
+}
+
+/// The change made to a single ledger entry in a [Storage], as returned by
+/// [Host::get_ledger_changes](crate::Host::get_ledger_changes).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntryDiff {
+    pub key: Rc<LedgerKey>,
+    /// Whether the entry is read-only, as defined by the footprint.
+    pub read_only: bool,
+    /// The entry and its live-until ledger before any changes were made, or
+    /// `None` if the entry did not exist.
+    pub old_entry: Option<EntryWithLiveUntil>,
+    /// The entry and its live-until ledger after all the changes, or `None`
+    /// if the entry does not exist (anymore).
+    pub new_entry: Option<EntryWithLiveUntil>,
 }
 
+pub type EntryWithLiveUntil = (Rc<LedgerEntry>, Option<u32>);
+
+/// A callback invoked by the host when a top-level invocation succeeds, with
+/// the storage footprint and the entries the invocation left changed. See
+/// [Host::set_storage_commit_hook](crate::Host::set_storage_commit_hook).
+pub type StorageCommitHook = Rc<dyn Fn(&Footprint, &[LedgerEntryDiff]) -> Result<(), HostError>>;
+
 // Notes on metering: all storage operations: `put`, `get`, `del`, `has` are
 // covered by the underneath `MeteredOrdMap` and the `Footprint`'s own map.
 impl Storage {
@@ -160,7 +209,25 @@ impl Storage {
         Self {
             mode: FootprintMode::Enforcing,
             footprint,
+            initial_entries: None,
             map,
+            ledger_access: Default::default(),
+        }
+    }
+
+    /// Constructs a new [Storage] in [FootprintMode::EnforcingLazy] using a
+    /// given [Footprint] and a [SnapshotSource] that entries are loaded from
+    /// on first access. Only keys declared in the [Footprint] are ever loaded.
+    pub fn with_enforcing_footprint_and_source(
+        footprint: Footprint,
+        src: Rc<dyn SnapshotSource>,
+    ) -> Self {
+        Self {
+            mode: FootprintMode::EnforcingLazy(src),
+            footprint,
+            map: Default::default(),
+            initial_entries: None,
+            ledger_access: Default::default(),
         }
     }
 
@@ -171,7 +238,89 @@ impl Storage {
             mode: FootprintMode::Recording(src),
             footprint: Footprint::default(),
             map: Default::default(),
+            initial_entries: None,
+            ledger_access: Default::default(),
+        }
+    }
+
+    /// Makes a storage in [FootprintMode::Enforcing] keep the initial entry
+    /// of every key as it is first written, which [Storage::get_ledger_changes]
+    /// needs in that mode. This is off by default, as the network doesn't
+    /// need it; the extra work it does on writes is not metered. It must be
+    /// enabled before anything is written.
+    pub fn record_initial_entries(&mut self) {
+        self.initial_entries.get_or_insert_with(Default::default);
+    }
+
+    /// Returns the change made to every entry loaded into the storage so far,
+    /// in key order. Entries that have only been read are included too, with
+    /// the same old and new values.
+    ///
+    /// In [FootprintMode::Enforcing] this fails unless
+    /// [Storage::record_initial_entries] was enabled.
+    pub fn get_ledger_changes(&self, budget: &Budget) -> Result<Vec<LedgerEntryDiff>, HostError> {
+        if matches!(self.mode, FootprintMode::Enforcing) && self.initial_entries.is_none() {
+            return Err((ScErrorType::Storage, ScErrorCode::InvalidAction).into());
+        }
+        self.ledger_changes(None, budget)
+    }
+
+    // Like `get_ledger_changes`, but the entries that are in `pre` are diffed
+    // against their state there rather than their initial state.
+    pub(crate) fn get_ledger_changes_since(
+        &self,
+        pre: &StorageMap,
+        budget: &Budget,
+    ) -> Result<Vec<LedgerEntryDiff>, HostError> {
+        self.ledger_changes(Some(pre), budget)
+    }
+
+    fn ledger_changes(
+        &self,
+        pre: Option<&StorageMap>,
+        budget: &Budget,
+    ) -> Result<Vec<LedgerEntryDiff>, HostError> {
+        // Allocation is not metered here, as with the other diffing of
+        // storage for embedders: the entries themselves are shared.
+        let mut changes = Vec::with_capacity(self.map.len());
+        for (key, new_entry) in self.map.iter(budget)? {
+            let pre_entry = match pre {
+                Some(pre) => pre.get::<Rc<LedgerKey>>(key, budget)?,
+                None => None,
+            };
+            let old_entry = match (pre_entry, &self.mode) {
+                (Some(pre_entry), _) => pre_entry.clone(),
+                (None, FootprintMode::Recording(src) | FootprintMode::EnforcingLazy(src)) => {
+                    if src.has(key)? {
+                        Some(src.get(key)?)
+                    } else {
+                        None
+                    }
+                }
+                // Keys that haven't been written still have their initial
+                // entry.
+                (None, FootprintMode::Enforcing) => match &self.initial_entries {
+                    Some(initial_entries) => initial_entries
+                        .get::<Rc<LedgerKey>>(key, budget)?
+                        .unwrap_or(new_entry)
+                        .clone(),
+                    // Only reached with a `pre` map, which has every key of
+                    // the initial map, so the key was added since.
+                    None => None,
+                },
+            };
+            let read_only = matches!(
+                self.footprint.0.get::<Rc<LedgerKey>>(key, budget)?,
+                Some(AccessType::ReadOnly)
+            );
+            changes.push(LedgerEntryDiff {
+                key: Rc::clone(key),
+                read_only,
+                old_entry,
+                new_entry: new_entry.clone(),
+            });
         }
+        Ok(changes)
     }
 
     /// Attempts to retrieve the [LedgerEntry] associated with a given
@@ -185,6 +334,10 @@ impl Storage {
     ///
     /// In [FootprintMode::Enforcing] mode, succeeds only if the read
     /// [LedgerKey] has been declared in the [Footprint].
+    ///
+    /// In [FootprintMode::EnforcingLazy] mode, additionally reads through to
+    /// the underlying [SnapshotSource] if the [LedgerKey] has not yet been
+    /// loaded.
     pub fn get(
         &mut self,
         key: &Rc<LedgerKey>,
@@ -230,7 +383,14 @@ impl Storage {
     ///
     /// In [FootprintMode::Enforcing] mode, succeeds only if the read
     /// [LedgerKey] has been declared in the [Footprint].
-    pub(crate) fn get_with_expiration(
+    ///
+    /// In [FootprintMode::EnforcingLazy] mode, additionally reads through to
+    /// the underlying [SnapshotSource] if the [LedgerKey] has not yet been
+    /// loaded.
+    ///
+    /// Embedders can use this to read back the expiration ledgers that
+    /// contracts requested via [Storage::bump].
+    pub fn get_with_expiration(
         &mut self,
         key: &Rc<LedgerKey>,
         budget: &Budget,
@@ -254,10 +414,12 @@ impl Storage {
             FootprintMode::Recording(_) => {
                 self.footprint.record_access(key, ty, budget)?;
             }
-            FootprintMode::Enforcing => {
+            FootprintMode::Enforcing | FootprintMode::EnforcingLazy(_) => {
                 self.footprint.enforce_access(key, ty, budget)?;
             }
         };
+        self.charge_ledger_write(key, val.map(|(e, _)| e), budget)?;
+        self.record_initial_entry(key, budget)?;
         self.map = self.map.insert(
             Rc::clone(key),
             val.map(|(e, expiration)| (Rc::clone(e), expiration)),
@@ -309,6 +471,9 @@ impl Storage {
     ///
     /// In [FootprintMode::Enforcing] mode, succeeds only if the access has been
     /// declared in the [Footprint].
+    ///
+    /// In [FootprintMode::EnforcingLazy] mode, additionally reads-through to
+    /// the underlying [SnapshotSource] if the key has not yet been loaded.
     pub fn has(&mut self, key: &Rc<LedgerKey>, budget: &Budget) -> Result<bool, HostError> {
         let _span = tracy_span!("storage has");
         self.prepare_read_only_access(key, budget)?;
@@ -388,6 +553,7 @@ impl Storage {
         if new_expiration > old_expiration
             && old_expiration.saturating_sub(ledger_seq) <= low_expiration_watermark
         {
+            self.record_initial_entry(&key, host.budget_ref())?;
             self.map = self.map.insert(
                 key,
                 Some((entry.clone(), Some(new_expiration))),
@@ -408,19 +574,136 @@ impl Storage {
                 self.footprint.record_access(key, ty, budget)?;
                 // In recording mode we treat the map as a cache
                 // that misses read-through to the underlying src.
-                if !self.map.contains_key::<Rc<LedgerKey>>(key, budget)? {
-                    let value = if src.has(&key)? {
-                        Some(src.get(key)?)
-                    } else {
-                        None
-                    };
-                    self.map = self.map.insert(key.clone(), value, budget)?;
-                }
+                let src = Rc::clone(src);
+                self.load_from_source(key, src.as_ref(), budget)?;
             }
             FootprintMode::Enforcing => {
                 self.footprint.enforce_access(key, ty, budget)?;
             }
+            FootprintMode::EnforcingLazy(ref src) => {
+                self.footprint.enforce_access(key, ty, budget)?;
+                // Same as recording mode, except that only keys that passed
+                // the footprint check are ever loaded.
+                let src = Rc::clone(src);
+                self.load_from_source(key, src.as_ref(), budget)?;
+            }
+        };
+        self.charge_ledger_read(key, budget)
+    }
+
+    // Charges the first access to `key` against the budget's ledger resource
+    // limits, if any, as a read of the entry stored for it at that point. So
+    // keys written without having been read before are charged as reads of
+    // nothing in [FootprintMode::Recording], as their entries aren't loaded.
+    fn charge_ledger_read(
+        &mut self,
+        key: &Rc<LedgerKey>,
+        budget: &Budget,
+    ) -> Result<(), HostError> {
+        if budget.get_ledger_resource_limits()?.is_none()
+            || self
+                .ledger_access
+                .contains_key::<Rc<LedgerKey>>(key, budget)?
+        {
+            return Ok(());
+        }
+        let bytes = match self.map.get::<Rc<LedgerKey>>(key, budget)? {
+            Some(Some((entry, _))) => ledger_entry_size(entry, budget)?,
+            None | Some(None) => 0,
+        };
+        budget.charge_ledger_read(bytes)?;
+        self.ledger_access = self.ledger_access.insert(Rc::clone(key), None, budget)?;
+        Ok(())
+    }
+
+    // Charges writing `entry` (or deleting the entry, if `None`) to `key`
+    // against the budget's ledger resource limits, if any. Only the last write
+    // to a key counts towards the bytes written.
+    fn charge_ledger_write(
+        &mut self,
+        key: &Rc<LedgerKey>,
+        entry: Option<&Rc<LedgerEntry>>,
+        budget: &Budget,
+    ) -> Result<(), HostError> {
+        if budget.get_ledger_resource_limits()?.is_none() {
+            return Ok(());
+        }
+        self.charge_ledger_read(key, budget)?;
+        let written = self
+            .ledger_access
+            .get::<Rc<LedgerKey>>(key, budget)?
+            .copied()
+            .flatten();
+        let bytes = match entry {
+            Some(entry) => ledger_entry_size(entry, budget)?,
+            None => 0,
+        };
+        budget.charge_ledger_write(written.is_none(), written.unwrap_or(0), bytes)?;
+        self.ledger_access = self
+            .ledger_access
+            .insert(Rc::clone(key), Some(bytes), budget)?;
+        Ok(())
+    }
+
+    // In [FootprintMode::Enforcing], records the entry `key` has before it is
+    // first written, if enabled, see `initial_entries`. This is not metered,
+    // so that enabling it doesn't change what a transaction is charged.
+    fn record_initial_entry(
+        &mut self,
+        key: &Rc<LedgerKey>,
+        budget: &Budget,
+    ) -> Result<(), HostError> {
+        if !matches!(self.mode, FootprintMode::Enforcing) {
+            return Ok(());
+        }
+        let Some(initial_entries) = &self.initial_entries else {
+            return Ok(());
         };
+        let updated = budget.with_free_budget(|| {
+            if initial_entries.contains_key::<Rc<LedgerKey>>(key, budget)? {
+                return Ok(None);
+            }
+            let entry = self
+                .map
+                .get::<Rc<LedgerKey>>(key, budget)?
+                .cloned()
+                .flatten();
+            Ok(Some(initial_entries.insert(
+                Rc::clone(key),
+                entry,
+                budget,
+            )?))
+        })?;
+        if let Some(updated) = updated {
+            self.initial_entries = Some(updated);
+        }
         Ok(())
     }
+
+    // Populates the map entry for `key` from `src` unless the key has already
+    // been loaded (or written). Missing entries are cached as `None`.
+    fn load_from_source(
+        &mut self,
+        key: &Rc<LedgerKey>,
+        src: &dyn SnapshotSource,
+        budget: &Budget,
+    ) -> Result<(), HostError> {
+        if !self.map.contains_key::<Rc<LedgerKey>>(key, budget)? {
+            let value = if src.has(key)? {
+                Some(src.get(key)?)
+            } else {
+                None
+            };
+            self.map = self.map.insert(key.clone(), value, budget)?;
+        }
+        Ok(())
+    }
+}
+
+// The size of a ledger entry as charged against ledger resource limits: that
+// of its XDR encoding.
+fn ledger_entry_size(entry: &LedgerEntry, budget: &Budget) -> Result<u32, HostError> {
+    let mut buf = Vec::new();
+    metered_write_xdr(budget, entry, &mut buf)?;
+    Ok(u32::try_from(buf.len()).unwrap_or(u32::MAX))
 }
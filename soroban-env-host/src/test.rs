@@ -8,13 +8,19 @@ mod bytes;
 mod complex;
 mod crypto;
 mod depth_limit;
+#[cfg(feature = "differential-testing")]
+mod differential;
+mod e2e_tests;
 mod event;
 mod hostile;
+mod interface_version;
 mod invocation;
 mod ledger;
 mod lifecycle;
 mod map;
+mod module_cache;
 mod num;
+mod observe;
 mod post_mvp;
 mod prng;
 mod storage;
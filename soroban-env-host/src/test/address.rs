@@ -71,3 +71,81 @@ fn test_contract_address_conversions() {
         .try_into_val(&host)
         .unwrap();
 }
+
+#[test]
+fn test_address_strkey_conversions() {
+    use soroban_env_common::{
+        xdr::{ScErrorCode, ScErrorType, ScString},
+        StringObject,
+    };
+
+    let host = Host::default();
+    let strkey_of = |addr: ScAddress| -> String {
+        let addr_obj = host.add_host_object(addr).unwrap();
+        let str_obj = host.address_to_strkey(addr_obj).unwrap();
+        let strkey = host
+            .visit_obj(str_obj, |s: &ScString| {
+                Ok(String::from_utf8(s.as_slice().to_vec()).unwrap())
+            })
+            .unwrap();
+        assert_eq!(strkey.len(), 56);
+        // Parsing the strkey back yields the same address.
+        let restored = host.strkey_to_address(str_obj).unwrap();
+        assert_eq!(
+            host.visit_obj(restored, |a: &ScAddress| Ok(a.clone()))
+                .unwrap(),
+            host.visit_obj(addr_obj, |a: &ScAddress| Ok(a.clone()))
+                .unwrap()
+        );
+        strkey
+    };
+
+    let account = ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32]))));
+    assert_eq!(
+        strkey_of(account),
+        stellar_strkey::ed25519::PublicKey([0; 32]).to_string()
+    );
+    let contract = ScAddress::Contract(Hash([7; 32]));
+    assert_eq!(
+        strkey_of(contract),
+        stellar_strkey::Contract([7; 32]).to_string()
+    );
+
+    let parse = |s: &str| {
+        let str_obj: StringObject =
+            host.add_host_object(ScString(s.as_bytes().to_vec().try_into().unwrap()))?;
+        host.strkey_to_address(str_obj)
+    };
+    let valid = stellar_strkey::Contract([7; 32]).to_string();
+    let mut bad_checksum = valid.clone();
+    bad_checksum.replace_range(55.., if valid.ends_with('A') { "B" } else { "A" });
+    let seed = stellar_strkey::ed25519::PrivateKey([1; 32]).to_string();
+    for invalid in [
+        "",
+        &valid[..55],
+        format!("{}A", valid).as_str(),
+        bad_checksum.as_str(),
+        valid.to_lowercase().as_str(),
+        seed.as_str(),
+    ] {
+        let err = parse(invalid).err().unwrap();
+        assert!(err.error.is_type(ScErrorType::Value));
+        assert!(err.error.is_code(ScErrorCode::InvalidInput));
+    }
+}
+
+#[test]
+fn test_address_object_scaddress_roundtrip() {
+    use soroban_env_common::{xdr::ScVal, AddressObject};
+
+    let host = Host::default();
+    for addr in [
+        ScAddress::Contract(Hash([3; 32])),
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([4; 32])))),
+    ] {
+        let val = host.to_host_val(&ScVal::Address(addr.clone())).unwrap();
+        let obj = AddressObject::try_from(val).unwrap();
+        assert_eq!(host.scaddress_from_address(obj).unwrap(), addr);
+        assert_eq!(host.from_host_val(val).unwrap(), ScVal::Address(addr));
+    }
+}
@@ -15,8 +15,10 @@ use crate::native_contract::base_types::Address;
 use crate::native_contract::testutils::{
     create_account, generate_signing_key, sign_payload_for_account, signing_key_to_account_id,
 };
-use crate::{host_vec, Host, LedgerInfo};
-use soroban_env_common::{AddressObject, Env, Symbol, SymbolStr, TryFromVal, TryIntoVal};
+use crate::{host_vec, Host, HostError, LedgerInfo};
+use soroban_env_common::{
+    AddressObject, Env, Symbol, SymbolStr, TryFromVal, TryIntoVal, VecObject,
+};
 
 use crate::native_contract::base_types::Vec as HostVec;
 
@@ -2672,3 +2674,200 @@ fn test_different_auth_trees_with_duplicate_addresses() {
         false,
     );
 }
+
+#[test]
+fn test_check_auth_cannot_be_called_directly() {
+    let test = AuthTest::setup_with_contract(1, 1, DELEGATED_ACCOUNT_TEST_CONTRACT);
+    let account_contract = test.contracts[0].clone();
+    let payload = test.host.bytes_new_from_slice(&[0_u8; 32]).unwrap();
+    let args = host_vec![
+        &test.host,
+        payload,
+        host_vec![&test.host],
+        host_vec![&test.host]
+    ];
+
+    // `__check_auth` is reserved for the host's own `require_auth`
+    // implementation, so it can't be invoked as a regular contract function.
+    let err = test
+        .host
+        .call(
+            account_contract.into(),
+            Symbol::try_from_val(&test.host, &"__check_auth").unwrap(),
+            args.into(),
+        )
+        .err()
+        .unwrap();
+    assert!(err.error.is_type(ScErrorType::Context));
+    assert!(err.error.is_code(ScErrorCode::InvalidAction));
+
+    // The same applies to any other function with the reserved prefix, even
+    // if the contract doesn't define it.
+    let err = test
+        .host
+        .call(
+            test.contracts[0].clone().into(),
+            Symbol::try_from_small_str("__foo").unwrap(),
+            host_vec![&test.host].into(),
+        )
+        .err()
+        .unwrap();
+    assert!(err.error.is_type(ScErrorType::Context));
+    assert!(err.error.is_code(ScErrorCode::InvalidAction));
+}
+
+#[test]
+fn test_authorize_as_curr_contract_rejects_malformed_entries() {
+    use crate::TypedContractFunctionSet;
+    use std::rc::Rc;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.switch_to_recording_auth(true).unwrap();
+    let addr = host
+        .add_host_object(ScAddress::Contract(crate::xdr::Hash([1; 32])))
+        .unwrap();
+    let contract = TypedContractFunctionSet::new().with_fn(
+        "authorize",
+        |host: &Host, entries: VecObject| -> Result<u32, HostError> {
+            host.authorize_as_curr_contract(entries)?;
+            Ok(0)
+        },
+    );
+    host.register_test_contract(addr, Rc::new(contract))
+        .unwrap();
+    let fn_name = Symbol::try_from_small_str("authorize").unwrap();
+
+    // Authorizing nothing is fine.
+    let entries = host_vec![&host];
+    host.call(addr, fn_name, host_vec![&host, entries].into())
+        .unwrap();
+
+    // Entries that aren't `InvokerContractAuthEntry` values fail the call.
+    let entries = host_vec![&host, 1_u32];
+    assert!(host
+        .call(addr, fn_name, host_vec![&host, entries].into())
+        .is_err());
+}
+
+#[test]
+fn test_require_auth_for_args_records_custom_args() {
+    use crate::TypedContractFunctionSet;
+    use std::rc::Rc;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited().unwrap();
+    host.with_mut_ledger_info(|li| {
+        li.sequence_number = 100;
+        li.max_entry_expiration = 10000;
+    })
+    .unwrap();
+    host.switch_to_recording_auth(true).unwrap();
+    let contract_address = ScAddress::Contract(crate::xdr::Hash([1; 32]));
+    let contract = host.add_host_object(contract_address.clone()).unwrap();
+    let user_address = ScAddress::Contract(crate::xdr::Hash([2; 32]));
+    let user = host.add_host_object(user_address.clone()).unwrap();
+    let functions = TypedContractFunctionSet::new()
+        .with_fn(
+            "full",
+            |host: &Host, user: AddressObject, _amount: u32| -> Result<u32, HostError> {
+                host.require_auth(user)?;
+                Ok(0)
+            },
+        )
+        .with_fn(
+            "partial",
+            |host: &Host, user: AddressObject, amount: u32| -> Result<u32, HostError> {
+                let args = host_vec![host, amount];
+                host.require_auth_for_args(user, args.into())?;
+                Ok(0)
+            },
+        );
+    host.register_test_contract(contract, Rc::new(functions))
+        .unwrap();
+
+    for (fn_name, expected_args) in [
+        (
+            "full",
+            vec![ScVal::Address(user_address.clone()), ScVal::U32(7)],
+        ),
+        ("partial", vec![ScVal::U32(7)]),
+    ] {
+        host.call(
+            contract,
+            Symbol::try_from_val(&host, &fn_name).unwrap(),
+            host_vec![&host, user, 7_u32].into(),
+        )
+        .unwrap();
+        let payloads = host.get_recorded_auth_payloads().unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].address, Some(user_address.clone()));
+        assert_eq!(
+            payloads[0].invocation.function,
+            SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: contract_address.clone(),
+                function_name: ScSymbol(fn_name.try_into().unwrap()),
+                args: expected_args.try_into().unwrap(),
+            })
+        );
+    }
+}
+
+#[test]
+fn test_recorded_auth_entries_are_ready_to_sign() {
+    use crate::TypedContractFunctionSet;
+    use std::rc::Rc;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited().unwrap();
+    host.with_mut_ledger_info(|li| {
+        li.sequence_number = 100;
+        li.max_entry_expiration = 10000;
+    })
+    .unwrap();
+    host.switch_to_recording_auth(true).unwrap();
+    let contract = host
+        .add_host_object(ScAddress::Contract(crate::xdr::Hash([1; 32])))
+        .unwrap();
+    let user_address = ScAddress::Contract(crate::xdr::Hash([2; 32]));
+    let user = host.add_host_object(user_address.clone()).unwrap();
+    let functions = TypedContractFunctionSet::new().with_fn(
+        "transfer",
+        |host: &Host, user: AddressObject, _amount: u32| -> Result<u32, HostError> {
+            host.require_auth(user)?;
+            Ok(0)
+        },
+    );
+    host.register_test_contract(contract, Rc::new(functions))
+        .unwrap();
+
+    host.call(
+        contract,
+        Symbol::try_from_val(&host, &"transfer").unwrap(),
+        host_vec![&host, user, 7_u32].into(),
+    )
+    .unwrap();
+
+    let payloads = host.get_recorded_auth_payloads().unwrap();
+    let entries = host.get_recorded_auth_entries(1000).unwrap();
+    assert_eq!(payloads.len(), 1);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].root_invocation, payloads[0].invocation);
+    assert_eq!(
+        entries[0].credentials,
+        SorobanCredentials::Address(SorobanAddressCredentials {
+            address: user_address,
+            nonce: payloads[0].nonce.unwrap(),
+            signature_expiration_ledger: 1000,
+            signature: ScVal::Void,
+        })
+    );
+
+    // A payload without an address is covered by the source account.
+    let entry = RecordedAuthPayload {
+        address: None,
+        nonce: None,
+        invocation: payloads[0].invocation.clone(),
+    }
+    .into_authorization_entry(1000);
+    assert_eq!(entry.credentials, SorobanCredentials::SourceAccount);
+}
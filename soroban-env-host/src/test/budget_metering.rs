@@ -398,3 +398,304 @@ fn total_amount_charged_from_random_inputs() -> Result<(), HostError> {
     .assert_eq(&actual);
     Ok(())
 }
+
+#[test]
+fn cost_breakdown_and_shadow_budget() -> Result<(), HostError> {
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(ContractCostType::HostMemCpy, 10, 0, 1, 0);
+    let budget = host.as_budget();
+
+    budget.charge(ContractCostType::HostMemCpy, Some(5))?;
+    budget.bulk_charge(ContractCostType::HostMemCpy, 2, Some(3))?;
+
+    let breakdown = budget.cost_breakdown()?;
+    assert_eq!(breakdown.len(), ContractCostType::variants().len());
+    let memcpy = breakdown
+        .iter()
+        .find(|b| b.cost_type == ContractCostType::HostMemCpy)
+        .unwrap();
+    assert_eq!(memcpy.iterations, 3);
+    assert_eq!(memcpy.input, Some(11));
+    assert_eq!(memcpy.cpu_insns, 30);
+    assert_eq!(memcpy.mem_bytes, 3);
+    assert_eq!(breakdown.iter().map(|b| b.cpu_insns).sum::<u64>(), 30);
+
+    // Charges made in shadow mode are tallied separately and leave the
+    // regular budget and breakdown untouched.
+    budget.with_shadow_budget(|| {
+        budget.charge(ContractCostType::HostMemCpy, Some(1))?;
+        budget.bulk_charge(ContractCostType::HostMemCpy, 4, Some(1))
+    })?;
+    assert_eq!(budget.get_cpu_insns_consumed()?, 30);
+    assert_eq!(budget.get_mem_bytes_consumed()?, 3);
+    assert_eq!(budget.get_tracker(ContractCostType::HostMemCpy)?.0, 3);
+    assert_eq!(budget.get_shadow_cpu_insns_consumed()?, 50);
+    assert_eq!(budget.get_shadow_mem_bytes_consumed()?, 5);
+
+    // Shadow charges are not limited by the regular budget.
+    budget.with_shadow_budget(|| {
+        budget.bulk_charge(ContractCostType::HostMemCpy, 100_000, Some(1))
+    })?;
+    assert_eq!(budget.get_cpu_insns_consumed()?, 30);
+
+    budget.reset_tracker()?;
+    assert_eq!(budget.get_shadow_cpu_insns_consumed()?, 0);
+    Ok(())
+}
+
+#[test]
+fn mem_peak_excludes_released_vm_memory() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id_obj = host.register_test_contract_wasm(VEC);
+    let budget = host.as_budget().clone();
+    budget.reset_unlimited()?;
+    let sym = Symbol::try_from_small_str("vec_err").unwrap();
+    let args = host.test_vec_obj::<u32>(&[1])?;
+
+    host.try_call(id_obj, sym, args)?;
+    let consumed = budget.get_mem_bytes_consumed()?;
+    let peak = budget.get_mem_bytes_peak()?;
+    let net = budget.get_mem_bytes_net()?;
+    // The VM instance and its linear memory are released once the call
+    // returns.
+    assert!(net < peak);
+    assert!(peak <= consumed);
+
+    // Calling the contract again charges for a new VM instance, but that
+    // instance only exists once the previous one has been released.
+    host.try_call(id_obj, sym, args)?;
+    assert!(budget.get_mem_bytes_consumed()? >= consumed + (peak - net));
+    assert!(budget.get_mem_bytes_peak()? < budget.get_mem_bytes_consumed()?);
+
+    budget.reset_tracker()?;
+    assert_eq!(budget.get_mem_bytes_peak()?, 0);
+    assert_eq!(budget.get_mem_bytes_net()?, 0);
+    Ok(())
+}
+
+#[test]
+fn cost_params_roundtrip_through_config() -> Result<(), HostError> {
+    let budget = Budget::default();
+    let (cpu, mem) = budget.get_cost_params()?;
+    assert_eq!(cpu.0.len(), ContractCostType::variants().len());
+    assert_eq!(mem.0.len(), ContractCostType::variants().len());
+
+    let restored = Budget::try_from_configs(1_000, 1_000, cpu.clone(), mem.clone())?;
+    assert_eq!(restored.get_cost_params()?, (cpu, mem));
+
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(ContractCostType::HostMemCpy, 10, 0, 1, 0);
+    let (cpu, mem) = host.as_budget().get_cost_params()?;
+    let ty = ContractCostType::HostMemCpy as usize;
+    assert_eq!((cpu.0[ty].const_term, cpu.0[ty].linear_term), (10, 0));
+    assert_eq!((mem.0[ty].const_term, mem.0[ty].linear_term), (1, 0));
+    Ok(())
+}
+
+#[test]
+fn custom_fuel_config_scales_wasm_cpu_charges() -> Result<(), HostError> {
+    use super::util::wasm_module_with_4n_insns;
+    use crate::budget::FuelConfig;
+
+    let run_with_fuel_config = |config: Option<FuelConfig>| -> Result<u64, HostError> {
+        let host = Host::test_host_with_recording_footprint();
+        if let Some(config) = config {
+            host.as_budget().set_fuel_config(config)?;
+        }
+        let id_obj = host.register_test_contract_wasm(&wasm_module_with_4n_insns(1000));
+        let sym = Symbol::try_from_small_str("test").unwrap();
+        let args = host.test_vec_obj::<u32>(&[4375])?;
+        let host = host
+            .test_budget(1_000_000, 1_048_576)
+            .enable_model(ContractCostType::WasmInsnExec, 6, 0, 0, 0)
+            .enable_model(ContractCostType::WasmMemAlloc, 0, 0, 1, 0);
+        host.call(id_obj, sym, args)?;
+        Ok(host
+            .as_budget()
+            .get_tracker(ContractCostType::WasmInsnExec)?
+            .0)
+    };
+
+    let default_config = Budget::default().get_fuel_config()?;
+    let default_fuel = run_with_fuel_config(None)?;
+    assert_eq!(default_fuel, 4005);
+
+    // Doubling the base cost doubles the fuel charged for the 4000 base
+    // instructions of the module.
+    let doubled_fuel = run_with_fuel_config(Some(FuelConfig {
+        base: default_config.base * 2,
+        ..default_config.clone()
+    }))?;
+    assert!(doubled_fuel >= default_fuel + 4000);
+
+    // A zero base cost would let instructions run for free.
+    let res = Budget::default().set_fuel_config(FuelConfig {
+        base: 0,
+        ..default_config
+    });
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
+
+#[test]
+fn try_call_with_budget_caps_callee_consumption() -> Result<(), HostError> {
+    use super::util::wasm_module_with_4n_insns;
+    use crate::{Error, TryFromVal, U64Val};
+
+    let host = Host::test_host_with_recording_footprint();
+    let id_obj = host.register_test_contract_wasm(&wasm_module_with_4n_insns(1000));
+    let sym = Symbol::try_from_small_str("test").unwrap();
+    let args = host.test_vec_obj::<u32>(&[4375])?;
+    let host = host
+        .test_budget(100_000, 1_048_576)
+        .enable_model(ContractCostType::WasmInsnExec, 6, 0, 0, 0)
+        .enable_model(ContractCostType::WasmMemAlloc, 0, 0, 1, 0);
+    let u64_val = |v: u64| U64Val::try_from_val(&host, &v);
+
+    // The callee needs about 24000 cpu insns, so it exceeds a sub-limit of
+    // 10000. This is reported to the caller as an error value, and what the
+    // callee consumed until then stays charged.
+    let res =
+        host.try_call_with_budget(id_obj, sym, args, u64_val(10_000)?, u64_val(1_048_576)?)?;
+    let err = Error::try_from_val(&host, &res)?;
+    assert!(err.is_type(ScErrorType::Budget));
+    assert!(err.is_code(ScErrorCode::ExceededLimit));
+    // The sub-limit is checked once a charge has been added, so the callee
+    // can overshoot it by the charge that crossed it, if any, but by no more
+    // than that; it is stopped well short of what it needs to complete.
+    let consumed = host.as_budget().get_cpu_insns_consumed()?;
+    let crossing = host.as_budget().get_cpu_insns_crossing_charge()?;
+    assert!(consumed <= 10_000 + crossing);
+    assert!(consumed > 10_000 / 2);
+    assert_eq!(
+        host.as_budget().get_cpu_insns_remaining()?,
+        100_000 - consumed
+    );
+
+    // With a sufficient sub-limit the call succeeds.
+    let res =
+        host.try_call_with_budget(id_obj, sym, args, u64_val(50_000)?, u64_val(1_048_576)?)?;
+    assert_eq!(
+        res.get_payload(),
+        Symbol::try_from_small_str("pass")?.to_val().get_payload()
+    );
+
+    // Running out of the overall budget still fails the caller.
+    host.as_budget().reset_limits(10_000, 1_048_576)?;
+    let res = host.try_call_with_budget(id_obj, sym, args, u64_val(50_000)?, u64_val(1_048_576)?);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::ExceededLimit)
+    ));
+    Ok(())
+}
+
+#[test]
+fn live_object_limit_is_enforced() -> Result<(), HostError> {
+    let host = Host::default();
+    host.as_budget().reset_unlimited()?;
+    let live = host.try_borrow_objects()?.len() as u32;
+    host.as_budget().set_max_live_objects(live + 2)?;
+
+    host.bytes_new()?;
+    host.bytes_new()?;
+    let res = host.bytes_new();
+    let code = (ScErrorType::Object, ScErrorCode::ExceededLimit);
+    assert!(HostError::result_matches_err(res, code));
+    assert_eq!(host.try_borrow_objects()?.len(), live as usize + 2);
+    Ok(())
+}
+
+#[test]
+fn object_size_limit_is_enforced() -> Result<(), HostError> {
+    let host = Host::default();
+    host.as_budget().reset_unlimited()?;
+    host.as_budget().set_max_object_bytes(16)?;
+    let code = (ScErrorType::Object, ScErrorCode::ExceededLimit);
+
+    host.bytes_new_from_slice(&[0; 16])?;
+    let res = host.bytes_new_from_slice(&[0; 17]);
+    assert!(HostError::result_matches_err(res, code));
+
+    // Vectors count 8 bytes per element.
+    host.vec_new_from_slice(&[Val::from_void().to_val(); 2])?;
+    let res = host.vec_new_from_slice(&[Val::from_void().to_val(); 3]);
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
+#[test]
+fn public_metered_helpers_charge_the_budget() -> Result<(), HostError> {
+    use crate::metered::{charge_heap_alloc, DeclaredSizeForMetering, MeteredAlloc};
+    use std::rc::Rc;
+
+    let host = Host::test_host()
+        .test_budget(100_000, 100_000)
+        .enable_model(ContractCostType::HostMemAlloc, 1, 0, 0, 1);
+    let rc = Rc::<u64>::metered_new(5, &host)?;
+    assert_eq!(*rc, 5);
+    charge_heap_alloc::<u64>(3, &host)?;
+    host.with_budget(|budget| {
+        assert_eq!(budget.get_tracker(ContractCostType::HostMemAlloc)?.0, 2);
+        assert_eq!(
+            budget.get_tracker(ContractCostType::HostMemAlloc)?.1,
+            Some(4 * u64::DECLARED_SIZE)
+        );
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn budget_from_config_settings() -> Result<(), HostError> {
+    use crate::xdr::{ConfigSettingContractComputeV0, ConfigSettingEntry, ContractCostParams};
+
+    let (cpu, mem) = Budget::default().get_cost_params()?;
+    let compute = ConfigSettingEntry::ContractComputeV0(ConfigSettingContractComputeV0 {
+        ledger_max_instructions: 1_000_000_000,
+        tx_max_instructions: 50_000_000,
+        fee_rate_per_instructions_increment: 100,
+        tx_memory_limit: 20_000_000,
+    });
+    let settings = [
+        compute.clone(),
+        ConfigSettingEntry::ContractCostParamsCpuInstructions(cpu.clone()),
+        ConfigSettingEntry::ContractCostParamsMemoryBytes(mem.clone()),
+    ];
+    let budget = Budget::try_from_config_settings(&settings)?;
+    assert_eq!(budget.get_cpu_insns_remaining()?, 50_000_000);
+    assert_eq!(budget.get_mem_bytes_remaining()?, 20_000_000);
+    assert_eq!(budget.get_cost_params()?, (cpu.clone(), mem.clone()));
+
+    // Every setting the budget needs must be present.
+    let res = Budget::try_from_config_settings(&settings[..2]);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::MissingValue)
+    ));
+
+    // And the cost params must cover every cost type.
+    let mut short_cpu = cpu.0.to_vec();
+    short_cpu.pop();
+    let short_cpu = ContractCostParams(short_cpu.try_into()?);
+    let res = Budget::try_from_config_settings(&[
+        compute,
+        ConfigSettingEntry::ContractCostParamsCpuInstructions(short_cpu.clone()),
+        ConfigSettingEntry::ContractCostParamsMemoryBytes(mem.clone()),
+    ]);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::InvalidInput)
+    ));
+    let res = Budget::try_from_configs(1_000, 1_000, cpu, short_cpu);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
@@ -101,6 +101,48 @@ fn bytes_slice_start_greater_than_len() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn bytes_xor() -> Result<(), HostError> {
+    let host = Host::default();
+    let a = host.bytes_new_from_slice(&[0x0f, 0xf0, 0xff, 0x00])?;
+    let b = host.bytes_new_from_slice(&[0xff, 0xff, 0x0f, 0x00])?;
+    let res = host.bytes_xor(a, b)?;
+    let exp = host.bytes_new_from_slice(&[0xf0, 0x0f, 0xf0, 0x00])?;
+    assert_eq!(host.obj_cmp(res.into(), exp.into())?, 0);
+    // xor-ing twice gives back the original
+    let back = host.bytes_xor(res, b)?;
+    assert_eq!(host.obj_cmp(back.into(), a.into())?, 0);
+
+    let short = host.bytes_new_from_slice(&[1, 2, 3])?;
+    let res = host.bytes_xor(a, short);
+    let code = (ScErrorType::Object, ScErrorCode::UnexpectedSize);
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
+#[test]
+fn bytes_fill() -> Result<(), HostError> {
+    let host = Host::default();
+    let obj = host.bytes_new_from_slice(&[1, 2, 3, 4, 5])?;
+    let res = host.bytes_fill(obj, 1_u32.into(), 4_u32.into(), 0_u32.into())?;
+    let exp = host.bytes_new_from_slice(&[1, 0, 0, 0, 5])?;
+    assert_eq!(host.obj_cmp(res.into(), exp.into())?, 0);
+    // the original is left untouched
+    let orig = host.bytes_new_from_slice(&[1, 2, 3, 4, 5])?;
+    assert_eq!(host.obj_cmp(obj.into(), orig.into())?, 0);
+    // an empty range is a no-op
+    let res = host.bytes_fill(obj, 5_u32.into(), 5_u32.into(), 7_u32.into())?;
+    assert_eq!(host.obj_cmp(res.into(), obj.into())?, 0);
+
+    let res = host.bytes_fill(obj, 3_u32.into(), 6_u32.into(), 7_u32.into());
+    let code = (ScErrorType::Object, ScErrorCode::IndexBounds);
+    assert!(HostError::result_matches_err(res, code));
+    let res = host.bytes_fill(obj, 0_u32.into(), 1_u32.into(), 256_u32.into());
+    let code = (ScErrorType::Value, ScErrorCode::InvalidInput);
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
 #[test]
 fn bytes_xdr_roundtrip() -> Result<(), HostError> {
     let host = Host::default();
@@ -135,6 +177,84 @@ fn bytes_xdr_roundtrip() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn bytes_xdr_roundtrip_all_value_types() -> Result<(), HostError> {
+    use crate::xdr::{
+        Duration, Hash, Int128Parts, Int256Parts, ScAddress, ScBytes, ScMap, ScMapEntry, ScString,
+        TimePoint, UInt128Parts, UInt256Parts,
+    };
+
+    let host = Host::default();
+    let roundtrip = |v: ScVal| -> Result<(), HostError> {
+        let bo = host.serialize_to_bytes(host.to_host_val(&v)?)?;
+        let back = host.from_host_val(host.deserialize_from_bytes(bo)?)?;
+        assert_eq!(v, back);
+        Ok(())
+    };
+    roundtrip(ScVal::Void)?;
+    roundtrip(ScVal::Bool(false))?;
+    roundtrip(ScVal::I64(i64::MIN))?;
+    roundtrip(ScVal::U64(u64::MAX))?;
+    roundtrip(ScVal::Timepoint(TimePoint(u64::MAX)))?;
+    roundtrip(ScVal::Duration(Duration(7)))?;
+    roundtrip(ScVal::U128(UInt128Parts { hi: 1, lo: 2 }))?;
+    roundtrip(ScVal::I128(Int128Parts { hi: -1, lo: 2 }))?;
+    roundtrip(ScVal::U256(UInt256Parts {
+        hi_hi: 1,
+        hi_lo: 2,
+        lo_hi: 3,
+        lo_lo: 4,
+    }))?;
+    roundtrip(ScVal::I256(Int256Parts {
+        hi_hi: -1,
+        hi_lo: 2,
+        lo_hi: 3,
+        lo_lo: 4,
+    }))?;
+    roundtrip(ScVal::Bytes(ScBytes(
+        host.map_err(vec![1, 2, 3].try_into())?,
+    )))?;
+    roundtrip(ScVal::String(ScString(
+        host.map_err("soroban".to_string().try_into())?,
+    )))?;
+    roundtrip(ScVal::Vec(Some(host.test_scvec::<u32>(&[])?)))?;
+    roundtrip(ScVal::Map(Some(ScMap(
+        host.map_err(
+            vec![ScMapEntry {
+                key: ScVal::U32(1),
+                val: ScVal::Vec(Some(host.test_scvec::<i32>(&[-1])?)),
+            }]
+            .try_into(),
+        )?,
+    ))))?;
+    roundtrip(ScVal::Address(ScAddress::Contract(Hash([7; 32]))))?;
+    Ok(())
+}
+
+#[test]
+fn deserialize_from_bytes_rejects_non_values_and_deep_values() -> Result<(), HostError> {
+    use crate::{budget::AsBudget, xdr::WriteXdr};
+
+    let host = Host::default();
+    // Ledger keys are valid XDR but aren't values a contract can hold.
+    let bytes = host.map_err(ScVal::LedgerKeyContractInstance.to_xdr())?;
+    let bo = host.bytes_new_from_slice(&bytes)?;
+    assert!(host.deserialize_from_bytes(bo).is_err());
+
+    let mut v = ScVal::Void;
+    for _ in 0..10 {
+        v = ScVal::Vec(Some(crate::xdr::ScVec(host.map_err(vec![v].try_into())?)));
+    }
+    let bo = host.serialize_to_bytes(host.to_host_val(&v)?)?;
+    host.as_budget().set_xdr_depth_limit(5)?;
+    let code = (ScErrorType::Context, ScErrorCode::ExceededLimit);
+    assert!(HostError::result_matches_err(
+        host.deserialize_from_bytes(bo),
+        code
+    ));
+    Ok(())
+}
+
 #[test]
 fn linear_memory_operations() -> Result<(), HostError> {
     use soroban_env_common::BytesObject;
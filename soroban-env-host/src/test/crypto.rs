@@ -1,4 +1,8 @@
-use crate::{xdr::ScVal, Env, Host, HostError};
+use crate::{
+    budget::AsBudget,
+    xdr::{ContractCostType, ScErrorCode, ScErrorType, ScVal},
+    Env, Host, HostError,
+};
 use hex::FromHex;
 use soroban_env_common::{EnvBase, U32Val};
 
@@ -105,3 +109,106 @@ fn recover_ecdsa_secp256k1_key_test() -> Result<(), HostError> {
     assert_eq!(host.obj_cmp(pk_obj.to_val(), pk_obj_2.to_val())?, 0);
     Ok(())
 }
+
+#[test]
+fn recover_ecdsa_secp256k1_key_rejects_invalid_inputs() -> Result<(), HostError> {
+    let host = Host::default();
+
+    let msg_digest: Vec<u8> =
+        FromHex::from_hex(b"ce0677bb30baa8cf067c88db9811f4333d131bf8bcf12fe7065d211dce971008")
+            .unwrap();
+    let sig: Vec<u8> = FromHex::from_hex(b"90f27b8b488db00b00606796d2987f6a5f59ae62ea05effe84fef5b8b0e549984a691139ad57a3f0b906637673aa2f63d1f55cb1a69199d4009eea23ceaddc93").unwrap();
+    // Same signature with 's' replaced by 'n - s', which is equally valid but
+    // not normalized to low form.
+    let high_s_sig: Vec<u8> = FromHex::from_hex(b"90f27b8b488db00b00606796d2987f6a5f59ae62ea05effe84fef5b8b0e54998b596eec652a85c0f46f99c898c55d09ae8b9803508b70667bf337469018864ae").unwrap();
+    let msg_digest_obj = host.test_bin_obj(msg_digest.as_slice())?;
+    let sig_obj = host.test_bin_obj(sig.as_slice())?;
+    let crypto_err = (ScErrorType::Crypto, ScErrorCode::InvalidInput);
+
+    // Recovery IDs range from 0 to 3.
+    let res = host.recover_key_ecdsa_secp256k1(msg_digest_obj, sig_obj, U32Val::from(4));
+    assert!(HostError::result_matches_err(res, crypto_err));
+
+    // High-s signatures are rejected.
+    let high_s_sig_obj = host.test_bin_obj(high_s_sig.as_slice())?;
+    let res = host.recover_key_ecdsa_secp256k1(msg_digest_obj, high_s_sig_obj, U32Val::from(1));
+    assert!(HostError::result_matches_err(res, crypto_err));
+
+    // Signatures must be exactly 64 bytes.
+    let short_sig_obj = host.test_bin_obj(&sig[..63])?;
+    let res = host.recover_key_ecdsa_secp256k1(msg_digest_obj, short_sig_obj, U32Val::from(1));
+    assert!(HostError::result_matches_err(res, crypto_err));
+
+    // Message digests must be exactly 32 bytes.
+    let short_digest_obj = host.test_bin_obj(&msg_digest[..31])?;
+    let res = host.recover_key_ecdsa_secp256k1(short_digest_obj, sig_obj, U32Val::from(1));
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+    ));
+    Ok(())
+}
+
+// Runs `f` and returns the number of times it charged `ty`, along with the
+// total input it charged it with.
+fn charged<T>(
+    host: &Host,
+    ty: ContractCostType,
+    f: impl FnOnce() -> Result<T, HostError>,
+) -> Result<(u64, u64), HostError> {
+    let (count_before, input_before) = host.as_budget().get_tracker(ty)?;
+    f()?;
+    let (count_after, input_after) = host.as_budget().get_tracker(ty)?;
+    Ok((
+        count_after - count_before,
+        input_after.unwrap_or(0) - input_before.unwrap_or(0),
+    ))
+}
+
+#[test]
+fn crypto_functions_charge_dedicated_cost_types() -> Result<(), HostError> {
+    let host = Host::default();
+    host.as_budget().reset_unlimited()?;
+
+    // Hashes are charged per byte hashed.
+    let obj = host.test_bin_obj(&[7; 100])?;
+    let res = charged(&host, ContractCostType::ComputeSha256Hash, || {
+        host.compute_hash_sha256(obj)
+    })?;
+    assert_eq!(res, (1, 100));
+    let res = charged(&host, ContractCostType::ComputeKeccak256Hash, || {
+        host.compute_hash_keccak256(obj)
+    })?;
+    assert_eq!(res, (1, 100));
+
+    // Signature verification is charged per byte of the signed message, on
+    // top of decoding the public key.
+    let pub_bytes: Vec<u8> =
+        FromHex::from_hex(b"3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")
+            .unwrap();
+    let sig_bytes: Vec<u8> = FromHex::from_hex(b"92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00").unwrap();
+    let obj_pub = host.test_bin_obj(&pub_bytes)?;
+    let obj_msg = host.test_bin_obj(&[0x72])?;
+    let obj_sig = host.test_bin_obj(&sig_bytes)?;
+    let res = charged(&host, ContractCostType::VerifyEd25519Sig, || {
+        host.verify_sig_ed25519(obj_pub, obj_msg, obj_sig)
+    })?;
+    assert_eq!(res, (1, 1));
+    let res = charged(&host, ContractCostType::ComputeEd25519PubKey, || {
+        host.verify_sig_ed25519(obj_pub, obj_msg, obj_sig)
+    })?;
+    assert_eq!(res.0, 1);
+
+    // Key recovery has a fixed cost, as its input is always a 32-byte digest.
+    let msg_digest: Vec<u8> =
+        FromHex::from_hex(b"ce0677bb30baa8cf067c88db9811f4333d131bf8bcf12fe7065d211dce971008")
+            .unwrap();
+    let sig: Vec<u8> = FromHex::from_hex(b"90f27b8b488db00b00606796d2987f6a5f59ae62ea05effe84fef5b8b0e549984a691139ad57a3f0b906637673aa2f63d1f55cb1a69199d4009eea23ceaddc93").unwrap();
+    let msg_digest_obj = host.test_bin_obj(msg_digest.as_slice())?;
+    let sig_obj = host.test_bin_obj(sig.as_slice())?;
+    let res = charged(&host, ContractCostType::RecoverEcdsaSecp256k1Key, || {
+        host.recover_key_ecdsa_secp256k1(msg_digest_obj, sig_obj, U32Val::from(1))
+    })?;
+    assert_eq!(res.0, 1);
+    Ok(())
+}
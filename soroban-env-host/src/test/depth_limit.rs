@@ -1,10 +1,12 @@
 use soroban_env_common::xdr::{ReadXdr, WriteXdr};
 
+use std::rc::Rc;
+
 use crate::{
     budget::AsBudget,
-    host::metered_clone::MeteredClone,
-    xdr::{ScErrorCode, ScErrorType, ScVal, ScVec},
-    Env, Host, HostError,
+    host::{metered_clone::MeteredClone, metered_xdr::metered_write_xdr},
+    xdr::{Hash, ScAddress, ScErrorCode, ScErrorType, ScVal, ScVec},
+    AddressObject, Env, Host, HostError, Limits, Symbol, TryFromVal, TypedContractFunctionSet,
 };
 
 #[test]
@@ -112,3 +114,205 @@ fn deep_scval_xdr_deserialization() -> Result<(), HostError> {
     assert!(HostError::result_matches_err(res, code));
     Ok(())
 }
+
+#[test]
+fn metered_xdr_respects_configured_depth_limit() -> Result<(), HostError> {
+    let host = Host::default();
+    host.as_budget().reset_unlimited()?;
+
+    let mut v = ScVal::from(ScVec::default());
+    for _ in 0..20 {
+        let vv = ScVec::try_from(vec![v])?;
+        v = ScVal::from(vv);
+    }
+    let mut bytes = vec![];
+    metered_write_xdr(host.budget_ref(), &v, &mut bytes)?;
+    assert_eq!(host.metered_from_xdr::<ScVal>(&bytes)?, v);
+
+    let code = (ScErrorType::Context, ScErrorCode::ExceededLimit);
+    host.as_budget().set_xdr_depth_limit(10)?;
+    let res = metered_write_xdr(host.budget_ref(), &v, &mut vec![]);
+    assert!(res.as_ref().is_err_and(|e| e.is_depth_limit_exceeded()));
+    assert!(HostError::result_matches_err(res, code));
+    let res = host.metered_from_xdr::<ScVal>(&bytes);
+    assert!(res.as_ref().is_err_and(|e| e.is_depth_limit_exceeded()));
+    assert!(HostError::result_matches_err(res, code));
+
+    let res = host.as_budget().set_xdr_depth_limit(0);
+    let code = (ScErrorType::Budget, ScErrorCode::InvalidInput);
+    assert!(HostError::result_matches_err(res, code));
+    assert_eq!(host.as_budget().get_xdr_depth_limit()?, 10);
+    Ok(())
+}
+
+#[test]
+fn metered_clone_respects_configured_depth_limit() -> Result<(), HostError> {
+    let host = Host::default();
+    host.as_budget().reset_unlimited()?;
+
+    let mut v = ScVal::from(ScVec::default());
+    for _ in 0..20 {
+        let vv = ScVec::try_from(vec![v])?;
+        v = ScVal::from(vv);
+    }
+    v.metered_clone(host.as_budget())?;
+
+    host.as_budget().set_depth_limit(10)?;
+    let res = v.metered_clone(host.as_budget());
+    let code = (ScErrorType::Context, ScErrorCode::ExceededLimit);
+    assert!(res.as_ref().is_err_and(|e| e.is_depth_limit_exceeded()));
+    assert!(HostError::result_matches_err(res, code));
+
+    let res = host.as_budget().set_depth_limit(0);
+    let code = (ScErrorType::Budget, ScErrorCode::InvalidInput);
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
+// The address of the `n`th contract of the chain registered by
+// `register_call_chain`.
+fn call_chain_address(host: &Host, n: u32) -> Result<AddressObject, HostError> {
+    let mut id = [0; 32];
+    id[..4].copy_from_slice(&n.to_be_bytes());
+    host.add_host_object(ScAddress::Contract(Hash(id)))
+}
+
+// Registers contracts `0..=len` such that `down(n)` on the `n`th contract
+// calls `down(n - 1)` on the next one, returning the number of nested calls
+// made below it. Distinct contracts are used as a contract may not re-enter
+// itself.
+fn register_call_chain(host: &Host, len: u32) -> Result<(), HostError> {
+    let contract = Rc::new(TypedContractFunctionSet::new().with_fn(
+        "down",
+        |host: &Host, n: u32| -> Result<u32, HostError> {
+            if n == 0 {
+                return Ok(0);
+            }
+            let next = call_chain_address(host, n - 1)?;
+            let res = host.call(
+                next,
+                Symbol::try_from_small_str("down")?,
+                host.test_vec_obj(&[n - 1])?,
+            )?;
+            Ok(u32::try_from_val(host, &res)? + 1)
+        },
+    ));
+    for n in 0..=len {
+        host.register_test_contract(call_chain_address(host, n)?, contract.clone())?;
+    }
+    Ok(())
+}
+
+fn call_down(host: &Host, n: u32) -> Result<u32, HostError> {
+    let res = host.call(
+        call_chain_address(host, n)?,
+        Symbol::try_from_small_str("down")?,
+        host.test_vec_obj(&[n])?,
+    )?;
+    Ok(u32::try_from_val(host, &res)?)
+}
+
+#[test]
+fn deep_contract_call_chain_fails_cleanly() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    register_call_chain(&host, 150)?;
+    let max_call_depth = host.get_limits()?.max_call_depth;
+    assert_eq!(max_call_depth, crate::DEFAULT_HOST_DEPTH_LIMIT);
+
+    // `down(n)` pushes `n + 1` frames.
+    assert_eq!(call_down(&host, max_call_depth - 1)?, max_call_depth - 1);
+
+    let err = call_down(&host, max_call_depth).unwrap_err();
+    assert!(err.is_depth_limit_exceeded());
+    let err = call_down(&host, 150).unwrap_err();
+    assert!(err.is_depth_limit_exceeded());
+
+    // The failure unwinds every frame and leaves budget to spare, so the host
+    // remains usable.
+    assert!(host.try_borrow_context()?.is_empty());
+    assert!(host.as_budget().get_cpu_insns_remaining()? > 0);
+    assert!(host.as_budget().get_mem_bytes_remaining()? > 0);
+    assert_eq!(call_down(&host, 10)?, 10);
+    Ok(())
+}
+
+#[test]
+fn configured_call_depth_limit() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    register_call_chain(&host, 10)?;
+    host.set_limits(Limits {
+        max_call_depth: 5,
+        ..Limits::default()
+    })?;
+
+    assert_eq!(call_down(&host, 4)?, 4);
+    let res = call_down(&host, 5);
+    let code = (ScErrorType::Context, ScErrorCode::ExceededLimit);
+    assert!(HostError::result_matches_err(res, code));
+    assert!(host.try_borrow_context()?.is_empty());
+
+    let res = host.set_limits(Limits {
+        max_call_depth: 0,
+        ..Limits::default()
+    });
+    let code = (ScErrorType::Budget, ScErrorCode::InvalidInput);
+    assert!(HostError::result_matches_err(res, code));
+    assert_eq!(host.get_limits()?.max_call_depth, 5);
+
+    // An invalid limit leaves the valid ones passed along with it unapplied.
+    let res = host.set_limits(Limits {
+        max_call_depth: 3,
+        max_value_nesting: 3,
+        max_xdr_depth: 0,
+    });
+    assert!(HostError::result_matches_err(res, code));
+    assert_eq!(
+        host.get_limits()?,
+        Limits {
+            max_call_depth: 5,
+            ..Limits::default()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn contract_error_is_not_a_depth_limit() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id = call_chain_address(&host, 0)?;
+    let contract = Rc::new(TypedContractFunctionSet::new().with_fn(
+        "fail",
+        |_host: &Host, _n: u32| -> Result<u32, HostError> {
+            Err((ScErrorType::Context, ScErrorCode::ExceededLimit).into())
+        },
+    ));
+    host.register_test_contract(contract_id, contract)?;
+
+    let res = host.call(
+        contract_id,
+        Symbol::try_from_small_str("fail")?,
+        host.test_vec_obj(&[0u32])?,
+    );
+    let code = (ScErrorType::Context, ScErrorCode::ExceededLimit);
+    assert!(res.as_ref().is_err_and(|e| !e.is_depth_limit_exceeded()));
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
+#[test]
+fn limits_are_kept_in_the_budget() -> Result<(), HostError> {
+    let host = Host::default();
+    assert_eq!(host.get_limits()?, Limits::default());
+
+    let limits = Limits {
+        max_call_depth: 7,
+        max_value_nesting: 8,
+        max_xdr_depth: 9,
+    };
+    host.set_limits(limits)?;
+    assert_eq!(host.get_limits()?, limits);
+    assert_eq!(host.as_budget().get_call_depth_limit()?, 7);
+    assert_eq!(host.as_budget().get_depth_limit()?, 8);
+    assert_eq!(host.as_budget().get_xdr_depth_limit()?, 9);
+    Ok(())
+}
@@ -0,0 +1,245 @@
+// Differential testing of contract execution across wasmi configurations.
+//
+// Every fixture below is a contract and a sequence of calls to it. The suite
+// runs each fixture on a fresh host per backend and records what a contract
+// can observably do: the result of every call, the contract events emitted and
+// the final state of storage. The observations of every backend must match
+// those of the reference backend (plain wasmi) exactly; budget consumption is
+// not compared, as that is expected to differ between backends.
+//
+// A backend here is a way of configuring the host that changes how it
+// executes Wasm without changing what the Wasm means: a VM configuration or a
+// module cache. All of them run on wasmi, since `Vm` is built directly on
+// wasmi's linker and instances; diffing against a second interpreter or
+// compiler needs the host to be able to dispatch to one first, and is out of
+// scope for this suite. The fixtures and `observe` don't depend on wasmi
+// though, so such an engine only has to be added as another `Backend`.
+//
+// The suite is slow-ish and only meaningful while execution is being worked
+// on, so it only runs with the `differential-testing` feature enabled.
+
+use crate::{
+    budget::AsBudget,
+    vm::VmConfig,
+    xdr::{AccountId, ContractEventType, PublicKey, ScBytes, ScSymbol, ScVal, ScVec, Uint256},
+    Env, Host, HostError, LedgerInfo, Symbol, TryFromVal, VecObject,
+};
+use soroban_test_wasms::{ADD_I32, COMPLEX, CONTRACT_STORAGE, ERR, LINEAR_MEMORY, VEC};
+
+trait Backend {
+    fn name(&self) -> &'static str;
+
+    // Configures a freshly created host to execute Wasm on this backend.
+    fn configure(&self, host: &Host) -> Result<(), HostError>;
+}
+
+// The reference backend: wasmi with the protocol's configuration, parsing
+// every module on instantiation.
+struct Wasmi;
+
+impl Backend for Wasmi {
+    fn name(&self) -> &'static str {
+        "wasmi"
+    }
+
+    fn configure(&self, _host: &Host) -> Result<(), HostError> {
+        Ok(())
+    }
+}
+
+// wasmi instantiating from modules kept in a module cache, so that the second
+// and later calls of a fixture reuse a module parsed by an earlier one.
+struct WasmiCached;
+
+impl Backend for WasmiCached {
+    fn name(&self) -> &'static str {
+        "wasmi (module cache)"
+    }
+
+    fn configure(&self, host: &Host) -> Result<(), HostError> {
+        host.enable_module_cache()
+    }
+}
+
+// wasmi with every optional Wasm proposal enabled. None of the fixtures use
+// them, so enabling them must not change how the fixtures execute.
+struct WasmiAllProposals;
+
+impl Backend for WasmiAllProposals {
+    fn name(&self) -> &'static str {
+        "wasmi (all proposals)"
+    }
+
+    fn configure(&self, host: &Host) -> Result<(), HostError> {
+        host.set_vm_config(Some(VmConfig {
+            reference_types: true,
+            multi_value: true,
+            sign_extension: true,
+            bulk_memory: true,
+        }))
+    }
+}
+
+struct Fixture {
+    name: &'static str,
+    wasm: &'static [u8],
+    calls: Vec<(&'static str, Vec<ScVal>)>,
+}
+
+fn sym(s: &str) -> ScVal {
+    ScVal::Symbol(ScSymbol(s.try_into().unwrap()))
+}
+
+fn fixtures() -> Vec<Fixture> {
+    vec![
+        Fixture {
+            name: "add_i32",
+            wasm: ADD_I32,
+            calls: vec![
+                ("add", vec![ScVal::I32(4), ScVal::I32(7)]),
+                // Traps on overflow.
+                ("add", vec![ScVal::I32(i32::MAX), ScVal::I32(1)]),
+            ],
+        },
+        Fixture {
+            name: "vec",
+            wasm: VEC,
+            calls: vec![
+                ("vec_err", vec![ScVal::U32(1)]),
+                // Indexes out of bounds.
+                ("vec_err", vec![ScVal::U32(5)]),
+            ],
+        },
+        Fixture {
+            name: "linear_memory",
+            wasm: LINEAR_MEMORY,
+            calls: vec![
+                ("bin_word", vec![ScVal::U32(0x1234_5678)]),
+                (
+                    "bin_inc",
+                    vec![ScVal::Bytes(ScBytes(
+                        vec![0, 1, 2, 255].try_into().unwrap(),
+                    ))],
+                ),
+            ],
+        },
+        Fixture {
+            name: "err",
+            wasm: ERR,
+            calls: vec![
+                ("err_eek", vec![]),
+                ("err_err", vec![]),
+                ("ok_err", vec![]),
+                ("ok_val_err", vec![]),
+                ("err", vec![]),
+                ("val", vec![]),
+            ],
+        },
+        Fixture {
+            name: "contract_data",
+            wasm: CONTRACT_STORAGE,
+            calls: vec![
+                ("put_persistent", vec![sym("a"), ScVal::U64(1)]),
+                ("put_temporary", vec![sym("b"), ScVal::U64(2)]),
+                ("put_instance", vec![sym("c"), ScVal::U64(3)]),
+                ("get_persistent", vec![sym("a")]),
+                ("del_temporary", vec![sym("b")]),
+                ("has_temporary", vec![sym("b")]),
+                // Reads a missing key.
+                ("get_instance", vec![sym("d")]),
+            ],
+        },
+        Fixture {
+            name: "complex",
+            wasm: COMPLEX,
+            calls: vec![("go", vec![])],
+        },
+    ]
+}
+
+fn test_account_id() -> AccountId {
+    AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([1; 32])))
+}
+
+// Runs `fixture` on a fresh host configured by `backend` and renders every
+// observable effect of it, one line each.
+fn observe(backend: &dyn Backend, fixture: &Fixture) -> Result<Vec<String>, HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited()?;
+    host.set_ledger_info(LedgerInfo {
+        protocol_version: crate::meta::get_ledger_protocol_version(crate::meta::INTERFACE_VERSION),
+        sequence_number: 1234,
+        timestamp: 1234,
+        network_id: [7; 32],
+        base_reserve: 1,
+        min_persistent_entry_expiration: 4096,
+        min_temp_entry_expiration: 16,
+        max_entry_expiration: 6_312_000,
+    })?;
+    backend.configure(&host)?;
+
+    // Contract IDs derive from the deployer and salt, so are the same on
+    // every backend.
+    let contract = host.register_test_contract_wasm_from_source_account(
+        fixture.wasm,
+        test_account_id(),
+        [0; 32],
+    );
+
+    let mut observed = vec![];
+    for (func, args) in fixture.calls.iter() {
+        let args = ScVal::Vec(Some(ScVec::try_from(args.clone())?));
+        let args = VecObject::try_from(host.to_host_val(&args)?)?;
+        // Failures are observed as the error they returned with.
+        let res = host
+            .try_call(contract, Symbol::try_from_val(&host, func)?, args)
+            .and_then(|v| host.from_host_val(v));
+        observed.push(format!("call {}: {:?}", func, res.map_err(|e| e.error)));
+    }
+
+    for e in host.get_events()?.0 {
+        if e.event.type_ != ContractEventType::Diagnostic {
+            observed.push(format!(
+                "event: {:?} failed_call={}",
+                e.event.body, e.failed_call
+            ));
+        }
+    }
+
+    let budget = host.as_budget();
+    budget.with_free_budget(|| {
+        let storage = host.try_borrow_storage()?;
+        for (key, entry) in storage.map.iter(budget)? {
+            let entry = match entry {
+                Some((entry, live_until)) => {
+                    format!("{:?} live_until={:?}", entry.data, live_until)
+                }
+                None => "deleted".to_string(),
+            };
+            observed.push(format!("storage: {:?} => {}", key, entry));
+        }
+        Ok(())
+    })?;
+    Ok(observed)
+}
+
+#[test]
+fn backends_agree_on_fixtures() -> Result<(), HostError> {
+    let reference = Wasmi;
+    let backends: [&dyn Backend; 2] = [&WasmiCached, &WasmiAllProposals];
+    for fixture in fixtures() {
+        let expected = observe(&reference, &fixture)?;
+        for backend in backends {
+            let observed = observe(backend, &fixture)?;
+            pretty_assertions::assert_eq!(
+                expected,
+                observed,
+                "backend '{}' differs from '{}' on fixture '{}'",
+                backend.name(),
+                reference.name(),
+                fixture.name
+            );
+        }
+    }
+    Ok(())
+}
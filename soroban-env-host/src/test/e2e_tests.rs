@@ -0,0 +1,117 @@
+use sha2::{Digest, Sha256};
+use soroban_env_common::xdr::{
+    ContractDataDurability, DiagnosticEvent, Hash, HostFunction, LedgerEntry, LedgerEntryData,
+    LedgerFootprint, LedgerKey, LedgerKeyContractCode, ReadXdr, ScBytes, ScErrorCode, ScErrorType,
+    ScVal, SorobanResources, WriteXdr,
+};
+use soroban_test_wasms::ADD_I32;
+
+use crate::{
+    budget::Budget,
+    e2e_invoke::{invoke_host_function, InvokeHostFunctionResult},
+    LedgerInfo,
+};
+
+use super::util::{generate_account_id, generate_bytes_array};
+
+fn test_ledger_info() -> LedgerInfo {
+    LedgerInfo {
+        protocol_version: crate::meta::get_ledger_protocol_version(crate::meta::INTERFACE_VERSION),
+        sequence_number: 100,
+        timestamp: 0,
+        network_id: generate_bytes_array(),
+        base_reserve: 0,
+        min_persistent_entry_expiration: 4096,
+        min_temp_entry_expiration: 16,
+        max_entry_expiration: 6_312_000,
+    }
+}
+
+fn upload_wasm_key(wasm: &[u8]) -> LedgerKey {
+    LedgerKey::ContractCode(LedgerKeyContractCode {
+        hash: Hash(Sha256::digest(wasm).try_into().unwrap()),
+    })
+}
+
+fn invoke_upload_wasm(
+    wasm: &[u8],
+    footprint: LedgerFootprint,
+    diagnostic_events: &mut Vec<DiagnosticEvent>,
+) -> InvokeHostFunctionResult {
+    let host_fn = HostFunction::UploadContractWasm(wasm.try_into().unwrap());
+    let resources = SorobanResources {
+        footprint,
+        instructions: 0,
+        read_bytes: 0,
+        write_bytes: 0,
+    };
+    invoke_host_function(
+        &Budget::default(),
+        true,
+        host_fn.to_xdr().unwrap(),
+        resources.to_xdr().unwrap(),
+        generate_account_id().to_xdr().unwrap(),
+        Vec::<Vec<u8>>::new().into_iter(),
+        test_ledger_info(),
+        Vec::<Vec<u8>>::new().into_iter(),
+        Vec::<Vec<u8>>::new().into_iter(),
+        generate_bytes_array().to_vec(),
+        diagnostic_events,
+    )
+    .unwrap()
+}
+
+#[test]
+fn invoke_upload_wasm_returns_hash_and_ledger_changes() {
+    let key = upload_wasm_key(ADD_I32);
+    let footprint = LedgerFootprint {
+        read_only: Default::default(),
+        read_write: vec![key.clone()].try_into().unwrap(),
+    };
+    let mut diagnostic_events = vec![];
+    let res = invoke_upload_wasm(ADD_I32, footprint, &mut diagnostic_events);
+
+    let LedgerKey::ContractCode(LedgerKeyContractCode { hash }) = &key else {
+        unreachable!()
+    };
+    assert_eq!(
+        ScVal::from_xdr(res.encoded_invoke_result.unwrap()).unwrap(),
+        ScVal::Bytes(ScBytes(hash.0.try_into().unwrap()))
+    );
+    assert!(res.encoded_contract_events.is_empty());
+
+    assert_eq!(res.ledger_changes.len(), 1);
+    let change = &res.ledger_changes[0];
+    assert!(!change.read_only);
+    assert_eq!(change.encoded_key, key.to_xdr().unwrap());
+    assert_eq!(change.old_entry_size_bytes, 0);
+    let new_entry = LedgerEntry::from_xdr(change.encoded_new_value.as_ref().unwrap()).unwrap();
+    let LedgerEntryData::ContractCode(code_entry) = new_entry.data else {
+        panic!("expected contract code entry");
+    };
+    assert_eq!(code_entry.code.as_slice(), ADD_I32);
+
+    let expiration_change = change.expiration_change.as_ref().unwrap();
+    assert_eq!(
+        expiration_change.durability,
+        ContractDataDurability::Persistent
+    );
+    assert_eq!(expiration_change.old_expiration_ledger, 0);
+    assert_eq!(expiration_change.new_expiration_ledger, 100 + 4096 - 1);
+}
+
+#[test]
+fn invoke_failure_is_reported_in_result() {
+    // The code entry is missing from the footprint, so the upload fails.
+    let mut diagnostic_events = vec![];
+    let res = invoke_upload_wasm(ADD_I32, LedgerFootprint::default(), &mut diagnostic_events);
+
+    let err = res.encoded_invoke_result.err().unwrap();
+    assert!(err.error.is_type(ScErrorType::Storage));
+    assert!(err.error.is_code(ScErrorCode::ExceededLimit));
+    assert!(res.ledger_changes.is_empty());
+    assert!(res.encoded_contract_events.is_empty());
+    assert!(diagnostic_events
+        .iter()
+        .all(|e| !e.in_successful_contract_call));
+}
@@ -1,17 +1,21 @@
 use crate::{
     budget::AsBudget,
     events::{
-        InternalContractEvent, InternalDiagnosticArg, InternalDiagnosticEvent, InternalEvent,
+        EventFilter, HostEvent, InternalContractEvent, InternalDiagnosticArg,
+        InternalDiagnosticEvent, InternalEvent,
     },
     test::util::AsScVal,
     xdr::{
         ContractCostType, ContractEvent, ContractEventBody, ContractEventType, ContractEventV0,
-        ExtensionPoint, Hash, ScAddress, ScMap, ScMapEntry, ScVal,
+        ExtensionPoint, Hash, ScAddress, ScErrorCode, ScErrorType, ScMap, ScMapEntry, ScSymbol,
+        ScVal,
     },
-    ContractFunctionSet, Env, Host, HostError, Symbol, SymbolSmall, Val,
+    ContractFunctionSet, DiagnosticLevel, DiagnosticSeverity, Env, Host, HostError, Symbol,
+    SymbolSmall, Val,
 };
 use expect_test::expect;
-use std::rc::Rc;
+use soroban_test_wasms::VEC;
+use std::{cell::RefCell, rc::Rc};
 
 pub struct ContractWithSingleEvent;
 
@@ -103,6 +107,62 @@ fn test_event_rollback() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn test_event_hook() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id = host.add_host_object(ScAddress::Contract(Hash([0; 32])))?;
+    host.register_test_contract(id, Rc::new(ContractWithMultipleEvents {}))?;
+    let sym = Symbol::try_from_small_str("add").unwrap();
+    let args = host.test_vec_obj::<i32>(&[1, 2])?;
+
+    let seen: Rc<RefCell<Vec<HostEvent>>> = Default::default();
+    let seen_in_hook = Rc::clone(&seen);
+    host.set_event_hook(
+        EventFilter {
+            contract_id: Some(Hash([0; 32])),
+            topic_prefix: vec![ScVal::I32(0)],
+        },
+        move |e| {
+            seen_in_hook.borrow_mut().push(e.clone());
+            Ok(())
+        },
+    )?;
+    host.call(id, sym, args)?;
+    // Both the contract and system events are streamed, the diagnostic one
+    // is not.
+    let types: Vec<ContractEventType> = seen.borrow().iter().map(|e| e.event.type_).collect();
+    assert_eq!(
+        types,
+        vec![ContractEventType::Contract, ContractEventType::System]
+    );
+
+    // Events from other contracts or with other topics are filtered out.
+    seen.borrow_mut().clear();
+    let seen_in_hook = Rc::clone(&seen);
+    host.set_event_hook(
+        EventFilter {
+            contract_id: None,
+            topic_prefix: vec![ScVal::I32(1)],
+        },
+        move |e| {
+            seen_in_hook.borrow_mut().push(e.clone());
+            Ok(())
+        },
+    )?;
+    host.call(id, sym, args)?;
+    assert!(seen.borrow().is_empty());
+
+    // Hook errors fail the emitting call.
+    host.set_event_hook(EventFilter::default(), |_| {
+        Err((ScErrorType::Events, ScErrorCode::InternalError).into())
+    })?;
+    assert!(host.call(id, sym, args).is_err());
+
+    host.remove_event_hook()?;
+    host.call(id, sym, args)?;
+    Ok(())
+}
+
 #[test]
 fn test_internal_contract_events_metering_not_free() -> Result<(), HostError> {
     let host = Host::test_host();
@@ -142,7 +202,9 @@ fn test_internal_diagnostic_event_metering_free() -> Result<(), HostError> {
     ];
     let args = vec![InternalDiagnosticArg::XdrVal(1_i32.as_scval())];
     let de = Rc::new(InternalDiagnosticEvent {
+        severity: DiagnosticSeverity::Error,
         contract_id,
+        function: None,
         topics,
         args,
     });
@@ -163,3 +225,282 @@ fn test_internal_diagnostic_event_metering_free() -> Result<(), HostError> {
     assert_eq!(host.as_budget().get_mem_bytes_consumed()?, 0);
     Ok(())
 }
+
+#[test]
+fn test_diagnostic_level_none_records_nothing() -> Result<(), HostError> {
+    let host = Host::test_host()
+        .test_budget(100000, 100000)
+        .enable_model(ContractCostType::HostMemAlloc, 10, 0, 1, 0)
+        .enable_model(ContractCostType::HostMemCpy, 10, 0, 1, 0);
+    assert!(!host.is_debug()?);
+
+    let id = Hash([0; 32]);
+    let func = Symbol::try_from_small_str("f")?;
+    host.log_diagnostics("ignored", &[Val::from_u32(1).to_val()])?;
+    host.fn_call_diagnostics(&id, &func, &[])?;
+    host.fn_return_diagnostics(&id, &func, &Val::VOID.to_val())?;
+    assert!(host
+        .get_diagnostic_events(DiagnosticSeverity::Trace)?
+        .is_empty());
+    assert_eq!(host.as_budget().get_cpu_insns_consumed()?, 0);
+    assert_eq!(host.as_budget().get_mem_bytes_consumed()?, 0);
+    assert_eq!(host.as_budget().get_shadow_cpu_insns_consumed()?, 0);
+    assert_eq!(host.as_budget().get_shadow_mem_bytes_consumed()?, 0);
+
+    // The same calls record events once diagnostics are enabled, still
+    // without charging the budget.
+    host.set_diagnostic_level(DiagnosticLevel::Debug)?;
+    host.log_diagnostics("recorded", &[Val::from_u32(1).to_val()])?;
+    host.fn_call_diagnostics(&id, &func, &[])?;
+    host.fn_return_diagnostics(&id, &func, &Val::VOID.to_val())?;
+    assert_eq!(
+        host.get_diagnostic_events(DiagnosticSeverity::Trace)?.len(),
+        3
+    );
+    assert_eq!(host.as_budget().get_cpu_insns_consumed()?, 0);
+    assert_eq!(host.as_budget().get_mem_bytes_consumed()?, 0);
+    Ok(())
+}
+
+#[test]
+fn test_diagnostic_event_severity_and_origin() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id_obj = host.register_test_contract_wasm(VEC);
+    let contract_id = host.contract_id_from_address(id_obj)?;
+    host.enable_debug()?;
+    let sym = Symbol::try_from_small_str("vec_err").unwrap();
+    let args = host.test_vec_obj::<u32>(&[1])?;
+    let _ = host.try_call(id_obj, sym, args)?;
+
+    let all = host.get_diagnostic_events(DiagnosticSeverity::Trace)?;
+    // The call into the contract is made from the host, not a contract.
+    let fn_call = &all[0];
+    assert_eq!(fn_call.severity, DiagnosticSeverity::Trace);
+    assert_eq!(fn_call.contract_id, None);
+    assert_eq!(fn_call.function, None);
+
+    // The out-of-bounds access is attributed to the contract function that
+    // made it.
+    let errors = host.get_diagnostic_events(DiagnosticSeverity::Error)?;
+    assert!(!errors.is_empty() && errors.len() < all.len());
+    assert!(errors
+        .iter()
+        .all(|e| e.severity == DiagnosticSeverity::Error));
+    assert_eq!(errors[0].contract_id, Some(contract_id));
+    assert_eq!(
+        errors[0].function,
+        Some(ScSymbol("vec_err".try_into().unwrap()))
+    );
+    // The failed call is reflected in the externalized XDR.
+    assert!(!errors[0].event.in_successful_contract_call);
+    assert_eq!(errors[0].event.event.type_, ContractEventType::Diagnostic);
+    Ok(())
+}
+
+#[test]
+fn failed_host_function_reports_name_and_args() -> Result<(), HostError> {
+    use crate::xdr::ScVec;
+
+    let host = Host::test_host_with_recording_footprint();
+    let id_obj = host.register_test_contract_wasm(VEC);
+    host.enable_debug()?;
+    // `vec_err` inserts its argument at index 1 of an empty vector.
+    let sym = Symbol::try_from_small_str("vec_err").unwrap();
+    let args = host.test_vec_obj::<u32>(&[7])?;
+    let _ = host.try_call(id_obj, sym, args)?;
+
+    let escalation = host
+        .get_diagnostic_events(DiagnosticSeverity::Error)?
+        .into_iter()
+        .find_map(|e| {
+            let ContractEventBody::V0(body) = e.event.event.body;
+            let ScVal::Vec(Some(data)) = body.data else {
+                return None;
+            };
+            match data.first() {
+                Some(ScVal::String(msg)) if msg.0.to_string_lossy().ends_with(": vec_insert") => {
+                    Some(data.to_vec())
+                }
+                _ => None,
+            }
+        })
+        .expect("no escalation event for vec_insert");
+    assert_eq!(
+        escalation[1..],
+        [
+            ScVal::Vec(Some(ScVec::default())),
+            ScVal::U32(1),
+            ScVal::U32(7)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn log_from_linear_memory_records_single_event() -> Result<(), HostError> {
+    use crate::{
+        xdr::{ScString, ScVec},
+        BytesObject, U32Val, VmCallerEnv,
+    };
+    use soroban_test_wasms::LINEAR_MEMORY;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_debug()?;
+    let msg = "sum: {} + {}";
+    let msg_obj: BytesObject = host.bytes_new_from_slice(msg.as_bytes())?;
+    let vals = host.test_vec_obj::<u32>(&[1, 2])?;
+    let msg_len = U32Val::from(msg.len() as u32);
+    // Values are laid out right after the message, at an 8-byte boundary.
+    let vals_pos = U32Val::from(16);
+    host.with_test_vm_frame(LINEAR_MEMORY, |vmcaller| {
+        VmCallerEnv::bytes_copy_to_linear_memory(
+            &host,
+            vmcaller,
+            msg_obj,
+            U32Val::from(0),
+            U32Val::from(0),
+            msg_len,
+        )?;
+        VmCallerEnv::vec_unpack_to_linear_memory(&host, vmcaller, vals, vals_pos, U32Val::from(2))?;
+        let before = host.get_diagnostic_events(DiagnosticSeverity::Debug)?.len();
+        VmCallerEnv::log_from_linear_memory(
+            &host,
+            vmcaller,
+            U32Val::from(0),
+            msg_len,
+            vals_pos,
+            U32Val::from(2),
+        )?;
+        let events = host.get_diagnostic_events(DiagnosticSeverity::Debug)?;
+        assert_eq!(events.len(), before + 1);
+        let event = events.last().unwrap();
+        assert_eq!(event.severity, DiagnosticSeverity::Debug);
+        assert_eq!(event.function, Some(ScSymbol("test".try_into().unwrap())));
+        let ContractEventBody::V0(ContractEventV0 { topics, data }) = &event.event.event.body;
+        assert_eq!(
+            topics.to_vec(),
+            vec![ScVal::Symbol(ScSymbol("log".try_into().unwrap()))]
+        );
+        let expected = ScVec(
+            vec![
+                ScVal::String(ScString(msg.try_into().unwrap())),
+                ScVal::U32(1),
+                ScVal::U32(2),
+            ]
+            .try_into()
+            .unwrap(),
+        );
+        assert_eq!(data, &ScVal::Vec(Some(expected)));
+        Ok(())
+    })?;
+
+    // Nothing is logged when diagnostics are disabled.
+    let host = Host::test_host_with_recording_footprint();
+    host.with_test_vm_frame(LINEAR_MEMORY, |vmcaller| {
+        VmCallerEnv::log_from_linear_memory(
+            &host,
+            vmcaller,
+            U32Val::from(0),
+            U32Val::from(0),
+            U32Val::from(0),
+            U32Val::from(0),
+        )?;
+        Ok(())
+    })?;
+    assert!(host
+        .get_diagnostic_events(DiagnosticSeverity::Trace)?
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn display_val_renders_nested_objects() -> Result<(), HostError> {
+    use crate::{
+        display::ScValDisplay,
+        xdr::{Int128Parts, ScVec},
+    };
+
+    let host = Host::test_host_with_recording_footprint();
+    let to = ScVal::Address(ScAddress::Contract(Hash([0; 32])));
+    let map = host.to_host_val(&ScVal::Map(Some(
+        host.map_err(
+            vec![
+                ScMapEntry {
+                    key: ScVal::Symbol(ScSymbol("amount".try_into().unwrap())),
+                    val: ScVal::I128(Int128Parts { hi: 0, lo: 100 }),
+                },
+                ScMapEntry {
+                    key: ScVal::Symbol(ScSymbol("to".try_into().unwrap())),
+                    val: to,
+                },
+            ]
+            .try_into(),
+        )?,
+    )))?;
+    assert_eq!(
+        host.display_val(map)?,
+        format!(
+            "{{amount: 100_i128, to: {}}}",
+            stellar_strkey::Contract([0; 32])
+        )
+    );
+
+    // Deeply nested values are elided rather than printed in full.
+    let mut v = ScVal::Vec(Some(ScVec::default()));
+    for _ in 0..100 {
+        v = ScVal::Vec(Some(ScVec::try_from(vec![v])?));
+    }
+    assert!(ScValDisplay(&v).to_string().contains("[...]"));
+    Ok(())
+}
+
+#[test]
+fn contract_events_respect_event_limits() -> Result<(), HostError> {
+    use crate::events::EventLimits;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited()?;
+    let emit = |topics: &[Val], data: Val| -> Result<(), HostError> {
+        host.with_test_contract_frame(Hash([0; 32]), Symbol::try_from_small_str("emit")?, || {
+            let topics = host.vec_new_from_slice(topics)?;
+            host.contract_event(topics, data)?;
+            Ok(Val::VOID.into())
+        })?;
+        Ok(())
+    };
+    let is_limit_err = |res: Result<(), HostError>| {
+        HostError::result_matches_err(res, (ScErrorType::Events, ScErrorCode::ExceededLimit))
+    };
+    let topic = host.bytes_new_from_slice(&[7; 16])?.to_val();
+    let nested = host
+        .vec_new_from_slice(&[host.vec_new()?.to_val()])?
+        .to_val();
+
+    // No limits by default.
+    assert_eq!(host.get_event_limits()?, EventLimits::default());
+    emit(&[topic; 10], nested)?;
+
+    host.set_event_limits(EventLimits {
+        max_topics: 2,
+        max_topic_bytes: 24,
+        max_data_depth: 2,
+    })?;
+    emit(&[topic; 2], nested)?;
+    assert!(is_limit_err(emit(&[topic; 3], Val::VOID.into())));
+    // A 16-byte `ScVal::Bytes` takes 24 bytes of XDR: the discriminant, the
+    // length and the contents.
+    let large_topic = host.bytes_new_from_slice(&[7; 17])?.to_val();
+    assert!(is_limit_err(emit(&[large_topic], Val::VOID.into())));
+    let too_nested = host.vec_new_from_slice(&[nested])?.to_val();
+    assert!(is_limit_err(emit(&[], too_nested)));
+
+    // Only the events that were within the limits were recorded.
+    let contract_events = host
+        .get_events()?
+        .0
+        .iter()
+        .filter(|e| e.event.type_ == ContractEventType::Contract)
+        .count();
+    assert_eq!(contract_events, 2);
+    Ok(())
+}
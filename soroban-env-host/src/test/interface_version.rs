@@ -0,0 +1,51 @@
+use crate::{
+    meta::{
+        get_ledger_protocol_version, get_pre_release_version, make_interface_version,
+        INTERFACE_VERSION,
+    },
+    vm::Vm,
+    xdr::{ScErrorCode, ScErrorType},
+    Host, HostError, Symbol,
+};
+use soroban_synth_wasm::{Arity, ModEmitter};
+
+fn wasm_module_with_interface_version(interface_version: u64) -> Vec<u8> {
+    let mut fe = ModEmitter::new_with_interface_version(interface_version).func(Arity(0), 0);
+    fe.push(Symbol::try_from_small_str("pass").unwrap());
+    fe.finish_and_export("test").finish()
+}
+
+fn instantiate(host: &Host, interface_version: u64) -> Result<(), HostError> {
+    let wasm = wasm_module_with_interface_version(interface_version);
+    Vm::new(host, [0; 32].into(), wasm.as_slice()).map(|_| ())
+}
+
+#[test]
+fn contract_interface_version_is_checked() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let proto = get_ledger_protocol_version(INTERFACE_VERSION);
+    let pre = get_pre_release_version(INTERFACE_VERSION);
+    let rejected = (ScErrorType::WasmVm, ScErrorCode::InvalidInput);
+
+    // Built against this host's interface exactly.
+    instantiate(&host, INTERFACE_VERSION)?;
+    // Built against a finalized older protocol.
+    instantiate(&host, make_interface_version(proto - 1, 0))?;
+
+    // Pre-releases of older protocols are never supported.
+    assert!(HostError::result_matches_err(
+        instantiate(&host, make_interface_version(proto - 1, 1)),
+        rejected
+    ));
+    // Pre-releases of the current protocol have to match exactly.
+    assert!(HostError::result_matches_err(
+        instantiate(&host, make_interface_version(proto, pre + 1)),
+        rejected
+    ));
+    // Newer protocols are never supported.
+    assert!(HostError::result_matches_err(
+        instantiate(&host, make_interface_version(proto + 1, 0)),
+        rejected
+    ));
+    Ok(())
+}
@@ -3,9 +3,10 @@ use std::rc::Rc;
 use expect_test::expect;
 use soroban_env_common::{
     xdr::{self, ScErrorCode},
-    Env, EnvBase, TryFromVal, Val,
+    AddressObject, Env, EnvBase, SymbolSmall, TryFromVal, Val,
 };
 
+use super::observe::ObservedHost;
 use crate::{
     events::HostEvent, xdr::ScErrorType, ContractFunctionSet, Error, Host, HostError, Symbol, Tag,
 };
@@ -152,6 +153,28 @@ fn invoke_cross_contract_indirect() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn observe_cross_contract_indirect() -> Result<(), HostError> {
+    let host = ObservedHost::new(
+        "observe_cross_contract_indirect",
+        Host::test_host_with_recording_footprint(),
+    );
+    // Observations need stable contract IDs, so these are registered from a
+    // fixed account and salts.
+    let account = xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256([1; 32])));
+    let id0_obj = host.register_test_contract_wasm_from_source_account(
+        INVOKE_CONTRACT,
+        account.clone(),
+        [0; 32],
+    );
+    let id1_obj = host.register_test_contract_wasm_from_source_account(ADD_I32, account, [1; 32]);
+    let sym = Symbol::try_from_small_str("add_with").unwrap();
+    let args = host.test_vec_obj::<i32>(&[5, 6])?;
+    let args = host.vec_push_back(args, id1_obj.to_val())?;
+    host.call(id0_obj, sym, args)?;
+    Ok(())
+}
+
 #[test]
 fn invoke_cross_contract_indirect_err() -> Result<(), HostError> {
     let host = Host::test_host_with_recording_footprint();
@@ -253,6 +276,114 @@ fn native_invoke_return_err_variants() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn unhandled_try_call_errors_are_tracked() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([0; 32])))?;
+    host.register_test_contract(addr, Rc::new(ReturnContractError))?;
+    let sym = Symbol::try_from_small_str("go")?;
+    let args = host.vec_new()?;
+
+    // Nothing is tracked until the check is enabled.
+    host.try_call(addr, sym, args)?;
+    assert!(host.take_unhandled_errors()?.is_empty());
+
+    host.enable_unhandled_error_check()?;
+    host.try_call(addr, sym, args)?;
+    host.try_call(addr, sym, args)?;
+    // Errors returned by `call` fail the call, so are never tracked.
+    assert!(host.call(addr, sym, args).is_err());
+    assert_eq!(
+        host.take_unhandled_errors()?,
+        vec![ReturnContractError::ERR, ReturnContractError::ERR]
+    );
+    assert!(host.take_unhandled_errors()?.is_empty());
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "unhandled error(s) returned by try_call")]
+fn dropping_host_with_unhandled_try_call_error_panics() {
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host
+        .add_host_object(xdr::ScAddress::Contract(xdr::Hash([0; 32])))
+        .unwrap();
+    host.register_test_contract(addr, Rc::new(ReturnContractError))
+        .unwrap();
+    host.enable_unhandled_error_check().unwrap();
+    let _ = host.try_call(
+        addr,
+        Symbol::try_from_small_str("go").unwrap(),
+        host.vec_new().unwrap(),
+    );
+}
+
+#[test]
+fn typed_native_contract_functions() -> Result<(), HostError> {
+    use crate::{TryIntoVal, TypedContractFunctionSet};
+
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([1; 32])))?;
+    let contract = TypedContractFunctionSet::new()
+        .with_fn("zero", |_host: &Host| -> Result<u32, HostError> { Ok(0) })
+        .with_fn(
+            "add",
+            |_host: &Host, a: u32, b: u32| -> Result<u32, HostError> {
+                a.checked_add(b)
+                    .ok_or_else(|| (ScErrorType::Value, ScErrorCode::ArithDomain).into())
+            },
+        )
+        .with_fn("neg", |_host: &Host, a: i128| -> Result<i128, HostError> {
+            Ok(-a)
+        });
+    host.register_test_contract(addr, Rc::new(contract))?;
+
+    let res = host.call(addr, Symbol::try_from_small_str("zero")?, host.vec_new()?)?;
+    assert_eq!(u32::try_from_val(&host, &res)?, 0);
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj::<u32>(&[4, 7])?,
+    )?;
+    assert_eq!(u32::try_from_val(&host, &res)?, 11);
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("neg")?,
+        host.vec_new_from_slice(&[i128::MAX.try_into_val(&host)?])?,
+    )?;
+    assert_eq!(i128::try_from_val(&host, &res)?, -i128::MAX);
+
+    // Errors returned by the function fail the call.
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj::<u32>(&[u32::MAX, 1])?,
+    );
+    let code = (ScErrorType::Value, ScErrorCode::ArithDomain);
+    assert!(HostError::result_matches_err(res, code));
+
+    // So do calls with the wrong number or types of arguments.
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj::<u32>(&[1])?,
+    );
+    let code = (ScErrorType::Context, ScErrorCode::UnexpectedSize);
+    assert!(HostError::result_matches_err(res, code));
+    let res = host.call(
+        addr,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj::<i32>(&[1, 2])?,
+    );
+    assert!(res.is_err());
+
+    // Unknown functions are reported the same way as for other test contracts.
+    let res = host.call(addr, Symbol::try_from_small_str("sub")?, host.vec_new()?);
+    let code = (ScErrorType::Context, ScErrorCode::MissingValue);
+    assert!(HostError::result_matches_err(res, code));
+    Ok(())
+}
+
 #[test]
 fn wasm_invoke_return_err_variants() -> Result<(), HostError> {
     // Here we test several variants of returning-a-Val-that-is-an-Error
@@ -281,3 +412,490 @@ fn wasm_invoke_return_err_variants() -> Result<(), HostError> {
     }
     Ok(())
 }
+
+#[test]
+fn call_trace_records_nested_invocations() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id0_obj = host.register_test_contract_wasm(INVOKE_CONTRACT);
+    let id1_obj = host.register_test_contract_wasm(ADD_I32);
+    assert_eq!(host.get_call_trace()?, None);
+    host.enable_call_trace()?;
+
+    let sym = Symbol::try_from_small_str("add_with").unwrap();
+    let args = host.test_vec_obj::<i32>(&[5, 6])?;
+    let args = host.vec_push_back(args, id1_obj.to_val())?;
+    host.call(id0_obj, sym, args)?;
+
+    let trace = host.get_call_trace()?.unwrap();
+    assert_eq!(trace.len(), 1);
+    let outer = &trace[0];
+    assert_eq!(outer.contract_id, host.contract_id_from_address(id0_obj)?);
+    assert_eq!(
+        outer.function,
+        xdr::ScSymbol("add_with".try_into().unwrap())
+    );
+    assert_eq!(outer.args.len(), 3);
+    assert_eq!(outer.result, Ok(xdr::ScVal::I32(11)));
+    assert_eq!(outer.sub_calls.len(), 1);
+    let inner = &outer.sub_calls[0];
+    assert_eq!(inner.contract_id, host.contract_id_from_address(id1_obj)?);
+    assert_eq!(inner.function, xdr::ScSymbol("add".try_into().unwrap()));
+    assert_eq!(inner.args, vec![xdr::ScVal::I32(5), xdr::ScVal::I32(6)]);
+    assert_eq!(inner.result, Ok(xdr::ScVal::I32(11)));
+    assert!(inner.sub_calls.is_empty());
+    assert!(matches!(outer.to_scval(), Ok(xdr::ScVal::Map(Some(_)))));
+
+    // Failing calls record their error.
+    let args = host.test_vec_obj::<i32>(&[i32::MAX, 1])?;
+    let args = host.vec_push_back(args, id1_obj.to_val())?;
+    assert!(host.call(id0_obj, sym, args).is_err());
+    let trace = host.get_call_trace()?.unwrap();
+    assert_eq!(trace.len(), 2);
+    let err: Error = (ScErrorType::WasmVm, ScErrorCode::InvalidAction).into();
+    assert_eq!(trace[1].sub_calls[0].result, Err(err));
+
+    // Calls that fail before the contract runs are recorded too, and leave
+    // the trace balanced for the calls that follow.
+    let missing = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([7; 32])))?;
+    assert!(host.call(missing, sym, args).is_err());
+    let args = host.test_vec_obj::<i32>(&[1, 2])?;
+    let args = host.vec_push_back(args, id1_obj.to_val())?;
+    host.call(id0_obj, sym, args)?;
+    let trace = host.get_call_trace()?.unwrap();
+    assert_eq!(trace.len(), 4);
+    assert!(trace[2].result.is_err());
+    assert!(trace[2].sub_calls.is_empty());
+    assert_eq!(trace[3].result, Ok(xdr::ScVal::I32(3)));
+
+    host.disable_call_trace()?;
+    assert_eq!(host.get_call_trace()?, None);
+    Ok(())
+}
+
+#[test]
+fn per_frame_costs_attribute_nested_invocations() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let id0_obj = host.register_test_contract_wasm(INVOKE_CONTRACT);
+    let id1_obj = host.register_test_contract_wasm(ADD_I32);
+    host.reset_per_frame_costs()?;
+    assert!(host.get_per_frame_costs()?.is_empty());
+
+    let sym = Symbol::try_from_small_str("add_with").unwrap();
+    let args = host.test_vec_obj::<i32>(&[5, 6])?;
+    let args = host.vec_push_back(args, id1_obj.to_val())?;
+    let budget = host.budget_cloned();
+    let cpu_before = budget.get_cpu_insns_consumed()?;
+    host.call(id0_obj, sym, args)?;
+    let cpu_total = budget.get_cpu_insns_consumed()? - cpu_before;
+
+    // The inner frame is popped first.
+    let costs = host.get_per_frame_costs()?;
+    assert_eq!(costs.len(), 2);
+    let (inner, outer) = (&costs[0], &costs[1]);
+    assert_eq!(inner.contract_id, host.contract_id_from_address(id1_obj)?);
+    assert_eq!(inner.function, xdr::ScSymbol("add".try_into().unwrap()));
+    assert_eq!(outer.contract_id, host.contract_id_from_address(id0_obj)?);
+    assert_eq!(
+        outer.function,
+        xdr::ScSymbol("add_with".try_into().unwrap())
+    );
+    // Costs are inclusive of sub-invocations.
+    assert!(inner.cpu_insns > 0 && inner.mem_bytes > 0);
+    assert!(outer.cpu_insns > inner.cpu_insns);
+    assert!(outer.mem_bytes > inner.mem_bytes);
+    assert!(outer.cpu_insns <= cpu_total);
+
+    host.reset_per_frame_costs()?;
+    assert!(host.get_per_frame_costs()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn dump_objects_attributes_objects_to_creating_function() -> Result<(), HostError> {
+    use crate::TypedContractFunctionSet;
+
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([1; 32])))?;
+    let contract = TypedContractFunctionSet::new().with_fn(
+        "make",
+        |host: &Host, n: u32| -> Result<u32, HostError> {
+            for _ in 0..n {
+                host.bytes_new_from_slice(&[0; 100])?;
+            }
+            Ok(n)
+        },
+    );
+    host.register_test_contract(addr, Rc::new(contract))?;
+    host.call(
+        addr,
+        Symbol::try_from_small_str("make")?,
+        host.test_vec_obj::<u32>(&[4])?,
+    )?;
+
+    let summaries = host.dump_objects()?;
+    assert_eq!(
+        summaries.iter().map(|s| s.count).sum::<usize>(),
+        host.try_borrow_objects()?.len()
+    );
+    let made = summaries
+        .iter()
+        .find(|s| {
+            s.object_type == "Bytes"
+                && s.created_in
+                    == Some((
+                        xdr::Hash([1; 32]),
+                        xdr::ScSymbol("make".try_into().unwrap()),
+                    ))
+        })
+        .unwrap();
+    assert_eq!(made.count, 4);
+    assert!(made.bytes >= 400);
+    // The contract address was created by the test, outside of any frame.
+    assert!(summaries
+        .iter()
+        .any(|s| s.object_type == "Address" && s.created_in.is_none()));
+    // Largest groups come first.
+    assert!(summaries.windows(2).all(|w| w[0].bytes >= w[1].bytes));
+    Ok(())
+}
+
+#[test]
+fn object_valued_instance_storage_is_readable_across_invocations() -> Result<(), HostError> {
+    use crate::{StorageType, TypedContractFunctionSet};
+
+    // Loading instance storage converts its values to host objects while
+    // the current frame is borrowed, which used to fail when recording the
+    // origin of those objects.
+    let host = Host::test_host_with_recording_footprint();
+    let addr = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([2; 32])))?;
+    let contract = TypedContractFunctionSet::new()
+        .with_fn("put", |host: &Host| -> Result<Val, HostError> {
+            let key = Symbol::try_from_small_str("admin")?.to_val();
+            let admin = host.get_current_contract_address()?.to_val();
+            host.put_contract_data(key, admin, StorageType::Instance)?;
+            Ok(Val::VOID.into())
+        })
+        .with_fn("get", |host: &Host| -> Result<Val, HostError> {
+            let key = Symbol::try_from_small_str("admin")?.to_val();
+            host.get_contract_data(key, StorageType::Instance)
+        });
+    host.register_test_contract(addr, Rc::new(contract))?;
+    host.call(addr, Symbol::try_from_small_str("put")?, host.vec_new()?)?;
+    for _ in 0..2 {
+        let admin = host.call(addr, Symbol::try_from_small_str("get")?, host.vec_new()?)?;
+        let admin = AddressObject::try_from_val(&host, &admin)?;
+        assert_eq!(
+            host.scaddress_from_address(admin)?,
+            xdr::ScAddress::Contract(xdr::Hash([2; 32]))
+        );
+    }
+    // The admin addresses loaded from instance storage are attributed to the
+    // function that loaded them.
+    let get = Some((xdr::Hash([2; 32]), xdr::ScSymbol("get".try_into().unwrap())));
+    assert!(host
+        .dump_objects()?
+        .iter()
+        .any(|s| s.object_type == "Address" && s.created_in == get));
+    Ok(())
+}
+
+#[test]
+fn snapshot_and_restore_host_state() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_debug()?;
+    let snapshot = host.snapshot_state()?;
+    let objects_before = host.try_borrow_objects()?.len();
+    let cpu_before = host.budget_cloned().get_cpu_insns_consumed()?;
+    let events_before = host.get_events()?.0.len();
+    let event_limits_before = host.get_event_limits()?;
+
+    // Configuration changed after the snapshot is rolled back too.
+    host.set_event_limits(crate::events::EventLimits {
+        max_topics: 1,
+        ..event_limits_before
+    })?;
+    host.set_ledger_auto_advance(Some(crate::LedgerAutoAdvance {
+        sequence_number: 1,
+        timestamp: 5,
+    }))?;
+    host.set_storage_commit_hook(|_, _| Ok(()))?;
+
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let contract_id = host.contract_id_from_address(contract_id_obj)?;
+    let sym = Symbol::try_from_small_str("add")?;
+    let res = host.call(contract_id_obj, sym, host.test_vec_obj(&[1i32, 2i32])?)?;
+    assert_eq!(i32::try_from_val(&host, &res)?, 3);
+    assert!(host.get_events()?.0.len() > events_before);
+
+    host.restore_state(snapshot.clone())?;
+    assert_eq!(host.try_borrow_objects()?.len(), objects_before);
+    assert_eq!(host.budget_cloned().get_cpu_insns_consumed()?, cpu_before);
+    assert_eq!(host.get_events()?.0.len(), events_before);
+    assert_eq!(host.get_event_limits()?, event_limits_before);
+    assert!(host.try_borrow_ledger_auto_advance()?.is_none());
+    assert!(host.try_borrow_storage_commit_hook()?.is_none());
+
+    // The contract instance and code are gone from storage, so calling the
+    // contract again fails.
+    let contract_id_obj = host.add_host_object(xdr::ScAddress::Contract(contract_id))?;
+    assert!(host
+        .call(contract_id_obj, sym, host.test_vec_obj(&[1i32, 2i32])?)
+        .is_err());
+
+    // The same snapshot can be restored more than once.
+    host.restore_state(snapshot)?;
+    assert_eq!(host.try_borrow_objects()?.len(), objects_before);
+    Ok(())
+}
+
+#[test]
+fn fork_host_for_speculative_execution() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let sym = Symbol::try_from_small_str("add")?;
+    let args = host.test_vec_obj(&[1i32, 2i32])?;
+    let objects_before = host.try_borrow_objects()?.len();
+    let cpu_before = host.budget_cloned().get_cpu_insns_consumed()?;
+    let events_before = host.get_events()?.0.len();
+
+    // Values of the parent host can be used in the fork.
+    let fork = host.fork()?;
+    let res = fork.call(contract_id_obj, sym, args)?;
+    assert_eq!(i32::try_from_val(&fork, &res)?, 3);
+    assert!(fork.try_borrow_objects()?.len() > objects_before);
+    assert!(fork.budget_cloned().get_cpu_insns_consumed()? > cpu_before);
+    assert!(fork.get_events()?.0.len() > events_before);
+
+    // None of that affected the parent host.
+    assert_eq!(host.try_borrow_objects()?.len(), objects_before);
+    assert_eq!(host.budget_cloned().get_cpu_insns_consumed()?, cpu_before);
+    assert_eq!(host.get_events()?.0.len(), events_before);
+
+    // Storage is copied too: a contract created in the fork doesn't exist in
+    // the parent.
+    let forked_contract = fork.register_test_contract_wasm(ADD_I32);
+    let forked_id = fork.contract_id_from_address(forked_contract)?;
+    let parent_address = host.add_host_object(xdr::ScAddress::Contract(forked_id))?;
+    assert!(host.call(parent_address, sym, args).is_err());
+    assert!(host.call(contract_id_obj, sym, args).is_ok());
+    Ok(())
+}
+
+#[test]
+fn fork_host_gets_its_own_module_cache() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_module_cache()?;
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    let sym = Symbol::try_from_small_str("add")?;
+    let args = host.test_vec_obj(&[1i32, 2i32])?;
+    let cache = host.get_module_cache()?.unwrap();
+    cache.clear()?;
+
+    // Modules parsed by the fork don't end up in the cache of the parent.
+    let fork = host.fork()?;
+    fork.call(contract_id_obj, sym, args)?;
+    assert_eq!(fork.get_module_cache()?.unwrap().len()?, 1);
+    assert_eq!(cache.len()?, 0);
+
+    // A fork starts out with the modules cached by the parent.
+    host.call(contract_id_obj, sym, args)?;
+    assert_eq!(cache.len()?, 1);
+    let fork = host.fork()?;
+    assert_eq!(fork.get_module_cache()?.unwrap().len()?, 1);
+    fork.get_module_cache()?.unwrap().clear()?;
+    assert_eq!(cache.len()?, 1);
+    Ok(())
+}
+
+// A pair of contracts where `outer` calls `middle`, which calls back into
+// `outer`. `outer` decides whether to permit re-entry from `middle`.
+struct ReentrantContract;
+
+impl ReentrantContract {
+    const OUTER_ID: [u8; 32] = [1; 32];
+    const MIDDLE_ID: [u8; 32] = [2; 32];
+}
+
+impl ContractFunctionSet for ReentrantContract {
+    fn call(&self, func: &Symbol, host: &Host, args: &[Val]) -> Option<Val> {
+        let func = SymbolSmall::try_from(func.to_val()).ok()?;
+        let outer = host
+            .add_host_object(xdr::ScAddress::Contract(xdr::Hash(Self::OUTER_ID)))
+            .unwrap();
+        let middle = host
+            .add_host_object(xdr::ScAddress::Contract(xdr::Hash(Self::MIDDLE_ID)))
+            .unwrap();
+        let val = if func == SymbolSmall::try_from_str("outer").unwrap() {
+            // args: [allowed callee, function to call on `middle`]
+            if let Ok(allowed) = AddressObject::try_from(args[0]) {
+                host.allow_reentry(allowed).unwrap();
+            }
+            host.try_call(middle, args[1].try_into().unwrap(), host.vec_new().unwrap())
+                .unwrap()
+        } else if func == SymbolSmall::try_from_str("middle").unwrap() {
+            host.call(
+                outer,
+                Symbol::try_from_small_str("inner").unwrap(),
+                host.vec_new().unwrap(),
+            )
+            .unwrap()
+        } else if func == SymbolSmall::try_from_str("middle_bad").unwrap() {
+            host.call(
+                outer,
+                Symbol::try_from_small_str("inner").unwrap(),
+                host.vec_new().unwrap(),
+            )
+            .unwrap();
+            Error::from_contract_error(1).to_val()
+        } else if func == SymbolSmall::try_from_str("inner").unwrap() {
+            let topics = host.test_vec_obj(&[1]).unwrap();
+            host.contract_event(topics, 0_u32.into()).unwrap();
+            7_u32.into()
+        } else {
+            return None;
+        };
+        Some(val)
+    }
+}
+
+#[test]
+fn allow_reentry_permits_specific_callee() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let outer = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash(
+        ReentrantContract::OUTER_ID,
+    )))?;
+    let middle = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash(
+        ReentrantContract::MIDDLE_ID,
+    )))?;
+    host.register_test_contract(outer, Rc::new(ReentrantContract))?;
+    host.register_test_contract(middle, Rc::new(ReentrantContract))?;
+    let outer_fn = Symbol::try_from_small_str("outer")?;
+    let middle_fn = Symbol::try_from_small_str("middle")?;
+    let call_args = |allowed: Val, func: Symbol| host.vec_new_from_slice(&[allowed, func.to_val()]);
+    let reentry_err: Val =
+        Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction).into();
+
+    // Re-entry is prohibited by default.
+    let res = host.call(outer, outer_fn, call_args(().into(), middle_fn)?)?;
+    assert!(res.shallow_eq(&reentry_err));
+
+    // Permitting some other contract doesn't help.
+    let res = host.call(outer, outer_fn, call_args(outer.to_val(), middle_fn)?)?;
+    assert!(res.shallow_eq(&reentry_err));
+
+    // Permitting the callee allows it to re-enter.
+    let res = host.call(outer, outer_fn, call_args(middle.to_val(), middle_fn)?)?;
+    assert!(res.shallow_eq(&7_u32.into()));
+
+    // The permission only lasts for the invocation that granted it.
+    let res = host.call(middle, middle_fn, host.vec_new()?);
+    assert!(res.is_ok());
+    let res = host.call(outer, outer_fn, call_args(().into(), middle_fn)?)?;
+    assert!(res.shallow_eq(&reentry_err));
+
+    // If the callee fails after re-entering, the effects of the re-entrant
+    // call are rolled back along with it.
+    let events_before = host.get_events()?.0.len();
+    let res = host.call(
+        outer,
+        outer_fn,
+        call_args(middle.to_val(), Symbol::try_from_small_str("middle_bad")?)?,
+    )?;
+    assert!(res.shallow_eq(&Error::from_contract_error(1).into()));
+    let events = host.get_events()?.0;
+    let new_contract_events: Vec<&HostEvent> = events[events_before..]
+        .iter()
+        .filter(|e| e.event.type_ == xdr::ContractEventType::Contract)
+        .collect();
+    assert_eq!(new_contract_events.len(), 1);
+    assert!(new_contract_events[0].failed_call);
+    Ok(())
+}
+
+#[test]
+fn get_current_call_stack_lists_invoking_contracts() -> Result<(), HostError> {
+    use crate::TypedContractFunctionSet;
+    use soroban_env_common::VecObject;
+
+    let host = Host::test_host_with_recording_footprint();
+    let outer = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([1; 32])))?;
+    let inner = host.add_host_object(xdr::ScAddress::Contract(xdr::Hash([2; 32])))?;
+    let stack_fn = Symbol::try_from_small_str("stack")?;
+    host.register_test_contract(
+        inner,
+        Rc::new(
+            TypedContractFunctionSet::new().with_fn(
+                "stack",
+                |host: &Host| -> Result<Val, HostError> {
+                    Ok(host.get_current_call_stack()?.to_val())
+                },
+            ),
+        ),
+    )?;
+    host.register_test_contract(
+        outer,
+        Rc::new(TypedContractFunctionSet::new().with_fn(
+            "call",
+            |host: &Host, inner: AddressObject| -> Result<Val, HostError> {
+                host.call(inner, Symbol::try_from_small_str("stack")?, host.vec_new()?)
+            },
+        )),
+    )?;
+    let check_stack = |stack: Val, expected: &[AddressObject]| -> Result<(), HostError> {
+        let stack = VecObject::try_from_val(&host, &stack)?;
+        assert_eq!(u32::from(host.vec_len(stack)?) as usize, expected.len());
+        for (i, addr) in expected.iter().enumerate() {
+            let elt = host.vec_get(stack, (i as u32).into())?;
+            assert_eq!(host.obj_cmp(elt, addr.to_val())?, 0);
+        }
+        Ok(())
+    };
+
+    // Outside of any contract the stack is empty.
+    check_stack(host.get_current_call_stack()?.to_val(), &[])?;
+    // A direct invocation only sees itself...
+    check_stack(host.call(inner, stack_fn, host.vec_new()?)?, &[inner])?;
+    // ... while a nested one sees its caller first.
+    let args = host.vec_new_from_slice(&[inner.to_val()])?;
+    check_stack(
+        host.call(outer, Symbol::try_from_small_str("call")?, args)?,
+        &[outer, inner],
+    )?;
+    Ok(())
+}
+
+#[test]
+fn function_call_counts_count_calls_from_wasm() -> Result<(), HostError> {
+    use soroban_synth_wasm::{Arity, ModEmitter, Operand};
+
+    let mut fe = ModEmitter::new().func(Arity(0), 0);
+    for _ in 0..3 {
+        fe.vec_new();
+        fe.drop();
+    }
+    fe.obj_from_u64(Operand::Const64(5));
+    fe.drop();
+    fe.push(Symbol::try_from_small_str("pass")?);
+    let wasm = fe.finish_and_export("test").finish();
+
+    let host = Host::test_host_with_recording_footprint();
+    let contract = host.register_test_contract_wasm(wasm.as_slice());
+    let test = Symbol::try_from_small_str("test")?;
+
+    // Nothing is counted until counting is enabled.
+    host.call(contract, test, host.vec_new()?)?;
+    assert_eq!(host.get_function_call_counts()?, None);
+
+    host.enable_function_call_counts()?;
+    host.call(contract, test, host.vec_new()?)?;
+    host.call(contract, test, host.vec_new()?)?;
+    let counts = host.get_function_call_counts()?.unwrap();
+    assert_eq!(
+        counts.into_iter().collect::<Vec<_>>(),
+        vec![("obj_from_u64", 2), ("vec_new", 6)]
+    );
+
+    host.disable_function_call_counts()?;
+    assert_eq!(host.get_function_call_counts()?, None);
+    Ok(())
+}
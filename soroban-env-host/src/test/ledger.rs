@@ -1,11 +1,16 @@
-use soroban_env_common::{xdr::ScBytes, Env};
+use soroban_env_common::{
+    xdr::{ScBytes, ScErrorCode, ScErrorType},
+    Env, TryFromVal,
+};
 
 use crate::{
     budget::Budget,
     storage::{Footprint, Storage, StorageMap},
-    Host, HostError, LedgerInfo,
+    DiagnosticLevel, Host, HostBuilder, HostError, LedgerInfo,
 };
 
+use super::util::generate_account_id;
+
 #[test]
 fn ledger_network_id() -> Result<(), HostError> {
     let budget = Budget::default();
@@ -28,3 +33,206 @@ fn ledger_network_id() -> Result<(), HostError> {
     assert_eq!(np, vec![7; 32],);
     Ok(())
 }
+
+#[test]
+fn ledger_network_id_follows_ledger_info() -> Result<(), HostError> {
+    let host = Host::default();
+    assert!(HostError::result_matches_err(
+        host.get_ledger_network_id(),
+        (ScErrorType::Context, ScErrorCode::InternalError)
+    ));
+
+    host.set_ledger_info(builder_ledger_info())?;
+    let first = host.get_ledger_network_id()?;
+    host.with_mut_ledger_info(|li| li.network_id = [8; 32])?;
+    let second = host.get_ledger_network_id()?;
+    let np = host.visit_obj(second, |np: &ScBytes| Ok(np.to_vec()))?;
+    assert_eq!(np, vec![8; 32]);
+    assert_ne!(host.obj_cmp(first.into(), second.into())?, 0);
+    Ok(())
+}
+
+fn builder_ledger_info() -> LedgerInfo {
+    LedgerInfo {
+        protocol_version: crate::meta::get_ledger_protocol_version(crate::meta::INTERFACE_VERSION),
+        sequence_number: 0,
+        timestamp: 0,
+        network_id: [7; 32],
+        base_reserve: 0,
+        min_persistent_entry_expiration: 4096,
+        min_temp_entry_expiration: 16,
+        max_entry_expiration: 6312000,
+    }
+}
+
+#[test]
+fn ledger_context_functions_follow_ledger_info() -> Result<(), HostError> {
+    let host = Host::default();
+    let code = (ScErrorType::Context, ScErrorCode::InternalError);
+    assert!(HostError::result_matches_err(
+        host.get_ledger_sequence(),
+        code
+    ));
+    assert!(HostError::result_matches_err(
+        host.get_ledger_timestamp(),
+        code
+    ));
+    assert!(HostError::result_matches_err(
+        host.get_max_live_until_ledger(),
+        code
+    ));
+
+    host.set_ledger_info(LedgerInfo {
+        sequence_number: 1000,
+        timestamp: u64::MAX,
+        max_entry_expiration: 100,
+        ..builder_ledger_info()
+    })?;
+    assert_eq!(
+        u32::from(host.get_ledger_version()?),
+        builder_ledger_info().protocol_version
+    );
+    assert_eq!(u32::from(host.get_ledger_sequence()?), 1000);
+    // Timestamps that don't fit a small value are returned as objects.
+    let timestamp = host.get_ledger_timestamp()?;
+    assert_eq!(u64::try_from_val(&host, &timestamp.to_val())?, u64::MAX);
+    // The current ledger counts towards the maximum expiration.
+    assert_eq!(u32::from(host.get_max_live_until_ledger()?), 1099);
+
+    host.with_mut_ledger_info(|li| {
+        li.sequence_number = u32::MAX;
+        li.timestamp = 5;
+    })?;
+    assert_eq!(u32::from(host.get_ledger_sequence()?), u32::MAX);
+    let timestamp = host.get_ledger_timestamp()?;
+    assert_eq!(u64::try_from_val(&host, &timestamp.to_val())?, 5);
+    assert_eq!(u32::from(host.get_max_live_until_ledger()?), u32::MAX);
+    Ok(())
+}
+
+#[test]
+fn host_builder_configures_host() -> Result<(), HostError> {
+    let host = HostBuilder::new()
+        .ledger_info(builder_ledger_info())
+        .source_account(generate_account_id())
+        .base_prng_seed([0; 32])
+        .diagnostic_level(DiagnosticLevel::Debug)
+        .build()?;
+    assert_eq!(
+        host.get_ledger_protocol_version()?,
+        builder_ledger_info().protocol_version
+    );
+    assert!(host.source_account_address()?.is_some());
+    assert!(host.is_debug()?);
+    assert!(host.try_borrow_base_prng()?.is_some());
+    Ok(())
+}
+
+#[test]
+fn host_builder_rejects_inconsistent_config() -> Result<(), HostError> {
+    let invalid = |res: Result<Host, HostError>, code: ScErrorCode| {
+        assert!(HostError::result_matches_err(
+            res,
+            (ScErrorType::Context, code)
+        ));
+    };
+    invalid(HostBuilder::new().build(), ScErrorCode::MissingValue);
+
+    let mut li = builder_ledger_info();
+    li.network_id = [0; 32];
+    invalid(
+        HostBuilder::new().ledger_info(li).build(),
+        ScErrorCode::InvalidInput,
+    );
+
+    let mut li = builder_ledger_info();
+    li.protocol_version += 1;
+    invalid(
+        HostBuilder::new().ledger_info(li).build(),
+        ScErrorCode::InvalidInput,
+    );
+
+    let mut li = builder_ledger_info();
+    li.min_temp_entry_expiration = li.max_entry_expiration + 1;
+    invalid(
+        HostBuilder::new().ledger_info(li).build(),
+        ScErrorCode::InvalidInput,
+    );
+
+    let budget = Budget::default();
+    budget.reset_limits(0, 0)?;
+    let res = HostBuilder::new()
+        .ledger_info(builder_ledger_info())
+        .budget(budget)
+        .build();
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Budget, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_ledger_can_be_changed_between_calls() -> Result<(), HostError> {
+    use crate::{
+        xdr::{Hash, ScAddress},
+        EnvBase, LedgerAutoAdvance, LedgerInfoBuilder, Symbol, TryFromVal,
+        TypedContractFunctionSet,
+    };
+    use std::rc::Rc;
+
+    // A host without ledger info starts from the builder defaults.
+    let host = Host::test_host();
+    host.with_test_ledger(|li| li.timestamp = 1000)?;
+    let expected = LedgerInfoBuilder::new().timestamp(1000).build();
+    host.with_ledger_info(|li| {
+        assert_eq!(li.timestamp, 1000);
+        assert_eq!(li.protocol_version, expected.protocol_version);
+        assert_eq!(li.max_entry_expiration, expected.max_entry_expiration);
+        Ok(())
+    })?;
+
+    let inner = host.add_host_object(ScAddress::Contract(Hash([1; 32])))?;
+    let outer = host.add_host_object(ScAddress::Contract(Hash([2; 32])))?;
+    let now = Symbol::try_from_small_str("now")?;
+    host.register_test_contract(
+        inner,
+        Rc::new(TypedContractFunctionSet::new().with_fn(
+            "now",
+            |host: &Host| -> Result<u64, HostError> {
+                u64::try_from_val(host, &host.get_ledger_timestamp()?.to_val())
+            },
+        )),
+    )?;
+    // Sub-invocations observe the same ledger as the invocation that made
+    // them.
+    host.register_test_contract(
+        outer,
+        Rc::new(TypedContractFunctionSet::new().with_fn(
+            "now",
+            move |host: &Host| -> Result<u64, HostError> {
+                let own = u64::try_from_val(host, &host.get_ledger_timestamp()?.to_val())?;
+                let res = host.call(inner, now, host.vec_new()?)?;
+                assert_eq!(u64::try_from_val(host, &res)?, own);
+                Ok(own)
+            },
+        )),
+    )?;
+    let call_now = |contract| -> Result<u64, HostError> {
+        let res = host.call(contract, now, host.vec_new()?)?;
+        u64::try_from_val(&host, &res)
+    };
+
+    assert_eq!(call_now(outer)?, 1000);
+    host.set_ledger_auto_advance(Some(LedgerAutoAdvance {
+        sequence_number: 1,
+        timestamp: 5,
+    }))?;
+    assert_eq!(call_now(outer)?, 1005);
+    assert_eq!(call_now(inner)?, 1010);
+    assert_eq!(u32::from(host.get_ledger_sequence()?), 2);
+
+    host.set_ledger_auto_advance(None)?;
+    assert_eq!(call_now(outer)?, 1010);
+    Ok(())
+}
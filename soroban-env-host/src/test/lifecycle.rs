@@ -9,7 +9,7 @@ use crate::{
         ContractExecutable, CreateContractArgs, ExtensionPoint, Hash, HashIdPreimage,
         HashIdPreimageContractId, LedgerEntryData, ScSymbol, ScVal, ScVec, Uint256,
     },
-    Env, Host, LedgerInfo, Symbol,
+    Env, EnvBase, Host, LedgerInfo, Symbol,
 };
 use sha2::{Digest, Sha256};
 use soroban_env_common::xdr::{
@@ -19,6 +19,7 @@ use soroban_env_common::xdr::{
 };
 use soroban_env_common::VecObject;
 use soroban_env_common::{xdr::ScBytes, TryIntoVal, Val};
+use soroban_synth_wasm::{Arity, ModEmitter};
 use soroban_test_wasms::{ADD_I32, CREATE_CONTRACT, UPDATEABLE_CONTRACT};
 
 use super::util::{generate_account_id, generate_bytes_array};
@@ -239,6 +240,67 @@ fn create_contract_from_source_account() {
     test_create_contract_from_source_account(&test_host(), &[]);
 }
 
+#[test]
+fn create_contract_emits_no_deploy_event_before_protocol_21() {
+    let host = test_host();
+    host.with_mut_ledger_info(|li| li.protocol_version = 20)
+        .unwrap();
+    test_create_contract_from_source_account(&host, &[]);
+    assert!(host
+        .get_events()
+        .unwrap()
+        .0
+        .iter()
+        .all(|e| e.event.type_ != ContractEventType::System));
+}
+
+// The deploy event is only emitted from protocol 21 on, which the host only
+// supports when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn create_contract_emits_deploy_event() {
+    let host = test_host();
+    assert!(host.get_ledger_protocol_version().unwrap() >= 21);
+    let wasm: &[u8] = &[];
+    let contract_id = test_create_contract_from_source_account(&host, wasm);
+    let wasm_hash = Hash(Sha256::digest(wasm).try_into().unwrap());
+
+    let events = host.get_events().unwrap().0;
+    let system_events: Vec<_> = events
+        .iter()
+        .filter(|e| e.event.type_ == ContractEventType::System)
+        .collect();
+    assert_eq!(system_events.len(), 1);
+    assert!(!system_events[0].failed_call);
+    assert_eq!(
+        system_events[0].event,
+        ContractEvent {
+            ext: ExtensionPoint::V0,
+            // Contracts created by a host function call aren't emitted from
+            // within a contract.
+            contract_id: None,
+            type_: ContractEventType::System,
+            body: ContractEventBody::V0(ContractEventV0 {
+                topics: vec![
+                    ScVal::Symbol(ScSymbol("deploy".try_into().unwrap())),
+                    ScVal::Address(ScAddress::Contract(contract_id)),
+                    ScVal::Vec(Some(ScVec(
+                        vec![
+                            ScVal::Symbol(ScSymbol("Wasm".try_into().unwrap())),
+                            ScVal::Bytes(ScBytes(wasm_hash.0.try_into().unwrap()))
+                        ]
+                        .try_into()
+                        .unwrap()
+                    )))
+                ]
+                .try_into()
+                .unwrap(),
+                data: ScVal::Vec(Some(ScVec(vec![].try_into().unwrap()))),
+            }),
+        }
+    );
+}
+
 pub(crate) fn sha256_hash_id_preimage<T: xdr::WriteXdr>(pre_image: T) -> xdr::Hash {
     let mut buf = DepthLimitedWrite::new(Vec::new(), DEFAULT_XDR_RW_DEPTH_LIMIT);
     pre_image
@@ -350,6 +412,65 @@ fn test_contract_wasm_update() {
     assert_eq!(updated_res, 30);
 }
 
+#[test]
+fn test_contract_wasm_update_errors_and_rollback() {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_addr_obj = host.register_test_contract_wasm(UPDATEABLE_CONTRACT);
+    let contract_id = host.contract_id_from_address(contract_addr_obj).unwrap();
+    let old_wasm_hash = get_contract_wasm_ref(&host, contract_id.clone());
+    let new_wasm_hash_obj = host
+        .upload_wasm(host.bytes_new_from_slice(ADD_I32).unwrap())
+        .unwrap();
+    let update_sym = Symbol::try_from_small_str("update").unwrap();
+
+    // Only a contract can update its own executable.
+    let res = host.update_current_contract_wasm(new_wasm_hash_obj);
+    assert!(crate::HostError::result_matches_err(
+        res,
+        (xdr::ScErrorType::Context, xdr::ScErrorCode::MissingValue)
+    ));
+
+    // The new Wasm has to be uploaded first.
+    let res = host.with_test_contract_frame(contract_id.clone(), update_sym, || {
+        host.update_current_contract_wasm(host.bytes_new_from_slice(&[0; 32])?)?;
+        Ok(Val::VOID.into())
+    });
+    assert!(crate::HostError::result_matches_err(
+        res,
+        (xdr::ScErrorType::Storage, xdr::ScErrorCode::MissingValue)
+    ));
+
+    // An update made by an invocation that fails afterwards is rolled back
+    // along with the rest of it, though its event is still reported.
+    let res = host.with_test_contract_frame(contract_id.clone(), update_sym, || {
+        host.update_current_contract_wasm(new_wasm_hash_obj)?;
+        Err(crate::Error::from_contract_error(1).into())
+    });
+    assert!(crate::HostError::result_matches_err(
+        res,
+        crate::Error::from_contract_error(1)
+    ));
+    assert_eq!(
+        get_contract_wasm_ref(&host, contract_id.clone()),
+        old_wasm_hash
+    );
+    let events = host.get_events().unwrap().0;
+    let update_event = events
+        .iter()
+        .rev()
+        .find(|e| e.event.type_ == ContractEventType::System)
+        .unwrap();
+    assert!(update_event.failed_call);
+
+    // The old Wasm has no `add` function.
+    let res = host.call(
+        contract_addr_obj,
+        Symbol::try_from_small_str("add").unwrap(),
+        host_vec![&host, 10_i32, 20_i32].into(),
+    );
+    assert!(res.is_err());
+}
+
 #[test]
 
 fn test_create_contract_from_source_account_recording_auth() {
@@ -393,3 +514,212 @@ fn test_create_contract_from_source_account_recording_auth() {
         }]
     );
 }
+
+#[test]
+fn test_upload_contract_wasm_resource_estimate() {
+    let host = Host::test_host_with_recording_footprint();
+    let (res, estimate) = host
+        .enact_recording_mode(HostFunction::UploadContractWasm(
+            ADD_I32.try_into().unwrap(),
+        ))
+        .unwrap();
+    let wasm_hash: Val = res.unwrap().try_into_val(&host).unwrap();
+    let wasm_hash = host
+        .hash_from_bytesobj_input("wasm_hash", wasm_hash.try_into().unwrap())
+        .unwrap();
+    let code_key = host.contract_code_ledger_key(&wasm_hash).unwrap();
+
+    assert!(estimate.cpu_insns > 0);
+    assert!(estimate.mem_bytes > 0);
+    assert!(estimate.mem_bytes_peak > 0 && estimate.mem_bytes_peak <= estimate.mem_bytes);
+    assert_eq!(
+        estimate
+            .footprint
+            .0
+            .get::<xdr::LedgerKey>(&code_key, host.as_budget())
+            .unwrap(),
+        Some(&AccessType::ReadWrite)
+    );
+    // The new code entry has to pay rent from the current ledger on.
+    assert_eq!(estimate.rent_bumps.len(), 1);
+    assert!(estimate.rent_bumps[0].is_persistent);
+    assert_eq!(estimate.rent_bumps[0].old_size_bytes, 0);
+    assert!(estimate.rent_bumps[0].new_size_bytes as usize > ADD_I32.len());
+}
+
+#[test]
+fn test_resource_estimate_requires_recording_footprint() {
+    let host = Host::test_host();
+    assert!(host
+        .enact_recording_mode(HostFunction::UploadContractWasm(
+            ADD_I32.try_into().unwrap(),
+        ))
+        .is_err());
+}
+
+fn wasm_with_imports(imports: &[(&str, &str, u32)]) -> Vec<u8> {
+    let mut me = ModEmitter::new();
+    for (module, fname, arity) in imports {
+        me.import_func(module, fname, Arity(*arity));
+    }
+    let mut fe = me.func(Arity(0), 0);
+    fe.i64_const(0);
+    fe.finish_and_export("test").finish()
+}
+
+// Imports are only checked from protocol 21 on, which the host only supports
+// when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn test_upload_wasm_validates_imports() {
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_debug().unwrap();
+    assert!(host.get_ledger_protocol_version().unwrap() >= 21);
+
+    // `i._` is `obj_from_u64`, which takes a single argument.
+    let good = wasm_with_imports(&[("i", "_", 1)]);
+    assert!(host
+        .upload_wasm(host.bytes_new_from_slice(&good).unwrap())
+        .is_ok());
+
+    for imports in [
+        &[("i", "_", 2)][..],
+        &[("i", "_", 1), ("zz", "_", 1)][..],
+        &[("i", "zz", 0)][..],
+    ] {
+        let bad = wasm_with_imports(imports);
+        let res = host.upload_wasm(host.bytes_new_from_slice(&bad).unwrap());
+        let err = res.unwrap_err();
+        assert!(err.error.is_type(xdr::ScErrorType::WasmVm));
+        assert!(err.error.is_code(xdr::ScErrorCode::InvalidInput));
+    }
+}
+
+#[test]
+fn test_upload_wasm_rejects_imports_from_later_protocols() {
+    let host = Host::test_host_with_recording_footprint();
+    // `v.i` is `vec_sort`, which is only available from protocol 21 on.
+    let info = crate::meta::find_host_function("v", "i").unwrap();
+    assert_eq!(info.name, "vec_sort");
+    assert_eq!(info.min_supported_protocol, Some(21));
+
+    host.with_mut_ledger_info(|li| li.protocol_version = 20)
+        .unwrap();
+    let wasm = wasm_with_imports(&[("v", "i", 1)]);
+    let err = host
+        .upload_wasm(host.bytes_new_from_slice(&wasm).unwrap())
+        .unwrap_err();
+    assert!(err.error.is_type(xdr::ScErrorType::WasmVm));
+    assert!(err.error.is_code(xdr::ScErrorCode::InvalidInput));
+}
+
+#[test]
+fn test_factory_contract_uploads_and_deploys_child() {
+    use crate::{HostError, TypedContractFunctionSet};
+    use soroban_env_common::{AddressObject, BytesObject, TryFromVal};
+    use std::rc::Rc;
+
+    let host = Host::test_host_with_recording_footprint();
+    let factory = host
+        .add_host_object(ScAddress::Contract(Hash([1; 32])))
+        .unwrap();
+    host.register_test_contract(
+        factory,
+        Rc::new(TypedContractFunctionSet::new().with_fn(
+            "deploy",
+            |host: &Host, wasm: BytesObject, salt: BytesObject| -> Result<Val, HostError> {
+                let wasm_hash = host.upload_wasm(wasm)?;
+                let deployer = host.get_current_contract_address()?;
+                Ok(host.create_contract(deployer, wasm_hash, salt)?.to_val())
+            },
+        )),
+    )
+    .unwrap();
+
+    let deploy = Symbol::try_from_small_str("deploy").unwrap();
+    let salt = host.bytes_new_from_slice(&[7; 32]).unwrap();
+    let args = host
+        .vec_new_from_slice(&[
+            host.bytes_new_from_slice(ADD_I32).unwrap().to_val(),
+            salt.to_val(),
+        ])
+        .unwrap();
+    let child = host.call(factory, deploy, args).unwrap();
+    let child = AddressObject::try_from_val(&host, &child).unwrap();
+
+    // The child's address is derived from the factory's address and the salt.
+    let expected = host.get_contract_id(factory, salt).unwrap();
+    assert_eq!(host.obj_cmp(child.to_val(), expected.to_val()).unwrap(), 0);
+
+    let res = host
+        .call(
+            child,
+            Symbol::try_from_small_str("add").unwrap(),
+            host.test_vec_obj(&[1_i32, 2_i32]).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(i32::try_from_val(&host, &res).unwrap(), 3);
+
+    // Deploying again with the same salt would collide with the child.
+    assert!(host.call(factory, deploy, args).is_err());
+}
+
+#[test]
+fn test_create_asset_contract_from_contract() {
+    use crate::{xdr::Asset, xdr::WriteXdr, HostError, TypedContractFunctionSet};
+    use soroban_env_common::{AddressObject, BytesObject, TryFromVal};
+    use std::rc::Rc;
+
+    let host = Host::test_host_with_recording_footprint();
+    let deployer = host
+        .add_host_object(ScAddress::Contract(Hash([1; 32])))
+        .unwrap();
+    host.register_test_contract(
+        deployer,
+        Rc::new(TypedContractFunctionSet::new().with_fn(
+            "deploy",
+            |host: &Host, asset: BytesObject| -> Result<Val, HostError> {
+                Ok(host.create_asset_contract(asset)?.to_val())
+            },
+        )),
+    )
+    .unwrap();
+
+    let asset = host
+        .bytes_new_from_slice(&Asset::Native.to_xdr().unwrap())
+        .unwrap();
+    let expected = host.get_asset_contract_id(asset).unwrap();
+    let deploy = Symbol::try_from_small_str("deploy").unwrap();
+    let args = host.vec_new_from_slice(&[asset.to_val()]).unwrap();
+
+    // Asset contracts don't depend on the deployer, so deploying one from a
+    // contract doesn't require any authorization and results in the same
+    // address as for any other deployer.
+    let res = host.call(deployer, deploy, args).unwrap();
+    let token = AddressObject::try_from_val(&host, &res).unwrap();
+    assert_eq!(host.obj_cmp(token.to_val(), expected.to_val()).unwrap(), 0);
+
+    // There can only be one contract per asset.
+    assert!(host.call(deployer, deploy, args).is_err());
+    assert!(host.create_asset_contract(asset).is_err());
+}
+
+// Test contracts are built against the host's own protocol, so this can only
+// upload them at protocol 20 when the "next" feature is off.
+#[cfg(not(feature = "next"))]
+#[test]
+fn test_upload_wasm_fails_to_link_bad_imports_before_protocol_21() {
+    let host = Host::test_host_with_recording_footprint();
+    assert_eq!(host.get_ledger_protocol_version().unwrap(), 20);
+
+    let good = wasm_with_imports(&[("i", "_", 1)]);
+    assert!(host
+        .upload_wasm(host.bytes_new_from_slice(&good).unwrap())
+        .is_ok());
+
+    // Without the import check the unknown import still fails, at link time.
+    let bad = wasm_with_imports(&[("i", "zz", 0)]);
+    assert!(host
+        .upload_wasm(host.bytes_new_from_slice(&bad).unwrap())
+        .is_err());
+}
@@ -264,6 +264,96 @@ fn map_values() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn map_entries() -> Result<(), HostError> {
+    let host = Host::default();
+
+    let empty = host.map_new()?;
+    assert_eq!(u32::from(host.vec_len(host.map_entries(empty)?)?), 0);
+
+    let mut map = host.map_new()?;
+    map = host.map_put(map, 2u32.into(), 20u32.into())?;
+    map = host.map_put(map, 1u32.into(), 10u32.into())?;
+    let entries = host.map_entries(map)?;
+
+    assert_eq!(u32::from(host.vec_len(entries)?), 2);
+    for (i, (k, v)) in [(1u32, 10u32), (2, 20)].into_iter().enumerate() {
+        let entry = host.vec_get(entries, U32Val::from(i as u32))?;
+        let expected = host.test_vec_obj::<u32>(&[k, v])?;
+        assert_eq!(host.obj_cmp(entry, expected.into())?, 0);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn map_navigate_keys() -> Result<(), HostError> {
+    let host = Host::default();
+    let missing = (ScErrorType::Object, ScErrorCode::MissingValue);
+    let u32_of = |v: Val| u32::from(U32Val::try_from(v).unwrap());
+
+    let empty = host.map_new()?;
+    assert!(HostError::result_matches_err(
+        host.map_min_key(empty),
+        missing
+    ));
+    assert!(HostError::result_matches_err(
+        host.map_max_key(empty),
+        missing
+    ));
+    assert!(HostError::result_matches_err(
+        host.map_next_key(empty, 1_u32.into()),
+        missing
+    ));
+
+    let mut obj = empty;
+    for k in [10_u32, 20, 30] {
+        obj = host.map_put(obj, k.into(), (k * 2).into())?;
+    }
+    assert_eq!(u32_of(host.map_min_key(obj)?), 10);
+    assert_eq!(u32_of(host.map_max_key(obj)?), 30);
+
+    // The key passed in does not need to be present in the map.
+    assert_eq!(u32_of(host.map_next_key(obj, 10_u32.into())?), 20);
+    assert_eq!(u32_of(host.map_next_key(obj, 15_u32.into())?), 20);
+    assert_eq!(u32_of(host.map_prev_key(obj, 30_u32.into())?), 20);
+    assert_eq!(u32_of(host.map_prev_key(obj, 25_u32.into())?), 20);
+    assert!(HostError::result_matches_err(
+        host.map_next_key(obj, 30_u32.into()),
+        missing
+    ));
+    assert!(HostError::result_matches_err(
+        host.map_prev_key(obj, 10_u32.into()),
+        missing
+    ));
+    // A key that happens to look like an error is still just a key.
+    let err_key: Val = Error::from_type_and_code(missing.0, missing.1).to_val();
+    let with_err_key = host.map_put(obj, err_key, 0_u32.into())?;
+    assert_eq!(
+        host.map_min_key(with_err_key)?.get_payload(),
+        err_key.get_payload()
+    );
+
+    // Walking the keys in both directions, up to the bounds, visits all of
+    // them.
+    let (min, max) = (host.map_min_key(obj)?, host.map_max_key(obj)?);
+    let mut keys = vec![u32_of(min)];
+    let mut k = min;
+    while host.obj_cmp(k, max)? != 0 {
+        k = host.map_next_key(obj, k)?;
+        keys.push(u32_of(k));
+    }
+    assert_eq!(keys, vec![10, 20, 30]);
+    let mut keys = vec![u32_of(max)];
+    let mut k = max;
+    while host.obj_cmp(k, min)? != 0 {
+        k = host.map_prev_key(obj, k)?;
+        keys.push(u32_of(k));
+    }
+    assert_eq!(keys, vec![30, 20, 10]);
+    Ok(())
+}
+
 #[test]
 fn map_stack_no_overflow_65536_boxed_keys_and_vals() {
     let mut map: Vec<(Rc<LedgerKey>, Option<Rc<LedgerEntry>>)> = Vec::new();
@@ -338,3 +428,85 @@ fn map_build_bad_element_integrity() -> Result<(), HostError> {
 
     Ok(())
 }
+
+// The length is only checked from protocol 21 on, which the host only supports
+// when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn map_unpack_to_linear_memory_checks_length() -> Result<(), HostError> {
+    use crate::VmCallerEnv;
+    use soroban_test_wasms::LINEAR_MEMORY;
+
+    let host = Host::test_host_with_recording_footprint();
+    let mut map = host.map_new()?;
+    for (k, v) in [("a", 1u32), ("b", 2u32)] {
+        let k = Symbol::try_from_small_str(k).unwrap();
+        map = host.map_put(map, k.to_val(), v.into())?;
+    }
+    host.with_test_vm_frame(LINEAR_MEMORY, |vmcaller| {
+        // Unpacking into a shorter slice must not write the remaining values
+        // past its end.
+        let res = VmCallerEnv::map_unpack_to_linear_memory(
+            &host,
+            vmcaller,
+            map,
+            U32Val::from(0),
+            U32Val::from(64),
+            U32Val::from(1),
+        );
+        assert!(HostError::result_matches_err(
+            res,
+            (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+        ));
+        Ok(())
+    })
+}
+
+#[test]
+fn metered_ord_map_random_mutations_keep_order() -> Result<(), HostError> {
+    use crate::{budget::Budget, MeteredOrdMap};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    let budget = Budget::default();
+    budget.reset_unlimited()?;
+    for seed in 0..16 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut map: MeteredOrdMap<u32, u32, Budget> = MeteredOrdMap::new();
+        let mut model: BTreeMap<u32, u32> = BTreeMap::new();
+        // Earlier versions of the map, along with what they held at the time,
+        // to roll back to. Later mutations must not affect them.
+        let mut checkpoints = vec![];
+        for _ in 0..200 {
+            match rng.gen_range(0..10) {
+                0..=4 => {
+                    let (k, v) = (rng.gen_range(0..50), rng.gen());
+                    map = map.insert(k, v, &budget)?;
+                    model.insert(k, v);
+                }
+                5..=7 => {
+                    let k = rng.gen_range(0..50);
+                    match map.remove(&k, &budget)? {
+                        Some((new, v)) => {
+                            map = new;
+                            assert_eq!(model.remove(&k), Some(v));
+                        }
+                        None => assert!(!model.contains_key(&k)),
+                    }
+                }
+                8 => checkpoints.push((map.clone(), model.clone())),
+                _ => {
+                    if let Some((old, old_model)) = checkpoints.pop() {
+                        map = old;
+                        model = old_model;
+                    }
+                }
+            }
+            map.check_invariants(&budget)?;
+            let entries: Vec<(u32, u32)> = map.iter(&budget)?.cloned().collect();
+            let expected: Vec<(u32, u32)> = model.iter().map(|(k, v)| (*k, *v)).collect();
+            assert_eq!(entries, expected);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use sha2::{Digest, Sha256};
+use soroban_env_common::{xdr::Hash, Env, TryFromVal};
+
+use crate::{budget::AsBudget, vm::ModuleCache, xdr::ContractCostType, Host, HostError, Symbol};
+use soroban_test_wasms::{ADD_I32, VEC};
+
+fn wasm_hash(wasm: &[u8]) -> Hash {
+    Hash(Sha256::digest(wasm).try_into().unwrap())
+}
+
+#[test]
+fn repeated_calls_hit_module_cache() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id_obj = host.register_test_contract_wasm(ADD_I32);
+    host.enable_module_cache()?;
+    host.as_budget().reset_unlimited()?;
+
+    for _ in 0..3 {
+        let res = host.call(
+            contract_id_obj,
+            Symbol::try_from_small_str("add")?,
+            host.test_vec_obj(&[1i32, 2i32])?,
+        )?;
+        assert_eq!(i32::try_from_val(&host, &res)?, 3);
+    }
+
+    let cache = host.get_module_cache()?.unwrap();
+    assert_eq!(cache.len()?, 1);
+    assert!(cache.contains(&wasm_hash(ADD_I32))?);
+    assert_eq!(cache.total_bytes()?, ADD_I32.len() as u64);
+    // One miss followed by two hits.
+    let budget = host.as_budget();
+    assert_eq!(budget.get_tracker(ContractCostType::VmInstantiation)?.0, 1);
+    assert_eq!(
+        budget
+            .get_tracker(ContractCostType::VmCachedInstantiation)?
+            .0,
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn module_cache_evicts_oldest_module() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let add_id = host.register_test_contract_wasm(ADD_I32);
+    let vec_id = host.register_test_contract_wasm(VEC);
+    // Only room for the larger of the two modules.
+    let cache = ModuleCache::new(&host, ADD_I32.len().max(VEC.len()) as u64)?;
+    host.set_module_cache(cache.clone())?;
+
+    host.call(
+        add_id,
+        Symbol::try_from_small_str("add")?,
+        host.test_vec_obj(&[1i32, 2i32])?,
+    )?;
+    assert!(cache.contains(&wasm_hash(ADD_I32))?);
+
+    host.try_call(
+        vec_id,
+        Symbol::try_from_small_str("vec_err")?,
+        host.test_vec_obj::<u32>(&[1])?,
+    )?;
+    assert_eq!(cache.len()?, 1);
+    assert!(!cache.contains(&wasm_hash(ADD_I32))?);
+    assert!(cache.contains(&wasm_hash(VEC))?);
+
+    cache.clear()?;
+    assert_eq!(cache.len()?, 0);
+    assert_eq!(cache.total_bytes()?, 0);
+    Ok(())
+}
+
+#[test]
+fn module_cache_does_not_skip_protocol_checks() -> Result<(), HostError> {
+    use crate::xdr::{ScErrorCode, ScErrorType};
+    use soroban_synth_wasm::{Arity, ModEmitter};
+
+    // Claims the current interface version, so is too new for any older
+    // protocol.
+    let mut fe = ModEmitter::new().func(Arity(0), 0);
+    fe.push(Symbol::try_from_small_str("pass")?);
+    let wasm = fe.finish_and_export("test").finish();
+
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id_obj = host.register_test_contract_wasm(wasm.as_slice());
+    host.enable_module_cache()?;
+    let test = Symbol::try_from_small_str("test")?;
+    host.call(contract_id_obj, test, host.vec_new()?)?;
+    assert!(host
+        .get_module_cache()?
+        .unwrap()
+        .contains(&wasm_hash(&wasm))?);
+
+    // The module cached at the current protocol must not let the contract
+    // through at an older one.
+    let proto = host.get_ledger_protocol_version()?;
+    host.with_mut_ledger_info(|li| li.protocol_version = proto - 1)?;
+    assert!(HostError::result_matches_err(
+        host.call(contract_id_obj, test, host.vec_new()?),
+        (ScErrorType::WasmVm, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
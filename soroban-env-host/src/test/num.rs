@@ -332,6 +332,16 @@ fn test_u256_arith() -> Result<(), HostError> {
     check_num_arith_rhs_u32_ok(&host, U256::new(0x10), 4, Host::u256_shr, U256::new(0x1))?;
     check_num_arith_rhs_u32_expect_err(&host, U256::new(0x10), 257, Host::u256_shr)?;
 
+    // rem_euclid
+    check_num_arith_ok(
+        &host,
+        U256::MAX,
+        U256::new(10),
+        Host::u256_rem_euclid,
+        U256::new(5),
+    )?;
+    check_num_arith_expect_err(&host, U256::new(1), U256::ZERO, Host::u256_rem_euclid)?;
+
     Ok(())
 }
 
@@ -385,6 +395,78 @@ fn test_i256_arith() -> Result<(), HostError> {
     check_num_arith_rhs_u32_ok(&host, I256::new(0x10), 4, Host::i256_shr, I256::new(0x1))?;
     check_num_arith_rhs_u32_expect_err(&host, I256::new(0x10), 256, Host::i256_shr)?;
 
+    // rem_euclid
+    check_num_arith_ok(
+        &host,
+        I256::new(-7),
+        I256::new(3),
+        Host::i256_rem_euclid,
+        I256::new(2),
+    )?;
+    check_num_arith_ok(
+        &host,
+        I256::new(7),
+        I256::new(-3),
+        Host::i256_rem_euclid,
+        I256::new(1),
+    )?;
+    check_num_arith_expect_err(&host, I256::new(1), I256::new(0), Host::i256_rem_euclid)?;
+    check_num_arith_expect_err(&host, I256::MIN, I256::new(-1), Host::i256_rem_euclid)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_u128_arith() -> Result<(), HostError> {
+    let host = Host::default();
+    // Operands and results on both sides of the small value boundary.
+    check_num_arith_ok(&host, u128::MAX - 2, 1_u128, Host::u128_add, u128::MAX - 1)?;
+    check_num_arith_ok(&host, 1_u128, 2_u128, Host::u128_add, 3_u128)?;
+    check_num_arith_expect_err(&host, u128::MAX - 2, 3_u128, Host::u128_add)?;
+
+    check_num_arith_ok(&host, u128::MAX, u128::MAX, Host::u128_sub, 0_u128)?;
+    check_num_arith_expect_err(&host, 0_u128, 1_u128, Host::u128_sub)?;
+
+    check_num_arith_ok(
+        &host,
+        u64::MAX as u128,
+        2_u128,
+        Host::u128_mul,
+        2 * u64::MAX as u128,
+    )?;
+    check_num_arith_expect_err(&host, u128::MAX, 2_u128, Host::u128_mul)?;
+
+    check_num_arith_ok(&host, u128::MAX, u128::MAX, Host::u128_div, 1_u128)?;
+    check_num_arith_expect_err(&host, 1_u128, 0_u128, Host::u128_div)?;
+
+    check_num_arith_ok(&host, u128::MAX, 10_u128, Host::u128_rem_euclid, 5_u128)?;
+    check_num_arith_expect_err(&host, 1_u128, 0_u128, Host::u128_rem_euclid)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_i128_arith() -> Result<(), HostError> {
+    let host = Host::default();
+    check_num_arith_ok(&host, i128::MAX - 2, 1_i128, Host::i128_add, i128::MAX - 1)?;
+    check_num_arith_ok(&host, -1_i128, 2_i128, Host::i128_add, 1_i128)?;
+    check_num_arith_expect_err(&host, i128::MAX - 2, 3_i128, Host::i128_add)?;
+
+    check_num_arith_ok(&host, i128::MIN + 2, 1_i128, Host::i128_sub, i128::MIN + 1)?;
+    check_num_arith_expect_err(&host, i128::MIN + 2, 3_i128, Host::i128_sub)?;
+
+    check_num_arith_ok(&host, i128::MAX, -1_i128, Host::i128_mul, i128::MIN + 1)?;
+    check_num_arith_expect_err(&host, i128::MAX, 2_i128, Host::i128_mul)?;
+
+    check_num_arith_ok(&host, i128::MIN + 1, -1_i128, Host::i128_div, i128::MAX)?;
+    check_num_arith_expect_err(&host, i128::MIN, -1_i128, Host::i128_div)?;
+    check_num_arith_expect_err(&host, 1_i128, 0_i128, Host::i128_div)?;
+
+    check_num_arith_ok(&host, -7_i128, 3_i128, Host::i128_rem_euclid, 2_i128)?;
+    check_num_arith_ok(&host, 7_i128, -3_i128, Host::i128_rem_euclid, 1_i128)?;
+    check_num_arith_expect_err(&host, 1_i128, 0_i128, Host::i128_rem_euclid)?;
+    check_num_arith_expect_err(&host, i128::MIN, -1_i128, Host::i128_rem_euclid)?;
+
     Ok(())
 }
 
@@ -0,0 +1,178 @@
+// Golden-file observations of host behavior.
+//
+// An `ObservedHost` wraps a `Host` used by a test and, when dropped at the end
+// of the test, renders what the host did into a JSON document: the tree of
+// contract invocations along with their arguments and results, the budget
+// consumed overall and by every contract frame, the final state of storage and
+// the contract events emitted. The document is compared against
+// `observations/<protocol>/<test name>.json` in the crate directory, and the
+// test fails if they differ, so that any unintended change in behavior between
+// releases shows up as a test failure along with a diff.
+//
+// A missing observation file fails the test just like a differing one. To
+// record new observations or accept intended changes, rerun the tests with
+// `UPDATE_OBSERVATIONS=1` set and commit the written files. Observed tests
+// must hence be deterministic: in particular, contracts have to be registered
+// from fixed accounts and salts rather than random ones.
+//
+// Observations are keyed by the ledger protocol version, so a protocol upgrade
+// starts a fresh set of files rather than silently overwriting the previous
+// ones.
+
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    budget::AsBudget,
+    xdr::{ContractEventType, Hash},
+    CallTraceNode, Host, HostError,
+};
+
+const UPDATE_OBSERVATIONS_VAR: &str = "UPDATE_OBSERVATIONS";
+
+pub(crate) struct ObservedHost {
+    test_name: &'static str,
+    host: Host,
+}
+
+impl ObservedHost {
+    /// Starts observing `host` on behalf of the test `test_name`, which has to
+    /// be unique across the crate, as it names the observation file.
+    pub(crate) fn new(test_name: &'static str, host: Host) -> Self {
+        host.enable_call_trace().unwrap();
+        host.reset_per_frame_costs().unwrap();
+        Self { test_name, host }
+    }
+
+    fn observation_path(&self, protocol: u32) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("observations")
+            .join(protocol.to_string())
+            .join(format!("{}.json", self.test_name))
+    }
+
+    fn observe(&self) -> Result<Value, HostError> {
+        let host = &self.host;
+        // Read the totals before doing anything else, since walking storage
+        // below is itself metered.
+        let budget = host.as_budget();
+        let (cpu_insns, mem_bytes) = (
+            budget.get_cpu_insns_consumed()?,
+            budget.get_mem_bytes_consumed()?,
+        );
+
+        let mut calls = vec![];
+        for root in host.get_call_trace()?.unwrap_or_default() {
+            render_call(&root, 0, &mut calls);
+        }
+
+        let frames: Vec<Value> = host
+            .get_per_frame_costs()?
+            .into_iter()
+            .map(|fc| {
+                json!(format!(
+                    "{}.{} cpu_insns={} mem_bytes={}",
+                    hex_id(&fc.contract_id),
+                    fc.function.0.to_string_lossy(),
+                    fc.cpu_insns,
+                    fc.mem_bytes
+                ))
+            })
+            .collect();
+
+        let storage = budget.with_free_budget(|| {
+            let storage = host.try_borrow_storage()?;
+            let mut entries = Map::new();
+            for (key, entry) in storage.map.iter(budget)? {
+                let val = match entry {
+                    Some((entry, live_until)) => {
+                        json!(format!("{:?} live_until={:?}", entry.data, live_until))
+                    }
+                    None => json!("deleted"),
+                };
+                entries.insert(format!("{:?}", key), val);
+            }
+            Ok(entries)
+        })?;
+
+        let events: Vec<Value> = host
+            .get_events()?
+            .0
+            .into_iter()
+            .filter(|e| e.event.type_ != ContractEventType::Diagnostic)
+            .map(|e| json!(format!("{:?} failed_call={}", e.event.body, e.failed_call)))
+            .collect();
+
+        Ok(json!({
+            "budget": { "cpu_insns": cpu_insns, "mem_bytes": mem_bytes },
+            "calls": calls,
+            "events": events,
+            "frames": frames,
+            "storage": storage,
+        }))
+    }
+
+    fn check(&self) {
+        let protocol = self.host.get_ledger_protocol_version().unwrap_or(0);
+        let path = self.observation_path(protocol);
+        let observed = serde_json::to_string_pretty(&self.observe().unwrap()).unwrap() + "\n";
+        if std::env::var_os(UPDATE_OBSERVATIONS_VAR).is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, observed).unwrap();
+            return;
+        }
+        let Ok(expected) = std::fs::read_to_string(&path) else {
+            panic!(
+                "no observation recorded at {}; rerun with {}=1 to record it",
+                path.display(),
+                UPDATE_OBSERVATIONS_VAR
+            );
+        };
+        pretty_assertions::assert_eq!(
+            expected,
+            observed,
+            "host behavior differs from {}; rerun with {}=1 if this is intended",
+            path.display(),
+            UPDATE_OBSERVATIONS_VAR
+        );
+    }
+}
+
+impl Deref for ObservedHost {
+    type Target = Host;
+
+    fn deref(&self) -> &Host {
+        &self.host
+    }
+}
+
+impl Drop for ObservedHost {
+    fn drop(&mut self) {
+        // A test that already failed has nothing meaningful to compare.
+        if !std::thread::panicking() {
+            self.check();
+        }
+    }
+}
+
+fn hex_id(id: &Hash) -> String {
+    hex::encode(id.0)
+}
+
+fn render_call(node: &CallTraceNode, depth: usize, out: &mut Vec<Value>) {
+    out.push(json!(format!(
+        "{}{}.{}({:?}) -> {:?}",
+        "  ".repeat(depth),
+        hex_id(&node.contract_id),
+        node.function.0.to_string_lossy(),
+        node.args,
+        node.result
+    )));
+    for sub_call in node.sub_calls.iter() {
+        render_call(sub_call, depth + 1, out);
+    }
+}
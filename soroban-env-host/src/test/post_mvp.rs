@@ -39,3 +39,91 @@ fn test_enabled_post_mvp_features() -> Result<(), HostError> {
     assert_eq!(res_i64, 0xffff_ffff_ffff_abcd_u64 as i64);
     Ok(())
 }
+
+#[test]
+fn test_disabled_post_mvp_feature_is_named() -> Result<(), HostError> {
+    use crate::{
+        events::HostEvent,
+        xdr::{ContractEventBody, ScErrorCode, ScErrorType, ScSymbol, ScVal},
+        VmConfig,
+    };
+
+    let wasm = post_mvp_wasm_module();
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_debug()?;
+    let config = VmConfig {
+        sign_extension: false,
+        ..host.get_vm_config()?
+    };
+    host.set_vm_config(Some(config))?;
+    assert_eq!(host.get_vm_config()?, config);
+
+    let res = crate::vm::Vm::new(&host, [0; 32].into(), wasm.as_slice());
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::WasmVm, ScErrorCode::InvalidAction)
+    ));
+    let named = host
+        .get_events()?
+        .0
+        .into_iter()
+        .any(|HostEvent { event, .. }| {
+            let ContractEventBody::V0(body) = event.body;
+            match body.data {
+                ScVal::Vec(Some(data)) => data
+                    .iter()
+                    .any(|v| *v == ScVal::Symbol(ScSymbol("sign_extension".try_into().unwrap()))),
+                _ => false,
+            }
+        });
+    assert!(named);
+
+    // Back to the protocol's own configuration, which accepts the module.
+    host.set_vm_config(None)?;
+    assert!(crate::vm::Vm::new(&host, [0; 32].into(), wasm.as_slice()).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_module_cache_honours_vm_config_override() -> Result<(), HostError> {
+    use crate::{
+        budget::AsBudget,
+        xdr::{ContractCostType, ScErrorCode, ScErrorType},
+        VmConfig,
+    };
+
+    let wasm = post_mvp_wasm_module();
+    let host = Host::test_host_with_recording_footprint();
+    host.enable_debug()?;
+    let addr = host.register_test_contract_wasm(wasm.as_slice());
+    host.enable_module_cache()?;
+    host.as_budget().reset_unlimited()?;
+    let test = Symbol::try_from_small_str("test")?;
+    host.call(addr, test, host.vec_new()?)?;
+
+    // The module is cached, but must not be reused under a configuration
+    // that rejects it.
+    host.set_vm_config(Some(VmConfig {
+        sign_extension: false,
+        ..host.get_vm_config()?
+    }))?;
+    assert!(HostError::result_matches_err(
+        host.call(addr, test, host.vec_new()?),
+        (ScErrorType::WasmVm, ScErrorCode::InvalidAction)
+    ));
+
+    // Back under the original configuration, the cached module is reused.
+    host.set_vm_config(None)?;
+    host.call(addr, test, host.vec_new()?)?;
+    let cache = host.get_module_cache()?.unwrap();
+    assert_eq!(cache.len()?, 1);
+    let budget = host.as_budget();
+    assert_eq!(budget.get_tracker(ContractCostType::VmInstantiation)?.0, 2);
+    assert_eq!(
+        budget
+            .get_tracker(ContractCostType::VmCachedInstantiation)?
+            .0,
+        1
+    );
+    Ok(())
+}
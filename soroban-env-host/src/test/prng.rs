@@ -1,5 +1,5 @@
 use crate::{
-    xdr::{Hash, ScAddress, ScVal, ScVec},
+    xdr::{Hash, ScAddress, ScErrorCode, ScErrorType, ScVal, ScVec},
     BytesObject, ContractFunctionSet, Env, EnvBase, Host, HostError, Symbol, SymbolSmall, U32Val,
     U64Object, Val, VecObject,
 };
@@ -19,6 +19,7 @@ const BYTES_NEW: SymbolSmall = ss_from_str("bytes_new");
 const U64_RANGE: SymbolSmall = ss_from_str("u64_range");
 const SHUFFLE: SymbolSmall = ss_from_str("shuffle");
 const RESEED: SymbolSmall = ss_from_str("reseed");
+const EMPTY_RANGE: SymbolSmall = ss_from_str("empty_rng");
 
 const SEED_LEN: u32 = 32;
 const LO: u64 = 12345;
@@ -43,6 +44,11 @@ impl ContractFunctionSet for PRNGUsingTest {
             host.prng_vec_shuffle(args[0].try_into().unwrap())
                 .unwrap()
                 .to_val()
+        } else if func == EMPTY_RANGE {
+            match host.prng_u64_in_inclusive_range(HI, LO) {
+                Ok(_) => ().into(),
+                Err(e) => e.error.to_val(),
+            }
         } else if func == RESEED {
             // The reseed method reseeds and then returns the result of bytes_new
             let _ = host.prng_reseed(args[0].try_into().unwrap()).unwrap();
@@ -125,3 +131,36 @@ fn prng_test() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn prng_is_deterministic_given_base_seed() -> Result<(), HostError> {
+    let draw = |seed: [u8; 32]| -> Result<Vec<u8>, HostError> {
+        let host = Host::test_host_with_recording_footprint();
+        host.set_base_prng_seed(seed)?;
+        let id = host.add_host_object(ScAddress::Contract(Hash([0; 32])))?;
+        host.register_test_contract(id, std::rc::Rc::new(PRNGUsingTest))?;
+        let args = host.test_vec_obj::<i32>(&[])?;
+        let bytes: BytesObject = host.call(id, BYTES_NEW.into(), args)?.try_into()?;
+        let mut buf = [0u8; SEED_LEN as usize];
+        host.bytes_copy_to_slice(bytes, U32Val::from(0), &mut buf)?;
+        Ok(buf.to_vec())
+    };
+    assert_eq!(draw([0; 32])?, draw([0; 32])?);
+    assert_ne!(draw([0; 32])?, draw([1; 32])?);
+    Ok(())
+}
+
+#[test]
+fn prng_u64_in_inclusive_range_rejects_empty_range() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    host.set_base_prng_seed([0; 32])?;
+    let id = host.add_host_object(ScAddress::Contract(Hash([0; 32])))?;
+    host.register_test_contract(id, std::rc::Rc::new(PRNGUsingTest))?;
+    let args = host.test_vec_obj::<i32>(&[])?;
+    let res = host.call(id, EMPTY_RANGE.into(), args);
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Value, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
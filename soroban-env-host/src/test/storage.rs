@@ -1,11 +1,13 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
-use crate::budget::Budget;
+use crate::budget::{Budget, LedgerResources};
 use crate::native_contract::testutils::HostVec;
-use crate::storage::{AccessType, Footprint};
+use crate::storage::{AccessType, Footprint, LedgerEntryDiff, Storage};
+use crate::test::util::MockSnapshotSource;
 use crate::xdr::{
-    ContractDataDurability, LedgerKey, LedgerKeyContractData, ScAddress, ScErrorCode, ScErrorType,
-    ScVal,
+    ContractDataDurability, ContractDataEntry, ExtensionPoint, LedgerEntry, LedgerEntryData,
+    LedgerEntryExt, LedgerKey, LedgerKeyContractData, ScAddress, ScErrorCode, ScErrorType,
+    ScSymbol, ScVal,
 };
 use crate::{host_vec, Host, HostError, MeteredOrdMap};
 use soroban_env_common::{AddressObject, Env, Symbol, TryFromVal, TryIntoVal};
@@ -108,6 +110,206 @@ fn footprint_attempt_to_write_readonly_entry() -> Result<(), HostError> {
     Ok(())
 }
 
+fn contract_data_key(i: i32) -> Rc<LedgerKey> {
+    Rc::new(LedgerKey::ContractData(LedgerKeyContractData {
+        contract: ScAddress::Contract([0; 32].into()),
+        key: ScVal::I32(i),
+        durability: ContractDataDurability::Persistent,
+    }))
+}
+
+fn contract_data_entry(i: i32, val: ScVal) -> Rc<LedgerEntry> {
+    Rc::new(LedgerEntry {
+        last_modified_ledger_seq: 0,
+        data: LedgerEntryData::ContractData(ContractDataEntry {
+            contract: ScAddress::Contract([0; 32].into()),
+            key: ScVal::I32(i),
+            val,
+            durability: ContractDataDurability::Persistent,
+            ext: ExtensionPoint::V0,
+        }),
+        ext: LedgerEntryExt::V0,
+    })
+}
+
+#[test]
+fn bump_extends_only_entries_below_low_watermark() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let budget = host.budget_cloned();
+    let key = contract_data_key(0);
+    let ledger_seq: u32 = host.get_ledger_sequence()?.into();
+    let mut storage = Storage::with_recording_footprint(Rc::new(MockSnapshotSource::new()));
+    storage.put(
+        &key,
+        &contract_data_entry(0, ScVal::U32(0)),
+        Some(ledger_seq + 100),
+        &budget,
+    )?;
+    let expiration = |storage: &mut Storage| -> Result<Option<u32>, HostError> {
+        Ok(storage.get_with_expiration(&key, &budget)?.1)
+    };
+
+    // 100 ledgers left is above the low watermark, so nothing happens.
+    storage.bump(&host, Rc::clone(&key), 99, 1000)?;
+    assert_eq!(expiration(&mut storage)?, Some(ledger_seq + 100));
+
+    // At the low watermark the entry is bumped to the high watermark.
+    storage.bump(&host, Rc::clone(&key), 100, 1000)?;
+    assert_eq!(expiration(&mut storage)?, Some(ledger_seq + 1000));
+
+    // Bumps never shorten the lifetime of an entry.
+    storage.bump(&host, Rc::clone(&key), 1000, 1000)?;
+    storage.bump(&host, Rc::clone(&key), 500, 500)?;
+    assert_eq!(expiration(&mut storage)?, Some(ledger_seq + 1000));
+
+    // Inverted watermarks are rejected.
+    assert!(HostError::result_matches_err(
+        storage.bump(&host, Rc::clone(&key), 10, 9),
+        (ScErrorType::Storage, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
+
+#[test]
+fn lazy_enforcing_storage_loads_only_footprint_entries() -> Result<(), HostError> {
+    let budget = Budget::default();
+    let ro_key = contract_data_key(0);
+    let rw_key = contract_data_key(1);
+    let unlisted_key = contract_data_key(2);
+    let src = Rc::new(MockSnapshotSource::from_entries(vec![
+        (
+            Rc::clone(&ro_key),
+            (contract_data_entry(0, ScVal::U32(10)), Some(100)),
+        ),
+        (
+            Rc::clone(&unlisted_key),
+            (contract_data_entry(2, ScVal::U32(12)), Some(100)),
+        ),
+    ]));
+    let om = [
+        (Rc::clone(&ro_key), AccessType::ReadOnly),
+        (Rc::clone(&rw_key), AccessType::ReadWrite),
+    ]
+    .into();
+    let footprint = Footprint(MeteredOrdMap::from_map(om, &budget)?);
+    let mut storage = Storage::with_enforcing_footprint_and_source(footprint, src);
+    assert_eq!(storage.map.len(), 0);
+
+    // In-footprint reads are loaded from the source on first access.
+    assert_eq!(
+        storage.get(&ro_key, &budget)?,
+        contract_data_entry(0, ScVal::U32(10))
+    );
+    assert!(storage
+        .map
+        .contains_key::<Rc<LedgerKey>>(&ro_key, &budget)?);
+    assert!(!storage.has(&rw_key, &budget)?);
+
+    // Footprint is still enforced for both reads and writes, and nothing
+    // outside of it is loaded.
+    assert!(HostError::result_matches_err(
+        storage.get(&unlisted_key, &budget),
+        (ScErrorType::Storage, ScErrorCode::ExceededLimit)
+    ));
+    assert!(!storage
+        .map
+        .contains_key::<Rc<LedgerKey>>(&unlisted_key, &budget)?);
+    assert!(HostError::result_matches_err(
+        storage.put(
+            &ro_key,
+            &contract_data_entry(0, ScVal::U32(20)),
+            Some(100),
+            &budget
+        ),
+        (ScErrorType::Storage, ScErrorCode::ExceededLimit)
+    ));
+
+    // Writes are not overwritten by subsequent lazy loads.
+    let new_entry = contract_data_entry(1, ScVal::U32(11));
+    storage.put(&rw_key, &new_entry, Some(100), &budget)?;
+    assert_eq!(storage.get(&rw_key, &budget)?, new_entry);
+    storage.del(&rw_key, &budget)?;
+    assert!(!storage.has(&rw_key, &budget)?);
+    Ok(())
+}
+
+#[test]
+fn ledger_changes_report_old_and_new_entries() -> Result<(), HostError> {
+    let budget = Budget::default();
+    let ro_key = contract_data_key(0);
+    let rw_key = contract_data_key(1);
+    let new_key = contract_data_key(2);
+    let ro_entry = (contract_data_entry(0, ScVal::U32(10)), Some(100));
+    let rw_entry = (contract_data_entry(1, ScVal::U32(11)), Some(100));
+    let footprint = Footprint(MeteredOrdMap::from_map(
+        [
+            (Rc::clone(&ro_key), AccessType::ReadOnly),
+            (Rc::clone(&rw_key), AccessType::ReadWrite),
+            (Rc::clone(&new_key), AccessType::ReadWrite),
+        ]
+        .into(),
+        &budget,
+    )?);
+    let map = MeteredOrdMap::from_map(
+        [
+            (Rc::clone(&ro_key), Some(ro_entry.clone())),
+            (Rc::clone(&rw_key), Some(rw_entry.clone())),
+            (Rc::clone(&new_key), None),
+        ]
+        .into(),
+        &budget,
+    )?;
+    let mut storage = Storage::with_enforcing_footprint_and_map(footprint, map);
+    // Ledger changes need the initial entries in enforcing mode.
+    assert!(HostError::result_matches_err(
+        storage.get_ledger_changes(&budget),
+        (ScErrorType::Storage, ScErrorCode::InvalidAction)
+    ));
+    storage.record_initial_entries();
+    let host = Host::with_storage_and_budget(storage, budget);
+
+    let updated_entry = (contract_data_entry(1, ScVal::U32(21)), Some(200));
+    let created_entry = (contract_data_entry(2, ScVal::U32(12)), Some(300));
+    host.with_mut_storage(|storage| {
+        let budget = host.budget_cloned();
+        storage.put(&rw_key, &updated_entry.0, updated_entry.1, &budget)?;
+        storage.put(&new_key, &created_entry.0, created_entry.1, &budget)
+    })?;
+
+    assert_eq!(host.get_footprint()?.0.len(), 3);
+    let changes = host.get_ledger_changes()?;
+    assert_eq!(
+        changes,
+        vec![
+            LedgerEntryDiff {
+                key: ro_key,
+                read_only: true,
+                old_entry: Some(ro_entry.clone()),
+                new_entry: Some(ro_entry),
+            },
+            LedgerEntryDiff {
+                key: Rc::clone(&rw_key),
+                read_only: false,
+                old_entry: Some(rw_entry.clone()),
+                new_entry: Some(updated_entry),
+            },
+            LedgerEntryDiff {
+                key: new_key,
+                read_only: false,
+                old_entry: None,
+                new_entry: Some(created_entry),
+            },
+        ]
+    );
+
+    // Deleted entries have no new value.
+    host.with_mut_storage(|storage| storage.del(&rw_key, &host.budget_cloned()))?;
+    let changes = host.get_ledger_changes()?;
+    assert_eq!(changes[1].old_entry, Some(rw_entry));
+    assert_eq!(changes[1].new_entry, None);
+    Ok(())
+}
+
 fn storage_fn_name(host: &Host, fn_name: &str, storage: &str) -> Symbol {
     Symbol::try_from_val(host, &format!("{}_{}", fn_name, storage).as_str()).unwrap()
 }
@@ -347,3 +549,310 @@ fn test_storage_mix() {
     test_storage(&host, contract_id, "temporary");
     test_storage(&host, contract_id, "instance");
 }
+
+// Expired entries are only recreated from protocol 21 on, which the host only
+// supports when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn expired_temp_entry_is_recreated_on_put() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+    let key_1 = Symbol::try_from_small_str("key_1").unwrap();
+    let ledger_key = Rc::new(LedgerKey::ContractData(LedgerKeyContractData {
+        contract: ScAddress::Contract(host.contract_id_from_address(contract_id)?),
+        key: ScVal::Symbol(ScSymbol("key_1".try_into().unwrap())),
+        durability: ContractDataDurability::Temporary,
+    }));
+    let put = |val: u64| {
+        host.call(
+            contract_id,
+            storage_fn_name(&host, "put", "temporary"),
+            host_vec![&host, key_1, val].into(),
+        )
+    };
+    let expiration =
+        || host.with_mut_storage(|s| Ok(s.get_with_expiration(&ledger_key, host.budget_ref())?.1));
+    let min_temp_expiration = host.with_ledger_info(|li| Ok(li.min_temp_entry_expiration))?;
+
+    put(1)?;
+    let first_expiration = expiration()?.unwrap();
+
+    // Overwriting a live entry keeps its expiration.
+    host.with_mut_ledger_info(|li| li.sequence_number = first_expiration)?;
+    put(2)?;
+    assert_eq!(expiration()?, Some(first_expiration));
+
+    // Once expired, the entry is recreated with a fresh expiration.
+    let seq = first_expiration + 1;
+    host.with_mut_ledger_info(|li| li.sequence_number = seq)?;
+    put(3)?;
+    assert_eq!(expiration()?, Some(seq + min_temp_expiration - 1));
+    Ok(())
+}
+
+#[test]
+fn storage_commit_hook_sees_changes_of_successful_invocations() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+    let key_1 = Symbol::try_from_small_str("key_1").unwrap();
+    let commits: Rc<RefCell<Vec<(usize, Vec<LedgerEntryDiff>)>>> = Default::default();
+    let recorded = commits.clone();
+    host.set_storage_commit_hook(move |footprint, changes| {
+        recorded
+            .borrow_mut()
+            .push((footprint.0.len(), changes.to_vec()));
+        Ok(())
+    })?;
+
+    host.call(
+        contract_id,
+        storage_fn_name(&host, "put", "persistent"),
+        host_vec![&host, key_1, 1234_u64].into(),
+    )?;
+    {
+        let commits = commits.borrow();
+        assert_eq!(commits.len(), 1);
+        let (footprint_len, changes) = &commits[0];
+        assert!(*footprint_len >= changes.len());
+        assert!(!changes.is_empty());
+        assert!(changes.iter().all(|c| c.old_entry != c.new_entry));
+    }
+
+    // A later invocation only sees its own changes, diffed against the
+    // state it started from.
+    let key_2 = Symbol::try_from_small_str("key_2").unwrap();
+    host.call(
+        contract_id,
+        storage_fn_name(&host, "put", "persistent"),
+        host_vec![&host, key_2, 1_u64].into(),
+    )?;
+    {
+        let commits = commits.borrow();
+        assert_eq!(commits.len(), 2);
+        let (_, changes) = &commits[1];
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].old_entry.is_none());
+        assert!(changes[0].new_entry.is_some());
+    }
+
+    // A failing hook doesn't fail the already committed invocation.
+    host.set_storage_commit_hook(|_, _| {
+        Err((ScErrorType::Storage, ScErrorCode::InternalError).into())
+    })?;
+    host.call(
+        contract_id,
+        storage_fn_name(&host, "put", "persistent"),
+        host_vec![&host, key_1, 5678_u64].into(),
+    )?;
+    let stored = host.call(
+        contract_id,
+        storage_fn_name(&host, "get", "persistent"),
+        host_vec![&host, key_1].into(),
+    )?;
+    assert_eq!(u64::try_from_val(&host, &stored)?, 5678);
+
+    // Once removed, the hook is no longer called.
+    host.remove_storage_commit_hook()?;
+    host.call(
+        contract_id,
+        storage_fn_name(&host, "put", "persistent"),
+        host_vec![&host, key_1, 9_u64].into(),
+    )?;
+    assert_eq!(commits.borrow().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn ledger_resource_limits_are_charged_by_storage() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+    let put = |key: &str, val: u64| {
+        host.call(
+            contract_id,
+            storage_fn_name(&host, "put", "persistent"),
+            host_vec![&host, Symbol::try_from_small_str(key).unwrap(), val].into(),
+        )
+    };
+    let unlimited = LedgerResources {
+        read_entries: u32::MAX,
+        write_entries: u32::MAX,
+        read_bytes: u32::MAX,
+        write_bytes: u32::MAX,
+    };
+    host.set_ledger_resource_limits(Some(unlimited))?;
+
+    put("key_1", 1)?;
+    let usage = host.get_ledger_resource_usage()?;
+    // At least the contract instance and code are read, and the data entry
+    // is written, which counts as a read too.
+    assert!(usage.read_entries >= 3);
+    assert_eq!(usage.write_entries, 1);
+    assert!(usage.read_bytes > 0);
+    assert!(usage.write_bytes > 0);
+
+    // Overwriting an entry replaces its bytes instead of adding to them.
+    put("key_1", 2)?;
+    assert_eq!(host.get_ledger_resource_usage()?, usage);
+
+    // Going over any of the limits fails, and in debug mode the error says
+    // which of them was exceeded. Entries are only counted on their first
+    // access since the limits were first set, so each case starts afresh.
+    for (limits, msg) in [
+        (
+            LedgerResources {
+                read_entries: 1,
+                ..unlimited
+            },
+            "ledger read entries limit exceeded",
+        ),
+        (
+            LedgerResources {
+                write_entries: 0,
+                ..unlimited
+            },
+            "ledger write entries limit exceeded",
+        ),
+        (
+            LedgerResources {
+                read_bytes: 1,
+                ..unlimited
+            },
+            "ledger read bytes limit exceeded",
+        ),
+        (
+            LedgerResources {
+                write_bytes: 1,
+                ..unlimited
+            },
+            "ledger write bytes limit exceeded",
+        ),
+    ] {
+        let host = Host::test_host_with_recording_footprint();
+        host.enable_debug()?;
+        let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+        host.set_ledger_resource_limits(Some(limits))?;
+        let err = host
+            .call(
+                contract_id,
+                storage_fn_name(&host, "put", "persistent"),
+                host_vec![&host, Symbol::try_from_small_str("key_1").unwrap(), 1_u64].into(),
+            )
+            .unwrap_err();
+        assert!(err.error.is_type(ScErrorType::Budget));
+        assert!(err.error.is_code(ScErrorCode::ExceededLimit));
+        assert!(format!("{:?}", err).contains(msg));
+    }
+    Ok(())
+}
+
+#[test]
+fn instance_storage_is_kept_in_the_instance_entry() -> Result<(), HostError> {
+    let host = Host::test_host_with_recording_footprint();
+    let contract_id = host.register_test_contract_wasm(CONTRACT_STORAGE);
+    let key_1 = Symbol::try_from_small_str("key_1").unwrap();
+    host.call(
+        contract_id,
+        storage_fn_name(&host, "put", "instance"),
+        host_vec![&host, key_1, 1234_u64].into(),
+    )?;
+
+    // No ledger entry is created for the key itself...
+    let key_val = ScVal::Symbol(ScSymbol("key_1".try_into().unwrap()));
+    for change in host.get_ledger_changes()? {
+        if let LedgerKey::ContractData(k) = change.key.as_ref() {
+            assert_ne!(k.key, key_val);
+        }
+    }
+    // ...as it is stored in the instance entry instead.
+    let instance_key =
+        host.contract_instance_ledger_key(&host.contract_id_from_address(contract_id)?)?;
+    let instance = host.retrieve_contract_instance_from_storage(&instance_key)?;
+    let storage = instance.storage.unwrap();
+    assert!(storage
+        .iter()
+        .any(|e| e.key == key_val && e.val == ScVal::U64(1234)));
+    Ok(())
+}
+
+#[test]
+fn storage_stays_ordered_across_puts_dels_and_rollbacks() -> Result<(), HostError> {
+    use crate::{xdr::Hash, StorageType, TypedContractFunctionSet, U32Val};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    let host = Host::test_host_with_recording_footprint();
+    host.as_budget().reset_unlimited()?;
+    let contract_id = Hash([3; 32]);
+    let contract = host.add_host_object(ScAddress::Contract(contract_id.clone()))?;
+    // `apply(key, val, op)` puts `val` under `key` for an even `op` and
+    // deletes `key` for an odd one, then fails for `op >= 2`, rolling the
+    // change back.
+    let functions = TypedContractFunctionSet::new().with_fn(
+        "apply",
+        |host: &Host, key: u32, val: u32, op: u32| -> Result<u32, HostError> {
+            let key = U32Val::from(key).to_val();
+            if op % 2 == 0 {
+                host.put_contract_data(key, U32Val::from(val).to_val(), StorageType::Persistent)?;
+            } else {
+                host.del_contract_data(key, StorageType::Persistent)?;
+            }
+            if op >= 2 {
+                return Err(crate::Error::from_contract_error(1).into());
+            }
+            Ok(0)
+        },
+    );
+    host.register_test_contract(contract, Rc::new(functions))?;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut model: BTreeMap<u32, u32> = BTreeMap::new();
+    for _ in 0..200 {
+        let key = rng.gen_range(0..20_u32);
+        let val = rng.gen_range(0..1000_u32);
+        // Only delete existing keys.
+        let op = match (model.contains_key(&key), rng.gen_range(0..4_u32)) {
+            (false, op) => op & !1,
+            (true, op) => op,
+        };
+        host.try_call(
+            contract,
+            Symbol::try_from_small_str("apply")?,
+            host_vec![&host, key, val, op].into(),
+        )?;
+        match op {
+            0 => {
+                model.insert(key, val);
+            }
+            1 => {
+                model.remove(&key);
+            }
+            _ => (),
+        }
+
+        let budget = host.budget_cloned();
+        let storage = host.try_borrow_storage()?;
+        storage.map.check_invariants(&budget)?;
+        let mut live = vec![];
+        for (k, v) in storage.map.iter(&budget)? {
+            let (LedgerKey::ContractData(k), Some((entry, _))) = (k.as_ref(), v) else {
+                continue;
+            };
+            let (ScAddress::Contract(id), ScVal::U32(key)) = (&k.contract, &k.key) else {
+                continue;
+            };
+            let LedgerEntryData::ContractData(ContractDataEntry {
+                val: ScVal::U32(val),
+                ..
+            }) = &entry.data
+            else {
+                continue;
+            };
+            if *id == contract_id {
+                live.push((*key, *val));
+            }
+        }
+        let expected: Vec<(u32, u32)> = model.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(live, expected);
+    }
+    Ok(())
+}
@@ -2,7 +2,10 @@ use std::convert::TryInto;
 
 use soroban_env_common::{EnvBase, StringObject, TryIntoVal};
 
-use crate::{Env, Host, HostError, Val};
+use crate::{
+    xdr::{ScErrorCode, ScErrorType},
+    Env, Host, HostError, Symbol, Val,
+};
 
 #[test]
 fn str_conversions() -> Result<(), HostError> {
@@ -31,3 +34,108 @@ fn str_conversions() -> Result<(), HostError> {
     }
     Ok(())
 }
+
+#[test]
+fn string_manipulation() -> Result<(), HostError> {
+    let host = Host::default();
+    let hello = host.string_new_from_slice("hello, ")?;
+    let world = host.string_new_from_slice("world")?;
+
+    let greeting = host.string_concat(hello, world)?;
+    let s: String = greeting.to_val().try_into_val(&host)?;
+    assert_eq!(s, "hello, world");
+
+    let sub = host.string_substr(greeting, 7_u32.into(), 12_u32.into())?;
+    let s: String = sub.to_val().try_into_val(&host)?;
+    assert_eq!(s, "world");
+    assert_eq!(host.string_compare(sub, world)?, 0);
+    assert_eq!(host.string_compare(hello, world)?, -1);
+    assert_eq!(host.string_compare(world, hello)?, 1);
+
+    let empty = host.string_substr(greeting, 3_u32.into(), 3_u32.into())?;
+    assert_eq!(u32::from(host.string_len(empty)?), 0);
+
+    let res = host.string_substr(greeting, 3_u32.into(), 13_u32.into());
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Object, ScErrorCode::IndexBounds)
+    ));
+    let res = host.string_substr(greeting, 4_u32.into(), 3_u32.into());
+    assert!(HostError::result_matches_err(
+        res,
+        (ScErrorType::Object, ScErrorCode::InvalidInput)
+    ));
+    Ok(())
+}
+
+#[test]
+fn symbol_to_string() -> Result<(), HostError> {
+    let host = Host::default();
+    let small = Symbol::try_from_small_str("abc")?;
+    let s: String = host.symbol_to_string(small)?.to_val().try_into_val(&host)?;
+    assert_eq!(s, "abc");
+
+    let long = "a_long_symbol_that_is_an_object";
+    let obj: Symbol = host.symbol_new_from_slice(long)?.into();
+    let s: String = host.symbol_to_string(obj)?.to_val().try_into_val(&host)?;
+    assert_eq!(s, long);
+    Ok(())
+}
+
+#[test]
+fn string_and_symbol_linear_memory_roundtrip() -> Result<(), HostError> {
+    use crate::VmCallerEnv;
+    use soroban_env_common::{Compare, SymbolObject, U32Val};
+    use soroban_test_wasms::LINEAR_MEMORY;
+
+    let host = Host::test_host_with_recording_footprint();
+    let s = host.string_new_from_slice("hello linear memory")?;
+    let sym: SymbolObject = host.symbol_new_from_slice("hello_linear_memory")?;
+    host.with_test_vm_frame(LINEAR_MEMORY, |vmcaller| {
+        let len = U32Val::from(19);
+        VmCallerEnv::string_copy_to_linear_memory(
+            &host,
+            vmcaller,
+            s,
+            U32Val::from(0),
+            U32Val::from(0),
+            len,
+        )?;
+        let s_copy =
+            VmCallerEnv::string_new_from_linear_memory(&host, vmcaller, U32Val::from(0), len)?;
+        assert_eq!(
+            host.compare(&s_copy.to_val(), &s.to_val())?,
+            core::cmp::Ordering::Equal
+        );
+
+        VmCallerEnv::symbol_copy_to_linear_memory(
+            &host,
+            vmcaller,
+            sym,
+            U32Val::from(0),
+            U32Val::from(64),
+            len,
+        )?;
+        let sym_copy =
+            VmCallerEnv::symbol_new_from_linear_memory(&host, vmcaller, U32Val::from(64), len)?;
+        assert_eq!(
+            host.compare(&sym_copy.to_val(), &sym.to_val())?,
+            core::cmp::Ordering::Equal
+        );
+
+        // Copying out more bytes than the object holds fails.
+        let res = VmCallerEnv::string_copy_to_linear_memory(
+            &host,
+            vmcaller,
+            s,
+            U32Val::from(1),
+            U32Val::from(0),
+            len,
+        );
+        assert!(HostError::result_matches_err(
+            res,
+            (ScErrorType::Object, ScErrorCode::IndexBounds)
+        ));
+        Ok(())
+    })
+}
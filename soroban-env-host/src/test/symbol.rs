@@ -1,5 +1,5 @@
 use crate::{Host, HostError};
-use soroban_env_common::{Symbol, TryFromVal};
+use soroban_env_common::{Compare, Env, EnvBase, Symbol, SymbolObject, SymbolStr, Tag, TryFromVal};
 
 #[test]
 fn invalid_chars() -> Result<(), HostError> {
@@ -48,3 +48,32 @@ fn zero_len() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn small_and_object_symbols() -> Result<(), HostError> {
+    let host = Host::default();
+
+    // Up to 9 characters fit in a small symbol, longer ones become objects.
+    let small = Symbol::try_from_val(&host, &"abcdefghi")?;
+    assert_eq!(small.as_val().get_tag(), Tag::SymbolSmall);
+    let obj = Symbol::try_from_val(&host, &"abcdefghij")?;
+    assert_eq!(obj.as_val().get_tag(), Tag::SymbolObject);
+    let obj = SymbolObject::try_from(obj)?;
+    assert_eq!(u32::from(host.symbol_len(obj)?), 10);
+
+    // Symbols compare by their contents, whichever way they are stored.
+    let small_as_obj = host.symbol_new_from_slice("abcdefghi")?;
+    assert_eq!(
+        host.compare(&small.to_val(), &small_as_obj.to_val())?,
+        core::cmp::Ordering::Equal
+    );
+    let long = "123456789012345678901234567890__";
+    let a = Symbol::try_from_val(&host, &long)?;
+    let b = host.symbol_new_from_slice(long)?;
+    assert_eq!(
+        host.compare(&a.to_val(), &b.to_val())?,
+        core::cmp::Ordering::Equal
+    );
+    assert_eq!(SymbolStr::try_from_val(&host, &a)?.to_string(), long);
+    Ok(())
+}
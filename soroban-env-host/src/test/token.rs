@@ -20,6 +20,7 @@ use crate::{
     Host, HostError, LedgerInfo,
 };
 use ed25519_dalek::SigningKey;
+use expect_test::expect;
 use soroban_env_common::{
     xdr::{
         self, AccountFlags, ContractExecutable, InvokeContractArgs, ScAddress, ScContractInstance,
@@ -28,7 +29,7 @@ use soroban_env_common::{
     xdr::{
         AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode12, AssetCode4, Hash, LedgerEntryData,
         LedgerKey, Liabilities, PublicKey, ScErrorCode, ScErrorType, TrustLineEntry,
-        TrustLineEntryExt, TrustLineEntryV1, TrustLineEntryV1Ext, TrustLineFlags,
+        TrustLineEntryExt, TrustLineEntryV1, TrustLineEntryV1Ext, TrustLineFlags, WriteXdr,
     },
     EnvBase, Val,
 };
@@ -548,6 +549,307 @@ fn test_direct_transfer() {
     );
 }
 
+#[test]
+fn test_transfer_many() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    let user_3 = TestSigner::account(&test.user_key_3);
+    test.create_default_account(&user);
+    test.create_default_account(&user_2);
+    test.create_default_account(&user_3);
+    test.create_default_trustline(&user);
+    test.create_default_trustline(&user_2);
+    test.create_default_trustline(&user_3);
+
+    token
+        .mint(&admin, user.address(&test.host), 100_000_000)
+        .unwrap();
+
+    // Transfer to two recipients with a single authorization.
+    token
+        .transfer_many(
+            &user,
+            &[
+                (user_2.address(&test.host), 10_000_000),
+                (user_3.address(&test.host), 20_000_000),
+            ],
+        )
+        .unwrap();
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 70_000_000);
+    assert_eq!(
+        token.balance(user_2.address(&test.host)).unwrap(),
+        10_000_000
+    );
+    assert_eq!(
+        token.balance(user_3.address(&test.host)).unwrap(),
+        20_000_000
+    );
+
+    // Can't transfer more than the balance in total, even if every single
+    // transfer is covered by it; nobody gets credited in that case.
+    assert_eq!(
+        to_contract_err(
+            token
+                .transfer_many(
+                    &user,
+                    &[
+                        (user_2.address(&test.host), 50_000_000),
+                        (user_3.address(&test.host), 50_000_000),
+                    ],
+                )
+                .err()
+                .unwrap()
+        ),
+        ContractError::BalanceError
+    );
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 70_000_000);
+    assert_eq!(
+        token.balance(user_2.address(&test.host)).unwrap(),
+        10_000_000
+    );
+
+    // Negative amounts are rejected.
+    assert_eq!(
+        to_contract_err(
+            token
+                .transfer_many(
+                    &user,
+                    &[
+                        (user_2.address(&test.host), 1),
+                        (user_3.address(&test.host), -1),
+                    ],
+                )
+                .err()
+                .unwrap()
+        ),
+        ContractError::NegativeAmountError
+    );
+
+    // An empty batch is a no-op.
+    token.transfer_many(&user, &[]).unwrap();
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 70_000_000);
+}
+
+#[test]
+fn test_burn_from_many() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    let spender = TestSigner::account(&test.user_key_3);
+    for u in [&user, &user_2, &spender] {
+        test.create_default_account(u);
+        test.create_default_trustline(u);
+    }
+    for u in [&user, &user_2] {
+        token
+            .mint(&admin, u.address(&test.host), 100_000_000)
+            .unwrap();
+    }
+    token
+        .approve(&user, spender.address(&test.host), 10_000_000, 200)
+        .unwrap();
+    token
+        .approve(&user_2, spender.address(&test.host), 20_000_000, 200)
+        .unwrap();
+
+    // Burn from two balances with a single authorization, spending the
+    // allowance for each; entries for the same balance add up.
+    token
+        .burn_from_many(
+            &spender,
+            &[
+                (user.address(&test.host), 4_000_000),
+                (user_2.address(&test.host), 5_000_000),
+                (user.address(&test.host), 3_000_000),
+            ],
+        )
+        .unwrap();
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 93_000_000);
+    assert_eq!(
+        token.balance(user_2.address(&test.host)).unwrap(),
+        95_000_000
+    );
+    assert_eq!(
+        token
+            .allowance(user.address(&test.host), spender.address(&test.host))
+            .unwrap(),
+        3_000_000
+    );
+    assert_eq!(
+        token
+            .allowance(user_2.address(&test.host), spender.address(&test.host))
+            .unwrap(),
+        15_000_000
+    );
+
+    // Every entry is checked against the remaining allowance, even if each
+    // would be covered on its own; nothing is burned in that case.
+    assert_eq!(
+        to_contract_err(
+            token
+                .burn_from_many(
+                    &spender,
+                    &[
+                        (user_2.address(&test.host), 1_000_000),
+                        (user.address(&test.host), 2_000_000),
+                        (user.address(&test.host), 2_000_000),
+                    ],
+                )
+                .err()
+                .unwrap()
+        ),
+        ContractError::AllowanceError
+    );
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 93_000_000);
+    assert_eq!(
+        token.balance(user_2.address(&test.host)).unwrap(),
+        95_000_000
+    );
+    assert_eq!(
+        token
+            .allowance(user_2.address(&test.host), spender.address(&test.host))
+            .unwrap(),
+        15_000_000
+    );
+
+    // Balances without any allowance for the spender can't be burned from.
+    assert_eq!(
+        to_contract_err(
+            token
+                .burn_from_many(&spender, &[(admin.address(&test.host), 1)])
+                .err()
+                .unwrap()
+        ),
+        ContractError::AllowanceError
+    );
+
+    // Negative amounts are rejected.
+    assert_eq!(
+        to_contract_err(
+            token
+                .burn_from_many(&spender, &[(user.address(&test.host), -1)])
+                .err()
+                .unwrap()
+        ),
+        ContractError::NegativeAmountError
+    );
+
+    // An empty batch is a no-op.
+    token.burn_from_many(&spender, &[]).unwrap();
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 93_000_000);
+}
+
+#[test]
+fn test_burn_from_many_native_not_supported() {
+    let test = TokenTest::setup();
+    let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    assert_eq!(
+        to_contract_err(
+            token
+                .burn_from_many(&user, &[(user.address(&test.host), 1)])
+                .err()
+                .unwrap()
+        ),
+        ContractError::OperationNotSupportedError
+    );
+}
+
+#[test]
+fn test_mint_many_and_max_supply() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    test.create_default_account(&user);
+    test.create_default_account(&user_2);
+    test.create_default_trustline(&user);
+    test.create_default_trustline(&user_2);
+
+    assert_eq!(token.total_supply().unwrap(), 0);
+    assert_eq!(token.max_supply().unwrap(), None);
+
+    // Mint to two recipients with a single authorization.
+    token
+        .mint_many(
+            &admin,
+            &[
+                (user.address(&test.host), 10_000_000),
+                (user_2.address(&test.host), 20_000_000),
+            ],
+        )
+        .unwrap();
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 10_000_000);
+    assert_eq!(
+        token.balance(user_2.address(&test.host)).unwrap(),
+        20_000_000
+    );
+    // Without a max supply the total supply isn't tracked.
+    assert_eq!(token.total_supply().unwrap(), 0);
+    token.burn(&user, 1_000_000).unwrap();
+    assert_eq!(token.total_supply().unwrap(), 0);
+
+    // The max supply can only be set by the admin.
+    assert!(token.set_max_supply(&user, 15_000_000).is_err());
+    token.set_max_supply(&admin, 15_000_000).unwrap();
+    assert_eq!(token.max_supply().unwrap(), Some(15_000_000));
+
+    // Mints are capped, and a batch exceeding it credits nobody.
+    assert_eq!(
+        to_contract_err(
+            token
+                .mint_many(
+                    &admin,
+                    &[
+                        (user.address(&test.host), 10_000_000),
+                        (user_2.address(&test.host), 6_000_000),
+                    ],
+                )
+                .err()
+                .unwrap()
+        ),
+        ContractError::MaxSupplyExceededError
+    );
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 9_000_000);
+    assert_eq!(
+        to_contract_err(
+            token
+                .mint(&admin, user.address(&test.host), 15_000_001)
+                .err()
+                .unwrap()
+        ),
+        ContractError::MaxSupplyExceededError
+    );
+    token
+        .mint(&admin, user.address(&test.host), 10_000_000)
+        .unwrap();
+    assert_eq!(token.total_supply().unwrap(), 10_000_000);
+
+    // Burns reduce the total supply once it is tracked.
+    token.burn(&user, 1_000_000).unwrap();
+    assert_eq!(token.total_supply().unwrap(), 9_000_000);
+
+    // The max supply can't be lowered below the total supply.
+    assert_eq!(
+        to_contract_err(token.set_max_supply(&admin, 8_999_999).err().unwrap()),
+        ContractError::MaxSupplyExceededError
+    );
+    token
+        .mint(&admin, user.address(&test.host), 6_000_000)
+        .unwrap();
+    assert_eq!(token.total_supply().unwrap(), 15_000_000);
+}
+
 #[test]
 fn test_transfer_with_allowance() {
     let test = TokenTest::setup();
@@ -732,6 +1034,19 @@ fn test_allowance_expiration() {
             .unwrap(),
         990
     );
+    assert_eq!(
+        token
+            .allowance_data(user.address(&test.host), user_2.address(&test.host))
+            .unwrap(),
+        (990, 200)
+    );
+    // No allowance has been approved in the opposite direction.
+    assert_eq!(
+        token
+            .allowance_data(user_2.address(&test.host), user.address(&test.host))
+            .unwrap(),
+        (0, 0)
+    );
     assert_eq!(token.balance(user_2.address(&test.host)).unwrap(), 10);
 
     // advance the ledger past the expiration of the allowance. Allowance is no longer usable
@@ -744,6 +1059,13 @@ fn test_allowance_expiration() {
             .unwrap(),
         0
     );
+    // The expiration of a lapsed allowance is still reported.
+    assert_eq!(
+        token
+            .allowance_data(user.address(&test.host), user_2.address(&test.host))
+            .unwrap(),
+        (0, 200)
+    );
 
     assert_eq!(
         to_contract_err(
@@ -782,6 +1104,12 @@ fn test_allowance_expiration() {
             .unwrap(),
         10_000
     );
+    assert_eq!(
+        token
+            .allowance_data(user.address(&test.host), user_2.address(&test.host))
+            .unwrap(),
+        (10_000, 201)
+    );
 }
 #[test]
 fn test_burn() {
@@ -1121,6 +1449,91 @@ fn test_clawback_on_contract() {
     assert_eq!(token.balance(user_2_addr).unwrap(), 100_000_000);
 }
 
+// Native contract balances are only rejected as such from protocol 21 on,
+// which the host only supports when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn test_cannot_clawback_native_from_contract() {
+    let test = TokenTest::setup();
+    let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    let user_acc_id = signing_key_to_account_id(&test.user_key);
+    let user = TestSigner::account_with_multisig(&user_acc_id, vec![&test.user_key]);
+    test.create_account(
+        &user_acc_id,
+        vec![(&test.user_key, 100)],
+        100_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+
+    let contract_addr = contract_id_to_address(&test.host, generate_bytes_array());
+    token
+        .transfer(&user, contract_addr.clone(), 10_000_000)
+        .unwrap();
+
+    // Contract balances of the native asset are rejected the same way as
+    // account balances are.
+    assert_eq!(
+        to_contract_err(
+            token
+                .clawback(&user, contract_addr.clone(), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::OperationNotSupportedError
+    );
+    assert_eq!(
+        to_contract_err(
+            token
+                .clawback(&user, user.address(&test.host), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::OperationNotSupportedError
+    );
+    assert_eq!(token.balance(contract_addr).unwrap(), 10_000_000);
+}
+
+#[cfg(not(feature = "next"))]
+#[test]
+fn test_cannot_clawback_native_from_contract_before_protocol_21() {
+    let test = TokenTest::setup();
+    assert_eq!(test.host.get_ledger_protocol_version().unwrap(), 20);
+    let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    let user_acc_id = signing_key_to_account_id(&test.user_key);
+    let user = TestSigner::account_with_multisig(&user_acc_id, vec![&test.user_key]);
+    test.create_account(
+        &user_acc_id,
+        vec![(&test.user_key, 100)],
+        100_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+
+    let contract_addr = contract_id_to_address(&test.host, generate_bytes_array());
+    token
+        .transfer(&user, contract_addr.clone(), 10_000_000)
+        .unwrap();
+
+    // The contract balance is rejected for not being clawbackable.
+    assert_eq!(
+        to_contract_err(
+            token
+                .clawback(&user, contract_addr.clone(), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::BalanceError
+    );
+    assert_eq!(token.balance(contract_addr).unwrap(), 10_000_000);
+}
+
 #[test]
 fn test_auth_revocable_on_contract() {
     let test = TokenTest::setup();
@@ -1167,154 +1580,506 @@ fn test_auth_revocable_on_contract() {
     test.update_account_flags(&issuer_ledger_key, 0);
 
     token
-        .set_authorized(&admin, user_1_addr.clone(), true)
+        .set_authorized(&admin, user_1_addr.clone(), true)
+        .unwrap();
+    assert!(token.authorized(user_1_addr).unwrap());
+}
+
+#[test]
+fn test_auth_required() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+
+    let issuer_ledger_key = test
+        .host
+        .to_account_key(signing_key_to_account_id(&test.issuer_key))
+        .unwrap();
+
+    let user_1 = generate_bytes_array();
+    let user_2 = generate_bytes_array();
+    let user_1_addr = contract_id_to_address(&test.host, user_1);
+    let user_2_addr = contract_id_to_address(&test.host, user_2);
+
+    let user_1_invoker = TestSigner::ContractInvoker(Hash(user_1));
+    let user_1_bytes = BytesN::<32>::try_from_val(
+        &test.host,
+        &test.host.bytes_new_from_slice(&user_1).unwrap(),
+    )
+    .unwrap();
+
+    token
+        .mint(&admin, user_1_addr.clone(), 100_000_000)
+        .unwrap();
+
+    // Set auth required on the issuer. New contract balances will need to be authorized manually
+    test.update_account_flags(&issuer_ledger_key, AccountFlags::RequiredFlag as u32);
+
+    // The user_2 balance needs to be authorized first
+    assert_eq!(
+        to_contract_err(
+            test.run_from_contract(&user_1_bytes, || {
+                token.transfer(&user_1_invoker, user_2_addr.clone(), 1)
+            })
+            .err()
+            .unwrap()
+        ),
+        ContractError::BalanceDeauthorizedError
+    );
+
+    // authorize user_2
+    token
+        .set_authorized(&admin, user_2_addr.clone(), true)
+        .unwrap();
+
+    test.run_from_contract(&user_1_bytes, || {
+        token.transfer(&user_1_invoker, user_2_addr.clone(), 1)
+    })
+    .unwrap();
+
+    assert_eq!(token.balance(user_1_addr.clone()).unwrap(), 99_999_999);
+    assert_eq!(token.balance(user_2_addr.clone()).unwrap(), 1);
+}
+
+#[test]
+fn test_set_admin() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let new_admin = TestSigner::account(&test.user_key);
+    let user = TestSigner::account(&test.user_key_2);
+    test.create_default_account(&new_admin);
+    test.create_default_trustline(&new_admin);
+    test.create_default_account(&user);
+    test.create_default_trustline(&user);
+
+    assert_eq!(
+        token.admin().unwrap().to_sc_address().unwrap(),
+        admin.address(&test.host).to_sc_address().unwrap()
+    );
+
+    // Give admin rights to the new admin.
+    token
+        .set_admin(&admin, new_admin.address(&test.host))
+        .unwrap();
+
+    assert_eq!(
+        token.admin().unwrap().to_sc_address().unwrap(),
+        new_admin.address(&test.host).to_sc_address().unwrap()
+    );
+
+    // Make sure admin functions are unavailable to the old admin.
+    assert_eq!(
+        token
+            .set_admin(&admin, new_admin.address(&test.host),)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    assert_eq!(
+        token
+            .mint(&admin, new_admin.address(&test.host), 1)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    assert_eq!(
+        token
+            .clawback(&admin, new_admin.address(&test.host), 1)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    assert_eq!(
+        token
+            .set_authorized(&admin, new_admin.address(&test.host), false,)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    assert_eq!(
+        token
+            .set_authorized(&admin, new_admin.address(&test.host), true)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+
+    // The admin functions are now available to the new admin.
+    token.mint(&new_admin, user.address(&test.host), 1).unwrap();
+    token
+        .clawback(&new_admin, user.address(&test.host), 1)
+        .unwrap();
+    token
+        .set_authorized(&new_admin, user.address(&test.host), false)
+        .unwrap();
+    token
+        .set_authorized(&new_admin, user.address(&test.host), true)
+        .unwrap();
+
+    // Return the admin rights to the old admin
+    token
+        .set_admin(&new_admin, admin.address(&test.host))
+        .unwrap();
+    // Make sure old admin can now perform admin operations
+    token.mint(&admin, user.address(&test.host), 1).unwrap();
+}
+
+#[test]
+fn test_propose_and_accept_admin() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let new_admin = TestSigner::account(&test.user_key);
+    let other = TestSigner::account(&test.user_key_2);
+    test.create_default_account(&new_admin);
+    test.create_default_trustline(&new_admin);
+    test.create_default_account(&other);
+
+    // Nothing to accept until an admin has been proposed.
+    assert_eq!(
+        to_contract_err(token.accept_admin(&new_admin).err().unwrap()),
+        ContractError::OperationNotSupportedError
+    );
+
+    // Only the current admin may propose a new one.
+    assert_eq!(
+        token
+            .propose_admin(&other, other.address(&test.host))
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    token
+        .propose_admin(&admin, new_admin.address(&test.host))
+        .unwrap();
+
+    // The proposal alone doesn't change the admin.
+    assert_eq!(
+        token.admin().unwrap().to_sc_address().unwrap(),
+        admin.address(&test.host).to_sc_address().unwrap()
+    );
+    token
+        .mint(&admin, new_admin.address(&test.host), 1)
+        .unwrap();
+
+    // Only the proposed admin may accept.
+    assert_eq!(
+        token.accept_admin(&other).err().unwrap().error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    token.accept_admin(&new_admin).unwrap();
+    assert_eq!(
+        token.admin().unwrap().to_sc_address().unwrap(),
+        new_admin.address(&test.host).to_sc_address().unwrap()
+    );
+    assert_eq!(
+        token
+            .mint(&admin, new_admin.address(&test.host), 1)
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+    token
+        .mint(&new_admin, new_admin.address(&test.host), 1)
+        .unwrap();
+
+    // The proposal is consumed by accepting it.
+    assert_eq!(
+        to_contract_err(token.accept_admin(&new_admin).err().unwrap()),
+        ContractError::OperationNotSupportedError
+    );
+
+    // `set_admin` discards any pending proposal.
+    token
+        .propose_admin(&new_admin, other.address(&test.host))
+        .unwrap();
+    token
+        .set_admin(&new_admin, admin.address(&test.host))
+        .unwrap();
+    assert_eq!(
+        to_contract_err(token.accept_admin(&other).err().unwrap()),
+        ContractError::OperationNotSupportedError
+    );
+}
+
+#[test]
+fn test_set_metadata() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    let old_name = token.name().unwrap().to_string();
+
+    let new_string = |s: &str| {
+        crate::native_contract::base_types::String::try_from_val(
+            &test.host,
+            &test.host.string_new_from_slice(s).unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Only the admin can update the metadata.
+    assert_eq!(
+        token
+            .set_metadata(&user, new_string("Fake"), new_string("FAKE"))
+            .err()
+            .unwrap()
+            .error,
+        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+    );
+
+    token
+        .set_metadata(&admin, new_string("Better Name"), new_string("BTR"))
         .unwrap();
-    assert!(token.authorized(user_1_addr).unwrap());
+    assert_eq!(token.name().unwrap().to_string(), "Better Name");
+    assert_eq!(token.symbol().unwrap().to_string(), "BTR");
+    assert_eq!(token.decimals().unwrap(), 7);
+
+    // The event is tagged with the previous name and carries the new
+    // metadata.
+    let event = test.host.get_events().unwrap().0.last().unwrap().clone();
+    let xdr::ContractEventBody::V0(body) = event.event.body;
+    assert_eq!(
+        body.topics[0],
+        ScVal::Symbol(ScSymbol("set_metadata".try_into().unwrap()))
+    );
+    assert_eq!(
+        body.topics[2],
+        ScVal::String(xdr::ScString(old_name.try_into().unwrap()))
+    );
+    assert_eq!(
+        body.data,
+        ScVal::Vec(Some(xdr::ScVec(
+            vec![
+                ScVal::String(xdr::ScString("Better Name".try_into().unwrap())),
+                ScVal::String(xdr::ScString("BTR".try_into().unwrap())),
+            ]
+            .try_into()
+            .unwrap()
+        )))
+    );
 }
 
 #[test]
-fn test_auth_required() {
+fn test_token_event_topics() {
     let test = TokenTest::setup();
     let admin = TestSigner::account(&test.issuer_key);
     let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    for u in [&user, &user_2] {
+        test.create_default_account(u);
+        test.create_default_trustline(u);
+    }
 
-    let issuer_ledger_key = test
-        .host
-        .to_account_key(signing_key_to_account_id(&test.issuer_key))
-        .unwrap();
-
-    let user_1 = generate_bytes_array();
-    let user_2 = generate_bytes_array();
-    let user_1_addr = contract_id_to_address(&test.host, user_1);
-    let user_2_addr = contract_id_to_address(&test.host, user_2);
+    let sym = |s: &str| ScVal::Symbol(ScSymbol(s.try_into().unwrap()));
+    let addr = |s: &TestSigner| ScVal::Address(s.address(&test.host).to_sc_address().unwrap());
+    let amount = |a: i128| {
+        ScVal::I128(xdr::Int128Parts {
+            hi: (a >> 64) as i64,
+            lo: a as u64,
+        })
+    };
+    let name = ScVal::String(xdr::ScString(
+        token.name().unwrap().to_string().try_into().unwrap(),
+    ));
+    // Every event is tagged with the token name after the SEP-41 topics.
+    let check_last_event = |topics: Vec<ScVal>, data: ScVal| {
+        let event = test.host.get_events().unwrap().0.last().unwrap().clone();
+        let xdr::ContractEventBody::V0(body) = event.event.body;
+        assert_eq!(body.topics.to_vec(), topics);
+        assert_eq!(body.data, data);
+    };
 
-    let user_1_invoker = TestSigner::ContractInvoker(Hash(user_1));
-    let user_1_bytes = BytesN::<32>::try_from_val(
-        &test.host,
-        &test.host.bytes_new_from_slice(&user_1).unwrap(),
-    )
-    .unwrap();
+    token.mint(&admin, user.address(&test.host), 1000).unwrap();
+    check_last_event(
+        vec![sym("mint"), addr(&admin), addr(&user), name.clone()],
+        amount(1000),
+    );
 
     token
-        .mint(&admin, user_1_addr.clone(), 100_000_000)
+        .transfer(&user, user_2.address(&test.host), 400)
         .unwrap();
+    check_last_event(
+        vec![sym("transfer"), addr(&user), addr(&user_2), name.clone()],
+        amount(400),
+    );
 
-    // Set auth required on the issuer. New contract balances will need to be authorized manually
-    test.update_account_flags(&issuer_ledger_key, AccountFlags::RequiredFlag as u32);
+    token.burn(&user, 100).unwrap();
+    check_last_event(vec![sym("burn"), addr(&user), name.clone()], amount(100));
 
-    // The user_2 balance needs to be authorized first
-    assert_eq!(
-        to_contract_err(
-            test.run_from_contract(&user_1_bytes, || {
-                token.transfer(&user_1_invoker, user_2_addr.clone(), 1)
-            })
-            .err()
-            .unwrap()
-        ),
-        ContractError::BalanceDeauthorizedError
+    token
+        .clawback(&admin, user.address(&test.host), 100)
+        .unwrap();
+    check_last_event(
+        vec![sym("clawback"), addr(&admin), addr(&user), name.clone()],
+        amount(100),
     );
 
-    // authorize user_2
     token
-        .set_authorized(&admin, user_2_addr.clone(), true)
+        .set_authorized(&admin, user.address(&test.host), false)
         .unwrap();
+    check_last_event(
+        vec![
+            sym("set_authorized"),
+            addr(&admin),
+            addr(&user),
+            name.clone(),
+        ],
+        ScVal::Bool(false),
+    );
 
-    test.run_from_contract(&user_1_bytes, || {
-        token.transfer(&user_1_invoker, user_2_addr.clone(), 1)
-    })
-    .unwrap();
-
-    assert_eq!(token.balance(user_1_addr.clone()).unwrap(), 99_999_999);
-    assert_eq!(token.balance(user_2_addr.clone()).unwrap(), 1);
+    token
+        .approve(&user_2, user.address(&test.host), 50, 200)
+        .unwrap();
+    check_last_event(
+        vec![sym("approve"), addr(&user_2), addr(&user), name.clone()],
+        ScVal::Vec(Some(xdr::ScVec(
+            vec![amount(50), ScVal::U32(200)].try_into().unwrap(),
+        ))),
+    );
 }
 
+// Pins the encoded XDR of the events, which is what downstream consumers
+// actually see, for fixed keys: the issuer and users are the ed25519 keys with
+// seeds of all 1s, 2s and 3s, so the token name is `:` followed by the
+// issuer's strkey.
 #[test]
-fn test_set_admin() {
-    let test = TokenTest::setup();
+fn test_token_event_xdr() {
+    let mut test = TokenTest::setup();
+    test.issuer_key = SigningKey::from_bytes(&[1; 32]);
+    test.user_key = SigningKey::from_bytes(&[2; 32]);
+    test.user_key_2 = SigningKey::from_bytes(&[3; 32]);
     let admin = TestSigner::account(&test.issuer_key);
     let token = test.default_token();
-    let new_admin = TestSigner::account(&test.user_key);
-    let user = TestSigner::account(&test.user_key_2);
-    test.create_default_account(&new_admin);
-    test.create_default_trustline(&new_admin);
-    test.create_default_account(&user);
-    test.create_default_trustline(&user);
+    let user = TestSigner::account(&test.user_key);
+    let user_2 = TestSigner::account(&test.user_key_2);
+    for u in [&user, &user_2] {
+        test.create_default_account(u);
+        test.create_default_trustline(u);
+    }
+    let last_event_body_xdr = || {
+        let event = test.host.get_events().unwrap().0.last().unwrap().clone();
+        hex::encode(event.event.body.to_xdr().unwrap())
+    };
 
-    assert_eq!(
-        token.admin().unwrap().to_sc_address().unwrap(),
-        admin.address(&test.host).to_sc_address().unwrap()
-    );
+    token.mint(&admin, user.address(&test.host), 1000).unwrap();
+    expect![[r#"00000000000000040000000f000000046d696e740000001200000000000000008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c0000001200000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940000000e000000393a47434649525936354f514537444650354b4c4e53325046324c565a4d555a594a58344f5a49455133364e324951414e5542355856594f4a520000000000000a000000000000000000000000000003e8"#]].assert_eq(&last_event_body_xdr());
 
-    // Give admin rights to the new admin.
     token
-        .set_admin(&admin, new_admin.address(&test.host))
+        .transfer(&user, user_2.address(&test.host), 400)
         .unwrap();
+    expect![[r#"00000000000000040000000f000000087472616e736665720000001200000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394000000120000000000000000ed4928c628d1c2c6eae90338905995612959273a5c63f93636c14614ac8737d10000000e000000393a47434649525936354f514537444650354b4c4e53325046324c565a4d555a594a58344f5a49455133364e324951414e5542355856594f4a520000000000000a00000000000000000000000000000190"#]].assert_eq(&last_event_body_xdr());
 
-    assert_eq!(
-        token.admin().unwrap().to_sc_address().unwrap(),
-        new_admin.address(&test.host).to_sc_address().unwrap()
-    );
+    token.burn(&user, 100).unwrap();
+    expect![[r#"00000000000000030000000f000000046275726e0000001200000000000000008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b3940000000e000000393a47434649525936354f514537444650354b4c4e53325046324c565a4d555a594a58344f5a49455133364e324951414e5542355856594f4a520000000000000a00000000000000000000000000000064"#]].assert_eq(&last_event_body_xdr());
+}
 
-    // Make sure admin functions are unavailable to the old admin.
-    assert_eq!(
-        token
-            .set_admin(&admin, new_admin.address(&test.host),)
-            .err()
-            .unwrap()
-            .error,
-        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
-    );
+// Amounts are only limited to the classic range from protocol 21 on, which the
+// host only supports when built with the "next" feature.
+#[cfg(feature = "next")]
+#[test]
+fn test_amounts_limited_to_classic_balance_range() {
+    let test = TokenTest::setup();
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let user = TestSigner::account(&test.user_key);
+    test.create_default_account(&user);
+    let contract_address = contract_id_to_address(&test.host, generate_bytes_array());
+
+    let too_large = i64::MAX as i128 + 1;
     assert_eq!(
-        token
-            .mint(&admin, new_admin.address(&test.host), 1)
-            .err()
-            .unwrap()
-            .error,
-        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+        to_contract_err(
+            token
+                .mint(&admin, contract_address.clone(), too_large)
+                .err()
+                .unwrap()
+        ),
+        ContractError::OverflowError
     );
+    token
+        .mint(&admin, contract_address.clone(), i64::MAX as i128)
+        .unwrap();
     assert_eq!(
-        token
-            .clawback(&admin, new_admin.address(&test.host), 1)
-            .err()
-            .unwrap()
-            .error,
-        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+        token.balance(contract_address.clone()).unwrap(),
+        i64::MAX as i128
     );
+    // The resulting balance is limited too, not just each amount.
     assert_eq!(
-        token
-            .set_authorized(&admin, new_admin.address(&test.host), false,)
-            .err()
-            .unwrap()
-            .error,
-        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+        to_contract_err(
+            token
+                .mint(&admin, contract_address.clone(), 1)
+                .err()
+                .unwrap()
+        ),
+        ContractError::OverflowError
     );
     assert_eq!(
-        token
-            .set_authorized(&admin, new_admin.address(&test.host), true)
-            .err()
-            .unwrap()
-            .error,
-        (ScErrorType::Auth, ScErrorCode::InvalidAction).into()
+        token.balance(contract_address.clone()).unwrap(),
+        i64::MAX as i128
     );
 
-    // The admin functions are now available to the new admin.
-    token.mint(&new_admin, user.address(&test.host), 1).unwrap();
+    // Allowances are not balances and may be effectively unlimited.
     token
-        .clawback(&new_admin, user.address(&test.host), 1)
+        .approve(&user, contract_address, i128::MAX, 200)
         .unwrap();
+}
+
+#[cfg(not(feature = "next"))]
+#[test]
+fn test_amounts_not_limited_to_classic_balance_range_before_protocol_21() {
+    let test = TokenTest::setup();
+    assert_eq!(test.host.get_ledger_protocol_version().unwrap(), 20);
+    let admin = TestSigner::account(&test.issuer_key);
+    let token = test.default_token();
+    let contract_address = contract_id_to_address(&test.host, generate_bytes_array());
+
     token
-        .set_authorized(&new_admin, user.address(&test.host), false)
+        .mint(&admin, contract_address.clone(), i64::MAX as i128 + 1)
         .unwrap();
     token
-        .set_authorized(&new_admin, user.address(&test.host), true)
+        .mint(&admin, contract_address.clone(), i64::MAX as i128)
         .unwrap();
+    assert_eq!(
+        token.balance(contract_address).unwrap(),
+        2 * i64::MAX as i128 + 1
+    );
+}
 
-    // Return the admin rights to the old admin
-    token
-        .set_admin(&new_admin, admin.address(&test.host))
-        .unwrap();
-    // Make sure old admin can now perform admin operations
-    token.mint(&admin, user.address(&test.host), 1).unwrap();
+#[test]
+fn test_native_token_metadata_is_immutable() {
+    let test = TokenTest::setup();
+    let account_id = signing_key_to_account_id(&test.user_key);
+    test.create_account(
+        &account_id,
+        vec![(&test.user_key, 100)],
+        100_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+    let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    let user = TestSigner::account_with_multisig(&account_id, vec![&test.user_key]);
+    let name = crate::native_contract::base_types::String::try_from_val(
+        &test.host,
+        &test.host.string_new_from_slice("not native").unwrap(),
+    )
+    .unwrap();
+
+    // There is no admin for the native token.
+    assert!(token.set_metadata(&user, name.clone(), name).is_err());
+    assert_eq!(token.name().unwrap().to_string(), "native");
 }
 
 #[test]
@@ -1341,6 +2106,76 @@ fn test_account_spendable_balance() {
     assert_eq!(token.spendable_balance(user_addr).unwrap(), 85_000_000);
 }
 
+#[test]
+fn test_trustline_spendable_balance() {
+    let test = TokenTest::setup();
+    let admin_acc_id = signing_key_to_account_id(&test.issuer_key);
+    let user_acc_id = signing_key_to_account_id(&test.user_key);
+    let admin = TestSigner::account_with_multisig(&admin_acc_id, vec![&test.issuer_key]);
+    let user = TestSigner::account_with_multisig(&user_acc_id, vec![&test.user_key]);
+    let token = test.default_token_with_admin_id(&admin.address(&test.host));
+
+    test.create_account(
+        &admin_acc_id,
+        vec![(&test.issuer_key, 100)],
+        10_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+    test.create_account(
+        &user_acc_id,
+        vec![(&test.user_key, 100)],
+        10_000_000,
+        1,
+        [1, 0, 0, 0],
+        None,
+        None,
+        0,
+    );
+    test.create_trustline(
+        &user_acc_id,
+        &admin_acc_id,
+        &test.asset_code,
+        1000,
+        10000,
+        TrustLineFlags::AuthorizedFlag as u32,
+        None,
+    );
+
+    // Trustlines don't need a reserve of the asset, so without liabilities
+    // the whole balance is spendable.
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 1000);
+    assert_eq!(
+        token.spendable_balance(user.address(&test.host)).unwrap(),
+        1000
+    );
+
+    // Selling liabilities aren't spendable.
+    test.create_trustline(
+        &user_acc_id,
+        &admin_acc_id,
+        &test.asset_code,
+        1000,
+        10000,
+        TrustLineFlags::AuthorizedFlag as u32,
+        Some((500, 300)),
+    );
+    assert_eq!(token.balance(user.address(&test.host)).unwrap(), 1000);
+    assert_eq!(
+        token.spendable_balance(user.address(&test.host)).unwrap(),
+        700
+    );
+
+    // The issuer can spend any amount of its own asset.
+    assert_eq!(
+        token.spendable_balance(admin.address(&test.host)).unwrap(),
+        i64::MAX as i128
+    );
+}
+
 #[test]
 fn test_trustline_auth() {
     let test = TokenTest::setup();
@@ -2212,6 +3047,14 @@ fn test_native_token_classic_balance_boundaries(
     expected_max_balance: i64,
 ) {
     let token = TestToken::new_from_asset(&test.host, Asset::Native);
+    assert_eq!(
+        token.balance(user.address(&test.host)).unwrap(),
+        init_balance as i128
+    );
+    assert_eq!(
+        token.spendable_balance(user.address(&test.host)).unwrap(),
+        (init_balance - expected_min_balance) as i128
+    );
 
     let new_balance_key = generate_signing_key();
     let new_balance_acc = signing_key_to_account_id(&new_balance_key);
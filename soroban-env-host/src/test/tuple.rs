@@ -33,3 +33,34 @@ fn tuple_array_conversions() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn tuple_array_length_mismatch() -> Result<(), HostError> {
+    let host = Host::default();
+
+    let res: Result<[Val; 1], _> = (1u32, 1i32).try_into_val(&host);
+    assert!(res.is_err());
+    let res: Result<[Val; 3], _> = (1u32, 1i32).try_into_val(&host);
+    assert!(res.is_err());
+
+    let val: [Val; 3] = (1u32, 1i32, 1u32).try_into_val(&host)?;
+    let res: Result<(u32, i32), _> = val.try_into_val(&host);
+    assert!(res.is_err());
+    let res: Result<(u32, i32, u32, i32), _> = [Val::from(1u32)].try_into_val(&host);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn tuple_vec_length_mismatch() -> Result<(), HostError> {
+    let host = Host::default();
+
+    let val: Val = (1u32, 1i32, 1u32).try_into_val(&host)?;
+    let res: Result<(u32, i32), _> = val.try_into_val(&host);
+    assert!(res.is_err());
+    let res: Result<(u32, i32, u32, i32), _> = val.try_into_val(&host);
+    assert!(res.is_err());
+
+    Ok(())
+}
@@ -6,14 +6,15 @@ use soroban_env_common::{
         AccountEntry, AccountId, ContractCostType, LedgerEntry, LedgerEntryData, LedgerKey,
         PublicKey, ScAddress, ScErrorCode, ScErrorType, ScVal, ScVec, Uint256,
     },
-    AddressObject, BytesObject, Env, EnvBase, Symbol, Val, VecObject,
+    AddressObject, BytesObject, Env, EnvBase, Symbol, Val, VecObject, VmCaller,
 };
 use soroban_synth_wasm::{Arity, ModEmitter, Operand};
 
 use crate::{
     budget::{AsBudget, Budget},
+    host::Frame,
     storage::{SnapshotSource, Storage},
-    xdr, Error, Host, HostError, LedgerInfo,
+    xdr, Error, Host, HostError, LedgerInfo, Vm,
 };
 
 use soroban_bench_utils::HostTracker;
@@ -74,6 +75,12 @@ impl MockSnapshotSource {
     pub(crate) fn new() -> Self {
         Self(BTreeMap::<Rc<LedgerKey>, (Rc<LedgerEntry>, Option<u32>)>::new())
     }
+
+    pub(crate) fn from_entries(
+        entries: Vec<(Rc<LedgerKey>, (Rc<LedgerEntry>, Option<u32>))>,
+    ) -> Self {
+        Self(entries.into_iter().collect())
+    }
 }
 impl SnapshotSource for MockSnapshotSource {
     fn get(&self, key: &Rc<LedgerKey>) -> Result<(Rc<LedgerEntry>, Option<u32>), HostError> {
@@ -240,6 +247,32 @@ impl Host {
         )
     }
 
+    // Instantiates a VM for the provided Wasm code and runs `f` in a frame of
+    // that VM, so that host functions operating on guest linear memory can be
+    // called directly.
+    pub(crate) fn with_test_vm_frame<F>(&self, contract_wasm: &[u8], f: F) -> Result<(), HostError>
+    where
+        F: FnOnce(&mut VmCaller<Host>) -> Result<(), HostError>,
+    {
+        let id = xdr::Hash(generate_bytes_array());
+        let vm = Vm::new(self, id.clone(), contract_wasm)?;
+        let frame = Frame::ContractVM {
+            vm: Rc::clone(&vm),
+            fn_name: Symbol::try_from_small_str("test").unwrap(),
+            args: vec![],
+            instance: xdr::ScContractInstance {
+                executable: xdr::ContractExecutable::Wasm(id),
+                storage: None,
+            },
+            relative_objects: vec![],
+        };
+        self.with_frame(frame, || {
+            vm.with_vmcaller(f)?;
+            Ok(Val::VOID.into())
+        })?;
+        Ok(())
+    }
+
     pub(crate) fn measured_call(
         &self,
         contract: AddressObject,
@@ -174,6 +174,36 @@ fn vec_slice_and_cmp() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn vec_slice_cost_depends_on_slice_length_only() -> Result<(), HostError> {
+    use crate::budget::AsBudget;
+
+    let host = Host::test_host();
+    host.as_budget().reset_unlimited()?;
+    let small = host.test_vec_obj::<u32>(&[7; 10])?;
+    let large = host.test_vec_obj::<u32>(&[7; 10_000])?;
+    let slice_cost = |v, start: u32, end: u32| -> Result<(u64, u64), HostError> {
+        let budget = host.as_budget();
+        let (cpu, mem) = (
+            budget.get_cpu_insns_consumed()?,
+            budget.get_mem_bytes_consumed()?,
+        );
+        host.vec_slice(v, start.into(), end.into())?;
+        Ok((
+            budget.get_cpu_insns_consumed()? - cpu,
+            budget.get_mem_bytes_consumed()? - mem,
+        ))
+    };
+
+    // Taking a window of a large vector costs the same as taking one of a
+    // vector of the window's size, wherever the window is.
+    let window = slice_cost(small, 0, 10)?;
+    assert_eq!(slice_cost(large, 0, 10)?, window);
+    assert_eq!(slice_cost(large, 5_000, 5_010)?, window);
+    assert!(slice_cost(large, 0, 1_000)? > window);
+    Ok(())
+}
+
 #[test]
 fn vec_slice_start_equal_to_end() -> Result<(), HostError> {
     let host = Host::default();
@@ -280,6 +310,27 @@ fn vec_index_of() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn vec_contains() -> Result<(), HostError> {
+    use crate::EnvBase;
+    let host = Host::default();
+    let obj0 = host.test_vec_obj::<u32>(&[3, 4, 2, 2, 2, 5])?;
+    assert!(bool::from(host.vec_contains(obj0, 2u32.into())?));
+    assert!(!bool::from(host.vec_contains(obj0, 1u32.into())?));
+
+    // Objects are compared by content rather than by handle.
+    let b0 = host.test_bin_obj(&[1, 2])?.to_val();
+    let objs = host.vec_new_from_slice(&[b0])?;
+    let b1 = host.test_bin_obj(&[1, 2])?.to_val();
+    assert!(bool::from(host.vec_contains(objs, b1)?));
+    let b2 = host.test_bin_obj(&[2, 1])?.to_val();
+    assert!(!bool::from(host.vec_contains(objs, b2)?));
+
+    let empty = host.vec_new()?;
+    assert!(!bool::from(host.vec_contains(empty, 2u32.into())?));
+    Ok(())
+}
+
 #[test]
 fn vec_binary_search() -> Result<(), HostError> {
     let host = Host::default();
@@ -297,6 +348,35 @@ fn vec_binary_search() -> Result<(), HostError> {
     Ok(())
 }
 
+#[test]
+fn vec_sort() -> Result<(), HostError> {
+    use crate::EnvBase;
+    let host = Host::default();
+    let obj0 = host.test_vec_obj::<u32>(&[5, 1, 9, 4, 2, 7, 4])?;
+    let sorted = host.vec_sort(obj0)?;
+    let exp = host.test_vec_obj::<u32>(&[1, 2, 4, 4, 5, 7, 9])?;
+    assert_eq!(host.compare(&sorted, &exp)?, Ordering::Equal);
+    // The input vector is left untouched.
+    let orig = host.test_vec_obj::<u32>(&[5, 1, 9, 4, 2, 7, 4])?;
+    assert_eq!(host.compare(&obj0, &orig)?, Ordering::Equal);
+    // The result can be binary searched.
+    let res = host.vec_binary_search(sorted, 7u32.into())?;
+    assert_eq!(res, 5 | (1 << 32));
+
+    // Objects are ordered by content.
+    let b0 = host.test_bin_obj(&[3, 2])?.to_val();
+    let b1 = host.test_bin_obj(&[1])?.to_val();
+    let b2 = host.test_bin_obj(&[2, 9, 9])?.to_val();
+    let objs = host.vec_new_from_slice(&[b0, b1, b2])?;
+    let sorted = host.vec_sort(objs)?;
+    let exp = host.vec_new_from_slice(&[b1, b2, b0])?;
+    assert_eq!(host.compare(&sorted, &exp)?, Ordering::Equal);
+
+    let empty = host.vec_new()?;
+    assert_eq!(u32::from(host.vec_len(host.vec_sort(empty)?)?), 0);
+    Ok(())
+}
+
 #[test]
 fn vec_build_bad_element_integrity() -> Result<(), HostError> {
     use crate::EnvBase;
@@ -336,3 +416,52 @@ fn vec_build_bad_element_integrity() -> Result<(), HostError> {
 
     Ok(())
 }
+
+#[test]
+fn vec_linear_memory_roundtrip() -> Result<(), HostError> {
+    use crate::VmCallerEnv;
+    use soroban_test_wasms::LINEAR_MEMORY;
+
+    let host = Host::test_host_with_recording_footprint();
+    let vec = host.test_vec_obj::<u32>(&[1, 2, 3])?;
+    let checks_length = host.get_ledger_protocol_version()? >= 21;
+    host.with_test_vm_frame(LINEAR_MEMORY, |vmcaller| {
+        // From protocol 21 on the output slice must be exactly as long as the
+        // vector; before that the whole vector is written regardless.
+        for len in [2u32, 4] {
+            let res = VmCallerEnv::vec_unpack_to_linear_memory(
+                &host,
+                vmcaller,
+                vec,
+                U32Val::from(0),
+                U32Val::from(len),
+            );
+            if checks_length {
+                assert!(HostError::result_matches_err(
+                    res,
+                    (ScErrorType::Object, ScErrorCode::UnexpectedSize)
+                ));
+            } else {
+                assert!(res.is_ok());
+            }
+        }
+        VmCallerEnv::vec_unpack_to_linear_memory(
+            &host,
+            vmcaller,
+            vec,
+            U32Val::from(0),
+            U32Val::from(3),
+        )?;
+        let copy = VmCallerEnv::vec_new_from_linear_memory(
+            &host,
+            vmcaller,
+            U32Val::from(0),
+            U32Val::from(3),
+        )?;
+        assert_eq!(
+            host.compare(&copy.to_val(), &vec.to_val())?,
+            Ordering::Equal
+        );
+        Ok(())
+    })
+}
@@ -8,12 +8,16 @@
 //! The implementation of WASM types and the WASM bytecode interpreter come from
 //! the [wasmi](https://github.com/paritytech/wasmi) project.
 
-mod dispatch;
+mod config;
+pub(crate) mod dispatch;
 mod fuel_refillable;
 mod func_info;
+mod module_cache;
 
+pub use config::VmConfig;
 #[cfg(any(test, feature = "testutils"))]
 pub(crate) use dispatch::dummy0;
+pub use module_cache::{ModuleCache, DEFAULT_MODULE_CACHE_MAX_BYTES};
 
 use crate::{
     budget::AsBudget,
@@ -33,10 +37,13 @@ use soroban_env_common::{
         DepthLimitedRead, ReadXdr, ScEnvMetaEntry, ScErrorCode, ScErrorType,
         DEFAULT_XDR_RW_DEPTH_LIMIT,
     },
-    ConversionError, SymbolStr, TryIntoVal, WasmiMarshal,
+    ConversionError, EnvBase, SymbolStr, TryFromVal, TryIntoVal, WasmiMarshal,
 };
 
-use wasmi::{Engine, FuelConsumptionMode, Instance, Linker, Memory, Module, Store, Value};
+use wasmi::{
+    core::ValueType, Engine, FuelConsumptionMode, FuelCosts, Instance, Linker, Memory, Module,
+    Store, Value,
+};
 
 #[cfg(any(test, feature = "testutils"))]
 use crate::VmCaller;
@@ -44,6 +51,10 @@ use crate::VmCaller;
 use wasmi::{Caller, StoreContextMut};
 impl wasmi::core::HostError for HostError {}
 
+// Protocol from which module imports are checked against the env interface
+// before instantiation (see `Vm::check_imports`).
+const CHECK_IMPORTS_MIN_PROTOCOL: u32 = 21;
+
 /// A [Vm] is a thin wrapper around an instance of [wasmi::Module]. Multiple
 /// [Vm]s may be held in a single [Host], and each contains a single WASM module
 /// instantiation.
@@ -58,9 +69,9 @@ impl wasmi::core::HostError for HostError {}
 pub struct Vm {
     #[allow(dead_code)]
     pub(crate) contract_id: Hash,
-    // TODO: consider moving store and possibly module to Host so they can be
-    // recycled across calls. Or possibly beyond, to be recycled across txs.
-    module: Module,
+    // The module may be shared with a `ModuleCache` on the host, so it can be
+    // recycled across calls, or across hosts sharing the cache.
+    module: Rc<Module>,
     store: RefCell<Store<Host>>,
     instance: Instance,
     memory: Option<Memory>,
@@ -169,6 +180,169 @@ impl Vm {
         }
     }
 
+    // Checks every import of the module against the env interface, so that a
+    // contract importing a host function that doesn't exist (or isn't
+    // available in the current protocol), or importing one with the wrong
+    // signature, is rejected with an error naming each bad import rather than
+    // with an opaque link error from instantiation.
+    //
+    // Looking up each import is charged as a comparison of its name; the
+    // messages naming the bad imports are only built if there are any, and
+    // only when diagnostics are enabled.
+    //
+    // Both the charges and the error are part of the invocation result, so the
+    // check only runs from `CHECK_IMPORTS_MIN_PROTOCOL` on; before that, bad
+    // imports keep failing at link time as they always have.
+    fn check_imports(host: &Host, m: &Module) -> Result<(), HostError> {
+        enum BadImport {
+            NotAFunction,
+            Unknown,
+            Unavailable(&'static meta::HostFunctionInfo),
+            Mismatched(&'static meta::HostFunctionInfo),
+        }
+
+        let ledger_proto = host.get_ledger_protocol_version()?;
+        if ledger_proto < CHECK_IMPORTS_MIN_PROTOCOL {
+            return Ok(());
+        }
+        let mut bad_imports = Vec::new();
+        for import in m.imports() {
+            host.charge_budget(
+                ContractCostType::HostMemCmp,
+                Some((import.module().len() as u64).saturating_add(import.name().len() as u64)),
+            )?;
+            let bad = match (
+                import.ty().func(),
+                meta::find_host_function(import.module(), import.name()),
+            ) {
+                (None, _) => Some(BadImport::NotAFunction),
+                (Some(_), None) => Some(BadImport::Unknown),
+                (Some(_), Some(hf)) if !hf.is_supported_at_protocol(ledger_proto) => {
+                    Some(BadImport::Unavailable(hf))
+                }
+                (Some(func_ty), Some(hf)) => {
+                    let all_i64 = |tys: &[ValueType]| tys.iter().all(|t| *t == ValueType::I64);
+                    if func_ty.params().len() != hf.arity()
+                        || func_ty.results().len() != 1
+                        || !all_i64(func_ty.params())
+                        || !all_i64(func_ty.results())
+                    {
+                        Some(BadImport::Mismatched(hf))
+                    } else {
+                        None
+                    }
+                }
+            };
+            if let Some(bad) = bad {
+                bad_imports.push((import.module(), import.name(), bad));
+            }
+        }
+        if bad_imports.is_empty() {
+            return Ok(());
+        }
+        let mut args: Vec<Val> = vec![];
+        host.with_debug_mode(|| {
+            for (module, name, bad) in bad_imports.iter() {
+                let msg = match bad {
+                    BadImport::NotAFunction => {
+                        format!("{module}.{name}: only function imports are supported")
+                    }
+                    BadImport::Unknown => format!("{module}.{name}: unknown host function"),
+                    BadImport::Unavailable(hf) => format!(
+                        "{module}.{name}: {} is not available in protocol {ledger_proto}",
+                        hf.name
+                    ),
+                    BadImport::Mismatched(hf) => format!(
+                        "{module}.{name}: {} expects {} i64 arguments and returns one i64",
+                        hf.name,
+                        hf.arity()
+                    ),
+                };
+                args.push(host.string_new_from_slice(&msg)?.to_val());
+            }
+            Ok(())
+        })?;
+        Err(host.err(
+            ScErrorType::WasmVm,
+            ScErrorCode::InvalidInput,
+            "contract has unresolved or mismatched host function imports",
+            args.as_slice(),
+        ))
+    }
+
+    pub(crate) fn wasmi_config(host: &Host) -> Result<wasmi::Config, HostError> {
+        let fuel_costs = host.as_budget().wasmi_fuel_costs()?;
+        Ok(Self::wasmi_config_for(&host.get_vm_config()?, fuel_costs))
+    }
+
+    pub(crate) fn wasmi_config_for(vm_config: &VmConfig, fuel_costs: FuelCosts) -> wasmi::Config {
+        let mut config = wasmi::Config::default();
+
+        // Turn off all optional wasm features, other than those enabled by
+        // the VmConfig.
+        config
+            .wasm_mutable_global(true)
+            .wasm_saturating_float_to_int(false)
+            .floats(false)
+            .consume_fuel(true)
+            .fuel_consumption_mode(FuelConsumptionMode::Eager)
+            .set_fuel_costs(fuel_costs);
+        vm_config.apply(&mut config);
+        config
+    }
+
+    // Called when `module_wasm_code` fails to parse: if it would have parsed
+    // with one of the proposals the host's VmConfig disables, fails naming
+    // that proposal rather than with wasmi's validation error. This only
+    // happens when diagnostics are enabled, since it means parsing again.
+    fn check_disabled_features(host: &Host, module_wasm_code: &[u8]) -> Result<(), HostError> {
+        let mut feature = None;
+        host.with_debug_mode(|| {
+            let fuel_costs = host.as_budget().wasmi_fuel_costs()?;
+            feature = host
+                .get_vm_config()?
+                .find_disabled_feature_used(module_wasm_code, fuel_costs);
+            Ok(())
+        })?;
+        if let Some(name) = feature {
+            let name = Symbol::try_from_val(host, &name)?;
+            return Err(host.err(
+                ScErrorType::WasmVm,
+                ScErrorCode::InvalidAction,
+                "contract uses a Wasm feature that is not enabled",
+                &[name.to_val()],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses and validates `module_wasm_code` into a [Module] of the given
+    /// [Engine], charging [ContractCostType::VmInstantiation] and checking
+    /// the module's [meta::INTERFACE_VERSION] and imports.
+    pub(crate) fn parse_module(
+        host: &Host,
+        engine: &Engine,
+        module_wasm_code: &[u8],
+    ) -> Result<Module, HostError> {
+        host.charge_budget(
+            ContractCostType::VmInstantiation,
+            Some(module_wasm_code.len() as u64),
+        )?;
+
+        let module = {
+            let _span0 = tracy_span!("parse module");
+            let res = Module::new(engine, module_wasm_code);
+            if res.is_err() {
+                Self::check_disabled_features(host, module_wasm_code)?;
+            }
+            host.map_err(res)?
+        };
+
+        Self::check_meta_section(host, &module)?;
+        Self::check_imports(host, &module)?;
+        Ok(module)
+    }
+
     /// Constructs a new instance of a [Vm] within the provided [Host],
     /// establishing a new execution context for a contract identified by
     /// `contract_id` with WASM bytecode provided in `module_wasm_code`.
@@ -178,8 +352,12 @@ impl Vm {
     ///   - Parses and performs WASM validation on the module.
     ///   - Checks that the module contains an [meta::INTERFACE_VERSION] that
     ///     matches the host.
+    ///   - Checks that every import of the module is a host function of the
+    ///     env interface, available in the current protocol, with a matching
+    ///     signature.
     ///   - Checks that the module has no floating point code or `start`
-    ///     function, or post-MVP wasm extensions.
+    ///     function, or post-MVP wasm extensions other than those enabled by
+    ///     the host's [VmConfig].
     ///   - Instantiates the module, leaving it ready to accept function
     ///     invocations.
     ///   - Looks up and caches its linear memory export named `memory`
@@ -193,42 +371,47 @@ impl Vm {
         module_wasm_code: &[u8],
     ) -> Result<Rc<Self>, HostError> {
         let _span = tracy_span!("Vm::new");
-
-        host.charge_budget(
-            ContractCostType::VmInstantiation,
-            Some(module_wasm_code.len() as u64),
-        )?;
-
-        let mut config = wasmi::Config::default();
-        let fuel_costs = host.as_budget().wasmi_fuel_costs()?;
-
-        // Turn off all optional wasm features.
-        config
-            .wasm_multi_value(false)
-            .wasm_mutable_global(true)
-            .wasm_saturating_float_to_int(false)
-            .wasm_sign_extension(true)
-            .floats(false)
-            .consume_fuel(true)
-            .fuel_consumption_mode(FuelConsumptionMode::Eager)
-            .set_fuel_costs(fuel_costs);
-
+        let config = Self::wasmi_config(host)?;
         let engine = Engine::new(&config);
-        let module = {
-            let _span0 = tracy_span!("parse module");
-            host.map_err(Module::new(&engine, module_wasm_code))?
-        };
+        let module = Rc::new(Self::parse_module(host, &engine, module_wasm_code)?);
+        Self::instantiate(host, contract_id, &engine, module)
+    }
 
-        Self::check_meta_section(host, &module)?;
+    /// Same as [Vm::new] but looks up the parsed module for `wasm_hash` in
+    /// the provided [ModuleCache] (populating it on a miss) rather than
+    /// parsing `module_wasm_code` unconditionally.
+    pub(crate) fn new_with_cache(
+        host: &Host,
+        contract_id: Hash,
+        wasm_hash: &Hash,
+        module_wasm_code: &[u8],
+        cache: &ModuleCache,
+    ) -> Result<Rc<Self>, HostError> {
+        let _span = tracy_span!("Vm::new_with_cache");
+        let (engine, module) = cache.get_or_parse(host, wasm_hash, module_wasm_code)?;
+        Self::instantiate(host, contract_id, &engine, module)
+    }
 
-        let mut store = Store::new(&engine, host.clone());
+    fn instantiate(
+        host: &Host,
+        contract_id: Hash,
+        engine: &Engine,
+        module: Rc<Module>,
+    ) -> Result<Rc<Self>, HostError> {
+        let mut store = Store::new(engine, host.clone());
         store.limiter(|host| host);
 
-        let mut linker = <Linker<Host>>::new(&engine);
+        let mut linker = <Linker<Host>>::new(engine);
 
         {
             let _span0 = tracy_span!("define host functions");
+            // Only functions available in the current protocol are linked, so
+            // contracts importing any other function fail to instantiate.
+            let ledger_proto = host.get_ledger_protocol_version()?;
             for hf in HOST_FUNCTIONS {
+                if !meta::is_protocol_in_range(ledger_proto, hf.min_proto, hf.max_proto) {
+                    continue;
+                }
                 let func = (hf.wrap)(&mut store);
                 host.map_err(
                     linker
@@ -348,12 +531,11 @@ impl Vm {
                 wasmi::Error::Trap(trap) => {
                     if let Some(code) = trap.trap_code() {
                         let err = code.into();
-                        return Err(if host.is_debug()? {
-                            // With diagnostics on: log as much detail as we can from wasmi.
-                            let msg = format!("VM call trapped: {:?}", &code);
-                            host.error(err, &msg, &[func_sym.to_val(), err.to_val()])
-                        } else {
-                            err.into()
+                        // With diagnostics on: log as much detail as we can from wasmi.
+                        let msg = host.if_debug_mode(|| format!("VM call trapped: {:?}", &code));
+                        return Err(match msg {
+                            Some(msg) => host.error(err, &msg, &[func_sym.to_val(), err.to_val()]),
+                            None => err.into(),
                         });
                     }
                     if let Some(he) = trap.downcast::<HostError>() {
@@ -371,13 +553,13 @@ impl Vm {
                     ));
                 }
                 e => {
-                    return Err(if host.is_debug()? {
-                        // With diagnostics on: log as much detail as we can from wasmi.
-                        let msg = format!("VM call failed: {:?}", &e);
-                        host.error(e.into(), &msg, &[func_sym.to_val()])
-                    } else {
-                        host.error(e.into(), "VM call failed", &[func_sym.to_val()])
-                    });
+                    // With diagnostics on: log as much detail as we can from wasmi.
+                    let msg = host.if_debug_mode(|| format!("VM call failed: {:?}", &e));
+                    return Err(host.error(
+                        e.into(),
+                        msg.as_deref().unwrap_or("VM call failed"),
+                        &[func_sym.to_val()],
+                    ));
                 }
             }
         }
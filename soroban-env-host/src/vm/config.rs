@@ -0,0 +1,103 @@
+use crate::{Host, HostError};
+use wasmi::{Engine, FuelCosts, Module};
+
+use super::Vm;
+
+/// The optional, post-MVP Wasm proposals a [Vm] accepts in contract code.
+///
+/// Which proposals are accepted is part of the protocol, so by default a host
+/// uses [VmConfig::for_protocol] of its ledger protocol version. Embedders
+/// staging the rollout of a proposal can override it with
+/// [Host::set_vm_config].
+///
+/// Mutable globals are always accepted, and floating point code never is, so
+/// neither is configurable here.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct VmConfig {
+    pub reference_types: bool,
+    pub multi_value: bool,
+    pub sign_extension: bool,
+    pub bulk_memory: bool,
+}
+
+impl VmConfig {
+    /// Returns the proposals accepted at ledger protocol version `protocol`.
+    pub fn for_protocol(_protocol: u32) -> Self {
+        // Every protocol so far accepts what the host accepted before the
+        // proposals were configurable: sign-extension along with the
+        // reference-types and bulk-memory proposals that wasmi enables by
+        // default. Accepting less than this is a consensus change, so must
+        // only ever apply from a new protocol version on.
+        VmConfig {
+            reference_types: true,
+            multi_value: false,
+            sign_extension: true,
+            bulk_memory: true,
+        }
+    }
+
+    // The proposals, by the name they're reported with when a contract uses
+    // one that's disabled.
+    fn features(&self) -> [(&'static str, bool); 4] {
+        [
+            ("reference_types", self.reference_types),
+            ("multi_value", self.multi_value),
+            ("sign_extension", self.sign_extension),
+            ("bulk_memory", self.bulk_memory),
+        ]
+    }
+
+    fn with_feature(mut self, name: &str) -> Self {
+        match name {
+            "reference_types" => self.reference_types = true,
+            "multi_value" => self.multi_value = true,
+            "sign_extension" => self.sign_extension = true,
+            "bulk_memory" => self.bulk_memory = true,
+            _ => (),
+        }
+        self
+    }
+
+    pub(crate) fn apply(&self, config: &mut wasmi::Config) {
+        config
+            .wasm_reference_types(self.reference_types)
+            .wasm_multi_value(self.multi_value)
+            .wasm_sign_extension(self.sign_extension)
+            .wasm_bulk_memory(self.bulk_memory);
+    }
+
+    /// Returns the name of a disabled proposal that, once enabled, makes
+    /// `wasm` validate, if there is one. This re-parses `wasm` once per
+    /// disabled proposal, so is only meant for explaining a failed parse.
+    pub(crate) fn find_disabled_feature_used(
+        &self,
+        wasm: &[u8],
+        fuel_costs: FuelCosts,
+    ) -> Option<&'static str> {
+        self.features()
+            .into_iter()
+            .filter(|(_, enabled)| !enabled)
+            .map(|(name, _)| name)
+            .find(|name| {
+                let config = Vm::wasmi_config_for(&self.with_feature(name), fuel_costs);
+                Module::new(&Engine::new(&config), wasm).is_ok()
+            })
+    }
+}
+
+impl Host {
+    /// Overrides the Wasm proposals accepted by this host, or with `None`
+    /// returns to those of [VmConfig::for_protocol].
+    pub fn set_vm_config(&self, config: Option<VmConfig>) -> Result<(), HostError> {
+        *self.try_borrow_vm_config_mut()? = config;
+        Ok(())
+    }
+
+    /// Returns the Wasm proposals accepted by this host.
+    pub fn get_vm_config(&self) -> Result<VmConfig, HostError> {
+        match *self.try_borrow_vm_config()? {
+            Some(config) => Ok(config),
+            None => Ok(VmConfig::for_protocol(self.get_ledger_protocol_version()?)),
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use super::FuelRefillable;
-use crate::{xdr::ContractCostType, EnvBase, Host, HostError, VmCaller, VmCallerEnv};
 use crate::{
-    AddressObject, Bool, BytesObject, DurationObject, Error, I128Object, I256Object, I256Val,
-    I32Val, I64Object, MapObject, StorageType, StringObject, Symbol, SymbolObject, TimepointObject,
-    U128Object, U256Object, U256Val, U32Val, U64Object, U64Val, Val, VecObject, Void,
+    events::InternalDiagnosticArg,
+    xdr::{ContractCostType, ScVal},
+    EnvBase, Host, HostError, VmCaller, VmCallerEnv,
+};
+use crate::{
+    AddressObject, Bool, BytesObject, DurationObject, Error, I128Object, I128Val, I256Object,
+    I256Val, I32Val, I64Object, MapObject, StorageType, StringObject, Symbol, SymbolObject,
+    TimepointObject, U128Object, U128Val, U256Object, U256Val, U32Val, U64Object, U64Val, Val,
+    VecObject, Void,
 };
 use soroban_env_common::{call_macro_with_all_host_functions, WasmiMarshal};
 use wasmi::{
@@ -26,6 +31,10 @@ pub(crate) trait RelativeObjectConversion: WasmiMarshal {
         let rel = self.absolute_to_relative(host)?;
         Ok(Self::marshal_from_self(rel))
     }
+    // Renders the (absolute) argument for the diagnostic event recorded when
+    // a host function fails. This must not create any host objects, so that
+    // enabling diagnostics doesn't change the objects a contract sees.
+    fn to_diagnostic_arg(&self) -> InternalDiagnosticArg;
 }
 
 macro_rules! impl_relative_object_conversion {
@@ -38,6 +47,55 @@ macro_rules! impl_relative_object_conversion {
             fn relative_to_absolute(self, host: &Host) -> Result<Self, HostError> {
                 Ok(Self::try_from(host.relative_to_absolute(self.into())?)?)
             }
+
+            // Types that may or may not carry an object (e.g. `Val` or
+            // `Symbol`) mostly carry small values, which need no translation
+            // and so skip the round trip through the generic `Val` conversion.
+            #[inline(always)]
+            fn try_marshal_from_relative_value(v: wasmi::Value, host: &Host) -> Result<Self, Trap> {
+                let val = Self::try_marshal_from_value(v).ok_or(BadSignature)?;
+                if !Val::from(val).is_object() {
+                    return Ok(val);
+                }
+                Ok(val.relative_to_absolute(host)?)
+            }
+
+            #[inline(always)]
+            fn marshal_relative_from_self(self, host: &Host) -> Result<wasmi::Value, Trap> {
+                if !Val::from(self).is_object() {
+                    return Ok(Self::marshal_from_self(self));
+                }
+                Ok(Self::marshal_from_self(self.absolute_to_relative(host)?))
+            }
+
+            fn to_diagnostic_arg(&self) -> InternalDiagnosticArg {
+                InternalDiagnosticArg::HostVal((*self).into())
+            }
+        }
+    };
+}
+
+macro_rules! impl_trivial_object_conversion {
+    ($T:ty) => {
+        impl RelativeObjectConversion for $T {
+            // Small values never refer to objects, so marshal straight to
+            // and from the VM's `i64`s without consulting the host.
+            #[inline(always)]
+            fn try_marshal_from_relative_value(
+                v: wasmi::Value,
+                _host: &Host,
+            ) -> Result<Self, Trap> {
+                Ok(Self::try_marshal_from_value(v).ok_or(BadSignature)?)
+            }
+
+            #[inline(always)]
+            fn marshal_relative_from_self(self, _host: &Host) -> Result<wasmi::Value, Trap> {
+                Ok(Self::marshal_from_self(self))
+            }
+
+            fn to_diagnostic_arg(&self) -> InternalDiagnosticArg {
+                InternalDiagnosticArg::HostVal((*self).into())
+            }
         }
     };
 }
@@ -68,18 +126,35 @@ impl_relative_object_conversion!(U128Object);
 impl_relative_object_conversion!(U256Object);
 
 impl_relative_object_conversion!(U64Val);
+impl_relative_object_conversion!(U128Val);
+impl_relative_object_conversion!(I128Val);
 impl_relative_object_conversion!(U256Val);
 impl_relative_object_conversion!(I256Val);
 
 // Trivial / non-relativizing impls are ok for types that can't carry objects.
-impl RelativeObjectConversion for i64 {}
-impl RelativeObjectConversion for u64 {}
-impl RelativeObjectConversion for Void {}
-impl RelativeObjectConversion for Bool {}
-impl RelativeObjectConversion for Error {}
-impl RelativeObjectConversion for StorageType {}
-impl RelativeObjectConversion for U32Val {}
-impl RelativeObjectConversion for I32Val {}
+impl_trivial_object_conversion!(Void);
+impl_trivial_object_conversion!(Bool);
+impl_trivial_object_conversion!(Error);
+impl_trivial_object_conversion!(U32Val);
+impl_trivial_object_conversion!(I32Val);
+
+// Types that aren't `Val`s at all are rendered as XDR values, since turning
+// e.g. a large `i64` into a `Val` would need a host object.
+impl RelativeObjectConversion for i64 {
+    fn to_diagnostic_arg(&self) -> InternalDiagnosticArg {
+        InternalDiagnosticArg::XdrVal(ScVal::I64(*self))
+    }
+}
+impl RelativeObjectConversion for u64 {
+    fn to_diagnostic_arg(&self) -> InternalDiagnosticArg {
+        InternalDiagnosticArg::XdrVal(ScVal::U64(*self))
+    }
+}
+impl RelativeObjectConversion for StorageType {
+    fn to_diagnostic_arg(&self) -> InternalDiagnosticArg {
+        InternalDiagnosticArg::XdrVal(ScVal::U64(self.clone() as u64))
+    }
+}
 
 ///////////////////////////////////////////////////////////////////////////////
 /// X-macro use: dispatch functions
@@ -105,7 +180,7 @@ macro_rules! generate_dispatch_functions {
                     // pattern-repetition matcher so that it will match all such
                     // descriptions.
                     $(#[$fn_attr:meta])*
-                    { $fn_str:literal, fn $fn_id:ident ($($arg:ident:$type:ty),*) -> $ret:ty }
+                    { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident ($($arg:ident:$type:ty),*) -> $ret:ty }
                 )*
             }
         )*
@@ -121,7 +196,9 @@ macro_rules! generate_dispatch_functions {
             $(
                 // This defines a "dispatch function" that does several things:
                 //
-                //  1. charges the budget for the call, failing if over budget.
+                //  1. charges the budget for the call, failing if over budget,
+                //     and checks the function is available in the current
+                //     protocol.
                 //  2. attempts to convert incoming wasmi i64 args to Vals or
                 //     Val-wrappers expected by host functions, failing if
                 //     any conversions fail.
@@ -143,6 +220,9 @@ macro_rules! generate_dispatch_functions {
 
                     let host = caller.data().clone();
 
+                    #[cfg(any(test, feature = "testutils"))]
+                    host.record_function_call(std::stringify!($fn_id)).map_err(|he| Trap::from(he))?;
+
                     // This is where the VM -> Host boundary is crossed.
                     // We first return all fuels from the VM back to the host such that
                     // the host maintains control of the budget.
@@ -152,6 +232,22 @@ macro_rules! generate_dispatch_functions {
                     // host budget, marshalling values. This does not account for the actual work
                     // being done in those functions, which are metered individually by the implementation.
                     host.charge_budget(ContractCostType::DispatchHostFunction, None)?;
+                    host.check_host_function_protocol(
+                        $min_proto,
+                        $max_proto,
+                        concat!("host function is not available in the current protocol: ", stringify!($fn_id)),
+                    )?;
+                    $(
+                        let $arg = <$type>::try_marshal_from_relative_value(Value::I64($arg), &host)?;
+                    )*
+                    // Hold on to the arguments in case the call fails, so that
+                    // the error can be reported along with them. This only
+                    // happens when diagnostics are enabled.
+                    let mut diagnostic_args: Vec<InternalDiagnosticArg> = vec![];
+                    host.with_debug_mode(|| {
+                        diagnostic_args = vec![$($arg.to_diagnostic_arg()),*];
+                        Ok(())
+                    })?;
                     let mut vmcaller = VmCaller(Some(caller));
                     // The odd / seemingly-redundant use of `wasmi::Value` here
                     // as intermediates -- rather than just passing Vals --
@@ -162,7 +258,7 @@ macro_rules! generate_dispatch_functions {
                     // happens to be a natural switching point for that: we have
                     // conversions to and from both Val and i64 / u64 for
                     // wasmi::Value.
-                    let res: Result<_, HostError> = host.$fn_id(&mut vmcaller, $(<$type>::try_marshal_from_relative_value(Value::I64($arg), &host)?),*);
+                    let res: Result<_, HostError> = host.$fn_id(&mut vmcaller, $($arg),*);
 
                     // On the off chance we got an error with no context, we can
                     // at least attach some here "at each host function call",
@@ -181,10 +277,12 @@ macro_rules! generate_dispatch_functions {
                         },
                         Err(hosterr) => {
                             // We make a new HostError here to capture the escalation event itself.
+                            // The diagnostic event names the failed function
+                            // and carries the arguments it was called with.
                             let escalation: HostError =
-                                host.error(hosterr.error,
+                                host.error_with_diagnostic_args(hosterr.error,
                                            concat!("escalating error to VM trap from failed host function call: ",
-                                                   stringify!($fn_id)), &[]);
+                                                   stringify!($fn_id)), diagnostic_args);
                             let trap: Trap = escalation.into();
                             Err(trap)
                         }
@@ -1,5 +1,9 @@
 use super::dispatch;
-use crate::Host;
+use crate::{
+    meta,
+    xdr::{ScErrorCode, ScErrorType},
+    Host, HostError, U32Val,
+};
 use soroban_env_common::call_macro_with_all_host_functions;
 use wasmi::{Func, Store};
 
@@ -11,12 +15,48 @@ pub(crate) struct HostFuncInfo {
     /// as.
     pub(crate) fn_str: &'static str,
 
+    /// The first ledger protocol version this host function is available in,
+    /// or `None` if it has been available since the first protocol.
+    pub(crate) min_proto: Option<u32>,
+
+    /// The last ledger protocol version this host function is available in,
+    /// or `None` if it is available in all later protocols.
+    pub(crate) max_proto: Option<u32>,
+
     /// Function that takes a wasmi::Store and _wraps_ a dispatch function
     /// for this host function, with the specific type of the dispatch function,
     /// into a Func in the Store.
     pub(crate) wrap: fn(&mut Store<Host>) -> Func,
 }
 
+impl Host {
+    // Checks that a host function with the given protocol bounds may be
+    // called at the current ledger protocol. Linking already leaves out
+    // functions that aren't available, so this only fails if the protocol
+    // changed after a module was linked, e.g. for a module instance that
+    // outlives a ledger upgrade in tests.
+    pub(crate) fn check_host_function_protocol(
+        &self,
+        min_proto: Option<u32>,
+        max_proto: Option<u32>,
+        msg: &'static str,
+    ) -> Result<(), HostError> {
+        if min_proto.is_none() && max_proto.is_none() {
+            return Ok(());
+        }
+        let ledger_proto = self.get_ledger_protocol_version()?;
+        if meta::is_protocol_in_range(ledger_proto, min_proto, max_proto) {
+            return Ok(());
+        }
+        Err(self.err(
+            ScErrorType::WasmVm,
+            ScErrorCode::InvalidAction,
+            msg,
+            &[U32Val::from(ledger_proto).to_val()],
+        ))
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 /// X-macro use: static HOST_FUNCTIONS array of HostFuncInfo
 ///////////////////////////////////////////////////////////////////////////////
@@ -41,7 +81,7 @@ macro_rules! generate_host_function_infos {
                     // pattern-repetition matcher so that it will match all such
                     // descriptions.
                     $(#[$fn_attr:meta])*
-                    { $fn_id:literal, fn $func_id:ident $args:tt -> $ret:ty }
+                    { $fn_id:literal, $min_proto:expr, $max_proto:expr, fn $func_id:ident $args:tt -> $ret:ty }
                 )*
             }
         )*
@@ -77,6 +117,8 @@ macro_rules! generate_host_function_infos {
                     HostFuncInfo {
                         mod_str: $mod_str,
                         fn_str: $fn_id,
+                        min_proto: $min_proto,
+                        max_proto: $max_proto,
                         wrap: |store| Func::wrap(store, dispatch::$func_id),
                     },
                 )*
@@ -87,3 +129,39 @@ macro_rules! generate_host_function_infos {
 
 // Here we invoke the x-macro passing generate_host_function_infos as its callback macro.
 call_macro_with_all_host_functions! { generate_host_function_infos }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::find_host_function;
+
+    #[test]
+    fn test_host_function_protocol_ranges_match_interface() {
+        for hf in HOST_FUNCTIONS {
+            let info = find_host_function(hf.mod_str, hf.fn_str).unwrap();
+            assert_eq!(hf.min_proto, info.min_supported_protocol);
+            assert_eq!(hf.max_proto, info.max_supported_protocol);
+        }
+    }
+
+    #[test]
+    fn test_all_host_functions_have_dispatch_cost_runners() {
+        use crate::cost_runner::dispatch_host_function::HOST_FUNCTION_DISPATCH_RUNS;
+        // Read the functions straight from env.json rather than from the
+        // x-macro generated from it, which the runners are generated from
+        // too.
+        let env: serde_json::Value =
+            serde_json::from_str(include_str!("../../../soroban-env-common/env.json")).unwrap();
+        let mut declared = vec![];
+        for module in env["modules"].as_array().unwrap() {
+            let mod_export = module["export"].as_str().unwrap();
+            for function in module["functions"].as_array().unwrap() {
+                declared.push((mod_export, function["export"].as_str().unwrap()));
+            }
+        }
+        let mut runs = HOST_FUNCTION_DISPATCH_RUNS.to_vec();
+        declared.sort();
+        runs.sort();
+        assert_eq!(declared, runs);
+    }
+}
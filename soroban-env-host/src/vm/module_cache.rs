@@ -0,0 +1,210 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+};
+
+use crate::{
+    budget::AsBudget,
+    host::error::TryBorrowOrErr,
+    xdr::{ContractCostType, Hash},
+    Host, HostError,
+};
+use wasmi::{Engine, FuelCosts, Module};
+
+use super::{Vm, VmConfig};
+
+/// The default upper bound on the total size (in bytes of Wasm code) of all
+/// modules retained by a [ModuleCache].
+pub const DEFAULT_MODULE_CACHE_MAX_BYTES: u64 = 16 * 1024 * 1024; // 16MB
+
+// Modules are cached per VmConfig and ledger protocol version they were
+// validated under, as a module accepted under one config or protocol (e.g.
+// by its interface version or imports) may be rejected under another.
+type ModuleKey = (Hash, VmConfig, u32);
+
+#[derive(Clone)]
+struct ModuleCacheImpl {
+    // One engine per VmConfig, created the first time a module is parsed
+    // under that config.
+    engines: BTreeMap<VmConfig, Engine>,
+    modules: BTreeMap<ModuleKey, (Rc<Module>, u64)>,
+    // Keys in insertion order, oldest first; used for eviction.
+    order: VecDeque<ModuleKey>,
+    total_bytes: u64,
+    max_bytes: u64,
+}
+
+/// A [ModuleCache] retains parsed and validated [wasmi::Module]s keyed by the
+/// [Hash] of their contract code, so that repeated instantiations of the same
+/// contract -- within a single invocation, or across invocations on hosts
+/// sharing the cache -- only pay for parsing and validation once.
+///
+/// Modules are cached separately for each [VmConfig] they are parsed under,
+/// so a cache honours the [VmConfig] of whichever host uses it, including
+/// one set with [Host::set_vm_config]. The modules of each [VmConfig] are
+/// compiled against a [wasmi::Engine] of that configuration owned by the
+/// cache, and every [Vm] instantiated from a cached module runs in a
+/// [wasmi::Store] of that engine.
+///
+/// The cache is bounded by the total size of the Wasm code it retains. When
+/// inserting a new module would exceed that bound, the oldest modules are
+/// evicted first. Modules larger than the bound are never cached.
+///
+/// Cloning a [ModuleCache] is cheap and produces a handle to the same cache.
+#[derive(Clone)]
+pub struct ModuleCache {
+    fuel_costs: FuelCosts,
+    inner: Rc<RefCell<ModuleCacheImpl>>,
+}
+
+impl ModuleCache {
+    /// Constructs a new, empty [ModuleCache] retaining at most `max_bytes` of
+    /// Wasm code. The cache engines are configured with the fuel costs of
+    /// the provided [Host]'s budget, so the cache should only be shared
+    /// between hosts with identical fuel costs.
+    pub fn new(host: &Host, max_bytes: u64) -> Result<Self, HostError> {
+        Ok(Self {
+            fuel_costs: host.as_budget().wasmi_fuel_costs()?,
+            inner: Rc::new(RefCell::new(ModuleCacheImpl {
+                engines: BTreeMap::new(),
+                modules: BTreeMap::new(),
+                order: VecDeque::new(),
+                total_bytes: 0,
+                max_bytes,
+            })),
+        })
+    }
+
+    // Returns the engine for modules parsed under `vm_config`, creating it
+    // if this is the first such module.
+    fn engine_for(&self, vm_config: &VmConfig) -> Result<Engine, HostError> {
+        let mut inner = self.inner.try_borrow_mut_or_err()?;
+        let engine = inner
+            .engines
+            .entry(*vm_config)
+            .or_insert_with(|| Engine::new(&Vm::wasmi_config_for(vm_config, self.fuel_costs)));
+        Ok(engine.clone())
+    }
+
+    /// Returns the cached module for `wasm_hash` under the host's current
+    /// [VmConfig] and ledger protocol version, along with the engine it was compiled against, parsing,
+    /// validating and inserting it first if it is not yet present.
+    ///
+    /// A cache hit is charged as [ContractCostType::VmCachedInstantiation],
+    /// a miss as [ContractCostType::VmInstantiation].
+    // Notes on metering: the cache lookup itself is not charged separately,
+    // it is covered by the `VmCachedInstantiation` charge on a hit.
+    pub(crate) fn get_or_parse(
+        &self,
+        host: &Host,
+        wasm_hash: &Hash,
+        wasm_code: &[u8],
+    ) -> Result<(Engine, Rc<Module>), HostError> {
+        let vm_config = host.get_vm_config()?;
+        let engine = self.engine_for(&vm_config)?;
+        let key = (
+            wasm_hash.clone(),
+            vm_config,
+            host.get_ledger_protocol_version()?,
+        );
+        let cached = self
+            .inner
+            .try_borrow_or_err()?
+            .modules
+            .get(&key)
+            .map(|(m, _)| Rc::clone(m));
+        if let Some(module) = cached {
+            host.charge_budget(
+                ContractCostType::VmCachedInstantiation,
+                Some(wasm_code.len() as u64),
+            )?;
+            return Ok((engine, module));
+        }
+
+        let module = Rc::new(Vm::parse_module(host, &engine, wasm_code)?);
+        self.insert(key, Rc::clone(&module), wasm_code.len() as u64)?;
+        Ok((engine, module))
+    }
+
+    fn insert(&self, key: ModuleKey, module: Rc<Module>, size: u64) -> Result<(), HostError> {
+        let mut inner = self.inner.try_borrow_mut_or_err()?;
+        if size > inner.max_bytes || inner.modules.contains_key(&key) {
+            return Ok(());
+        }
+        while inner.total_bytes.saturating_add(size) > inner.max_bytes {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some((_, evicted_size)) = inner.modules.remove(&oldest) {
+                inner.total_bytes = inner.total_bytes.saturating_sub(evicted_size);
+            }
+        }
+        inner.modules.insert(key.clone(), (module, size));
+        inner.order.push_back(key);
+        inner.total_bytes = inner.total_bytes.saturating_add(size);
+        Ok(())
+    }
+
+    /// Returns `true` if a module for `wasm_hash` is currently cached under
+    /// any [VmConfig] or protocol version.
+    pub fn contains(&self, wasm_hash: &Hash) -> Result<bool, HostError> {
+        Ok(self
+            .inner
+            .try_borrow_or_err()?
+            .modules
+            .keys()
+            .any(|(hash, _, _)| hash == wasm_hash))
+    }
+
+    /// Returns the number of modules currently cached, counting a module
+    /// cached under several [VmConfig]s or protocol versions once for each.
+    pub fn len(&self) -> Result<usize, HostError> {
+        Ok(self.inner.try_borrow_or_err()?.modules.len())
+    }
+
+    /// Returns the total size in bytes of the Wasm code of all cached modules.
+    pub fn total_bytes(&self) -> Result<u64, HostError> {
+        Ok(self.inner.try_borrow_or_err()?.total_bytes)
+    }
+
+    // Returns a new cache that starts out with the modules (and engines) of
+    // this one, but is independent of it from then on.
+    pub(crate) fn deep_clone(&self) -> Result<Self, HostError> {
+        Ok(Self {
+            fuel_costs: self.fuel_costs,
+            inner: Rc::new(RefCell::new(self.inner.try_borrow_or_err()?.clone())),
+        })
+    }
+
+    /// Removes all modules from the cache.
+    pub fn clear(&self) -> Result<(), HostError> {
+        let mut inner = self.inner.try_borrow_mut_or_err()?;
+        inner.modules.clear();
+        inner.order.clear();
+        inner.total_bytes = 0;
+        Ok(())
+    }
+}
+
+impl Host {
+    /// Installs a [ModuleCache] on this host. Subsequent contract invocations
+    /// will look up (and populate) parsed Wasm modules in the cache rather
+    /// than re-parsing contract code on every call.
+    pub fn set_module_cache(&self, cache: ModuleCache) -> Result<(), HostError> {
+        *self.try_borrow_module_cache_mut()? = Some(cache);
+        Ok(())
+    }
+
+    /// Returns a handle to the [ModuleCache] installed on this host, if any.
+    pub fn get_module_cache(&self) -> Result<Option<ModuleCache>, HostError> {
+        Ok(self.try_borrow_module_cache()?.clone())
+    }
+
+    /// Installs a new, empty [ModuleCache] with
+    /// [DEFAULT_MODULE_CACHE_MAX_BYTES] capacity on this host.
+    pub fn enable_module_cache(&self) -> Result<(), HostError> {
+        let cache = ModuleCache::new(self, DEFAULT_MODULE_CACHE_MAX_BYTES)?;
+        self.set_module_cache(cache)
+    }
+}
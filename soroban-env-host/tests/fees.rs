@@ -22,6 +22,40 @@ fn expiration_entry_size() {
     );
 }
 
+#[test]
+fn default_fee_configuration_charges_nothing() {
+    let tx_resources = TransactionResources {
+        instructions: 1_000_000,
+        read_entries: 3,
+        write_entries: 2,
+        read_bytes: 5000,
+        write_bytes: 2000,
+        contract_events_size_bytes: 300,
+        transaction_size_bytes: 1500,
+    };
+    assert_eq!(
+        compute_transaction_resource_fee(&tx_resources, &FeeConfiguration::default()),
+        (0, 0)
+    );
+    assert_eq!(
+        compute_rent_fee(
+            &vec![LedgerEntryRentChange {
+                is_persistent: true,
+                new_size_bytes: 100,
+                new_expiration_ledger: 100_000,
+                ..Default::default()
+            }],
+            &RentFeeConfiguration {
+                persistent_rent_rate_denominator: 1000,
+                temporary_rent_rate_denominator: 1000,
+                ..Default::default()
+            },
+            50_000,
+        ),
+        0
+    );
+}
+
 #[test]
 fn resource_fee_computation() {
     // No resources
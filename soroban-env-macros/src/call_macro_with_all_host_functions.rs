@@ -12,7 +12,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::path;
 
-pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
+/// Reads and parses the env interface file at `file_lit`, relative to the
+/// manifest of the crate invoking the macro.
+pub fn load(file_lit: &LitStr) -> Result<Root, Error> {
     let file_str = file_lit.value();
     let file_path = path::abs_from_rel_to_manifest(&file_str);
 
@@ -23,12 +25,17 @@ pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
         )
     })?;
 
-    let root: Root = serde_json::from_reader(file).map_err(|e| {
+    serde_json::from_reader(file).map_err(|e| {
         Error::new(
             file_lit.span(),
             format!("error parsing file '{file_str}': {e}"),
         )
-    })?;
+    })
+}
+
+pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
+    let file_str = file_lit.value();
+    let root = load(&file_lit)?;
 
     let mut export_names = HashMap::<String, String>::new();
     for m in root.modules.iter() {
@@ -75,6 +82,15 @@ pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
                 ));
             }
 
+            if let (Some(min), Some(max)) = (f.min_supported_protocol, f.max_supported_protocol) {
+                if min > max {
+                    return Err(Error::new(
+                        file_lit.span(),
+                        format!("invalid protocol range in '{file_str}': {path_name} has min_supported_protocol {min} greater than max_supported_protocol {max}"),
+                    ));
+                }
+            }
+
             match export_names.entry(export_name.clone()) {
                 Entry::Occupied(existing) => {
                     let existing_name = existing.get();
@@ -110,9 +126,19 @@ pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
 
             let r#return = format_ident!("{}", &f.r#return);
 
+            // Protocol bounds are passed as `Option<u32>` expressions.
+            let min_proto = match f.min_supported_protocol {
+                Some(p) => quote! { Some(#p) },
+                None => quote! { None },
+            };
+            let max_proto = match f.max_supported_protocol {
+                Some(p) => quote! { Some(#p) },
+                None => quote! { None },
+            };
+
             quote! {
                 #[doc = #docs]
-                { #export, fn #name(#(#args),*) -> #r#return }
+                { #export, #min_proto, #max_proto, fn #name(#(#args),*) -> #r#return }
             }
         });
 
@@ -139,12 +165,15 @@ pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
                     //
                     //  mod $mod_id:ident $mod_str:literal {
                     //     ...
-                    //     { $fn_str:literal, fn $fn_id:ident $args:tt -> $ret:ty }
+                    //     { $fn_str:literal, $min_proto:expr, $max_proto:expr, fn $fn_id:ident $args:tt -> $ret:ty }
                     //     ...
                     //  }
                     //
                     // Where the sub token-tree $args:tt is a normal parenthesized
-                    // argument list of comma-separated arg:type pairs
+                    // argument list of comma-separated arg:type pairs, and
+                    // $min_proto / $max_proto are `Option<u32>` expressions
+                    // bounding the ledger protocol versions the function is
+                    // available in (inclusive, `None` meaning unbounded).
 
                     #(#modules)*
                 }
@@ -173,6 +202,10 @@ pub struct Function {
     pub args: Vec<Arg>,
     pub r#return: String,
     pub docs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_supported_protocol: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_supported_protocol: Option<u32>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
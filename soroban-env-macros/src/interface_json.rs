@@ -0,0 +1,99 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde::Serialize;
+use syn::{Error, LitStr};
+
+use crate::call_macro_with_all_host_functions::{load, Root};
+
+// The schema of the generated description. It is deliberately separate from
+// the env.json input types so that the input file can evolve without changing
+// what SDKs in other languages consume; any change to these types should be
+// treated as a breaking change for those consumers.
+
+#[derive(Serialize)]
+struct Interface {
+    modules: Vec<Module>,
+}
+
+#[derive(Serialize)]
+struct Module {
+    name: String,
+    export: String,
+    functions: Vec<Function>,
+}
+
+#[derive(Serialize)]
+struct Function {
+    name: String,
+    export: String,
+    /// The full `<module-export>.<function-export>` name the function is
+    /// imported under by Wasm contracts.
+    import: String,
+    args: Vec<Arg>,
+    r#return: String,
+    docs: String,
+    /// The first ledger protocol version the function is available in, if
+    /// bounded.
+    min_supported_protocol: Option<u32>,
+    /// The last ledger protocol version the function is available in, if
+    /// bounded.
+    max_supported_protocol: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct Arg {
+    name: String,
+    r#type: String,
+}
+
+impl From<Root> for Interface {
+    fn from(root: Root) -> Self {
+        let modules = root
+            .modules
+            .into_iter()
+            .map(|m| Module {
+                functions: m
+                    .functions
+                    .into_iter()
+                    .map(|f| Function {
+                        import: format!("{}.{}", m.export, f.export),
+                        name: f.name,
+                        export: f.export,
+                        args: f
+                            .args
+                            .into_iter()
+                            .map(|a| Arg {
+                                name: a.name,
+                                r#type: a.r#type,
+                            })
+                            .collect(),
+                        r#return: f.r#return,
+                        docs: f.docs.unwrap_or_default(),
+                        min_supported_protocol: f.min_supported_protocol,
+                        max_supported_protocol: f.max_supported_protocol,
+                    })
+                    .collect(),
+                name: m.name,
+                export: m.export,
+            })
+            .collect();
+        Interface { modules }
+    }
+}
+
+pub fn generate(file_lit: LitStr) -> Result<TokenStream, Error> {
+    let interface = Interface::from(load(&file_lit)?);
+    let json = serde_json::to_string_pretty(&interface).map_err(|e| {
+        Error::new(
+            file_lit.span(),
+            format!("error serializing interface description: {e}"),
+        )
+    })?;
+    Ok(quote! {
+        /// A machine-readable JSON description of every host function in the
+        /// env interface: its module, export names, argument and return types
+        /// and docs. Intended for generating bindings to the interface in
+        /// languages other than Rust.
+        pub const ENV_INTERFACE_JSON: &str = #json;
+    })
+}
@@ -1,4 +1,5 @@
 mod call_macro_with_all_host_functions;
+mod interface_json;
 mod path;
 
 extern crate proc_macro;
@@ -90,3 +91,12 @@ pub fn generate_call_macro_with_all_host_functions(input: TokenStream) -> TokenS
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+#[proc_macro]
+pub fn generate_interface_json(input: TokenStream) -> TokenStream {
+    let file = parse_macro_input!(input as LitStr);
+    match interface_json::generate(file) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
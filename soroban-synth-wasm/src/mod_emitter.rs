@@ -58,11 +58,26 @@ pub struct ModEmitter {
 
 impl ModEmitter {
     pub fn new() -> Self {
+        Self::with_env_meta(&soroban_env_common::meta::XDR)
+    }
+
+    /// Same as [`ModEmitter::new`] but claims to be built against the env
+    /// interface version `interface_version` rather than the current one, for
+    /// testing how hosts treat contracts built for other versions.
+    pub fn new_with_interface_version(interface_version: u64) -> Self {
+        // This is the XDR of an `ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion`:
+        // the union discriminant (0) followed by the version, both big-endian.
+        let mut xdr = 0u32.to_be_bytes().to_vec();
+        xdr.extend_from_slice(&interface_version.to_be_bytes());
+        Self::with_env_meta(&xdr)
+    }
+
+    fn with_env_meta(env_meta_xdr: &[u8]) -> Self {
         let mut module = Module::new();
 
         let metasection = CustomSection {
             name: soroban_env_common::meta::ENV_META_V0_SECTION_NAME,
-            data: &soroban_env_common::meta::XDR,
+            data: env_meta_xdr,
         };
         module.section(&metasection);
 